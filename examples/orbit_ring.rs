@@ -0,0 +1,20 @@
+extern crate bevy;
+use bevy::prelude::*;
+
+use solar_system::{SimSet, SolarSystemPlugin};
+
+// The radius in AU of the reference ring
+const RING_RADIUS_AU: f32 = 1.;
+
+// This draws a ring in the ecliptic plane at Earth's mean orbital distance. It runs after the body
+// positions have been updated, so anything it computes from them is for the current time step.
+fn draw_ring(mut gizmos: Gizmos) {
+    gizmos.circle(Vec3::ZERO, Dir3::Z, RING_RADIUS_AU, Color::srgb(0.2, 0.2, 0.4));
+}
+
+pub fn main() {
+    let mut app = App::new();
+    app.add_plugins((DefaultPlugins, SolarSystemPlugin::default().with_ecliptic_grid(false)))
+        .add_systems(FixedUpdate, draw_ring.after(SimSet::UpdateBodies))
+        .run();
+}
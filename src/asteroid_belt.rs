@@ -0,0 +1,225 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages, view::NoFrustumCulling},
+};
+
+use crate::{
+    kepler_orbit as kepler,
+    simulation::{Body, BodyProperties},
+    uom_wrapper::si::{
+        angle::degree,
+        f64::{Angle, Length, Mass, Time},
+        length::astronomical_unit,
+        mass::kilogram,
+    },
+    Layers, SimSeed, SimSet, Simulation,
+};
+
+// The stream of the simulation's seed the belt is drawn from
+const SEED_STREAM: u64 = 0;
+
+const ASTEROID_COLOR: Color = Color::srgb(0.55, 0.5, 0.45);
+
+// This controls the belt of asteroids between Mars and Jupiter. Each asteroid is on its own Kepler
+// orbit with a semimajor axis, eccentricity, and inclination drawn uniformly from the given
+// ranges, and with its orientation and its position along the orbit drawn at random from the
+// simulation's seed. The same seed gives the same belt. The belt is drawn again when it changes.
+#[derive(Resource)]
+pub struct AsteroidBelt {
+    pub count: usize,
+    pub inner_au: f64,
+    pub outer_au: f64,
+    pub max_eccentricity: f64,
+    pub max_inclination_deg: f64,
+}
+
+impl Default for AsteroidBelt {
+    fn default() -> Self {
+        Self {
+            count: 3000,
+            inner_au: 2.1,
+            outer_au: 3.3,
+            max_eccentricity: 0.2,
+            max_inclination_deg: 10.,
+        }
+    }
+}
+
+impl AsteroidBelt {
+    // Return the properties of the belt's asteroids at the given Julian Date drawn from the given
+    // seed
+    fn mk_asteroids(&self, seed: SimSeed, jd: Time) -> Vec<BodyProperties> {
+        let mut rng = seed.rng(SEED_STREAM);
+        let sun_mass = BodyProperties::properties_for(Body::Sun).mass();
+        let mass = Mass::new::<kilogram>(0.);
+        let deg = Angle::new::<degree>;
+        (0..self.count)
+            .map(|_| {
+                let a = Length::new::<astronomical_unit>(
+                    self.inner_au + (self.outer_au - self.inner_au) * rng.f64(),
+                );
+                let phase = rng.f64() * kepler::period(sun_mass, mass, a);
+                BodyProperties::new(mass, Length::new::<astronomical_unit>(0.)).with_sun_orbit(
+                    a,
+                    self.max_eccentricity * rng.f64(),
+                    deg(self.max_inclination_deg * rng.f64()),
+                    deg(360. * rng.f64()),
+                    deg(360. * rng.f64()),
+                    jd - phase,
+                )
+            })
+            .collect()
+    }
+}
+
+// This is the point list drawing the asteroids.
+#[derive(Component)]
+struct BeltPoints;
+
+// Add the belt's asteroids to the simulation and spawn the points drawing them, returning the
+// points unless the belt is empty
+fn spawn_belt(
+    (belt, seed): (&AsteroidBelt, SimSeed),
+    sim: &mut Simulation,
+    commands: &mut Commands,
+    (meshes, materials): (&mut Assets<Mesh>, &mut Assets<StandardMaterial>),
+) -> Option<Entity> {
+    if belt.count == 0 {
+        return None;
+    }
+
+    let asteroids = belt.mk_asteroids(seed, sim.current_time());
+    sim.add_asteroids(asteroids);
+    let mesh = Mesh::new(PrimitiveTopology::PointList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, sim.asteroid_positions());
+    let points = commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(mesh),
+            material: materials.add(StandardMaterial {
+                base_color: ASTEROID_COLOR,
+                unlit: true,
+                ..default()
+            }),
+            ..default()
+        },
+        NotShadowCaster,
+        NotShadowReceiver,
+        // The points move, so the bounds computed when the mesh was created don't hold.
+        NoFrustumCulling,
+        BeltPoints,
+    ));
+    Some(points.id())
+}
+
+// The asteroids are drawn as a single list of points rather than as avatars. The GPU draws each
+// point a pixel wide however far away it is. They aren't bodies, so they have no labels and can't
+// be selected.
+fn create_belt(
+    (belt, seed): (Res<AsteroidBelt>, Res<SimSeed>),
+    mut sim: ResMut<Simulation>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    spawn_belt((&belt, *seed), &mut sim, &mut commands, (&mut meshes, &mut materials));
+}
+
+// When the belt changes after startup, e.g., when a quality preset with another count is selected,
+// its asteroids and points are replaced. The new points are marked as spawned for the bodies, so
+// they are despawned along with everything else when another scenario is loaded.
+fn rebuild_belt(
+    (belt, seed): (Res<AsteroidBelt>, Res<SimSeed>),
+    mut sim: ResMut<Simulation>,
+    mut commands: Commands,
+    (mut meshes, mut materials): (ResMut<Assets<Mesh>>, ResMut<Assets<StandardMaterial>>),
+    points: Query<Entity, With<BeltPoints>>,
+) {
+    if !belt.is_changed() || belt.is_added() {
+        return;
+    }
+
+    for points in &points {
+        commands.entity(points).despawn();
+    }
+    sim.clear_asteroids();
+    let assets = (&mut *meshes, &mut *materials);
+    if let Some(points) = spawn_belt((&belt, *seed), &mut sim, &mut commands, assets) {
+        commands.entity(points).insert(crate::BodyEntity);
+    }
+}
+
+fn update_belt(
+    sim: Res<Simulation>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    points: Query<&Handle<Mesh>, With<BeltPoints>>,
+) {
+    for handle in &points {
+        if let Some(mesh) = meshes.get_mut(handle) {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, sim.asteroid_positions());
+        }
+    }
+}
+
+fn apply_belt_layer(layers: Res<Layers>, mut points: Query<&mut Visibility, With<BeltPoints>>) {
+    if !layers.is_changed() {
+        return;
+    }
+    let visibility = if layers.asteroids { Visibility::Inherited } else { Visibility::Hidden };
+    for mut points_vis in &mut points {
+        points_vis.set_if_neq(visibility);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<AsteroidBelt>()
+        .add_systems(crate::SpawnBodies, create_belt)
+        .add_systems(Update, (rebuild_belt, apply_belt_layer).chain())
+        .add_systems(FixedUpdate, update_belt.in_set(SimSet::Render))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{default_epoch, uom_wrapper::si::length::meter};
+
+    #[test]
+    fn test_belt_reproducible() {
+        let belt = AsteroidBelt { count: 10, ..default() };
+        let positions = |seed| -> Vec<_> {
+            belt.mk_asteroids(seed, default_epoch())
+                .iter()
+                .map(|props| props.sun_ecliptic_position(default_epoch()))
+                .collect()
+        };
+        assert_eq!(positions(SimSeed(1)), positions(SimSeed(1)));
+        assert_ne!(positions(SimSeed(1)), positions(SimSeed(2)));
+    }
+
+    #[test]
+    fn test_belt_same_seed_same_elements() {
+        let belt = AsteroidBelt { count: 100, ..default() };
+        let elements = || -> Vec<_> {
+            belt.mk_asteroids(SimSeed(42), default_epoch())
+                .iter()
+                .map(|props| props.elements_at(default_epoch()))
+                .collect()
+        };
+        let first = elements();
+        assert!(first.iter().all(Option::is_some));
+        assert_eq!(first, elements());
+    }
+
+    #[test]
+    fn test_belt_between_mars_and_jupiter() {
+        let belt = AsteroidBelt { count: 500, ..default() };
+        let au = Length::new::<astronomical_unit>(1.).get::<meter>();
+        for props in belt.mk_asteroids(SimSeed::default(), default_epoch()) {
+            let a = props.apsis().get::<astronomical_unit>() / (1. + props.eccentricity());
+            assert!((2.1..=3.3).contains(&a), "{}", a);
+            let pos = props.sun_ecliptic_position(default_epoch());
+            assert!((pos.z / pos.magnitude()).abs() <= (10f64).to_radians().sin() + 1e-9);
+            assert!(pos.magnitude() / au < 3.3 * 1.2 + 1e-9);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::{prelude::*, render::render_resource::Face};
+
+// This turns on the glow around the bodies with atmospheres. It is off unless the plugin is built
+// with atmospheres. Changes take effect at startup.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub struct AtmosphereGlow(pub bool);
+
+// This is the glow of a body's atmosphere. It is drawn as a shell around the body, thickness_radii
+// of the body's radius thick, so it scales with the body's avatar.
+#[derive(Clone, Debug)]
+pub struct Atmosphere {
+    pub thickness_radii: f32,
+
+    // The alpha channel controls how bright the glow is.
+    pub color: Color,
+}
+
+impl Atmosphere {
+    // Return the scale of the shell relative to the avatar
+    pub fn shell_scale(&self) -> f32 {
+        1. + self.thickness_radii
+    }
+
+    // Only the far side of the shell is drawn, so the body hides all of it but the rim showing
+    // around the body's edge. The glow adds to what is behind it, so it brightens the sky around
+    // the body without covering the body, its night side included.
+    pub fn mk_material(&self) -> StandardMaterial {
+        StandardMaterial {
+            base_color: self.color,
+            alpha_mode: AlphaMode::Add,
+            unlit: true,
+            cull_mode: Some(Face::Front),
+            ..default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atmosphere_material_draws_only_far_side() {
+        let atmosphere = Atmosphere {
+            thickness_radii: 0.06,
+            color: Color::srgba(0.4, 0.6, 1., 0.35),
+        };
+        let material = atmosphere.mk_material();
+        assert_eq!(material.cull_mode, Some(Face::Front));
+        assert_eq!(material.alpha_mode, AlphaMode::Add);
+        assert_eq!(atmosphere.shell_scale(), 1.06);
+    }
+}
@@ -0,0 +1,265 @@
+use std::{collections::HashSet, f64::consts::PI};
+
+use bevy::prelude::*;
+
+use crate::{
+    clock::SimClock,
+    occultations::Occultations,
+    selection::Selected,
+    simulation::{Body, SolarSystem},
+    uom_wrapper::si::{f64::Time, time::day},
+    SimSet, Simulation,
+};
+
+// The key switching the auto-slowdown on and off
+const TOGGLE_KEY: KeyCode = KeyCode::Insert;
+
+// The most in degrees the Moon may be off of the line through the Sun and Earth, as seen from
+// Earth, at new or full moon for there to be an eclipse. It allows for the Moon's parallax, so it
+// is generous for lunar eclipses.
+const ECLIPSE_LIMIT_DEG: f64 = 1.5;
+
+// The time around now is sampled for events this many times per window, but at least every
+// MAX_INTERVAL_DAYS, so the Moon moves no more than a few degrees between samples.
+const SAMPLES_PER_WINDOW: f64 = 8.;
+const MAX_INTERVAL_DAYS: f64 = 0.25;
+
+// This slows the simulation down around events, so they don't flash by at a high time scale. While
+// it is enabled, the time scale is multiplied by the factor from the window before each event until
+// the window after it. The events are the eclipses of the Sun and the Moon, and the selected body's
+// conjunctions with the Sun as seen from the observer of the occultations and its closest
+// approaches to its primary. They are predicted along the Kepler orbits the bodies are currently
+// on, so an event isn't stepped over when a step spans more than the window. The time scale itself
+// is left alone, so it can still be changed while slowed, and the simulation speeds back up to it
+// afterward.
+#[derive(Clone, Debug, PartialEq, Resource)]
+pub struct AutoSlowdown {
+    pub enabled: bool,
+    pub window: Time,
+    pub factor: f64,
+}
+
+impl Default for AutoSlowdown {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: Time::new::<day>(2.),
+            factor: 1. / 16.,
+        }
+    }
+}
+
+// These are the events the simulation slows down for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Watched {
+    Eclipse,
+    // The body's conjunction with the Sun as seen from the observer
+    Conjunction { body: Body, observer: Body },
+    // The body's closest approach to its primary
+    Periapsis(Body),
+}
+
+impl Watched {
+    // Return the events to watch for in a simulation of the given bodies
+    fn all(bodies: &HashSet<Body>, selected: Option<Body>, observer: Body) -> Vec<Self> {
+        let mut watched = Vec::new();
+        if [Body::Sun, Body::Earth, Body::Moon].iter().all(|body| bodies.contains(body)) {
+            watched.push(Self::Eclipse);
+        }
+        if let Some(body) = selected.filter(|body| bodies.contains(body)) {
+            if ![Body::Sun, observer].contains(&body) && bodies.contains(&observer) {
+                watched.push(Self::Conjunction { body, observer });
+            }
+            if body.primary().is_some_and(|primary| bodies.contains(&primary)) {
+                watched.push(Self::Periapsis(body));
+            }
+        }
+        watched
+    }
+
+    fn describe(&self, sim: &Simulation) -> String {
+        match *self {
+            Self::Eclipse => "an eclipse".to_string(),
+            Self::Conjunction { body, .. } => format!("{}'s conjunction", sim.name_of(body)),
+            Self::Periapsis(body) => format!("{}'s periapsis", sim.name_of(body)),
+        }
+    }
+
+    // Return the quantity reaching a local minimum at the event, an angle in radians or a distance
+    // in meters, the given span from now
+    fn gap_after(&self, sys: &SolarSystem, span: Time) -> f64 {
+        let pos = |body| sys.predicted_position_of(body, span);
+        match *self {
+            Self::Eclipse => {
+                let earth = pos(Body::Earth);
+                let elongation = (pos(Body::Moon) - earth).angle(&(pos(Body::Sun) - earth));
+                elongation.min(PI - elongation)
+            }
+            Self::Conjunction { body, observer } => {
+                let from = pos(observer);
+                (pos(body) - from).angle(&(pos(Body::Sun) - from))
+            }
+            Self::Periapsis(body) => (pos(body) - pos(body.primary().unwrap())).norm(),
+        }
+    }
+
+    // Return the most the gap may be at its minimum for the event to happen
+    fn limit(&self) -> f64 {
+        match self {
+            Self::Eclipse => ECLIPSE_LIMIT_DEG.to_radians(),
+            _ => f64::INFINITY,
+        }
+    }
+
+    // Determine whether the event happens between the given spans from now, the first negative for
+    // the past, by sampling the gap every interval. A sample smaller than its neighbors marks a
+    // minimum. Its value is refined with the parabola through the three samples before it's
+    // compared with the limit.
+    fn happens_between(&self, sys: &SolarSystem, from: Time, to: Time, interval: Time) -> bool {
+        let count = ((to - from).get::<day>() / interval.get::<day>()).ceil() as usize;
+        let gaps: Vec<f64> =
+            (0..=count).map(|i| self.gap_after(sys, from + interval * i as f64)).collect();
+        gaps.windows(3).any(|w| {
+            let curvature = w[0] - 2. * w[1] + w[2];
+            let least = w[1] - (w[2] - w[0]).powi(2) / (8. * curvature);
+            w[1] < w[0] && w[1] <= w[2] && least < self.limit()
+        })
+    }
+}
+
+fn toggle_slowdown(input: Res<ButtonInput<KeyCode>>, mut slowdown: ResMut<AutoSlowdown>) {
+    if input.just_pressed(TOGGLE_KEY) {
+        slowdown.enabled = !slowdown.enabled;
+        info!("Auto-slowdown: {}", slowdown.enabled);
+    }
+}
+
+// Before each step, the clock is slowed if an event happens within the window of the time from now
+// to the end of the step. While slowed, the step is short, so the simulation speeds back up once
+// the event is a window behind it.
+fn slow_near_events(
+    fixed: Res<bevy::time::Time<Fixed>>,
+    (slowdown, selected, occultations): (Res<AutoSlowdown>, Res<Selected>, Res<Occultations>),
+    sim: Res<Simulation>,
+    mut clock: ResMut<SimClock>,
+) {
+    if clock.paused || clock.orbits_paused || clock.real_time {
+        return;
+    }
+
+    let near = if slowdown.enabled {
+        let window = slowdown.window;
+        let to = clock.step_span(fixed.timestep().as_secs_f64()) + window;
+        let interval = (window / SAMPLES_PER_WINDOW).min(Time::new::<day>(MAX_INTERVAL_DAYS));
+        Watched::all(&sim.bodies(), selected.0, occultations.observer)
+            .into_iter()
+            .find(|event| event.happens_between(&sim.solar_system, -window, to, interval))
+    } else {
+        None
+    };
+    let factor = if near.is_some() { slowdown.factor } else { 1. };
+    if clock.slowdown != factor {
+        clock.slowdown = factor;
+        match near {
+            Some(event) => info!("Slowing down for {}", event.describe(&sim)),
+            None => info!("Speeding back up"),
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<AutoSlowdown>()
+        .add_systems(Update, toggle_slowdown)
+        .add_systems(FixedUpdate, slow_near_events.before(SimSet::Advance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    // Determine whether the event happens within a day of the given number of days from now
+    fn happens_around(sim: &Simulation, event: Watched, days: f64) -> bool {
+        let (span, window) = (Time::new::<day>(days), Time::new::<day>(1.));
+        let interval = Time::new::<day>(MAX_INTERVAL_DAYS);
+        event.happens_between(&sim.solar_system, span - window, span + window, interval)
+    }
+
+    #[test]
+    fn test_eclipse_predicted() {
+        // The Moon was full on 2023-01-07 and new on 2023-01-21, but neither was eclipsed.
+        let mut sim = Simulation::init_at(default_epoch());
+        for days in [5.8, 20.4] {
+            assert!(!happens_around(&sim, Watched::Eclipse, days), "{}", days);
+        }
+
+        // The simulated Moon crosses the Sun about 111 days in, a day or two after the real solar
+        // eclipse of 2023-04-20.
+        sim.advance_by(Time::new::<day>(100.));
+        assert!(happens_around(&sim, Watched::Eclipse, 11.));
+        assert!(!happens_around(&sim, Watched::Eclipse, 4.));
+    }
+
+    #[test]
+    fn test_periapsis_predicted() {
+        let sim = Simulation::init_at(default_epoch());
+        let next = sim.solar_system.next_periapsis_time(Body::Mercury).unwrap();
+        let next = next - sim.current_time();
+        let event = Watched::Periapsis(Body::Mercury);
+        assert!(happens_around(&sim, event, next.get::<day>()));
+        // Mercury is at aphelion about 44 days after perihelion.
+        assert!(!happens_around(&sim, event, next.get::<day>() + 44.));
+    }
+
+    #[test]
+    fn test_conjunction_predicted() {
+        // Mercury passed between Earth and the Sun on 2023-01-07.
+        let sim = Simulation::init_at(default_epoch());
+        let event = Watched::Conjunction { body: Body::Mercury, observer: Body::Earth };
+        assert!(happens_around(&sim, event, 6.5));
+        assert!(!happens_around(&sim, event, 20.));
+    }
+
+    #[test]
+    fn test_slows_before_eclipse() {
+        let mut sim = Simulation::init_at(default_epoch());
+        sim.advance_by(Time::new::<day>(100.));
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(sim)
+            .insert_resource(SimClock { time_scale: 64., ..default() })
+            .insert_resource(AutoSlowdown { enabled: true, ..default() })
+            .init_resource::<Selected>()
+            .init_resource::<Occultations>()
+            .add_systems(Update, slow_near_events);
+        app.update();
+        assert_eq!(app.world().resource::<SimClock>().slowdown, 1.);
+
+        // A step at this time scale spans more than a day, so the slowdown starts a step before
+        // the window.
+        app.world_mut().resource_mut::<Simulation>().advance_by(Time::new::<day>(8.));
+        app.update();
+        let clock = app.world().resource::<SimClock>();
+        assert_eq!(clock.slowdown, AutoSlowdown::default().factor);
+        assert_eq!(clock.time_scale, 64.);
+
+        app.world_mut().resource_mut::<AutoSlowdown>().enabled = false;
+        app.update();
+        assert_eq!(app.world().resource::<SimClock>().slowdown, 1.);
+    }
+
+    #[test]
+    fn test_watched_events() {
+        let bodies = Simulation::init_at(default_epoch()).bodies();
+        assert_eq!(Watched::all(&bodies, None, Body::Earth), vec![Watched::Eclipse]);
+        assert_eq!(
+            Watched::all(&bodies, Some(Body::Mars), Body::Earth),
+            vec![
+                Watched::Eclipse,
+                Watched::Conjunction { body: Body::Mars, observer: Body::Earth },
+                Watched::Periapsis(Body::Mars),
+            ]
+        );
+        assert_eq!(Watched::all(&bodies, Some(Body::Sun), Body::Earth), vec![Watched::Eclipse]);
+    }
+}
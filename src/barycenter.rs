@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+use crate::{simulation::Body, DrawnPositions, Scenarios, Simulation};
+
+// The key toggling the marker
+const TOGGLE_KEY: KeyCode = KeyCode::KeyY;
+
+// The length in AU of each arm of the marker's cross, a little shorter than the Sun's radius
+const MARKER_ARM_AU: f32 = 0.003;
+
+const MARKER_COLOR: Color = Color::srgb(0., 1., 0.5);
+
+// This controls the marker at the barycenter of the solar system, the point the Sun and the planets
+// all orbit. The marker is a cross joined to the Sun's center by a line. The barycenter is never
+// more than about two solar radii from the Sun's center, so the marker is hidden inside the Sun's
+// avatar unless the bodies are drawn at their true sizes.
+#[derive(Default, Resource)]
+pub struct BarycenterMarker {
+    pub visible: bool,
+}
+
+// Y toggles the marker. Showing it logs how far the barycenter is from the Sun's center.
+fn toggle_marker(
+    input: Res<ButtonInput<KeyCode>>,
+    sim: Res<Simulation>,
+    mut marker: ResMut<BarycenterMarker>,
+) {
+    if input.just_pressed(TOGGLE_KEY) {
+        marker.visible = !marker.visible;
+        if marker.visible {
+            let offset = sim.barycenter().distance(sim.position_of(Body::Sun));
+            let sun_radii = offset / sim.radius_of(Body::Sun);
+            info!("Barycenter: {:.2} solar radii from the Sun's center", sun_radii);
+        }
+    }
+}
+
+// The marker is drawn every frame shifted along with the Sun's avatar, so it stays attached to it
+// between steps.
+fn draw_marker(
+    sim: Res<Simulation>,
+    marker: Res<BarycenterMarker>,
+    drawn: DrawnPositions,
+    mut gizmos: Gizmos,
+) {
+    if !marker.visible {
+        return;
+    }
+
+    let center = sim.barycenter() + drawn.shift_of(Body::Sun);
+    for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+        gizmos.line(center - MARKER_ARM_AU * axis, center + MARKER_ARM_AU * axis, MARKER_COLOR);
+    }
+    gizmos.line(drawn.of(Body::Sun), center, MARKER_COLOR.with_alpha(0.5));
+}
+
+// Recenter the simulation on the barycenter, so the Sun wobbles around the origin rather than the
+// whole solar system drifting away from it. This must be called after setup and after any custom
+// bodies are added, before the app runs. Any scenario loaded later is recentered too.
+pub fn center_on_barycenter(app: &mut App) -> &mut App {
+    app.world_mut().resource_mut::<Simulation>().recenter_on_barycenter();
+    if let Some(mut scenarios) = app.world_mut().get_resource_mut::<Scenarios>() {
+        scenarios.barycentric = true;
+    }
+    app
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<BarycenterMarker>()
+        .add_systems(Update, (toggle_marker, draw_marker.after(crate::update_avatars)))
+}
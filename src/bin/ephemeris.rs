@@ -0,0 +1,72 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, BufWriter, Write},
+    process::ExitCode,
+};
+
+use uom::si::f64;
+
+const USAGE: &str = "usage: ephemeris [--epoch EPOCH] START_JD END_JD STEP_DAYS [OUTPUT.csv]";
+
+fn parse_arg(arg: Option<&String>, name: &str) -> Result<f64, String> {
+    let arg = arg.ok_or(format!("missing {}", name))?;
+    arg.parse().map_err(|_| format!("{} is not a number: {}", name, arg))
+}
+
+// Remove the `--epoch EPOCH` option from the arguments, returning the epoch the simulation starts
+// at. It's the simulation's default epoch when the option isn't given.
+fn take_epoch(args: &mut Vec<String>) -> Result<f64::Time, String> {
+    let Some(at) = args.iter().position(|arg| arg == "--epoch") else {
+        return Ok(solar_system::default_epoch());
+    };
+    args.remove(at);
+    if at == args.len() {
+        return Err("--epoch needs an epoch".to_string());
+    }
+    let arg = args.remove(at);
+    solar_system::parse_epoch(&arg)
+        .ok_or(format!("EPOCH is not a Julian Date or UTC date: {}", arg))
+}
+
+// This writes the positions of the bodies as simulated to a CSV file without opening a window. The
+// CSV is written to standard output when no output file is given. The simulation starts at the
+// given epoch, like the app's, so the positions match what the app shows when started there.
+pub fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let parsed = (|| {
+        Ok::<_, String>((
+            take_epoch(&mut args)?,
+            parse_arg(args.first(), "START_JD")?,
+            parse_arg(args.get(1), "END_JD")?,
+            parse_arg(args.get(2), "STEP_DAYS")?,
+        ))
+    })();
+    let (epoch, start_jd, end_jd, step_days) = match parsed {
+        Ok(range) => range,
+        Err(msg) => {
+            eprintln!("{}\n{}", msg, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut out: Box<dyn Write> = match args.get(3) {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(err) => {
+                eprintln!("cannot create {}: {}", path, err);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    match solar_system::write_ephemeris_csv(&mut out, epoch, start_jd, end_jd, step_days)
+        .and_then(|_| out.flush())
+    {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("failed to write the ephemeris: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
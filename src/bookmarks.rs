@@ -0,0 +1,170 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    camera_animation::{CameraAnimation, DEFAULT_DURATION_S},
+    follow_camera::CameraTarget,
+    Observer,
+};
+
+// The keys of the bookmark slots. The slot number is the key's index plus one.
+const SLOT_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+// The number of values in an encoded observer
+const FIELD_COUNT: usize = 13;
+
+// These are the bookmarked observer states by slot number. They are saved to a file each time one
+// is bookmarked, so they survive restarts.
+#[derive(Resource)]
+pub struct CameraBookmarks {
+    pub path: PathBuf,
+    slots: BTreeMap<u8, Observer>,
+}
+
+impl Default for CameraBookmarks {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("camera-bookmarks.txt"),
+            slots: BTreeMap::new(),
+        }
+    }
+}
+
+// An observer is encoded as its position, facing, up, and focus vectors followed by its orbit
+// radius, separated by spaces. Rust writes the shortest decimal form of a float that reads back to
+// the same float, so decoding the encoding gives back the same observer.
+fn encode(observer: &Observer) -> String {
+    let Observer { position, facing, up, focus, orbit_radius } = observer;
+    [position.to_array(), facing.to_array(), up.to_array(), focus.to_array()]
+        .concat()
+        .iter()
+        .chain([orbit_radius])
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// None is returned if the text isn't an encoded observer.
+fn decode(text: &str) -> Option<Observer> {
+    let vals = text.split_whitespace().map(|x| x.parse().ok()).collect::<Option<Vec<f32>>>()?;
+    if vals.len() != FIELD_COUNT || vals.iter().any(|x| !x.is_finite()) {
+        return None;
+    }
+    let vec = |i: usize| Vec3::from_slice(&vals[i..i + 3]);
+    // The directions were unit length when encoded, so they are used as is to keep them exact.
+    let dir = |i: usize| Some(vec(i)).filter(|v| v.is_normalized()).map(Dir3::new_unchecked);
+    Some(Observer {
+        position: vec(0),
+        facing: dir(3)?,
+        up: dir(6)?,
+        focus: vec(9),
+        orbit_radius: vals[12],
+    })
+}
+
+// Each line of the file is a slot number followed by the encoded observer in that slot.
+fn encode_slots(slots: &BTreeMap<u8, Observer>) -> String {
+    slots.iter().map(|(slot, observer)| format!("{} {}\n", slot, encode(observer))).collect()
+}
+
+fn decode_slots(text: &str) -> BTreeMap<u8, Observer> {
+    text.lines()
+        .filter_map(|line| {
+            let (slot, observer) = line.trim().split_once(' ')?;
+            let slot = slot.parse().ok()?;
+            match decode(observer) {
+                Some(observer) => Some((slot, observer)),
+                None => {
+                    warn!("Ignoring malformed camera bookmark {}", slot);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+// The bookmarks saved in an earlier session are loaded if there are any.
+fn load_bookmarks(mut bookmarks: ResMut<CameraBookmarks>) {
+    if let Ok(text) = fs::read_to_string(&bookmarks.path) {
+        bookmarks.slots = decode_slots(&text);
+    }
+}
+
+// Ctrl plus a number key bookmarks the observer in that slot, and the number key alone flies the
+// observer back to it. Recalling a bookmark stops following any body.
+fn use_bookmarks(
+    input: Res<ButtonInput<KeyCode>>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut target: ResMut<CameraTarget>,
+    mut anim: ResMut<CameraAnimation>,
+    mut observer: Query<&mut Observer>,
+) {
+    let Some(slot) = SLOT_KEYS.iter().position(|key| input.just_pressed(*key)) else {
+        return;
+    };
+    let slot = slot as u8 + 1;
+    let mut observer = observer.single_mut();
+
+    if input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        bookmarks.slots.insert(slot, observer.clone());
+        let path = &bookmarks.path;
+        match fs::write(path, encode_slots(&bookmarks.slots)) {
+            Ok(()) => info!("Bookmarked the camera in slot {}", slot),
+            Err(err) => error!("Failed to save camera bookmarks to {}: {}", path.display(), err),
+        }
+    } else if let Some(saved) = bookmarks.slots.get(&slot) {
+        target.0 = None;
+        observer.set_focus(saved.focus);
+        anim.start(observer.pose(), saved.pose(), DEFAULT_DURATION_S);
+        info!("Camera bookmark: {}", slot);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<CameraBookmarks>()
+        .add_systems(Startup, load_bookmarks)
+        .add_systems(Update, use_bookmarks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observer() -> Observer {
+        let mut observer = Observer::new();
+        observer.set_focus(Vec3::new(0.983_3, -0.1, 1e-5));
+        let facing = Dir3::new(Vec3::new(-1., 1., -1.)).unwrap();
+        let up = Dir3::new(Vec3::Z - Vec3::Z.dot(*facing) * *facing).unwrap();
+        observer.orbit(facing, up, 1.234_567);
+        observer
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        assert_eq!(decode(&encode(&observer())), Some(observer()));
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed() {
+        assert_eq!(decode("1 2 3"), None);
+        assert_eq!(decode("0 0 80 0 0 -2 0 1 0 0 0 0 80"), None);
+        assert_eq!(decode("0 0 80 0 0 -1 0 1 0 0 0 0 NaN"), None);
+    }
+
+    #[test]
+    fn test_slots_round_trip() {
+        let slots = BTreeMap::from([(1, Observer::new()), (7, observer())]);
+        assert_eq!(decode_slots(&encode_slots(&slots)), slots);
+    }
+}
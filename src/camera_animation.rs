@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+
+// The default time in seconds a camera animation takes
+pub const DEFAULT_DURATION_S: f32 = 1.;
+
+// This is a position and orientation of the observer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ObserverPose {
+    pub position: Vec3,
+    pub facing: Dir3,
+    pub up: Dir3,
+}
+
+impl ObserverPose {
+    fn rotation(&self) -> Quat {
+        Transform::IDENTITY.looking_to(self.facing, self.up).rotation
+    }
+
+    // This interpolates between this pose and another one. t is the fraction of the way to the
+    // other pose.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let rot = self.rotation().slerp(other.rotation(), t);
+        Self {
+            position: self.position.lerp(other.position, t),
+            facing: rot * Dir3::NEG_Z,
+            up: rot * Dir3::Y,
+        }
+    }
+}
+
+// This eases in and out of a transition, where t is the fraction of the transition's duration that
+// has elapsed.
+fn ease_in_out(t: f32) -> f32 {
+    let t = t.clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+struct Flight {
+    start: ObserverPose,
+    end: ObserverPose,
+    duration: f32,
+    elapsed: f32,
+}
+
+// This animates the observer from one pose to another.
+#[derive(Default, Resource)]
+pub struct CameraAnimation {
+    flight: Option<Flight>,
+}
+
+impl CameraAnimation {
+    // Start animating from one pose to another over the given duration in seconds. This replaces
+    // any animation in progress.
+    pub fn start(&mut self, from: ObserverPose, to: ObserverPose, duration: f32) {
+        self.flight = Some(Flight {
+            start: from,
+            end: to,
+            duration,
+            elapsed: 0.,
+        });
+    }
+
+    // Stop any animation in progress, leaving the observer where it is. This is done when the user
+    // moves the camera, so the animation doesn't fight them.
+    pub fn cancel(&mut self) {
+        self.flight = None;
+    }
+
+    // Tell whether an animation is in progress
+    pub fn is_active(&self) -> bool {
+        self.flight.is_some()
+    }
+
+    // Advance the animation by dt seconds and return the pose the observer should now have. None is
+    // returned if there is no animation in progress.
+    pub fn advance(&mut self, dt: f32) -> Option<ObserverPose> {
+        let flight = self.flight.as_mut()?;
+        flight.elapsed += dt;
+        if flight.elapsed >= flight.duration {
+            let end = flight.end;
+            self.flight = None;
+            Some(end)
+        } else {
+            Some(flight.start.lerp(&flight.end, ease_in_out(flight.elapsed / flight.duration)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poses() -> (ObserverPose, ObserverPose) {
+        let start = ObserverPose {
+            position: Vec3::new(0., -80., 0.),
+            facing: Dir3::Y,
+            up: Dir3::Z,
+        };
+        let end = ObserverPose {
+            position: Vec3::new(0., 0., 80.),
+            facing: Dir3::NEG_Z,
+            up: Dir3::Y,
+        };
+        (start, end)
+    }
+
+    #[test]
+    fn test_animation_midway() {
+        let (start, end) = poses();
+        let mut anim = CameraAnimation::default();
+        anim.start(start, end, DEFAULT_DURATION_S);
+        let mid = anim.advance(DEFAULT_DURATION_S / 2.).unwrap();
+        assert!(mid.position.distance(Vec3::new(0., -40., 40.)) < 1e-4, "{:?}", mid);
+        let between = (*start.facing + *end.facing).normalize();
+        assert!(mid.facing.dot(between) > 0.999_9, "{:?}", mid);
+    }
+
+    #[test]
+    fn test_animation_ends_at_end() {
+        let (start, end) = poses();
+        let mut anim = CameraAnimation::default();
+        anim.start(start, end, DEFAULT_DURATION_S);
+        assert_ne!(anim.advance(0.6), Some(end));
+        assert_eq!(anim.advance(0.6), Some(end));
+        assert_eq!(anim.advance(0.1), None);
+    }
+
+    #[test]
+    fn test_animation_cancelled() {
+        let (start, end) = poses();
+        let mut anim = CameraAnimation::default();
+        anim.start(start, end, DEFAULT_DURATION_S);
+        anim.advance(0.2);
+        assert!(anim.is_active());
+        anim.cancel();
+        assert!(!anim.is_active());
+        assert_eq!(anim.advance(0.2), None);
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+
+use crate::{
+    selection::Selected,
+    uom_wrapper::{
+        jd_to_gregorian,
+        si::{
+            f64::Time,
+            time::{day, minute, second},
+        },
+    },
+    Simulation,
+};
+
+// The slowest and fastest time scales selectable from the keyboard
+const MIN_TIME_SCALE: f64 = 0.25;
+const MAX_TIME_SCALE: f64 = 4096.;
+
+// The key switching real-time mode on and off
+const REAL_TIME_KEY: KeyCode = KeyCode::KeyT;
+
+// The keys stepping the paused simulation back and forward
+const STEP_BACK_KEY: KeyCode = KeyCode::KeyJ;
+const STEP_FORWARD_KEY: KeyCode = KeyCode::KeyK;
+
+// The key jumping to the selected body's next perihelion
+const PERIHELION_KEY: KeyCode = KeyCode::KeyN;
+
+// The simulated time in minutes that passes each step at a time scale of 1 when the steps are
+// taken at REFERENCE_HZ
+pub const STEP_MIN: f64 = 30.;
+
+// The rate in Hz of the FixedUpdate steps STEP_MIN is given for. It is Bevy's default rate.
+pub const REFERENCE_HZ: f64 = 64.;
+
+// This controls the passage of simulated time. The time scale multiplies the simulated time that
+// passes each step. A step's span is proportional to the real time it covers, so the simulated
+// time passing each real second, STEP_MIN * REFERENCE_HZ minutes at a time scale of 1, doesn't
+// depend on the FixedUpdate rate. A lower rate takes fewer, longer steps, each still advanced in
+// sub-steps no longer than STEP_MIN, so it saves the work done once per step, like drawing the
+// overlays, at the cost of the bodies moving in coarser steps. In real-time mode, the simulated
+// time that passes each step is the real time the step covers instead, so the simulation keeps
+// pace with the actual sky. While paused, the single steps are the number of steps of STEP_MIN
+// still to be taken, negative to step backward. The slowdown multiplies the time scale while the
+// auto-slowdown is slowing the simulation around an event. It is 1 otherwise. While only the orbits
+// are paused, the steps turn the bodies on their axes without moving them. See
+// Simulation::advance_spin_by.
+#[derive(Resource)]
+pub struct SimClock {
+    pub paused: bool,
+    pub orbits_paused: bool,
+    pub time_scale: f64,
+    pub slowdown: f64,
+    pub real_time: bool,
+    pub single_steps: i32,
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            orbits_paused: false,
+            time_scale: 1.,
+            slowdown: 1.,
+            real_time: false,
+            single_steps: 0,
+        }
+    }
+}
+
+impl SimClock {
+    // Return the simulated time to pass in a step covering step_real_s seconds of real time
+    pub fn step_span(&self, step_real_s: f64) -> Time {
+        if self.real_time {
+            Time::new::<second>(step_real_s)
+        } else {
+            let scale = self.time_scale * self.slowdown;
+            Time::new::<minute>(scale * STEP_MIN * REFERENCE_HZ * step_real_s)
+        }
+    }
+
+    // Return the simulated days that pass each real second when a step covers step_real_s seconds
+    // of real time. At large time scales, a step is taken in several sub-steps, but the time the
+    // step covers is the same.
+    pub fn sim_days_per_sec(&self, step_real_s: f64) -> f64 {
+        if self.paused {
+            0.
+        } else {
+            self.step_span(step_real_s).get::<day>() / step_real_s
+        }
+    }
+
+    // Return the simulated time to pass in the next single step, and count it as taken. None is
+    // returned if no single step is pending.
+    pub fn take_single_step(&mut self) -> Option<Time> {
+        let dir = self.single_steps.signum();
+        if dir == 0 {
+            return None;
+        }
+        self.single_steps -= dir;
+        Some(Time::new::<minute>(dir as f64 * STEP_MIN))
+    }
+}
+
+// The spacebar pauses and resumes the simulation. Since the simulation advances by a fixed step
+// each FixedUpdate rather than by the elapsed wall-clock time, it resumes at the same Julian Date
+// it was paused at. With shift held, it pauses and resumes only the orbits, so the bodies hold
+// their places but keep spinning. The orbits resume from the same Julian Date too.
+fn toggle_pause(input: Res<ButtonInput<KeyCode>>, mut clock: ResMut<SimClock>) {
+    if !input.just_pressed(KeyCode::Space) {
+        return;
+    }
+    if input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        clock.orbits_paused = !clock.orbits_paused;
+        info!("Orbits paused: {}", clock.orbits_paused);
+    } else {
+        clock.paused = !clock.paused;
+    }
+}
+
+// The comma and period keys halve and double the time scale.
+fn change_time_scale(input: Res<ButtonInput<KeyCode>>, mut clock: ResMut<SimClock>) {
+    if input.just_pressed(KeyCode::Comma) {
+        clock.time_scale = (clock.time_scale / 2.).max(MIN_TIME_SCALE);
+    } else if input.just_pressed(KeyCode::Period) {
+        clock.time_scale = (clock.time_scale * 2.).min(MAX_TIME_SCALE);
+    }
+}
+
+// While paused, J and K step the simulation back and forward by one step. Resuming drops any
+// steps not yet taken.
+fn single_step(input: Res<ButtonInput<KeyCode>>, mut clock: ResMut<SimClock>) {
+    if !clock.paused {
+        clock.single_steps = 0;
+    } else if input.just_pressed(STEP_BACK_KEY) {
+        clock.single_steps -= 1;
+    } else if input.just_pressed(STEP_FORWARD_KEY) {
+        clock.single_steps += 1;
+    }
+}
+
+fn toggle_real_time(input: Res<ButtonInput<KeyCode>>, mut clock: ResMut<SimClock>) {
+    if input.just_pressed(REAL_TIME_KEY) {
+        clock.real_time = !clock.real_time;
+        info!("Real-time mode: {}", clock.real_time);
+    }
+}
+
+// N fast-forwards the simulation to the selected body's next closest approach to its primary, its
+// perihelion if it orbits the Sun. Jumping far ahead, e.g., to Halley's next return, takes a
+// moment, since the simulation is still advanced in steps no longer than STEP_MIN.
+fn jump_to_perihelion(
+    input: Res<ButtonInput<KeyCode>>,
+    selected: Res<Selected>,
+    mut sim: ResMut<Simulation>,
+) {
+    if !input.just_pressed(PERIHELION_KEY) {
+        return;
+    }
+    let Some(body) = selected.0 else {
+        return;
+    };
+    match sim.jump_to_next_periapsis(body) {
+        Some(time) => info!("{} at periapsis: {}", sim.name_of(body), jd_to_gregorian(time)),
+        None => info!("{} won't pass periapsis again", sim.name_of(body)),
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<SimClock>().add_systems(
+        Update,
+        (toggle_pause, change_time_scale, toggle_real_time, single_step, jump_to_perihelion),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_span_scaled() {
+        let clock = SimClock { time_scale: 4., ..default() };
+        assert_eq!(clock.step_span(1. / 64.), Time::new::<minute>(120.));
+    }
+
+    #[test]
+    fn test_step_span_slowed() {
+        let clock = SimClock { time_scale: 64., slowdown: 1. / 16., ..default() };
+        assert_eq!(clock.step_span(1. / 64.), Time::new::<minute>(4. * STEP_MIN));
+        let clock = SimClock { slowdown: 1. / 16., real_time: true, ..default() };
+        assert_eq!(clock.step_span(1. / 64.), Time::new::<second>(1. / 64.));
+    }
+
+    #[test]
+    fn test_sim_days_per_sec() {
+        let clock = SimClock { time_scale: 48., ..default() };
+        assert_eq!(clock.sim_days_per_sec(1. / 64.), 64.);
+        let clock = SimClock { real_time: true, ..default() };
+        assert_eq!(clock.sim_days_per_sec(1. / 64.), 1. / 86_400.);
+        let clock = SimClock { paused: true, ..default() };
+        assert_eq!(clock.sim_days_per_sec(1. / 64.), 0.);
+    }
+
+    #[test]
+    fn test_sim_days_per_sec_independent_of_rate() {
+        let clock = SimClock { time_scale: 2., ..default() };
+        let exp = 2. * STEP_MIN * REFERENCE_HZ / 1_440.;
+        for hz in [8., 30., 64., 120.] {
+            assert!((clock.sim_days_per_sec(1. / hz) - exp).abs() < 1e-12, "{}", hz);
+        }
+        assert_eq!(clock.step_span(1. / 8.), Time::new::<minute>(8. * 2. * STEP_MIN));
+    }
+
+    #[test]
+    fn test_take_single_step() {
+        let mut clock = SimClock { paused: true, single_steps: -1, ..default() };
+        assert_eq!(clock.take_single_step(), Some(Time::new::<minute>(-STEP_MIN)));
+        assert_eq!(clock.take_single_step(), None);
+    }
+
+    #[test]
+    fn test_step_span_real_time() {
+        let clock = SimClock { time_scale: 4., real_time: true, ..default() };
+        assert_eq!(clock.step_span(1. / 64.), Time::new::<second>(1. / 64.));
+    }
+}
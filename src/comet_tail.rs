@@ -0,0 +1,123 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+
+use crate::{simulation::Body, BodyModel, SimSet, Simulation};
+
+// The length in AU of a comet's tail when the comet is 1 AU from the Sun. The tail's length falls
+// off with the sunlight driving it.
+const TAIL_LENGTH_AT_1AU: f32 = 0.1;
+
+// Past this distance in AU from the Sun, around Neptune's orbit, a comet has no tail.
+const TAIL_FADE_DIST_AU: f32 = 30.;
+
+// The width of the tail's end as a fraction of the tail's length
+const TAIL_SPREAD: f32 = 0.15;
+
+// A comet is only labeled when it is within this distance in AU of the Sun.
+const LABEL_DIST_AU: f32 = 5.;
+
+// Return the length in AU of the tail of a comet sun_dist AU from the Sun. The length is
+// proportional to the sunlight the comet receives, less what it would receive out at the fade
+// distance, so the tail shrinks to nothing there.
+fn tail_length(sun_dist: f32) -> f32 {
+    let sunlight = |dist: f32| dist.powi(-2);
+    (TAIL_LENGTH_AT_1AU * (sunlight(sun_dist) - sunlight(TAIL_FADE_DIST_AU))).max(0.)
+}
+
+// This is the tail of a comet.
+#[derive(Component)]
+struct CometTail(Body);
+
+// The tail is a cone with its point at the comet, widening away from it. It is scaled to its length
+// and width as the comet moves.
+fn create_tails(
+    sim: Res<Simulation>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    bodies: Query<&Body>,
+) {
+    let mesh = meshes.add(Cone { radius: 0.5, height: 1. }.mesh());
+    for body in bodies.iter().filter(|body| body.is_comet()) {
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: materials.add(StandardMaterial {
+                    base_color: sim.color_of(*body).with_alpha(0.4),
+                    emissive: sim.color_of(*body).to_linear(),
+                    alpha_mode: AlphaMode::Add,
+                    unlit: true,
+                    ..default()
+                }),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            NotShadowCaster,
+            NotShadowReceiver,
+            CometTail(*body),
+        ));
+    }
+}
+
+// The tail streams away from the Sun.
+fn update_tails(
+    sim: Res<Simulation>,
+    mut tails: Query<(&CometTail, &mut Transform, &mut Visibility)>,
+) {
+    let sun_pos = sim.position_of(Body::Sun);
+    for (tail, mut transform, mut visibility) in &mut tails {
+        let comet_pos = sim.position_of(tail.0);
+        let length = tail_length(comet_pos.distance(sun_pos));
+        let Some(away) = Dir3::new(comet_pos - sun_pos).ok().filter(|_| length > 0.) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        *transform = Transform::from_translation(comet_pos + length / 2. * *away)
+            .with_rotation(Quat::from_rotation_arc(Vec3::NEG_Y, *away))
+            .with_scale(Vec3::new(TAIL_SPREAD * length, length, TAIL_SPREAD * length));
+    }
+}
+
+// Comets far from the Sun are unlabeled so their labels don't clutter the outer solar system. This
+// runs after the labels are updated, so it only ever hides them.
+fn update_comet_labels(
+    sim: Res<Simulation>,
+    bodies: Query<(&Body, &BodyModel)>,
+    mut labels: Query<&mut Visibility>,
+) {
+    let sun_pos = sim.position_of(Body::Sun);
+    for (_, model) in bodies.iter().filter(|(body, _)| body.is_comet()) {
+        if model.position().distance(sun_pos) < LABEL_DIST_AU {
+            continue;
+        }
+        if let Some(mut visibility) = model.label().and_then(|label| labels.get_mut(label).ok()) {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(crate::SpawnBodies, create_tails.after(crate::create_body_models))
+        .add_systems(FixedUpdate, update_tails.in_set(SimSet::Render))
+        .add_systems(Update, update_comet_labels.after(crate::update_labels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_length_grows_toward_sun() {
+        assert!(tail_length(0.6) > tail_length(1.));
+        assert!(tail_length(1.) > tail_length(5.));
+    }
+
+    #[test]
+    fn test_tail_length_vanishes_past_neptune() {
+        assert_eq!(tail_length(TAIL_FADE_DIST_AU), 0.);
+        assert_eq!(tail_length(35.), 0.);
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{selection::cursor_ray, starfield::ecliptic_to_equatorial};
+
+// The key toggling the readout
+const TOGGLE_KEY: KeyCode = KeyCode::F1;
+
+// Return the longitude and latitude in degrees of a direction, with the longitude from 0 to 360
+// measured from +X toward +Y and the latitude measured toward +Z. Every longitude meets at the
+// poles, so the longitude there is 0.
+fn lon_lat(direction: Vec3) -> (f32, f32) {
+    let dir = direction.normalize_or_zero();
+    let lon = if dir.truncate().length() < 1e-6 {
+        0.
+    } else {
+        dir.y.atan2(dir.x).to_degrees().rem_euclid(360.)
+    };
+    (lon, dir.z.clamp(-1., 1.).asin().to_degrees())
+}
+
+// Format a right ascension in degrees as hours, minutes, and seconds, e.g., "5h 35m 17s"
+fn fmt_ra(ra_deg: f32) -> String {
+    let secs = (ra_deg / 15. * 3600.).round() as u32 % (24 * 3600);
+    format!("{}h {:02}m {:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// Format a declination or latitude in degrees as signed degrees and arcminutes, e.g., "+22° 01′"
+fn fmt_dec(dec_deg: f32) -> String {
+    let arcmin = (dec_deg.abs() * 60.).round() as u32;
+    let sign = if dec_deg < 0. { '-' } else { '+' };
+    format!("{}{}° {:02}′", sign, arcmin / 60, arcmin % 60)
+}
+
+// The readout gives the equatorial and ecliptic coordinates of a direction in the ecliptic frame.
+fn readout_text(direction: Vec3) -> String {
+    let (ra, dec) = lon_lat(ecliptic_to_equatorial(direction));
+    let (lon, lat) = lon_lat(direction);
+    format!(
+        "RA {}  Dec {}\necliptic lon {:.2}°  lat {}",
+        fmt_ra(ra),
+        fmt_dec(dec),
+        lon,
+        fmt_dec(lat)
+    )
+}
+
+#[derive(Component)]
+struct CursorCoords;
+
+// The readout is centered along the bottom of the window.
+fn create_readout(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.),
+                width: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    visibility: Visibility::Hidden,
+                    ..TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 16.,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )
+                    .with_text_justify(JustifyText::Center)
+                },
+                CursorCoords,
+            ));
+        });
+}
+
+fn toggle_readout(
+    input: Res<ButtonInput<KeyCode>>,
+    mut readout: Query<&mut Visibility, With<CursorCoords>>,
+) {
+    if input.just_pressed(TOGGLE_KEY) {
+        for mut vis in &mut readout {
+            *vis = match *vis {
+                Visibility::Hidden => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+// The stars are so far away that where the cursor points on the celestial sphere only depends on
+// the direction of the ray through the cursor. The readout is blank while the cursor is outside of
+// the window.
+fn update_readout(
+    window: Query<&Window, With<PrimaryWindow>>,
+    cam: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut readout: Query<(&mut Text, &Visibility), With<CursorCoords>>,
+) {
+    let (cam, cam_trans) = cam.single();
+    let value = cursor_ray(window.single(), cam, cam_trans)
+        .map_or(String::new(), |ray| readout_text(*ray.direction));
+    for (mut text, vis) in &mut readout {
+        if *vis != Visibility::Hidden {
+            text.sections[0].value.clone_from(&value);
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Startup, create_readout)
+        .add_systems(Update, (toggle_readout, update_readout).chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lon_lat_along_axes() {
+        assert_eq!(lon_lat(Vec3::X), (0., 0.));
+        assert_eq!(lon_lat(Vec3::NEG_Y), (270., 0.));
+        assert_eq!(lon_lat(Vec3::Z), (0., 90.));
+    }
+
+    #[test]
+    fn test_lon_lat_at_pole_not_nan() {
+        let (lon, lat) = lon_lat(Vec3::new(1e-9, -1e-9, -1.00001));
+        assert_eq!((lon, lat), (0., -90.));
+        let text = readout_text(Vec3::Z);
+        assert!(!text.contains("NaN"), "{}", text);
+    }
+
+    #[test]
+    fn test_readout_ecliptic_pole() {
+        // The north ecliptic pole is at 18h right ascension, and its declination is 90° less the
+        // obliquity.
+        let text = readout_text(Vec3::Z);
+        assert!(text.starts_with("RA 18h 00m 00s  Dec +66° 34′"), "{}", text);
+        assert!(text.ends_with("lat +90° 00′"), "{}", text);
+    }
+
+    #[test]
+    fn test_fmt_ra_wraps_at_a_day() {
+        assert_eq!(fmt_ra(83.82), "5h 35m 17s");
+        assert_eq!(fmt_ra(359.9999), "0h 00m 00s");
+    }
+}
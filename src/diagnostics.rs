@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::{render_settings::RenderSettings, sim_rate::SimRate, Simulation};
+
+// The key toggling the overlay
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+
+#[derive(Component)]
+struct DiagnosticsText;
+
+fn create_overlay(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                right: Val::Px(10.),
+                ..default()
+            })
+        },
+        DiagnosticsText,
+    ));
+}
+
+fn toggle_overlay(
+    input: Res<ButtonInput<KeyCode>>,
+    mut overlay: Query<&mut Visibility, With<DiagnosticsText>>,
+) {
+    if input.just_pressed(TOGGLE_KEY) {
+        for mut vis in &mut overlay {
+            *vis = match *vis {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+// This reports how fast simulated time is passing, how far the simulation has drifted from
+// conserving energy and angular momentum, and the quality preset last selected.
+fn update_overlay(
+    sim: Res<Simulation>,
+    (rate, settings): (Res<SimRate>, Res<RenderSettings>),
+    mut overlay: Query<(&mut Text, &Visibility), With<DiagnosticsText>>,
+) {
+    for (mut text, vis) in &mut overlay {
+        if *vis == Visibility::Hidden {
+            continue;
+        }
+        let diag = sim.diagnostics();
+        text.sections[0].value = [
+            format!("simulated time: {}", rate.description()),
+            format!("energy drift: {:+.3e}", diag.energy_drift),
+            format!("angular momentum drift: {:.3e}", diag.angular_momentum_drift),
+            format!("quality preset: {:?}", settings.preset),
+        ]
+        .join("\n");
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Startup, create_overlay)
+        .add_systems(Update, (toggle_overlay, update_overlay).chain())
+}
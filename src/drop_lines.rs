@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+use crate::{
+    uom_wrapper::si::{angle::degree, f32::Angle},
+    DrawnPositions, Simulation,
+};
+
+// The radius in AU of the marker where a drop line meets the ecliptic plane
+const FOOT_RADIUS_AU: f32 = 0.02;
+
+// This controls the display of lines dropped from the bodies perpendicularly to the ecliptic plane,
+// showing how far above or below it each body is.
+#[derive(Resource)]
+pub struct DropLines {
+    pub visible: bool,
+
+    // Only bodies whose orbits are inclined at least this much to the ecliptic get drop lines, so
+    // the nearly coplanar planets don't clutter the view.
+    pub min_inclination: Angle,
+}
+
+impl Default for DropLines {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            min_inclination: Angle::new::<degree>(3.),
+        }
+    }
+}
+
+// H toggles the drop lines.
+fn toggle_drop_lines(input: Res<ButtonInput<KeyCode>>, mut lines: ResMut<DropLines>) {
+    if input.just_pressed(KeyCode::KeyH) {
+        lines.visible = !lines.visible;
+    }
+}
+
+// The lines are drawn every frame from where the avatars are drawn.
+fn draw_drop_lines(
+    sim: Res<Simulation>,
+    lines: Res<DropLines>,
+    drawn: DrawnPositions,
+    mut gizmos: Gizmos,
+) {
+    if !lines.visible {
+        return;
+    }
+
+    for body in sim.bodies() {
+        if sim.inclination_of(body) < lines.min_inclination {
+            continue;
+        }
+        let pos = drawn.of(body);
+        let foot = Vec3::new(pos.x, pos.y, 0.);
+        let color = *sim.color_of(body);
+        gizmos.line(pos, foot, color);
+        gizmos.circle(foot, Dir3::Z, FOOT_RADIUS_AU, color);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<DropLines>()
+        .add_systems(Update, (toggle_drop_lines, draw_drop_lines.after(crate::update_avatars)))
+}
@@ -0,0 +1,251 @@
+use bevy::prelude::*;
+
+use crate::{
+    shadow::Shadow,
+    simulation::Body,
+    uom_wrapper::si::{f64::Length, length::astronomical_unit},
+    BodyModel, SimSet, Simulation, SolarSystemEvent,
+};
+
+// The color the eclipsed body glows while it is in shadow. It is reddish like the Moon in a total
+// lunar eclipse.
+const TINT: LinearRgba = LinearRgba::rgb(0.6, 0.1, 0.05);
+
+// The number of times per second the tint pulses
+const PULSE_RATE_HZ: f32 = 1.;
+
+// How much of the source's light a body in shadow is missing. A body in the penumbra still sees
+// part of the source, while a body touching the umbra has part of it in complete shadow.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum EclipseSeverity {
+    Penumbral,
+    Umbral,
+}
+
+// A solar eclipse is when the Moon's shadow touches Earth, and a lunar eclipse is when Earth's
+// shadow touches the Moon.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Eclipse {
+    Solar,
+    Lunar,
+}
+
+// These are the eclipses currently happening. A solar eclipse is when the Moon's shadow touches
+// Earth, and a lunar eclipse is when Earth's shadow touches the Moon.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub struct EclipseState {
+    pub solar: Option<EclipseSeverity>,
+    pub lunar: Option<EclipseSeverity>,
+}
+
+// Return how deep into the shadow of the occluder, lit by the Sun, the target reaches. The
+// positions and radii are in AU.
+fn shadow_severity(
+    sun_pos: Vec3,
+    sun_radius: f32,
+    occluder_pos: Vec3,
+    occluder_radius: f32,
+    target_pos: Vec3,
+    target_radius: f32,
+) -> Option<EclipseSeverity> {
+    let au = |x: f32| Length::new::<astronomical_unit>(x as f64);
+    let axis = (occluder_pos - sun_pos).normalize();
+    let behind = (target_pos - occluder_pos).dot(axis);
+    if behind <= 0. {
+        return None;
+    }
+    let off_axis = (target_pos - occluder_pos - behind * axis).length();
+    let gap = au(off_axis - target_radius);
+
+    let shadow = Shadow::new(
+        au(sun_radius),
+        au(occluder_radius),
+        au(occluder_pos.distance(sun_pos)),
+    );
+    // The near side of the target is where the umbra is widest.
+    if gap < shadow.umbra_radius_at(au(behind - target_radius)) {
+        Some(EclipseSeverity::Umbral)
+    } else if gap < shadow.penumbra_radius_at(au(behind)) {
+        Some(EclipseSeverity::Penumbral)
+    } else {
+        None
+    }
+}
+
+// The bodies' true positions are used, since where they are drawn depends on the radial mode.
+fn severity_in(sim: &Simulation, occluder: Body, target: Body) -> Option<EclipseSeverity> {
+    let pos = |body| sim.position_in::<astronomical_unit>(body).as_vec3();
+    shadow_severity(
+        pos(Body::Sun),
+        sim.radius_of(Body::Sun),
+        pos(occluder),
+        sim.radius_of(occluder),
+        pos(target),
+        sim.radius_of(target),
+    )
+}
+
+// Return the events telling how the eclipses changed from the previous state to the current one
+fn eclipse_events(previous: EclipseState, current: EclipseState) -> Vec<SolarSystemEvent> {
+    let changes = [
+        (Eclipse::Solar, previous.solar, current.solar),
+        (Eclipse::Lunar, previous.lunar, current.lunar),
+    ];
+    changes
+        .into_iter()
+        .filter_map(|(eclipse, before, after)| match (before, after) {
+            (None, Some(severity)) => Some(SolarSystemEvent::EclipseStarted { eclipse, severity }),
+            (Some(before), Some(severity)) if before != severity => {
+                Some(SolarSystemEvent::EclipseChanged { eclipse, severity })
+            }
+            (Some(_), None) => Some(SolarSystemEvent::EclipseEnded(eclipse)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn detect_eclipses(
+    sim: Res<Simulation>,
+    mut state: ResMut<EclipseState>,
+    mut events: EventWriter<SolarSystemEvent>,
+) {
+    let current = EclipseState {
+        solar: severity_in(&sim, Body::Moon, Body::Earth),
+        lunar: severity_in(&sim, Body::Earth, Body::Moon),
+    };
+    let previous = *state;
+    if state.set_if_neq(current) {
+        info!("Eclipses: solar {:?}, lunar {:?}", current.solar, current.lunar);
+        events.send_batch(eclipse_events(previous, current));
+    }
+}
+
+// The body in shadow pulses with a reddish glow, brighter when the umbra reaches it.
+fn tint_eclipsed(
+    time: Res<Time>,
+    state: Res<EclipseState>,
+    bodies: Query<(&Body, &BodyModel)>,
+    avatars: Query<&Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !state.is_changed() && state.solar.is_none() && state.lunar.is_none() {
+        return;
+    }
+
+    let pulse = 0.5 + 0.5 * (std::f32::consts::TAU * PULSE_RATE_HZ * time.elapsed_seconds()).sin();
+    for (body, model) in &bodies {
+        let severity = match body {
+            Body::Earth => state.solar,
+            Body::Moon => state.lunar,
+            _ => continue,
+        };
+        let emissive = match severity {
+            None => LinearRgba::BLACK,
+            Some(EclipseSeverity::Penumbral) => TINT * 0.3 * pulse,
+            Some(EclipseSeverity::Umbral) => TINT * pulse,
+        };
+        let material = model.avatar().and_then(|avatar| avatars.get(avatar).ok());
+        if let Some(material) = material.and_then(|handle| materials.get_mut(handle)) {
+            material.emissive = emissive;
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<EclipseState>()
+        .add_systems(FixedUpdate, detect_eclipses.after(SimSet::UpdateBodies))
+        .add_systems(Update, tint_eclipsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // These are in AU.
+    const SUN_RADIUS: f32 = 4.650e-3;
+    const EARTH_RADIUS: f32 = 4.259e-5;
+    const MOON_RADIUS: f32 = 1.161e-5;
+    const KM: f32 = 1. / 1.495_978_7e8;
+
+    // Return the severity of the solar eclipse when the Moon is moon_dist_km from Earth on the
+    // Sun's side and gamma is how far in Earth radii the axis of the Moon's shadow passes from
+    // Earth's center.
+    fn solar(earth_sun_au: f32, moon_dist_km: f32, gamma: f32) -> Option<EclipseSeverity> {
+        let earth = Vec3::new(earth_sun_au, 0., 0.);
+        let moon_x = earth_sun_au - moon_dist_km * KM;
+        let moon = Vec3::new(moon_x, 0., gamma * EARTH_RADIUS * moon_x / earth_sun_au);
+        shadow_severity(Vec3::ZERO, SUN_RADIUS, moon, MOON_RADIUS, earth, EARTH_RADIUS)
+    }
+
+    // Return the severity of the lunar eclipse when the Moon is moon_dist_km from Earth on the far
+    // side from the Sun and gamma is how far in Earth radii the Moon's center is from the axis of
+    // Earth's shadow.
+    fn lunar(earth_sun_au: f32, moon_dist_km: f32, gamma: f32) -> Option<EclipseSeverity> {
+        let earth = Vec3::new(earth_sun_au, 0., 0.);
+        let moon = Vec3::new(earth_sun_au + moon_dist_km * KM, 0., gamma * EARTH_RADIUS);
+        shadow_severity(Vec3::ZERO, SUN_RADIUS, earth, EARTH_RADIUS, moon, MOON_RADIUS)
+    }
+
+    #[test]
+    fn test_total_solar_eclipse_2024_04_08() {
+        assert_eq!(solar(1.0014, 359_800., 0.3431), Some(EclipseSeverity::Umbral))
+    }
+
+    #[test]
+    fn test_partial_solar_eclipse_2022_10_25() {
+        assert_eq!(solar(0.9943, 369_900., 1.0701), Some(EclipseSeverity::Penumbral))
+    }
+
+    #[test]
+    fn test_no_solar_eclipse_off_node() {
+        assert_eq!(solar(1., 384_400., 5.), None)
+    }
+
+    #[test]
+    fn test_total_lunar_eclipse_2022_11_08() {
+        assert_eq!(lunar(0.9905, 370_100., 0.2570), Some(EclipseSeverity::Umbral))
+    }
+
+    #[test]
+    fn test_penumbral_lunar_eclipse_2023_05_05() {
+        assert_eq!(lunar(1.0087, 375_300., -1.0351), Some(EclipseSeverity::Penumbral))
+    }
+
+    #[test]
+    fn test_lunar_eclipse_sends_events() {
+        let eclipsed = EclipseState {
+            solar: None,
+            lunar: lunar(0.9905, 370_100., 0.2570),
+        };
+        assert_eq!(
+            eclipse_events(EclipseState::default(), eclipsed),
+            vec![SolarSystemEvent::EclipseStarted {
+                eclipse: Eclipse::Lunar,
+                severity: EclipseSeverity::Umbral
+            }]
+        );
+        let waning = EclipseState {
+            lunar: lunar(0.9905, 370_100., 1.2),
+            ..eclipsed
+        };
+        assert_eq!(
+            eclipse_events(eclipsed, waning),
+            vec![SolarSystemEvent::EclipseChanged {
+                eclipse: Eclipse::Lunar,
+                severity: EclipseSeverity::Penumbral
+            }]
+        );
+        assert_eq!(
+            eclipse_events(waning, EclipseState::default()),
+            vec![SolarSystemEvent::EclipseEnded(Eclipse::Lunar)]
+        );
+    }
+
+    #[test]
+    fn test_no_eclipse_when_target_sunward() {
+        let earth = Vec3::new(1., 0., 0.);
+        let moon = Vec3::new(1. + 384_400. * KM, 0., 0.);
+        let act = shadow_severity(Vec3::ZERO, SUN_RADIUS, moon, MOON_RADIUS, earth, EARTH_RADIUS);
+        assert_eq!(act, None)
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages},
+    window::PrimaryWindow,
+};
+use bevy_mod_billboard::prelude::*;
+
+use crate::{label_scale, LabelStyle, Observer, WorldBounds};
+
+// The key toggling the grid
+const TOGGLE_KEY: KeyCode = KeyCode::KeyG;
+
+// The number of line segments approximating each ring
+const RING_SEGMENTS: usize = 128;
+
+// The colors of the grid lines and of the disk filling the ecliptic plane
+const LINE_COLOR: Color = Color::srgba(0.35, 0.4, 0.6, 0.6);
+const DISK_COLOR: Color = Color::srgba(0.2, 0.25, 0.4, 0.05);
+
+// This controls the reference grid in the ecliptic plane. The rings are centered on the origin and
+// labeled with their radii in AU. The spokes are evenly spaced, with the first pointing toward the
+// vernal equinox. Changes to the rings and spokes take effect at startup.
+#[derive(Resource)]
+pub struct EclipticGrid {
+    pub visible: bool,
+    pub ring_radii_au: Vec<f32>,
+    pub spokes: usize,
+}
+
+impl Default for EclipticGrid {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            ring_radii_au: vec![1., 2., 5., 10., 20., 50., 100.],
+            spokes: 12,
+        }
+    }
+}
+
+// The lines are drawn as a line list. The GPU draws lines a pixel wide however far away they are,
+// so the rings stay legible when seen edge on.
+fn mk_grid_mesh(ring_radii: &[f32], spokes: usize, spoke_len: f32) -> Mesh {
+    let mut points = Vec::new();
+    for radius in ring_radii {
+        let point = |i: usize| {
+            let (sin, cos) = (std::f32::consts::TAU * i as f32 / RING_SEGMENTS as f32).sin_cos();
+            Vec3::new(radius * cos, radius * sin, 0.)
+        };
+        for i in 0..RING_SEGMENTS {
+            points.extend([point(i), point(i + 1)]);
+        }
+    }
+    for i in 0..spokes {
+        let (sin, cos) = (std::f32::consts::TAU * i as f32 / spokes as f32).sin_cos();
+        points.extend([Vec3::ZERO, Vec3::new(spoke_len * cos, spoke_len * sin, 0.)]);
+    }
+    Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, points)
+}
+
+#[derive(Component)]
+struct GridRoot;
+
+#[derive(Component)]
+struct GridLabel;
+
+fn create_grid(
+    (grid, bounds): (Res<EclipticGrid>, Res<WorldBounds>),
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let world_radius = bounds.radius_au;
+    let radii: Vec<f32> =
+        grid.ring_radii_au.iter().copied().filter(|r| *r > 0. && *r <= world_radius).collect();
+    let material = |color: Color| StandardMaterial {
+        base_color: color,
+        unlit: true,
+        alpha_mode: AlphaMode::Blend,
+        cull_mode: None,
+        ..default()
+    };
+    commands
+        .spawn((
+            SpatialBundle {
+                visibility: if grid.visible { Visibility::Inherited } else { Visibility::Hidden },
+                ..default()
+            },
+            GridRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: meshes.add(mk_grid_mesh(&radii, grid.spokes, world_radius)),
+                    material: materials.add(material(LINE_COLOR)),
+                    ..default()
+                },
+                NotShadowCaster,
+                NotShadowReceiver,
+            ));
+            parent.spawn((
+                PbrBundle {
+                    mesh: meshes.add(Circle::new(world_radius).mesh().resolution(RING_SEGMENTS)),
+                    material: materials.add(material(DISK_COLOR)),
+                    ..default()
+                },
+                NotShadowCaster,
+                NotShadowReceiver,
+            ));
+            for radius in &radii {
+                parent.spawn((
+                    BillboardTextBundle {
+                        text: Text::from_section(
+                            format!("{} AU", radius),
+                            TextStyle {
+                                color: LINE_COLOR.with_alpha(1.),
+                                ..default()
+                            },
+                        ),
+                        transform: Transform::from_xyz(*radius, 0., 0.),
+                        ..default()
+                    },
+                    GridLabel,
+                ));
+            }
+        });
+}
+
+// G toggles the grid.
+fn toggle_grid(
+    input: Res<ButtonInput<KeyCode>>,
+    mut grid: ResMut<EclipticGrid>,
+    mut root: Query<&mut Visibility, With<GridRoot>>,
+) {
+    if input.just_pressed(TOGGLE_KEY) {
+        grid.visible = !grid.visible;
+        for mut visibility in &mut root {
+            *visibility = if grid.visible { Visibility::Inherited } else { Visibility::Hidden };
+        }
+    }
+}
+
+// Like the body labels, the ring labels are scaled with their distance from the observer and the
+// window's height so they keep the same size on screen.
+fn scale_labels(
+    (grid, style): (Res<EclipticGrid>, Res<LabelStyle>),
+    observer: Query<&Observer>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut labels: Query<&mut Transform, With<GridLabel>>,
+) {
+    if !grid.visible {
+        return;
+    }
+    let observer_pos = *observer.single().position();
+    let window = window.single();
+    for mut transform in &mut labels {
+        let scale = label_scale(transform.translation.distance(observer_pos), window, &style);
+        transform.scale = Vec3::splat(scale);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<EclipticGrid>()
+        .add_systems(Startup, create_grid)
+        .add_systems(Update, (toggle_grid, scale_labels).chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_mesh_vertex_count() {
+        let mesh = mk_grid_mesh(&[1., 2.], 12, 100.);
+        assert_eq!(mesh.count_vertices(), 2 * (2 * RING_SEGMENTS + 12));
+    }
+
+    #[test]
+    fn test_grid_mesh_lies_in_ecliptic() {
+        let mesh = mk_grid_mesh(&[1., 5.], 4, 10.);
+        let points = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().as_float3().unwrap();
+        assert!(points.iter().all(|p| p[2] == 0.));
+        let max = points.iter().map(|p| Vec3::from(*p).length()).fold(0., f32::max);
+        assert!((max - 10.).abs() < 1e-4, "{}", max);
+    }
+}
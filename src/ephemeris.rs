@@ -0,0 +1,102 @@
+use std::io::{self, Write};
+
+use crate::{
+    simulation::{Body, Propagator, SolarSystem},
+    uom_wrapper::si::{
+        f64::{Length, Time},
+        length::{astronomical_unit, meter},
+        time::{day, minute},
+    },
+    Simulation,
+};
+
+// Write the header row. Each body has a column for each of its heliocentric ecliptic coordinates.
+fn write_header(out: &mut impl Write) -> io::Result<()> {
+    write!(out, "jd")?;
+    for body in Body::VARIANTS {
+        let name = format!("{:?}", body).to_lowercase();
+        write!(out, ",{0}_x_au,{0}_y_au,{0}_z_au", name)?;
+    }
+    writeln!(out)
+}
+
+// Write the positions of the bodies from the start to the end Julian Date inclusive every step
+// days as CSV. The solar system is propagated from the given epoch the simulation starts at the
+// same way the live simulation advances it, so the positions match what the simulation shows at
+// the same times.
+pub fn write_ephemeris_csv(
+    out: &mut impl Write,
+    epoch: Time,
+    start_jd: f64,
+    end_jd: f64,
+    step_days: f64,
+) -> io::Result<()> {
+    if step_days.is_nan() || step_days <= 0. {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "step must be positive"));
+    }
+    let sys = SolarSystem::init(epoch, Propagator::default());
+    let max_step = Time::new::<minute>(Simulation::DT);
+    let m_per_au = Length::new::<astronomical_unit>(1.).get::<meter>();
+
+    write_header(out)?;
+    for (jd, positions) in sys.ephemeris(
+        Time::new::<day>(start_jd),
+        Time::new::<day>(end_jd),
+        Time::new::<day>(step_days),
+        max_step,
+    ) {
+        write!(out, "{}", jd.get::<day>())?;
+        for body in Body::VARIANTS {
+            let pos = positions[body] / m_per_au;
+            write!(out, ",{},{},{}", pos.x, pos.y, pos.z)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_write_ephemeris_csv() {
+        let mut out = Vec::new();
+        let (start, end) = (Simulation::EPOCH_JD, Simulation::EPOCH_JD + 1.);
+        write_ephemeris_csv(&mut out, default_epoch(), start, end, 0.5).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let rows: Vec<_> = csv.lines().collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows[0].starts_with("jd,sun_x_au,sun_y_au,sun_z_au,mercury_x_au"), "{}", rows[0]);
+        assert_eq!(rows[1].split(',').count(), 1 + 3 * Body::VARIANTS.len());
+        assert!(rows[1].starts_with("2459945.5,"), "{}", rows[1]);
+        assert!(rows[3].starts_with("2459946.5,"), "{}", rows[3]);
+    }
+
+    #[test]
+    fn test_write_ephemeris_csv_bad_step() {
+        let mut out = Vec::new();
+        assert!(write_ephemeris_csv(&mut out, default_epoch(), 0., 1., 0.).is_err());
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_write_ephemeris_csv_from_epoch() {
+        // Starting at J2000 rather than the default epoch, the first row holds the bodies where
+        // they are when the simulation starts then.
+        let j2000 = Time::new::<day>(2_451_545.);
+        let mut out = Vec::new();
+        write_ephemeris_csv(&mut out, j2000, 2_451_545., 2_451_545., 1.).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let row: Vec<f64> =
+            csv.lines().nth(1).unwrap().split(',').map(|v| v.parse().unwrap()).collect();
+        let earth = 1 + 3 * Body::VARIANTS.iter().position(|b| *b == Body::Earth).unwrap();
+        let sys = SolarSystem::init(j2000, Propagator::default());
+        let m_per_au = Length::new::<astronomical_unit>(1.).get::<meter>();
+        let expected = sys.position_of(Body::Earth) / m_per_au;
+        assert_eq!(row[0], 2_451_545.);
+        assert!((row[earth] - expected.x).abs() < 1e-9, "{} {}", row[earth], expected.x);
+        assert!((row[earth + 1] - expected.y).abs() < 1e-9, "{} {}", row[earth + 1], expected.y);
+    }
+}
@@ -0,0 +1,73 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    eclipses::{Eclipse, EclipseSeverity},
+    selection::Selected,
+    simulation::Body,
+    uom_wrapper::si::velocity::meter_per_second,
+    SimSet, Simulation,
+};
+
+// These are the things happening in the solar system that an app embedding the simulation can
+// react to. Its systems read them with an EventReader<SolarSystemEvent>. The events about the
+// bodies are sent during FixedUpdate after SimSet::UpdateBodies, as they are detected.
+#[derive(Clone, Copy, Debug, Event, PartialEq)]
+pub enum SolarSystemEvent {
+    // An eclipse began with the given severity.
+    EclipseStarted {
+        eclipse: Eclipse,
+        severity: EclipseSeverity,
+    },
+    // An eclipse under way deepened or lessened to the given severity.
+    EclipseChanged {
+        eclipse: Eclipse,
+        severity: EclipseSeverity,
+    },
+    EclipseEnded(Eclipse),
+    // A body went behind the Sun as seen from the observer of the Occultations resource.
+    OccultationStarted { body: Body, observer: Body },
+    // A body came back out from behind the Sun as seen from the observer.
+    OccultationEnded { body: Body, observer: Body },
+    // A body passed closest to the Sun in the observer's sky, in front of it or behind it.
+    Conjunction { body: Body, observer: Body },
+    // A body passed its perihelion, or its closest approach to its planet if it's a moon.
+    Perihelion(Body),
+    // The selected body changed. None means nothing is selected.
+    BodySelected(Option<Body>),
+}
+
+// A body passes its perihelion when it stops approaching its primary and starts receding. The
+// speed each body was receding at the last step is remembered. The speeds are forgotten when
+// another scenario is loaded, since they were taken at another time.
+fn detect_perihelia(
+    sim: Res<Simulation>,
+    mut receding: Local<HashMap<Body, f64>>,
+    mut events: EventWriter<SolarSystemEvent>,
+) {
+    if sim.is_added() {
+        receding.clear();
+    }
+    for body in sim.bodies() {
+        let Some(primary) = body.primary() else {
+            continue;
+        };
+        let speed = sim.radial_velocity_in::<meter_per_second>(body, primary);
+        if let Some(last) = receding.insert(body, speed) {
+            if last < 0. && speed >= 0. {
+                events.send(SolarSystemEvent::Perihelion(body));
+            }
+        }
+    }
+}
+
+fn announce_selection(selected: Res<Selected>, mut events: EventWriter<SolarSystemEvent>) {
+    if selected.is_changed() && !selected.is_added() {
+        events.send(SolarSystemEvent::BodySelected(selected.0));
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_event::<SolarSystemEvent>()
+        .add_systems(FixedUpdate, detect_perihelia.after(SimSet::UpdateBodies))
+        .add_systems(Update, announce_selection)
+}
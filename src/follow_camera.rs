@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera_animation::{CameraAnimation, ObserverPose, DEFAULT_DURATION_S},
+    selection::Selected,
+    simulation::Body,
+    BodyModel, Observer,
+};
+
+// The key making the camera follow the selected body
+const FOLLOW_KEY: KeyCode = KeyCode::KeyF;
+
+// The key returning the camera to the user's control
+const RELEASE_KEY: KeyCode = KeyCode::Escape;
+
+// The distance the camera follows a body at in multiples of the radius of the body's avatar
+pub const FOLLOW_DISTANCE_RADII: f32 = 10.;
+
+// The rate per second the camera closes the gap to where it should be while following. Easing
+// toward the following position keeps the camera from jerking as the body moves.
+pub const FOLLOW_RATE: f32 = 3.;
+
+// The amount the camera is raised above the ecliptic out of the line from the Sun through the
+// followed body, so the body doesn't hide the Sun
+const ELEVATION: f32 = 0.3;
+
+// This controls flying the camera to the selected body. The camera ends up looking at the body
+// from distance_radii times the radius of the body's avatar away, on the side it approached from.
+#[derive(Resource)]
+pub struct FlyTo {
+    pub key: KeyCode,
+    pub duration_s: f32,
+    pub distance_radii: f32,
+}
+
+impl Default for FlyTo {
+    fn default() -> Self {
+        Self {
+            key: KeyCode::KeyX,
+            duration_s: 2. * DEFAULT_DURATION_S,
+            distance_radii: 4.,
+        }
+    }
+}
+
+// This asks for the camera to fly to a body, as if it were selected and the fly to key pressed.
+#[derive(Event)]
+pub struct FlyToBody(pub Body);
+
+// This is the body the camera follows, if any.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub struct CameraTarget(pub Option<Body>);
+
+// Return the pose of an observer following a body, where t is the fraction of the way it moves
+// from its current position to where it should be. The observer stays on the far side of the body
+// from the Sun, looking at the body with the Sun behind it.
+pub fn follow_pose(
+    current: &ObserverPose,
+    sun_pos: Vec3,
+    body_pos: Vec3,
+    distance: f32,
+    t: f32,
+) -> ObserverPose {
+    let away = (body_pos - sun_pos).normalize_or(-*current.facing);
+    let offset_dir = (away + ELEVATION * Vec3::Z).normalize_or(away);
+    let position = current.position.lerp(body_pos + distance * offset_dir, t);
+    let facing = Dir3::new(body_pos - position).unwrap_or(current.facing);
+    let up = Dir3::new(*current.up - current.up.dot(*facing) * *facing).unwrap_or(current.up);
+    ObserverPose { position, facing, up }
+}
+
+// Return the pose of an observer framing a body from the given distance. The observer keeps its
+// bearing from the body and turns to look at it.
+pub fn framing_pose(current: &ObserverPose, body_pos: Vec3, distance: f32) -> ObserverPose {
+    let back = (current.position - body_pos).normalize_or(-*current.facing);
+    let facing = Dir3::new(-back).unwrap_or(current.facing);
+    let up = Dir3::new(*current.up - current.up.dot(*facing) * *facing).unwrap_or(current.up);
+    ObserverPose {
+        position: body_pos + distance * back,
+        facing,
+        up,
+    }
+}
+
+// F makes the camera follow the selected body, and Escape stops following it, returning the
+// observer's focus to the Sun.
+fn choose_target(
+    input: Res<ButtonInput<KeyCode>>,
+    selected: Res<Selected>,
+    mut target: ResMut<CameraTarget>,
+    mut observer: Query<&mut Observer>,
+) {
+    if input.just_pressed(RELEASE_KEY) {
+        target.0 = None;
+        observer.single_mut().set_focus(Vec3::ZERO);
+    } else if input.just_pressed(FOLLOW_KEY) && selected.0.is_some() {
+        target.0 = selected.0;
+    }
+}
+
+// This flies the observer to the selected body when the fly to key is pressed, or to the body
+// asked for by a FlyToBody event, refocusing on it. The flight ends where the body was when it
+// began. Following stops.
+pub fn fly_to_body(
+    (input, selected): (Res<ButtonInput<KeyCode>>, Res<Selected>),
+    fly_to: Res<FlyTo>,
+    mut requests: EventReader<FlyToBody>,
+    mut target: ResMut<CameraTarget>,
+    mut anim: ResMut<CameraAnimation>,
+    mut observer: Query<&mut Observer>,
+    bodies: Query<(&Body, &BodyModel)>,
+) {
+    let requested = requests.read().last().map(|request| request.0);
+    let dest = requested.or(selected.0.filter(|_| input.just_pressed(fly_to.key)));
+    let Some((_, model)) = bodies.iter().find(|(body, _)| Some(**body) == dest) else {
+        return;
+    };
+    target.0 = None;
+    let mut observer = observer.single_mut();
+    observer.set_focus(*model.position());
+    let end = framing_pose(
+        &observer.pose(),
+        *model.position(),
+        fly_to.distance_radii * model.avatar_radius(),
+    );
+    anim.start(observer.pose(), end, fly_to.duration_s);
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<CameraTarget>()
+        .init_resource::<FlyTo>()
+        .add_event::<FlyToBody>()
+        .add_systems(Update, (choose_target, fly_to_body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pose() -> ObserverPose {
+        ObserverPose {
+            position: Vec3::new(0., 0., 80.),
+            facing: Dir3::NEG_Z,
+            up: Dir3::Y,
+        }
+    }
+
+    #[test]
+    fn test_follow_pose_faces_body() {
+        let body = Vec3::new(5., 0., 0.);
+        let act = follow_pose(&pose(), Vec3::ZERO, body, 1., 0.5);
+        let to_body = (body - act.position).normalize();
+        assert!(act.facing.dot(to_body) > 0.999_9, "{:?}", act);
+    }
+
+    #[test]
+    fn test_follow_pose_arrives() {
+        let body = Vec3::new(5., 0., 0.);
+        let act = follow_pose(&pose(), Vec3::ZERO, body, 2., 1.);
+        assert!((act.position.distance(body) - 2.).abs() < 1e-5, "{:?}", act);
+        assert!(act.position.x > body.x, "{:?}", act);
+    }
+
+    #[test]
+    fn test_framing_pose_keeps_bearing() {
+        let body = Vec3::new(5., 0., 0.);
+        let act = framing_pose(&pose(), body, 0.1);
+        let exp = body + 0.1 * (Vec3::new(0., 0., 80.) - body).normalize();
+        assert!(act.position.distance(exp) < 1e-5, "{:?}", act);
+        assert!(act.facing.dot((body - exp).normalize()) > 0.999_9, "{:?}", act);
+        assert!(act.up.dot(*act.facing).abs() < 1e-5, "{:?}", act);
+    }
+}
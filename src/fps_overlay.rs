@@ -0,0 +1,110 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use crate::sim_rate::SimRate;
+
+// The key toggling the overlay
+const TOGGLE_KEY: KeyCode = KeyCode::F4;
+
+#[derive(Component)]
+struct FpsText;
+
+// The frame rate and frame time are smoothed over the last few frames. They are unknown until a
+// few frames have been drawn. The simulated days per second the clock asks for are shown alongside
+// the rate measured, since the simulation falls short of the former when the frames take too long
+// for every FixedUpdate to run.
+fn overlay_text(fps: Option<f64>, frame_time_ms: Option<f64>, rate: &SimRate) -> String {
+    let fmt = |val: Option<f64>| val.map_or(String::from("-"), |val| format!("{:.1}", val));
+    format!(
+        "FPS: {}\nframe time: {} ms\nsim days/sec: {:.3} requested\nsimulated time: {}",
+        fmt(fps),
+        fmt(frame_time_ms),
+        rate.requested_days_per_sec(),
+        rate.description(),
+    )
+}
+
+fn create_overlay(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.),
+                right: Val::Px(10.),
+                ..default()
+            })
+        },
+        FpsText,
+    ));
+}
+
+fn toggle_overlay(
+    input: Res<ButtonInput<KeyCode>>,
+    mut overlay: Query<&mut Visibility, With<FpsText>>,
+) {
+    if input.just_pressed(TOGGLE_KEY) {
+        for mut vis in &mut overlay {
+            *vis = match *vis {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+// The simulation rates come from SimRate, the same source the HUD and the diagnostics overlay use.
+fn update_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    rate: Res<SimRate>,
+    mut overlay: Query<(&mut Text, &Visibility), With<FpsText>>,
+) {
+    for (mut text, vis) in &mut overlay {
+        if *vis == Visibility::Hidden {
+            continue;
+        }
+        let smoothed = |path| diagnostics.get(path).and_then(|diag| diag.smoothed());
+        text.sections[0].value = overlay_text(
+            smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+            smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+            &rate,
+        );
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_plugins(FrameTimeDiagnosticsPlugin)
+        .add_systems(Startup, create_overlay)
+        .add_systems(Update, (toggle_overlay, update_overlay).chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_text() {
+        let rate = SimRate::requesting(0.5);
+        assert_eq!(
+            overlay_text(Some(59.94), Some(16.68), &rate),
+            "FPS: 59.9\nframe time: 16.7 ms\nsim days/sec: 0.500 requested\n\
+             simulated time: measuring..."
+        );
+    }
+
+    #[test]
+    fn test_overlay_text_before_measured() {
+        let rate = SimRate::default();
+        assert!(overlay_text(None, None, &rate).starts_with("FPS: -\nframe time: - ms"));
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera_animation::{CameraAnimation, ObserverPose},
+    selection::shift_held,
+    upright, CameraMode, Observer, WorldBounds,
+};
+
+// The key turning the observer about its facing direction until its up direction is as close to
+// ecliptic north as it can be
+const LEVEL_KEY: KeyCode = KeyCode::Home;
+
+// The fraction of the observer's distance from the Sun it travels each second. Scaling the speed
+// with the distance makes navigating near Earth feel the same as navigating past Neptune.
+const SPEED_PER_DIST: f32 = 0.5;
+
+// The slowest the observer travels in AU per second, so it can still fly away from the Sun
+const MIN_SPEED_AU: f32 = 0.01;
+
+// The rate in radians per second the arrow keys turn the observer
+const TURN_RATE: f32 = 1.;
+
+// Return how fast in AU per second the observer travels at the given position
+fn fly_speed(position: Vec3) -> f32 {
+    (SPEED_PER_DIST * position.length()).max(MIN_SPEED_AU)
+}
+
+// Return the closest position to the given one that is inside the rendering volume
+fn clamp_to_world(position: Vec3, bounds: &WorldBounds) -> Vec3 {
+    position.clamp_length_max(bounds.radius_au)
+}
+
+// Return the pose turned about its facing direction by the given angle in radians. Positive angles
+// roll it to the right.
+fn roll(pose: &ObserverPose, angle: f32) -> ObserverPose {
+    ObserverPose {
+        up: Quat::from_axis_angle(*pose.facing, angle) * pose.up,
+        ..*pose
+    }
+}
+
+// Return the pose rolled level with the ecliptic, so ecliptic north is straight up without the
+// observer turning away from where it's facing
+fn level(pose: &ObserverPose) -> ObserverPose {
+    ObserverPose {
+        up: upright(pose.facing, Vec3::Z),
+        ..*pose
+    }
+}
+
+// In free-fly mode, W and S move the observer forward and backward, A and D move it left and right,
+// and Q and E move it down and up. The left and right arrows turn the observer left and right, or
+// roll it left and right while shift is held, and the up and down arrows pitch it up and down. The
+// level key rolls it level with the ecliptic. Flying cancels any camera animation in progress.
+fn fly(
+    (time, bounds): (Res<Time>, Res<WorldBounds>),
+    input: Res<ButtonInput<KeyCode>>,
+    mut anim: ResMut<CameraAnimation>,
+    mut observer: Query<&mut Observer>,
+) {
+    let axis = |neg, pos| {
+        input.pressed(pos) as i8 as f32 - input.pressed(neg) as i8 as f32
+    };
+    let forward = axis(KeyCode::KeyS, KeyCode::KeyW);
+    let right = axis(KeyCode::KeyA, KeyCode::KeyD);
+    let up = axis(KeyCode::KeyQ, KeyCode::KeyE);
+    let (yaw, roll_rate) = if shift_held(&input) {
+        (0., axis(KeyCode::ArrowLeft, KeyCode::ArrowRight))
+    } else {
+        (axis(KeyCode::ArrowRight, KeyCode::ArrowLeft), 0.)
+    };
+    let pitch = axis(KeyCode::ArrowDown, KeyCode::ArrowUp);
+    let leveling = input.just_pressed(LEVEL_KEY);
+    if [forward, right, up, yaw, pitch, roll_rate].iter().all(|rate| *rate == 0.) && !leveling {
+        return;
+    }
+
+    anim.cancel();
+    let dt = time.delta_seconds();
+    let mut observer = observer.single_mut();
+    let mut pose = roll(&observer.pose(), roll_rate * TURN_RATE * dt);
+    if leveling {
+        pose = level(&pose);
+    }
+
+    let right_dir = pose.facing.cross(*pose.up);
+    let rot = Quat::from_axis_angle(*pose.up, yaw * TURN_RATE * dt)
+        * Quat::from_axis_angle(right_dir, pitch * TURN_RATE * dt);
+    pose.facing = rot * pose.facing;
+    pose.up = rot * pose.up;
+
+    let step = fly_speed(pose.position) * dt;
+    let motion = forward * *pose.facing + right * right_dir + up * *pose.up;
+    pose.position = clamp_to_world(pose.position + step * motion, &bounds);
+
+    observer.set_pose(&pose);
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Update, fly.run_if(resource_equals(CameraMode::FreeFly)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_to_world_inside() {
+        let pos = Vec3::new(1., 2., 3.);
+        assert_eq!(clamp_to_world(pos, &WorldBounds::default()), pos)
+    }
+
+    #[test]
+    fn test_clamp_to_world_outside() {
+        let bounds = WorldBounds::default();
+        let pos = clamp_to_world(Vec3::new(0., 0., 2. * bounds.radius_au), &bounds);
+        assert!((pos.length() - bounds.radius_au).abs() < 1e-3, "{:?}", pos);
+    }
+
+    #[test]
+    fn test_clamp_to_raised_world() {
+        let bounds = WorldBounds { radius_au: 150., ..default() };
+        let pos = Vec3::new(0., 0., 120.);
+        assert_eq!(clamp_to_world(pos, &bounds), pos);
+        assert!(clamp_to_world(pos, &WorldBounds::default()).length() < 120.);
+    }
+
+    #[test]
+    fn test_fly_speed_scales_with_distance() {
+        assert_eq!(fly_speed(Vec3::new(30., 0., 0.)), 30. * fly_speed(Vec3::new(1., 0., 0.)))
+    }
+
+    #[test]
+    fn test_roll_turns_up_about_facing() {
+        let pose = ObserverPose {
+            position: Vec3::ZERO,
+            facing: Dir3::NEG_Z,
+            up: Dir3::Y,
+        };
+        let rolled = roll(&pose, std::f32::consts::FRAC_PI_2);
+        assert_eq!(rolled.facing, pose.facing);
+        assert!(rolled.up.distance(pose.facing.cross(*pose.up)) < 1e-6, "{:?}", rolled);
+    }
+
+    #[test]
+    fn test_level_points_up_to_ecliptic_north() {
+        let pose = ObserverPose {
+            position: Vec3::ZERO,
+            facing: Dir3::X,
+            up: Dir3::Y,
+        };
+        let leveled = level(&pose);
+        assert_eq!(leveled.facing, pose.facing);
+        assert!(leveled.up.distance(Vec3::Z) < 1e-6, "{:?}", leveled);
+    }
+
+    #[test]
+    fn test_fly_speed_at_sun() {
+        assert_eq!(fly_speed(Vec3::ZERO), MIN_SPEED_AU)
+    }
+}
@@ -0,0 +1,309 @@
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, MeshVertexAttribute, VertexAttributeValues},
+};
+
+use crate::{BodyModel, Simulation, simulation::Body};
+
+// The number of longitudinal and latitudinal divisions of the exported spheres
+const SPHERE_SECTORS: usize = 32;
+const SPHERE_STACKS: usize = 16;
+
+// The glTF constants used. See https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html
+const GLB_MAGIC: u32 = 0x4654_6c67;
+const GLB_VERSION: u32 = 2;
+const GLB_JSON_CHUNK: u32 = 0x4e4f_534a;
+const GLB_BIN_CHUNK: u32 = 0x004e_4942;
+const GL_FLOAT: u32 = 5126;
+const GL_UNSIGNED_INT: u32 = 5125;
+const GL_ARRAY_BUFFER: u32 = 34962;
+const GL_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+// This is where and when a snapshot of the scene is exported
+#[derive(Clone, Resource)]
+pub struct SnapshotExport {
+    pub path: PathBuf,
+    pub key: KeyCode,
+}
+
+impl Default for SnapshotExport {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("solar-system.glb"),
+            key: KeyCode::F9,
+        }
+    }
+}
+
+// This is what is exported for a body
+pub struct BodySnapshot {
+    pub name: String,
+    pub position: Vec3,
+    pub radius: f32,
+    pub color: Color,
+    pub emissive: bool,
+}
+
+struct SphereData {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+fn mk_unit_sphere() -> SphereData {
+    let mesh = Sphere::new(1.).mesh().uv(SPHERE_SECTORS, SPHERE_STACKS);
+    let vec3s = |attr: MeshVertexAttribute| match mesh.attribute(attr) {
+        Some(VertexAttributeValues::Float32x3(vals)) => vals.clone(),
+        _ => Vec::new(),
+    };
+    let positions = vec3s(Mesh::ATTRIBUTE_POSITION);
+    let normals = vec3s(Mesh::ATTRIBUTE_NORMAL);
+    let indices = match mesh.indices() {
+        Some(Indices::U32(idxs)) => idxs.clone(),
+        Some(Indices::U16(idxs)) => idxs.iter().map(|&i| u32::from(i)).collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    SphereData { positions, normals, indices }
+}
+
+fn escape_json(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut esc, c| {
+            match c {
+                '"' => esc.push_str("\\\""),
+                '\\' => esc.push_str("\\\\"),
+                c if c.is_control() => {
+                    let _ = write!(esc, "\\u{:04x}", c as u32);
+                }
+                c => esc.push(c),
+            }
+            esc
+        })
+}
+
+// glTF colors are linear. Since emissive factors are limited to [0, 1], HDR emissive colors are
+// normalized, and the KHR_materials_emissive_strength extension carries their brightness.
+fn mk_material_json(body: &BodySnapshot) -> String {
+    let lin = body.color.to_linear();
+    let name = escape_json(&body.name);
+    if body.emissive {
+        let strength = lin.red.max(lin.green).max(lin.blue).max(1.);
+        format!(
+            concat!(
+                r#"{{"name":"{}","pbrMetallicRoughness":{{"baseColorFactor":[0,0,0,1],"#,
+                r#""metallicFactor":0,"roughnessFactor":1}},"emissiveFactor":[{},{},{}],"#,
+                r#""extensions":{{"KHR_materials_emissive_strength":{{"emissiveStrength":{}}}}}}}"#,
+            ),
+            name,
+            lin.red / strength,
+            lin.green / strength,
+            lin.blue / strength,
+            strength,
+        )
+    } else {
+        format!(
+            concat!(
+                r#"{{"name":"{}","pbrMetallicRoughness":{{"baseColorFactor":[{},{},{},{}],"#,
+                r#""metallicFactor":0,"roughnessFactor":0.5}}}}"#,
+            ),
+            name, lin.red, lin.green, lin.blue, lin.alpha,
+        )
+    }
+}
+
+fn pad_to_4(bytes: &mut Vec<u8>, fill: u8) {
+    bytes.resize(bytes.len().next_multiple_of(4), fill);
+}
+
+// This encodes the bodies as a binary glTF scene. Each body is a node named after it holding a unit
+// sphere scaled to the body's radius and translated to its world position. All of the nodes share
+// the same vertex data.
+pub fn encode_glb(bodies: &[BodySnapshot]) -> Vec<u8> {
+    let sphere = mk_unit_sphere();
+
+    let mut bin = Vec::new();
+    for pos in &sphere.positions {
+        pos.iter().for_each(|c| bin.extend_from_slice(&c.to_le_bytes()));
+    }
+    let norm_offset = bin.len();
+    for norm in &sphere.normals {
+        norm.iter().for_each(|c| bin.extend_from_slice(&c.to_le_bytes()));
+    }
+    let idx_offset = bin.len();
+    for idx in &sphere.indices {
+        bin.extend_from_slice(&idx.to_le_bytes());
+    }
+    let bin_len = bin.len();
+    pad_to_4(&mut bin, 0);
+
+    let emissive_used = bodies.iter().any(|b| b.emissive);
+    let mut nodes = Vec::new();
+    let mut meshes = Vec::new();
+    let mut materials = Vec::new();
+    for (i, body) in bodies.iter().enumerate() {
+        let name = escape_json(&body.name);
+        let p = body.position;
+        let r = body.radius;
+        nodes.push(format!(
+            r#"{{"name":"{name}","mesh":{i},"translation":[{},{},{}],"scale":[{r},{r},{r}]}}"#,
+            p.x, p.y, p.z,
+        ));
+        meshes.push(format!(
+            concat!(
+                r#"{{"name":"{}","primitives":[{{"attributes":{{"POSITION":0,"NORMAL":1}},"#,
+                r#""indices":2,"material":{}}}]}}"#,
+            ),
+            name, i,
+        ));
+        materials.push(mk_material_json(body));
+    }
+
+    let mut json = String::new();
+    let _ = write!(json, r#"{{"asset":{{"version":"2.0","generator":"solar-system"}},"#);
+    if emissive_used {
+        let _ = write!(json, r#""extensionsUsed":["KHR_materials_emissive_strength"],"#);
+    }
+    let _ = write!(
+        json,
+        r#""scene":0,"scenes":[{{"name":"Solar System","nodes":[{}]}}],"#,
+        (0..bodies.len()).map(|i| i.to_string()).collect::<Vec<_>>().join(","),
+    );
+    let _ = write!(json, r#""nodes":[{}],"#, nodes.join(","));
+    let _ = write!(json, r#""meshes":[{}],"#, meshes.join(","));
+    let _ = write!(json, r#""materials":[{}],"#, materials.join(","));
+    let _ = write!(
+        json,
+        concat!(
+            r#""accessors":["#,
+            r#"{{"bufferView":0,"componentType":{f},"count":{n},"type":"VEC3","#,
+            r#""min":[-1,-1,-1],"max":[1,1,1]}},"#,
+            r#"{{"bufferView":1,"componentType":{f},"count":{n},"type":"VEC3"}},"#,
+            r#"{{"bufferView":2,"componentType":{u},"count":{m},"type":"SCALAR"}}],"#,
+        ),
+        f = GL_FLOAT,
+        u = GL_UNSIGNED_INT,
+        n = sphere.positions.len(),
+        m = sphere.indices.len(),
+    );
+    let _ = write!(
+        json,
+        concat!(
+            r#""bufferViews":["#,
+            r#"{{"buffer":0,"byteOffset":0,"byteLength":{},"target":{a}}},"#,
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{a}}},"#,
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{e}}}],"#,
+            r#""buffers":[{{"byteLength":{}}}]}}"#,
+        ),
+        norm_offset,
+        norm_offset,
+        idx_offset - norm_offset,
+        idx_offset,
+        bin_len - idx_offset,
+        bin_len,
+        a = GL_ARRAY_BUFFER,
+        e = GL_ELEMENT_ARRAY_BUFFER,
+    );
+    let mut json = json.into_bytes();
+    pad_to_4(&mut json, b' ');
+
+    let tot_len = 12 + 8 + json.len() + 8 + bin.len();
+    let mut glb = Vec::with_capacity(tot_len);
+    for word in [GLB_MAGIC, GLB_VERSION, tot_len as u32, json.len() as u32, GLB_JSON_CHUNK] {
+        glb.extend_from_slice(&word.to_le_bytes());
+    }
+    glb.extend_from_slice(&json);
+    for word in [bin.len() as u32, GLB_BIN_CHUNK] {
+        glb.extend_from_slice(&word.to_le_bytes());
+    }
+    glb.extend_from_slice(&bin);
+    glb
+}
+
+// This writes the current state of the scene to the configured path when the export key is
+// pressed.
+fn export_snapshot(
+    input: Res<ButtonInput<KeyCode>>,
+    export: Res<SnapshotExport>,
+    sim: Res<Simulation>,
+    bodies: Query<(&Body, &BodyModel)>,
+) {
+    if !input.just_pressed(export.key) {
+        return;
+    }
+
+    let snapshots: Vec<_> = bodies
+        .iter()
+        .map(|(body, model)| BodySnapshot {
+            name: sim.name_of(*body),
+            position: *model.position(),
+            radius: model.avatar_radius(),
+            color: *sim.color_of(*body),
+            emissive: sim.luminosity_of(*body) > 0.,
+        })
+        .collect();
+
+    match fs::write(&export.path, encode_glb(&snapshots)) {
+        Ok(()) => info!("Exported scene snapshot to {}", export.path.display()),
+        Err(err) => error!("Failed to export scene snapshot to {}: {}", export.path.display(), err),
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<SnapshotExport>().add_systems(Update, export_snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mk_bodies() -> Vec<BodySnapshot> {
+        vec![
+            BodySnapshot {
+                name: String::from("Sun"),
+                position: Vec3::ZERO,
+                radius: 0.1,
+                color: Color::from(10. * Srgba::rgb(0.9922, 0.9843, 0.8275)),
+                emissive: true,
+            },
+            BodySnapshot {
+                name: String::from("Earth"),
+                position: Vec3::new(1., 0., 0.),
+                radius: 0.01,
+                color: Color::srgb_u8(0x2f, 0x6a, 0x69),
+                emissive: false,
+            },
+        ]
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_encode_glb_layout() {
+        let glb = encode_glb(&mk_bodies());
+        assert_eq!(read_u32(&glb, 0), GLB_MAGIC);
+        assert_eq!(read_u32(&glb, 4), GLB_VERSION);
+        assert_eq!(read_u32(&glb, 8) as usize, glb.len());
+
+        let json_len = read_u32(&glb, 12) as usize;
+        assert_eq!(json_len % 4, 0);
+        assert_eq!(read_u32(&glb, 16), GLB_JSON_CHUNK);
+
+        let bin_start = 20 + json_len;
+        assert_eq!(read_u32(&glb, bin_start) as usize, glb.len() - bin_start - 8);
+        assert_eq!(read_u32(&glb, bin_start + 4), GLB_BIN_CHUNK);
+    }
+
+    #[test]
+    fn test_encode_glb_json_names_and_emission() {
+        let glb = encode_glb(&mk_bodies());
+        let json_len = read_u32(&glb, 12) as usize;
+        let json = std::str::from_utf8(&glb[20..20 + json_len]).unwrap();
+        assert!(json.contains(r#""name":"Sun","mesh":0"#));
+        assert!(json.contains(r#""name":"Earth","mesh":1"#));
+        assert!(json.contains("KHR_materials_emissive_strength"));
+    }
+}
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::{clock::SimClock, sim_rate::SimRate, uom_wrapper::jd_to_gregorian, Simulation};
+
+#[derive(Component)]
+struct HudText;
+
+fn create_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.),
+            left: Val::Px(10.),
+            ..default()
+        }),
+        HudText,
+    ));
+}
+
+// This shows the simulated date, how fast the clock is running, and how fast simulated time is
+// measured to pass.
+fn update_hud(
+    sim: Res<Simulation>,
+    (clock, measured): (Res<SimClock>, Res<SimRate>),
+    mut hud: Query<&mut Text, With<HudText>>,
+) {
+    let rate = if clock.paused {
+        String::from("paused")
+    } else if clock.real_time {
+        String::from("real time")
+    } else {
+        format!("{}×", clock.time_scale)
+    };
+    let rate = if clock.orbits_paused && !clock.paused {
+        format!("{}, orbits paused", rate)
+    } else {
+        rate
+    };
+    let date = jd_to_gregorian(sim.current_time());
+    for mut text in &mut hud {
+        text.sections[0].value =
+            format!("{}\ntime scale: {}\nsimulated time: {}", date, rate, measured.description());
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Startup, create_hud).add_systems(Update, update_hud)
+}
@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+
+use crate::{
+    selection::Selected,
+    simulation::Body,
+    uom_wrapper::si::{
+        angle::degree,
+        f32::Length,
+        length::{astronomical_unit, kilometer},
+        mass::kilogram,
+        time::day,
+        velocity::kilometer_per_second,
+    },
+    Simulation,
+};
+
+// The width of the panel in logical pixels. Bevy scales logical pixels by the window's scale
+// factor, so the panel keeps the same physical size on high DPI displays.
+const PANEL_WIDTH: f32 = 280.;
+
+const FONT_SIZE: f32 = 16.;
+
+// This is what the panel shows about a body.
+#[derive(Clone, Debug)]
+struct BodyInfo {
+    name: String,
+    mass_kg: f64,
+    radius_km: f32,
+    radius_au: f32,
+    period_days: Option<f64>,
+    sun_distance_au: f32,
+    orbital_speed_km_s: f32,
+    // The angle in arcseconds between where the body is and where it appears to be from Earth
+    apparent_shift_arcsec: Option<f64>,
+    // The body's speed relative to Earth and how fast it is receding from Earth, negative when
+    // it is approaching
+    earth_speed_km_s: Option<f64>,
+    earth_radial_speed_km_s: Option<f64>,
+    // The percentage of the body's disk seen from Earth that the Sun lights
+    earth_lit_percent: Option<f64>,
+    // The angle in arcseconds the body's disk spans as seen from Earth and roughly how bright it
+    // looks from there, as an apparent magnitude
+    earth_angular_diameter_arcsec: Option<f64>,
+    earth_magnitude: Option<f64>,
+}
+
+// Return an angle in arcseconds as arcminutes when it is at least an arcminute
+fn fmt_arcsec(arcsec: f64) -> String {
+    if arcsec >= 60. {
+        format!("{:.1}'", arcsec / 60.)
+    } else {
+        format!("{:.1}\"", arcsec)
+    }
+}
+
+impl BodyInfo {
+    // The orbital speed is relative to the body's primary, so a moon's speed is how fast it
+    // circles its planet. A body appears from Earth where it was when the light seen left it,
+    // displaced further by Earth's motion.
+    fn of(sim: &Simulation, body: Body) -> Self {
+        let vel = |body| sim.velocity_in::<kilometer_per_second>(body);
+        let rel_vel = match body.primary() {
+            Some(primary) => vel(body) - vel(primary),
+            None => vel(body),
+        };
+        let pos = |body| sim.position_in::<astronomical_unit>(body);
+        let not_earth = Some(body).filter(|body| *body != Body::Earth);
+        let apparent_shift = not_earth.map(|body| {
+            let geometric = pos(body) - pos(Body::Earth);
+            let apparent = sim.apparent_position_of(body, Body::Earth);
+            geometric.angle_between(apparent).to_degrees() * 3600.
+        });
+        let earth_speed = not_earth.map(|body| {
+            sim.relative_velocity_in::<kilometer_per_second>(body, Body::Earth).length()
+        });
+        let earth_radial_speed = not_earth
+            .map(|body| sim.radial_velocity_in::<kilometer_per_second>(body, Body::Earth));
+        let earth_lit = not_earth
+            .filter(|body| sim.luminosity_of(*body) == 0.)
+            .map(|body| 100. * sim.illuminated_fraction_of(body, Body::Earth));
+        let earth_angular_diameter = not_earth.map(|body| {
+            let diameter = sim.solar_system.angular_diameter_of(body, Body::Earth);
+            diameter.get::<degree>() * 3600.
+        });
+        let earth_magnitude =
+            not_earth.and_then(|body| sim.solar_system.apparent_magnitude_of(body, Body::Earth));
+        Self {
+            name: sim.name_of(body),
+            mass_kg: sim.mass_of(body).get::<kilogram>(),
+            radius_km: Length::new::<astronomical_unit>(sim.radius_of(body)).get::<kilometer>(),
+            radius_au: sim.radius_of(body),
+            period_days: sim.orbital_period_of(body).map(|t| t.get::<day>()),
+            sun_distance_au: pos(body).distance(pos(Body::Sun)) as f32,
+            orbital_speed_km_s: rel_vel.length() as f32,
+            apparent_shift_arcsec: apparent_shift,
+            earth_speed_km_s: earth_speed,
+            earth_radial_speed_km_s: earth_radial_speed,
+            earth_lit_percent: earth_lit,
+            earth_angular_diameter_arcsec: earth_angular_diameter,
+            earth_magnitude,
+        }
+    }
+
+    fn text(&self) -> String {
+        let period = match self.period_days {
+            Some(days) => format!("{:.2} days ({:.2} years)", days, days / 365.25),
+            None => String::from("none"),
+        };
+        let mut text = format!(
+            "{}\nmass: {:.4e} kg\nradius: {:.1} km ({:.3e} AU)\norbital period: {}\n\
+             distance from Sun: {:.4} AU\norbital speed: {:.2} km/s",
+            self.name,
+            self.mass_kg,
+            self.radius_km,
+            self.radius_au,
+            period,
+            self.sun_distance_au,
+            self.orbital_speed_km_s,
+        );
+        if let Some(shift) = self.apparent_shift_arcsec {
+            text += &format!("\nlight time shift from Earth: {:.1}\"", shift);
+        }
+        if let (Some(speed), Some(radial)) = (self.earth_speed_km_s, self.earth_radial_speed_km_s) {
+            let motion = if radial < 0. { "approaching" } else { "receding" };
+            text += &format!(
+                "\nspeed relative to Earth: {:.2} km/s\n{} Earth at {:.2} km/s",
+                speed,
+                motion,
+                radial.abs()
+            );
+        }
+        if let Some(lit) = self.earth_lit_percent {
+            text += &format!("\nlit as seen from Earth: {:.0}%", lit);
+        }
+        if let Some(diameter) = self.earth_angular_diameter_arcsec {
+            text += &format!("\nangular diameter from Earth: {}", fmt_arcsec(diameter));
+        }
+        if let Some(magnitude) = self.earth_magnitude {
+            text += &format!("\napparent magnitude from Earth: {:.1}", magnitude);
+        }
+        text
+    }
+}
+
+#[derive(Component)]
+struct InfoPanel;
+
+#[derive(Component)]
+struct InfoText;
+
+// The panel is docked to the right side of the window.
+fn create_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.),
+                    right: Val::Px(10.),
+                    width: Val::Px(PANEL_WIDTH),
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            InfoPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: FONT_SIZE,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                InfoText,
+            ));
+        });
+}
+
+// This shows the selected body's information and hides the panel when nothing is selected.
+fn update_panel(
+    sim: Res<Simulation>,
+    selected: Res<Selected>,
+    mut panel: Query<&mut Visibility, With<InfoPanel>>,
+    mut text: Query<&mut Text, With<InfoText>>,
+) {
+    let visibility = match selected.0 {
+        Some(body) => {
+            let info = BodyInfo::of(&sim, body).text();
+            for mut text in &mut text {
+                text.sections[0].value.clone_from(&info);
+            }
+            Visibility::Inherited
+        }
+        None => Visibility::Hidden,
+    };
+    for mut panel_vis in &mut panel {
+        panel_vis.set_if_neq(visibility);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Startup, create_panel).add_systems(Update, update_panel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_body_info_earth() {
+        let sim = Simulation::init_at(default_epoch());
+        let info = BodyInfo::of(&sim, Body::Earth);
+        assert_eq!(info.name, "Earth");
+        assert!((info.radius_km - 6_371.).abs() < 1., "{:?}", info);
+        assert!(info.period_days.is_some_and(|d| (d - 365.256).abs() < 0.01), "{:?}", info);
+        assert!((info.sun_distance_au - 0.983).abs() < 0.01, "{:?}", info);
+        assert!((info.orbital_speed_km_s - 30.).abs() < 1., "{:?}", info);
+    }
+
+    #[test]
+    fn test_body_info_moon_speed_relative_to_earth() {
+        let sim = Simulation::init_at(default_epoch());
+        let info = BodyInfo::of(&sim, Body::Moon);
+        assert!((info.orbital_speed_km_s - 1.).abs() < 0.1, "{:?}", info);
+    }
+
+    #[test]
+    fn test_body_info_text_sun() {
+        let sim = Simulation::init_at(default_epoch());
+        let text = BodyInfo::of(&sim, Body::Sun).text();
+        assert!(text.starts_with("Sun\n"), "{}", text);
+        assert!(text.contains("orbital period: none"), "{}", text);
+    }
+
+    #[test]
+    fn test_body_info_moon_relative_to_earth() {
+        let sim = Simulation::init_at(default_epoch());
+        let info = BodyInfo::of(&sim, Body::Moon);
+        let speed = info.earth_speed_km_s.unwrap();
+        let radial = info.earth_radial_speed_km_s.unwrap();
+        assert!((speed - info.orbital_speed_km_s as f64).abs() < 1e-3, "{:?}", info);
+        assert!(radial.abs() <= speed, "{:?}", info);
+        assert!(BodyInfo::of(&sim, Body::Earth).earth_speed_km_s.is_none());
+        assert!(BodyInfo::of(&sim, Body::Moon).text().contains("Earth at"));
+    }
+
+    #[test]
+    fn test_body_info_lit_from_earth() {
+        let sim = Simulation::init_at(default_epoch());
+        let moon = BodyInfo::of(&sim, Body::Moon);
+        let exp = 100. * sim.illuminated_fraction_of(Body::Moon, Body::Earth);
+        assert_eq!(moon.earth_lit_percent, Some(exp));
+        assert!(moon.text().contains(&format!("lit as seen from Earth: {:.0}%", exp)));
+        assert!(BodyInfo::of(&sim, Body::Sun).earth_lit_percent.is_none());
+        assert!(BodyInfo::of(&sim, Body::Earth).earth_lit_percent.is_none());
+    }
+
+    #[test]
+    fn test_body_info_apparent_shift() {
+        let sim = Simulation::init_at(default_epoch());
+        let shift = BodyInfo::of(&sim, Body::Sun).apparent_shift_arcsec.unwrap();
+        assert!((shift - 20.5).abs() < 0.5, "{}", shift);
+        assert!(BodyInfo::of(&sim, Body::Earth).apparent_shift_arcsec.is_none());
+    }
+
+    #[test]
+    fn test_body_info_moon_angular_diameter_and_magnitude() {
+        let sim = Simulation::init_at(default_epoch());
+        let moon = BodyInfo::of(&sim, Body::Moon);
+        let diameter = moon.earth_angular_diameter_arcsec.unwrap();
+        assert!((diameter / 60. - 31.).abs() < 3., "{:?}", moon);
+        assert!(moon.earth_magnitude.is_some_and(|mag| mag < -2.), "{:?}", moon);
+        assert!(moon.text().contains(&format!("from Earth: {:.1}'", diameter / 60.)));
+        assert!(BodyInfo::of(&sim, Body::Earth).earth_magnitude.is_none());
+    }
+
+    #[test]
+    fn test_fmt_arcsec() {
+        assert_eq!(fmt_arcsec(45.25), "45.2\"");
+        assert_eq!(fmt_arcsec(1_860.), "31.0'");
+    }
+}
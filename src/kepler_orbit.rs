@@ -1,13 +1,15 @@
 use std::f64::consts;
 
-use nalgebra::Vector2;
+use nalgebra::{Vector2, Vector3};
+use crate::simulation::OrbitalElements;
 use crate::uom_wrapper::{
     G,
     rem_euclid,
     si::{
         angle::{radian, revolution},
-        f64::{Angle, Length, Mass, Time, Velocity},
+        f64::{Angle, Length, Mass, MassDensity, Time, Velocity},
         length::meter,
+        time::second,
         velocity::meter_per_second,
     },
     typenum::P3,
@@ -15,10 +17,41 @@ use crate::uom_wrapper::{
 
 const TOL: f64 = 1e-8;
 
+// Above this eccentricity, the elliptical Kepler equation is solved starting from the aphelion.
+const HIGH_ECCENTRICITY: f64 = 0.8;
+
+// The largest number of Newton-Raphson iterations used to solve the hyperbolic Kepler equation
+const MAX_ITERATIONS: usize = 100;
+
+// Orbits with eccentricities within this of 1 are treated as parabolic.
+const PARABOLIC_TOL: f64 = 1e-6;
+
 pub fn apsis(eccentricity: f64, semimajor_axis: Length) -> Length {
     (1. + eccentricity) * semimajor_axis
 }
 
+pub fn periapsis(eccentricity: f64, semimajor_axis: Length) -> Length {
+    (1. - eccentricity) * semimajor_axis
+}
+
+// This is the distance from the primary at a given true anomaly.
+// See https://en.wikipedia.org/wiki/True_anomaly
+pub fn orbital_radius(semimajor_axis: Length, eccentricity: f64, true_anomaly: Angle) -> Length {
+    let e = eccentricity;
+    semimajor_axis * (1. - e.powi(2)) / (1. + f64::from(e * true_anomaly.cos()))
+}
+
+// This is the fluid Roche limit, the distance from a primary inside of which a satellite held
+// together only by its own gravity is pulled apart by tides. See
+// https://en.wikipedia.org/wiki/Roche_limit
+pub fn roche_limit(
+    primary_radius: Length,
+    primary_density: MassDensity,
+    satellite_density: MassDensity,
+) -> Length {
+    2.44 * primary_radius * f64::from(primary_density / satellite_density).cbrt()
+}
+
 pub fn period(primary_mass: Mass, satellite_mass: Mass, semimajor_axis: Length) -> Time {
     let tot_mass = primary_mass + satellite_mass;
     consts::TAU * (semimajor_axis.powi(P3::new()) / (G * tot_mass)).sqrt()
@@ -31,12 +64,13 @@ pub fn mean_anomaly(period: Time, periapsis_time: Time, current_time: Time) -> A
     )
 }
 
-// See http://www.stargazing.net/kepler/mean.html
+// See http://www.stargazing.net/kepler/mean.html. Starting from π keeps Newton-Raphson from
+// oscillating for very eccentric orbits like comets'.
 pub fn eccentric_anomaly(eccentricity: f64, mean_anomaly: Angle) -> Angle {
     let tol = Angle::new::<radian>(TOL);
     let e = eccentricity;
     let ma = mean_anomaly;
-    let mut ea = ma;
+    let mut ea = if e > HIGH_ECCENTRICITY { Angle::HALF_TURN } else { ma };
     let mut d = Angle::new::<radian>(f64::INFINITY);
     while d.abs() >= tol {
         d = ea - Angle::new::<radian>((e * ea.sin()).into()) - ma;
@@ -88,18 +122,259 @@ pub fn velocity_mps(eccentricity: f64, speed: Velocity, position: &Vector2<f64>)
     v_dir.scale(speed.get::<meter_per_second>())
 }
 
+// Solve the hyperbolic Kepler equation, M = e sinh(H) - H, for the hyperbolic anomaly H. The
+// eccentricity must be greater than 1. The starting guess is from Danby, Fundamentals of Celestial
+// Mechanics, and keeps Newton-Raphson from overshooting when the orbit is nearly parabolic. See
+// https://en.wikipedia.org/wiki/Hyperbolic_trajectory
+pub fn hyperbolic_anomaly(eccentricity: f64, mean_anomaly: Angle) -> Angle {
+    let e = eccentricity;
+    let ma = mean_anomaly.get::<radian>();
+    let mut ha = ma.signum() * (2. * ma.abs() / e + 1.8).ln();
+    for _ in 0..MAX_ITERATIONS {
+        let d = (e * ha.sinh() - ha - ma) / (e * ha.cosh() - 1.);
+        ha -= d;
+        if d.abs() < TOL {
+            break;
+        }
+    }
+    Angle::new::<radian>(ha)
+}
+
+// See https://en.wikipedia.org/wiki/Hyperbolic_trajectory
+pub fn hyperbolic_true_anomaly(eccentricity: f64, hyperbolic_anomaly: Angle) -> Angle {
+    let e = eccentricity;
+    let ha = hyperbolic_anomaly.get::<radian>();
+    Angle::new::<radian>(2. * (((e + 1.) / (e - 1.)).sqrt() * (ha / 2.).tanh()).atan())
+}
+
+// Solve Barker's equation for the true anomaly of a body on a parabolic orbit. See
+// https://en.wikipedia.org/wiki/Parabolic_trajectory#Barker's_equation
+pub fn parabolic_true_anomaly(
+    primary_mass: Mass,
+    satellite_mass: Mass,
+    periapsis: Length,
+    periapsis_time: Time,
+    current_time: Time,
+) -> Angle {
+    let mu = G * (primary_mass + satellite_mass);
+    let n = (mu / (2. * periapsis.powi(P3::new()))).sqrt();
+    let a = 1.5 * f64::from(n * (current_time - periapsis_time));
+    let b = (a + (a.powi(2) + 1.).sqrt()).cbrt();
+    Angle::new::<radian>(2. * (b - 1. / b).atan())
+}
+
+// Return the true anomaly of a body on an orbit of any eccentricity. The orbit is described by its
+// periapsis distance rather than its semimajor axis, since a parabolic orbit has no semimajor axis.
+pub fn conic_true_anomaly(
+    primary_mass: Mass,
+    satellite_mass: Mass,
+    periapsis: Length,
+    eccentricity: f64,
+    periapsis_time: Time,
+    current_time: Time,
+) -> Angle {
+    let e = eccentricity;
+    if (e - 1.).abs() < PARABOLIC_TOL {
+        return parabolic_true_anomaly(
+            primary_mass, satellite_mass, periapsis, periapsis_time, current_time,
+        );
+    }
+
+    let a = periapsis / (1. - e);
+    if e < 1. {
+        let t = period(primary_mass, satellite_mass, a);
+        true_anomaly(e, eccentric_anomaly(e, mean_anomaly(t, periapsis_time, current_time)))
+    } else {
+        let mu = G * (primary_mass + satellite_mass);
+        let n = (mu / (-a).powi(P3::new())).sqrt();
+        let ma = Angle::new::<radian>(f64::from(n * (current_time - periapsis_time)));
+        hyperbolic_true_anomaly(e, hyperbolic_anomaly(e, ma))
+    }
+}
+
+// This is the distance from the primary at a given true anomaly for an orbit of any eccentricity.
+pub fn conic_radius(periapsis: Length, eccentricity: f64, true_anomaly: Angle) -> Length {
+    let e = eccentricity;
+    periapsis * (1. + e) / (1. + f64::from(e * true_anomaly.cos()))
+}
+
+// TODO: Replace Vector2<Velocity> once uom PRs accepted.
+// Return the velocity in the orbital plane at a given true anomaly for an orbit of any
+// eccentricity. See https://en.wikipedia.org/wiki/Perifocal_coordinate_system
+pub fn conic_velocity_mps(
+    primary_mass: Mass,
+    satellite_mass: Mass,
+    periapsis: Length,
+    eccentricity: f64,
+    true_anomaly: Angle,
+) -> Vector2<f64> {
+    let e = eccentricity;
+    let semilatus_rectum = periapsis * (1. + e);
+    let scale: Velocity = (G * (primary_mass + satellite_mass) / semilatus_rectum).sqrt();
+    Vector2::new(-f64::from(true_anomaly.sin()), e + f64::from(true_anomaly.cos()))
+        .scale(scale.get::<meter_per_second>())
+}
+
+// Recover the elements of an orbit from the satellite's position and velocity relative to its
+// primary. The angles are measured in the frame of the state vectors, and the true anomaly is the
+// satellite's. An equatorial orbit's ascending node is put on the x axis, and a circular orbit's
+// periapsis at its ascending node. A hyperbolic orbit has a negative semimajor axis. See
+// https://en.wikipedia.org/wiki/Orbital_elements
+pub fn elements_from_state(
+    primary_mass: Mass,
+    satellite_mass: Mass,
+    position_m: &Vector3<f64>,
+    velocity_mps: &Vector3<f64>,
+) -> OrbitalElements {
+    let mu = (G * (primary_mass + satellite_mass)).value;
+    let (r, v) = (position_m, velocity_mps);
+    let h = r.cross(v);
+    let e_vec = ((v.norm_squared() - mu / r.norm()) * r - r.dot(v) * v) / mu;
+    let node = Vector3::z().cross(&h);
+    let node_dir = if node.norm() > TOL * h.norm() { node.normalize() } else { Vector3::x() };
+    let in_plane_dir = h.normalize().cross(&node_dir);
+    let angle_from_node = |vec: &Vector3<f64>| {
+        rem_euclid(
+            Angle::new::<radian>(vec.dot(&in_plane_dir).atan2(vec.dot(&node_dir))),
+            Angle::FULL_TURN,
+        )
+    };
+
+    let eccentricity = e_vec.norm();
+    let periapsis_argument = if eccentricity > TOL {
+        angle_from_node(&e_vec)
+    } else {
+        Angle::new::<radian>(0.)
+    };
+    OrbitalElements {
+        semimajor_axis: Length::new::<meter>(1. / (2. / r.norm() - v.norm_squared() / mu)),
+        eccentricity,
+        inclination: Angle::new::<radian>((h.z / h.norm()).acos()),
+        ascending_node: rem_euclid(
+            Angle::new::<radian>(node_dir.y.atan2(node_dir.x)),
+            Angle::FULL_TURN,
+        ),
+        periapsis_argument,
+        true_anomaly: rem_euclid(angle_from_node(r) - periapsis_argument, Angle::FULL_TURN),
+    }
+}
+
+// Return the time of the periapsis passage nearest to when the satellite was at a given true
+// anomaly. The orbit may be elliptical or hyperbolic but not parabolic.
+pub fn periapsis_time(
+    primary_mass: Mass,
+    satellite_mass: Mass,
+    semimajor_axis: Length,
+    eccentricity: f64,
+    true_anomaly: Angle,
+    current_time: Time,
+) -> Time {
+    let e = eccentricity;
+    let half_tan = f64::from((true_anomaly / 2.).tan());
+    let ma = if e < 1. {
+        let ea = 2. * (((1. - e) / (1. + e)).sqrt() * half_tan).atan();
+        ea - e * ea.sin()
+    } else {
+        let ha = 2. * (((e - 1.) / (e + 1.)).sqrt() * half_tan).atanh();
+        e * ha.sinh() - ha
+    };
+    let mu = G * (primary_mass + satellite_mass);
+    let n = (mu / semimajor_axis.abs().powi(P3::new())).sqrt();
+    current_time - Time::new::<second>(ma / n.value)
+}
+
+// Return the Stumpff functions C(z) and S(z) of the universal variable formulation. Near z = 0,
+// their series are used, since the closed forms lose all precision there.
+fn stumpff(z: f64) -> (f64, f64) {
+    if z > TOL {
+        let s = z.sqrt();
+        ((1. - s.cos()) / z, (s - s.sin()) / (s * z))
+    } else if z < -TOL {
+        let s = (-z).sqrt();
+        ((s.cosh() - 1.) / -z, (s.sinh() - s) / (s * -z))
+    } else {
+        (0.5 - z / 24., 1. / 6. - z / 120.)
+    }
+}
+
+// TODO: Replace Vector3<Length> and Vector3<Velocity> once uom PRs accepted.
+// Move a satellite along its Kepler orbit, of any eccentricity, for a span of time, which may be
+// negative. The position and velocity are relative to the primary, whose GM is mu in m³/s². This
+// solves the universal Kepler equation for the universal anomaly χ and applies the Lagrange
+// coefficients. See Curtis, Orbital Mechanics for Engineering Students, §3.7 and §3.8.
+pub fn propagate_state(
+    mu: f64,
+    position_m: &Vector3<f64>,
+    velocity_mps: &Vector3<f64>,
+    span: Time,
+) -> (Vector3<f64>, Vector3<f64>) {
+    let dt = span.get::<second>();
+    let (r0, v0) = (position_m, velocity_mps);
+    let r0_mag = r0.norm();
+    let sqrt_mu = mu.sqrt();
+    let radial = r0.dot(v0) / (r0_mag * sqrt_mu);
+    let alpha = 2. / r0_mag - v0.norm_squared() / mu;
+
+    let mut chi = sqrt_mu * alpha.abs() * dt;
+    for _ in 0..MAX_ITERATIONS {
+        let z = alpha * chi.powi(2);
+        let (c, s) = stumpff(z);
+        let f = radial * r0_mag * chi.powi(2) * c
+            + (1. - alpha * r0_mag) * chi.powi(3) * s
+            + r0_mag * chi
+            - sqrt_mu * dt;
+        let df = radial * r0_mag * chi * (1. - z * s)
+            + (1. - alpha * r0_mag) * chi.powi(2) * c
+            + r0_mag;
+        let d = f / df;
+        chi -= d;
+        if d.abs() <= TOL * chi.abs() {
+            break;
+        }
+    }
+
+    let z = alpha * chi.powi(2);
+    let (c, s) = stumpff(z);
+    let f = 1. - chi.powi(2) / r0_mag * c;
+    let g = dt - chi.powi(3) / sqrt_mu * s;
+    let r = f * r0 + g * v0;
+    let r_mag = r.norm();
+    let f_dot = sqrt_mu / (r_mag * r0_mag) * (z * s - 1.) * chi;
+    let g_dot = 1. - chi.powi(2) / r_mag * c;
+    (r, f_dot * r0 + g_dot * v0)
+}
+
 #[cfg(test)]
 mod tests {
     use std::f64::consts;
 
     use crate::uom_wrapper::{
-        G, si::{angle::degree, f64::{Angle, Mass, Time}, mass::kilogram, time::second}
+        G,
+        si::{
+            angle::degree,
+            f64::{Angle, Mass, MassDensity, Time},
+            length::astronomical_unit,
+            mass::kilogram,
+            mass_density::kilogram_per_cubic_meter,
+            time::second,
+        },
     };
 
     use crate::test::{assert_abs_eq, assert_rel_eq};
 
     use super::*;
 
+    #[test]
+    fn test_roche_limit() {
+        let density = MassDensity::new::<kilogram_per_cubic_meter>(1000.);
+        let act = roche_limit(
+            Length::new::<meter>(1.),
+            8. * density,
+            density,
+        );
+        assert_rel_eq!(act.get::<meter>(), 4.88)
+    }
+
     #[test]
     fn test_period() {
         let act_per = period(
@@ -136,6 +411,26 @@ mod tests {
         assert_abs_eq!(ma.get::<radian>(), ea.get::<radian>() - f64::from(e * ea.sin()))
     }
 
+    #[test]
+    fn test_eccentric_anomaly_converges_for_eris() {
+        let e = 0.436_07;
+        for i in 0..36 {
+            let ma = Angle::new::<degree>(10. * i as f64);
+            let ea = eccentric_anomaly(e, ma);
+            assert_abs_eq!(ma.get::<radian>(), ea.get::<radian>() - f64::from(e * ea.sin()))
+        }
+    }
+
+    #[test]
+    fn test_eccentric_anomaly_converges_for_halley() {
+        let e = 0.967_143;
+        for ma in [0., 1e-6, 1e-3, 0.01, 0.1, 1., 3., 6.2] {
+            let ma = Angle::new::<radian>(ma);
+            let ea = eccentric_anomaly(e, ma);
+            assert_abs_eq!(ma.get::<radian>(), ea.get::<radian>() - f64::from(e * ea.sin()))
+        }
+    }
+
     #[test]
     fn test_true_anomaly_circle() {
         assert_eq!(
@@ -155,6 +450,20 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_periapsis() {
+        assert_rel_eq!(periapsis(0.5, Length::new::<meter>(2.)).get::<meter>(), 1.)
+    }
+
+    #[test]
+    fn test_orbital_radius_extremes() {
+        let a = Length::new::<meter>(2.);
+        let r = orbital_radius(a, 0.5, Angle::new::<revolution>(0.));
+        assert_rel_eq!(r.get::<meter>(), periapsis(0.5, a).get::<meter>());
+        let r = orbital_radius(a, 0.5, Angle::new::<revolution>(0.5));
+        assert_rel_eq!(r.get::<meter>(), apsis(0.5, a).get::<meter>());
+    }
+
     #[test]
     fn test_radial_distance_extremes() {
         let d = radial_distance(Length::new::<meter>(2.), 0.5, Angle::new::<revolution>(0.));
@@ -195,4 +504,133 @@ mod tests {
         let act_pos = position_m(Length::new::<meter>(2.), Angle::new::<degree>(30.));
         assert_rel_eq!(act_pos, exp_pos)
     }
+
+    #[test]
+    fn test_hyperbolic_anomaly_analytic() {
+        let (e, ha) = (2., 1.5);
+        let ma = Angle::new::<radian>(e * f64::sinh(ha) - ha);
+        assert_abs_eq!(hyperbolic_anomaly(e, ma).get::<radian>(), ha)
+    }
+
+    #[test]
+    fn test_hyperbolic_anomaly_converges() {
+        for e in [1.000_01, 1.1, 1.5, 2., 3.] {
+            for ma in [-50., -1., 0., 0.01, 1., 10., 1000.] {
+                let ha = hyperbolic_anomaly(e, Angle::new::<radian>(ma)).get::<radian>();
+                assert!((e * ha.sinh() - ha - ma).abs() < 1e-6 * ma.abs().max(1.), "{} {}", e, ma);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hyperbolic_true_anomaly_relation() {
+        let (e, ha) = (1.5, 0.8f64);
+        let ta = hyperbolic_true_anomaly(e, Angle::new::<radian>(ha));
+        assert_abs_eq!(f64::from(ta.cos()), (e - ha.cosh()) / (e * ha.cosh() - 1.))
+    }
+
+    #[test]
+    fn test_parabolic_true_anomaly_quarter() {
+        // tan(ν/2) + tan³(ν/2)/3 = 4/3 when ν = 90°.
+        let (mu, q) = (2., 1.);
+        let dt = 4. / 3. / f64::sqrt(mu / (2. * q * q * q));
+        let ta = parabolic_true_anomaly(
+            Mass::new::<kilogram>(1. / G.value),
+            Mass::new::<kilogram>(1. / G.value),
+            Length::new::<meter>(q),
+            Time::new::<second>(0.),
+            Time::new::<second>(dt),
+        );
+        assert_abs_eq!(ta.get::<degree>(), 90.)
+    }
+
+    #[test]
+    fn test_conic_true_anomaly_elliptical() {
+        let (m1, m2) = (Mass::new::<kilogram>(2e30), Mass::new::<kilogram>(6e24));
+        let (a, e) = (Length::new::<meter>(1.5e11), 0.3);
+        let (tp, t) = (Time::new::<second>(0.), Time::new::<second>(1e7));
+        let exp = true_anomaly(e, eccentric_anomaly(e, mean_anomaly(period(m1, m2, a), tp, t)));
+        let act = conic_true_anomaly(m1, m2, periapsis(e, a), e, tp, t);
+        assert_eq!(act, exp)
+    }
+
+    #[test]
+    fn test_conic_velocity_vis_viva() {
+        let (m1, m2) = (Mass::new::<kilogram>(2e30), Mass::new::<kilogram>(1e10));
+        let (q, e) = (Length::new::<meter>(3.8e10), 1.2);
+        let ta = conic_true_anomaly(
+            m1, m2, q, e, Time::new::<second>(0.), Time::new::<second>(3e6),
+        );
+        let r = conic_radius(q, e, ta);
+        let v = conic_velocity_mps(m1, m2, q, e, ta).magnitude();
+        assert_rel_eq!(v, speed(m1, m2, q / (1. - e), r).get::<meter_per_second>())
+    }
+
+    #[test]
+    fn test_conic_velocity_perpendicular_at_periapsis() {
+        let m = Mass::new::<kilogram>(1e30);
+        let v = conic_velocity_mps(m, m, Length::new::<meter>(1e10), 2., Angle::new::<radian>(0.));
+        assert_eq!(v[0], 0.);
+        assert!(v[1] > 0.)
+    }
+
+    #[test]
+    fn test_elements_from_earth_j2000_state() {
+        // Earth's heliocentric ecliptic state at J2000 from JPL Horizons, in km and km/s
+        let pos = Vector3::new(-2.521_092_86e7, 1.449_279_20e8, -6.164_166e2) * 1e3;
+        let vel = Vector3::new(-2.983_983_33e1, -5.207_633_90, 6.168_44e-5) * 1e3;
+        let elements = elements_from_state(
+            Mass::new::<kilogram>(1.988_5e30), Mass::new::<kilogram>(5.972_17e24), &pos, &vel,
+        );
+        assert!((elements.semimajor_axis.get::<astronomical_unit>() - 1.).abs() < 0.001);
+        assert!((elements.eccentricity - 0.016_7).abs() < 0.000_5);
+        assert!(elements.inclination.get::<degree>() < 0.01);
+    }
+
+    #[test]
+    fn test_elements_from_state_inclined_circle() {
+        let m = Mass::new::<kilogram>(0.5 / G.value);
+        let (sin_i, cos_i) = consts::FRAC_PI_6.sin_cos();
+        let pos = Vector3::new(0., cos_i, sin_i);
+        let vel = Vector3::new(-1., 0., 0.);
+        let elements = elements_from_state(m, m, &pos, &vel);
+        assert_abs_eq!(elements.semimajor_axis.get::<meter>(), 1.);
+        assert_abs_eq!(elements.eccentricity, 0.);
+        assert_abs_eq!(elements.inclination.get::<radian>(), consts::FRAC_PI_6);
+        assert_abs_eq!(elements.ascending_node.get::<radian>(), 0.);
+        assert_abs_eq!(elements.true_anomaly.get::<radian>(), consts::FRAC_PI_2)
+    }
+
+    #[test]
+    fn test_periapsis_time_round_trips() {
+        let (m1, m2) = (Mass::new::<kilogram>(2e30), Mass::new::<kilogram>(6e24));
+        let q = Length::new::<meter>(1e11);
+        let (tp, t) = (Time::new::<second>(1e6), Time::new::<second>(4e6));
+        for e in [0., 0.3, 0.9, 1.5] {
+            let ta = conic_true_anomaly(m1, m2, q, e, tp, t);
+            let act = periapsis_time(m1, m2, q / (1. - e), e, ta, t);
+            assert!((act - tp).abs().get::<second>() < 1e-3, "{}: {:?}", e, act);
+        }
+    }
+
+    #[test]
+    fn test_propagate_state_quarter_circle() {
+        let (pos, vel) = (Vector3::new(1., 0., 0.), Vector3::new(0., 1., 0.));
+        let quarter = Time::new::<second>(consts::FRAC_PI_2);
+        let (act_pos, act_vel) = propagate_state(1., &pos, &vel, quarter);
+        assert!((act_pos - Vector3::new(0., 1., 0.)).norm() < 1e-9, "{}", act_pos);
+        assert!((act_vel - Vector3::new(-1., 0., 0.)).norm() < 1e-9, "{}", act_vel);
+        let (back_pos, _) = propagate_state(1., &act_pos, &act_vel, -quarter);
+        assert!((back_pos - pos).norm() < 1e-9, "{}", back_pos);
+    }
+
+    #[test]
+    fn test_propagate_state_hyperbola_keeps_energy_and_momentum() {
+        let (pos, vel) = (Vector3::new(1., 0., 0.), Vector3::new(0.5, 1.5, 0.2));
+        let energy = |p: &Vector3<f64>, v: &Vector3<f64>| v.norm_squared() / 2. - 1. / p.norm();
+        let (act_pos, act_vel) = propagate_state(1., &pos, &vel, Time::new::<second>(20.));
+        assert!(act_pos.norm() > 10., "{}", act_pos);
+        assert_abs_eq!(energy(&act_pos, &act_vel), energy(&pos, &vel));
+        assert!((act_pos.cross(&act_vel) - pos.cross(&vel)).norm() < 1e-8);
+    }
 }
@@ -0,0 +1,194 @@
+use bevy::{
+    math::{DQuat, DVec3},
+    prelude::*,
+};
+
+use crate::{
+    simulation::Body,
+    uom_wrapper::si::{length::astronomical_unit, mass::kilogram, velocity::meter_per_second},
+    DrawnPositions, Simulation,
+};
+
+// The key cycling through the pairs of bodies whose Lagrange points are shown
+const CYCLE_KEY: KeyCode = KeyCode::Backslash;
+
+// The pairs of bodies, each a primary and the secondary orbiting it, in the order they are cycled
+// through
+const PAIRS: [(Body, Body); 3] =
+    [(Body::Sun, Body::Earth), (Body::Earth, Body::Moon), (Body::Sun, Body::Jupiter)];
+
+// The radius of a marker as a fraction of the distance between the pair
+const MARKER_FRACTION: f32 = 0.01;
+
+// The largest number of Newton-Raphson iterations used to find a collinear point
+const MAX_ITERATIONS: usize = 50;
+
+const TOL: f64 = 1e-12;
+
+const MARKER_COLOR: Color = Color::srgb(0.4, 1., 0.6);
+
+// This is the pair of bodies whose Lagrange points are shown, if any.
+#[derive(Default, Resource)]
+pub struct LagrangeView {
+    pub pair: Option<(Body, Body)>,
+}
+
+// Return the positions of L1, L2, and L3 along the line from the primary to the secondary. They
+// are measured from the pair's barycenter in units of the distance between them, with the
+// secondary at 1 - mu, where mu is the secondary's fraction of the pair's mass. Each is where the
+// gravity of the two bodies balances the centrifugal force in the frame turning with them. See
+// https://en.wikipedia.org/wiki/Lagrange_point
+fn collinear_points(mu: f64) -> [f64; 3] {
+    let hill = (mu / 3.).cbrt();
+    let guesses = [1. - mu - hill, 1. - mu + hill, -1. - 5. * mu / 12.];
+    guesses.map(|mut x| {
+        for _ in 0..MAX_ITERATIONS {
+            let (r1, r2) = (x + mu, x - 1. + mu);
+            let f = x - (1. - mu) * r1 / r1.abs().powi(3) - mu * r2 / r2.abs().powi(3);
+            let df = 1. + 2. * (1. - mu) / r1.abs().powi(3) + 2. * mu / r2.abs().powi(3);
+            let d = f / df;
+            x -= d;
+            if d.abs() < TOL {
+                break;
+            }
+        }
+        x
+    })
+}
+
+// Return the five Lagrange points of a secondary of mass m2 orbiting a primary of mass m1. The
+// secondary's velocity relative to the primary orients the plane of its orbit. L4 leads the
+// secondary by 60° and L5 trails it by 60°, each at the corner of an equilateral triangle with the
+// two bodies.
+fn lagrange_points(
+    (p1, m1): (DVec3, f64),
+    (p2, m2): (DVec3, f64),
+    relative_velocity: DVec3,
+) -> [DVec3; 5] {
+    let separation = p2 - p1;
+    let mu = m2 / (m1 + m2);
+    let barycenter = p1 + mu * separation;
+    let [l1, l2, l3] = collinear_points(mu).map(|x| barycenter + x * separation);
+    let pole = separation.cross(relative_velocity).normalize_or_zero();
+    let corner = |angle: f64| p1 + DQuat::from_axis_angle(pole, angle) * separation;
+    let sixty = 60_f64.to_radians();
+    [l1, l2, l3, corner(sixty), corner(-sixty)]
+}
+
+fn points_in(sim: &Simulation, (primary, secondary): (Body, Body)) -> [DVec3; 5] {
+    let mass = |body| sim.mass_of(body).get::<kilogram>();
+    lagrange_points(
+        (sim.position_in::<astronomical_unit>(primary), mass(primary)),
+        (sim.position_in::<astronomical_unit>(secondary), mass(secondary)),
+        sim.relative_velocity_in::<meter_per_second>(secondary, primary),
+    )
+}
+
+// The key cycles through the pairs and off.
+fn cycle_pair(
+    input: Res<ButtonInput<KeyCode>>,
+    sim: Res<Simulation>,
+    mut view: ResMut<LagrangeView>,
+) {
+    if !input.just_pressed(CYCLE_KEY) {
+        return;
+    }
+    view.pair = match view.pair.and_then(|pair| PAIRS.iter().position(|p| *p == pair)) {
+        None => Some(PAIRS[0]),
+        Some(idx) => PAIRS.get(idx + 1).copied(),
+    };
+    match view.pair {
+        Some((primary, secondary)) => info!(
+            "Showing the Lagrange points of the {}–{} system",
+            sim.name_of(primary),
+            sim.name_of(secondary)
+        ),
+        None => info!("Hiding the Lagrange points"),
+    }
+}
+
+// Return where a point found from the pair at the latest step is drawn, given how far each of the
+// pair is drawn from where it is then. The point is shifted like the points along the line through
+// the pair, so a point at the secondary moves with it, and one opposite it moves the other way.
+fn drawn_point(point: Vec3, (p1, p2): (Vec3, Vec3), (shift1, shift2): (Vec3, Vec3)) -> Vec3 {
+    let separation = p2 - p1;
+    let along = (point - p1).dot(separation) / separation.length_squared().max(f32::MIN_POSITIVE);
+    point + shift1 + along * (shift2 - shift1)
+}
+
+// Each point is marked with a sphere scaled to the distance between the pair, so the points of
+// the Earth–Moon system are as easy to see as those of the Sun–Jupiter system. The points are
+// drawn every frame, shifted along with the pair's avatars between steps.
+fn draw_lagrange_points(
+    sim: Res<Simulation>,
+    view: Res<LagrangeView>,
+    drawn: DrawnPositions,
+    mut gizmos: Gizmos,
+) {
+    let Some(pair) = view.pair else {
+        return;
+    };
+    let positions = (sim.position_of(pair.0), sim.position_of(pair.1));
+    let shifts = (drawn.shift_of(pair.0), drawn.shift_of(pair.1));
+    let radius = MARKER_FRACTION * drawn.of(pair.0).distance(drawn.of(pair.1));
+    for point in points_in(&sim, pair) {
+        let point = drawn_point(sim.display_position(point, None), positions, shifts);
+        gizmos.sphere(point, Quat::IDENTITY, radius, MARKER_COLOR);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<LagrangeView>()
+        .add_systems(Update, (cycle_pair, draw_lagrange_points.after(crate::update_avatars)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        default_epoch,
+        uom_wrapper::si::{f64::Time, time::day},
+    };
+
+    #[test]
+    fn test_collinear_points_earth_moon() {
+        let [l1, l2, l3] = collinear_points(0.012_150_6);
+        assert!((l1 - 0.836_915).abs() < 1e-5, "{}", l1);
+        assert!((l2 - 1.155_682).abs() < 1e-5, "{}", l2);
+        assert!((l3 + 1.005_063).abs() < 1e-5, "{}", l3);
+    }
+
+    #[test]
+    fn test_drawn_points_shift_with_pair() {
+        let pair = (Vec3::ZERO, Vec3::X);
+        let shifts = (Vec3::ZERO, 0.1 * Vec3::Y);
+        // A point at the secondary moves with it, one opposite it the other way, and one at the
+        // corner of the equilateral triangle half as far.
+        assert_eq!(drawn_point(Vec3::X, pair, shifts), Vec3::new(1., 0.1, 0.));
+        assert_eq!(drawn_point(-Vec3::X, pair, shifts), Vec3::new(-1., -0.1, 0.));
+        let corner = Vec3::new(0.5, 0.866, 0.);
+        assert_eq!(drawn_point(corner, pair, shifts), corner + 0.05 * Vec3::Y);
+        // When the pair moves together, every point moves with it.
+        let together = (Vec3::Z, Vec3::Z);
+        assert_eq!(drawn_point(corner, pair, together), corner + Vec3::Z);
+    }
+
+    #[test]
+    fn test_sun_earth_l4_l5_stay_60_degrees_from_earth() {
+        let mut sim = Simulation::init_at(default_epoch());
+        for _ in 0..3 {
+            let [.., l4, l5] = points_in(&sim, (Body::Sun, Body::Earth));
+            let sun = sim.position_in::<astronomical_unit>(Body::Sun);
+            let earth = sim.position_in::<astronomical_unit>(Body::Earth) - sun;
+            let vel = sim.relative_velocity_in::<meter_per_second>(Body::Earth, Body::Sun);
+            let pole = earth.cross(vel);
+            for (point, sign) in [(l4 - sun, 1.), (l5 - sun, -1.)] {
+                let angle = earth.angle_between(point).to_degrees();
+                assert!((angle - 60.).abs() < 1e-6, "{}", angle);
+                assert!((point.length() - earth.length()).abs() < 1e-9);
+                assert_eq!(earth.cross(point).dot(pole).signum(), sign);
+            }
+            sim.advance_by(Time::new::<day>(30.));
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    render_settings::{RenderSettings, RenderTuning},
+    simulation::Body,
+    BodyModel, Simulation, Trail, WorldBounds,
+};
+
+// The key showing and hiding the layers panel
+const TOGGLE_KEY: KeyCode = KeyCode::Quote;
+
+const FONT_SIZE: f32 = 14.;
+
+// The width in logical pixels of the column of body names
+const NAME_WIDTH: f32 = 90.;
+
+// These are the parts of a body's depiction that can be hidden one body at a time.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Layer {
+    Label,
+    Trail,
+    Light,
+}
+
+impl Layer {
+    const ALL: [Self; 3] = [Self::Label, Self::Trail, Self::Light];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Label => "label",
+            Self::Trail => "trail",
+            Self::Light => "light",
+        }
+    }
+}
+
+// This is what is hidden to declutter the view: the labels, trails, and lights of individual
+// bodies, and all of the moons or all of the asteroids at once. Hiding the moons hides their
+// avatars along with their labels and trails. The layers are saved to a file each time they are
+// changed in the panel, next to the camera bookmarks, so they survive restarts.
+#[derive(Debug, PartialEq, Resource)]
+pub struct Layers {
+    pub path: PathBuf,
+    hidden: HashSet<(Layer, Body)>,
+    pub moons: bool,
+    pub asteroids: bool,
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("layers.txt"),
+            hidden: HashSet::new(),
+            moons: true,
+            asteroids: true,
+        }
+    }
+}
+
+impl Layers {
+    pub fn shows_body(&self, body: Body) -> bool {
+        self.moons || !body.is_moon()
+    }
+
+    pub fn shows(&self, layer: Layer, body: Body) -> bool {
+        self.shows_body(body) && !self.hidden.contains(&(layer, body))
+    }
+
+    pub fn toggle(&mut self, layer: Layer, body: Body) {
+        if !self.hidden.remove(&(layer, body)) {
+            self.hidden.insert((layer, body));
+        }
+    }
+}
+
+// Each line of the file names something hidden, "moons", "asteroids", or a layer followed by a
+// body, e.g., "label Mars". The bodies are written in RON, so custom bodies read back too.
+fn encode(layers: &Layers) -> String {
+    let mut lines: Vec<String> = layers
+        .hidden
+        .iter()
+        .filter_map(|(layer, body)| {
+            ron::to_string(body).ok().map(|body| format!("{} {}", layer.name(), body))
+        })
+        .collect();
+    lines.sort();
+    if !layers.moons {
+        lines.push(String::from("moons"));
+    }
+    if !layers.asteroids {
+        lines.push(String::from("asteroids"));
+    }
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn decode(text: &str) -> Layers {
+    let mut layers = Layers::default();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match line {
+            "moons" => layers.moons = false,
+            "asteroids" => layers.asteroids = false,
+            _ => {
+                let parsed = line.split_once(' ').and_then(|(name, body)| {
+                    let layer = Layer::ALL.into_iter().find(|layer| layer.name() == name)?;
+                    Some((layer, ron::from_str(body).ok()?))
+                });
+                match parsed {
+                    Some(hidden) => {
+                        layers.hidden.insert(hidden);
+                    }
+                    None => warn!("Ignoring malformed layer {}", line),
+                }
+            }
+        }
+    }
+    layers
+}
+
+// The layers saved in an earlier session are loaded if there are any.
+fn load_layers(mut layers: ResMut<Layers>) {
+    if let Ok(text) = fs::read_to_string(&layers.path) {
+        *layers = Layers {
+            path: layers.path.clone(),
+            ..decode(&text)
+        };
+    }
+}
+
+// These are the check boxes of the panel.
+#[derive(Clone, Copy, Component)]
+enum LayerToggle {
+    Body(Layer, Body),
+    Moons,
+    Asteroids,
+}
+
+impl LayerToggle {
+    fn is_on(&self, layers: &Layers) -> bool {
+        match self {
+            Self::Body(layer, body) => !layers.hidden.contains(&(*layer, *body)),
+            Self::Moons => layers.moons,
+            Self::Asteroids => layers.asteroids,
+        }
+    }
+
+    fn text(&self, layers: &Layers) -> String {
+        let name = match self {
+            Self::Body(layer, _) => layer.name(),
+            Self::Moons => "moons",
+            Self::Asteroids => "asteroids",
+        };
+        format!("[{}] {}", if self.is_on(layers) { "x" } else { " " }, name)
+    }
+
+    fn flip(&self, layers: &mut Layers) {
+        match self {
+            Self::Body(layer, body) => layers.toggle(*layer, *body),
+            Self::Moons => layers.moons = !layers.moons,
+            Self::Asteroids => layers.asteroids = !layers.asteroids,
+        }
+    }
+}
+
+#[derive(Component)]
+struct LayersPanel;
+
+fn spawn_toggle(parent: &mut ChildBuilder, toggle: LayerToggle, layers: &Layers) {
+    parent.spawn((
+        TextBundle::from_section(
+            toggle.text(layers),
+            TextStyle {
+                font_size: FONT_SIZE,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::right(Val::Px(8.)),
+            ..default()
+        }),
+        Interaction::default(),
+        toggle,
+    ));
+}
+
+// The panel lists the bodies by name, each with a check box for its label, its trail, and its
+// light, if it has them. It is built after the labels and trails are, so it knows which bodies have
+// them, and it is rebuilt along with them when another scenario is loaded.
+fn create_panel(
+    sim: Res<Simulation>,
+    layers: Res<Layers>,
+    mut commands: Commands,
+    bodies: Query<(&Body, &BodyModel, Has<Trail>)>,
+) {
+    let mut rows: Vec<(String, Body, Vec<Layer>)> = bodies
+        .iter()
+        .map(|(body, model, has_trail)| {
+            let has = [model.label().is_some(), has_trail, sim.luminosity_of(*body) > 0.];
+            let body_layers = Layer::ALL.into_iter().zip(has);
+            let body_layers = body_layers.filter_map(|(layer, has)| has.then_some(layer)).collect();
+            (sim.name_of(*body), *body, body_layers)
+        })
+        .collect();
+    rows.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    let text_style = TextStyle {
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+        ..default()
+    };
+    let row_style = Style {
+        flex_direction: FlexDirection::Row,
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(60.),
+                    left: Val::Px(10.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            LayersPanel,
+        ))
+        .with_children(|panel| {
+            panel
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_toggle(row, LayerToggle::Moons, &layers);
+                    spawn_toggle(row, LayerToggle::Asteroids, &layers);
+                });
+            for (name, body, body_layers) in rows {
+                panel
+                    .spawn(NodeBundle {
+                        style: row_style.clone(),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(TextBundle::from_section(name, text_style.clone()).with_style(
+                            Style {
+                                width: Val::Px(NAME_WIDTH),
+                                ..default()
+                            },
+                        ));
+                        for layer in body_layers {
+                            spawn_toggle(row, LayerToggle::Body(layer, body), &layers);
+                        }
+                    });
+            }
+        });
+}
+
+fn toggle_panel(
+    input: Res<ButtonInput<KeyCode>>,
+    mut panel: Query<&mut Visibility, With<LayersPanel>>,
+) {
+    if input.just_pressed(TOGGLE_KEY) {
+        for mut vis in &mut panel {
+            *vis = match *vis {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+// Clicking a check box flips it, and the layers are saved.
+fn click_toggles(
+    mut layers: ResMut<Layers>,
+    toggles: Query<(&Interaction, &LayerToggle), Changed<Interaction>>,
+) {
+    let mut flipped = false;
+    for (_, toggle) in toggles.iter().filter(|(int, _)| **int == Interaction::Pressed) {
+        toggle.flip(&mut layers);
+        flipped = true;
+    }
+    if !flipped {
+        return;
+    }
+
+    let path = &layers.path;
+    if let Err(err) = fs::write(path, encode(&layers)) {
+        error!("Failed to save the layers to {}: {}", path.display(), err);
+    }
+}
+
+fn update_toggles(layers: Res<Layers>, mut toggles: Query<(&LayerToggle, &mut Text)>) {
+    if !layers.is_changed() {
+        return;
+    }
+    for (toggle, mut text) in &mut toggles {
+        text.sections[0].value = toggle.text(&layers);
+    }
+}
+
+// When the layers change, the moons' avatars are hidden or shown, and the lights of the luminous
+// bodies are despawned when hidden and spawned again when shown.
+fn apply_layers(
+    (layers, sim): (Res<Layers>, Res<Simulation>),
+    (settings, tuning, bounds): (Res<RenderSettings>, Res<RenderTuning>, Res<WorldBounds>),
+    mut commands: Commands,
+    bodies: Query<(&Body, &BodyModel)>,
+    mut avatars: Query<(&mut Visibility, Option<&Children>)>,
+    lights: Query<(), With<PointLight>>,
+) {
+    if !layers.is_changed() {
+        return;
+    }
+
+    for (body, model) in &bodies {
+        let Some(avatar) = model.avatar() else {
+            continue;
+        };
+        let Ok((mut visibility, children)) = avatars.get_mut(avatar) else {
+            continue;
+        };
+        visibility.set_if_neq(if layers.shows_body(*body) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if sim.luminosity_of(*body) == 0. {
+            continue;
+        }
+
+        let light = children.into_iter().flatten().find(|child| lights.contains(**child));
+        match (layers.shows(Layer::Light, *body), light) {
+            (true, None) => {
+                let bundle = crate::mk_light_bundle(
+                    &sim,
+                    *body,
+                    (&settings, &tuning, &bounds),
+                    model.avatar_radius(),
+                );
+                commands.entity(avatar).with_children(|parent| {
+                    parent.spawn(bundle);
+                });
+            }
+            (false, Some(light)) => commands.entity(*light).despawn_recursive(),
+            _ => (),
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<Layers>()
+        .add_systems(Startup, load_layers.before(crate::spawn_bodies))
+        .add_systems(crate::SpawnBodies, create_panel.after(crate::create_labels))
+        .add_systems(
+            Update,
+            (toggle_panel, click_toggles, (update_toggles, apply_layers)).chain(),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_hiding_moons_hides_their_layers() {
+        let mut layers = Layers::default();
+        assert!(layers.shows(Layer::Trail, Body::Moon));
+        layers.moons = false;
+        assert!(!layers.shows_body(Body::Moon));
+        assert!(!layers.shows(Layer::Trail, Body::Moon));
+        assert!(layers.shows(Layer::Trail, Body::Earth));
+    }
+
+    #[test]
+    fn test_layers_toggle() {
+        let mut layers = Layers::default();
+        layers.toggle(Layer::Light, Body::Sun);
+        assert!(!layers.shows(Layer::Light, Body::Sun));
+        assert!(layers.shows(Layer::Label, Body::Sun));
+        layers.toggle(Layer::Light, Body::Sun);
+        assert!(layers.shows(Layer::Light, Body::Sun));
+    }
+
+    #[test]
+    fn test_layers_round_trip() {
+        let mut layers = Layers::default();
+        layers.toggle(Layer::Label, Body::Mars);
+        layers.toggle(Layer::Trail, Body::Custom(2));
+        layers.asteroids = false;
+        assert_eq!(encode(&layers), "label Mars\ntrail Custom(2)\nasteroids\n");
+        assert_eq!(decode(&encode(&layers)), layers);
+    }
+
+    #[test]
+    fn test_decode_skips_malformed() {
+        let layers = decode("label Vulcan\nshadow Mars\nmoons\n");
+        assert_eq!(layers, Layers { moons: false, ..default() });
+    }
+}
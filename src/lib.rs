@@ -1,11 +1,17 @@
 extern crate bevy;
 
-use std::collections::HashSet;
+use std::{collections::HashSet, path::PathBuf};
 
 use bevy::{
-    core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping},
-    ecs::component::{ComponentHooks, StorageType},
+    ecs::{
+        component::{ComponentHooks, StorageType},
+        schedule::ScheduleLabel,
+        system::SystemParam,
+    },
+    math::DVec3,
+    pbr::{NotShadowCaster, NotShadowReceiver},
     prelude::*,
+    sprite::Anchor,
     utils::HashMap,
     window::PrimaryWindow,
 };
@@ -16,59 +22,219 @@ use bevy_mod_billboard::prelude::*;
 extern crate bevy_framepace;
 use bevy_framepace::FramepacePlugin;
 
+extern crate nalgebra;
+use nalgebra::Vector3;
+
+extern crate serde;
+use serde::{Deserialize, Serialize};
+
 extern crate strum;
 
 mod uom_wrapper;
+pub use uom_wrapper::parse_epoch;
 use uom_wrapper::{
+    Conversion,
     MPS_TO_AUPD,
     si::{
         angle::radian,
         f32::{Angle, Length},
         f64::{self, Time},
-        length::{astronomical_unit, inch, meter},
+        length::{self, astronomical_unit, meter},
         time::{day, minute},
+        velocity::{self, meter_per_second},
     },
 };
 
 mod kepler_orbit;
 
+mod photometry;
+
+mod shadow;
+use shadow::Shadow;
+
+mod sim_rate;
+
 mod simulation;
-use simulation::{Body, SolarSystem};
+pub use simulation::{Body, BodyProperties, OrbitalElements, Propagator, SolarSystem};
+use simulation::SimDiagnostics;
 
-#[cfg(test)]
-mod test;
+mod asteroid_belt;
 
-// The radius of the rendering volume in AU.
-const WORLD_RADIUS_AU: f32 = 100.;
+mod atmosphere;
+use atmosphere::{Atmosphere, AtmosphereGlow};
+
+mod auto_slowdown;
+pub use auto_slowdown::AutoSlowdown;
+
+mod barycenter;
+pub use barycenter::center_on_barycenter;
+
+mod bookmarks;
+
+mod camera_animation;
+use camera_animation::{CameraAnimation, ObserverPose};
+
+mod clock;
+use clock::SimClock;
+
+mod comet_tail;
+
+mod cursor_coords;
+
+mod diagnostics;
+
+mod drop_lines;
+
+mod eclipses;
+pub use eclipses::{Eclipse, EclipseSeverity};
+
+mod ecliptic_grid;
+
+mod ephemeris;
+pub use ephemeris::write_ephemeris_csv;
+
+mod events;
+pub use events::SolarSystemEvent;
+
+mod fps_overlay;
+
+mod free_fly;
+
+mod follow_camera;
+use follow_camera::{follow_pose, CameraTarget, FOLLOW_DISTANCE_RADII, FOLLOW_RATE};
+
+mod gltf_export;
+pub use gltf_export::SnapshotExport;
+
+mod hud;
+
+mod info_panel;
+
+mod lagrange;
+
+mod layers;
+pub use layers::{Layer, Layers};
+
+mod measure;
+
+mod meridians;
+pub use meridians::MeridianMarkers;
+
+mod minimap;
+
+mod occultations;
+pub use occultations::Occultations;
+
+mod orbit_control;
+
+mod orbit_markers;
+
+mod palette;
+use palette::Palette;
+
+mod radial_mode;
+pub use radial_mode::RadialMode;
+
+mod recording;
+pub use recording::FrameRecording;
+
+mod reference_frame;
+pub use reference_frame::ReferenceFrame;
+
+mod render_scale;
+
+mod render_settings;
+use render_settings::{next_lod, AvatarLod, AvatarMeshes, RenderSettings, RenderTuning};
+
+mod retrograde;
+
+mod ring_shadows;
+
+mod rings;
+use rings::{Rings, RingsOf};
+
+mod roche;
+
+mod saved_state;
+
+mod scale_bar;
+
+mod scale_mode;
+pub use scale_mode::VisibilityTuning;
+use scale_mode::ScaleMode;
+
+mod scenarios;
+pub use scenarios::{Scenario, Scenarios};
+
+mod scene;
+pub use scene::{add_bodies, read_scenario, read_scene, BodyDef, StateVector};
+
+mod screenshot;
+
+mod search;
+
+mod seed;
+pub use seed::SimSeed;
+
+mod selection;
+
+mod shadow_cone;
+
+mod spacecraft;
+pub use spacecraft::Spacecraft;
+
+mod starfield;
 
-// Twice the minimum angular resolution in radians of the human eye
-const EYE_ANG_RES_RAD: f32 = 3e-4 * 2.;
+mod terminator;
 
-// The minimum distance in meters someone typically sits away from a laptop display.
-const MIN_OBSERVER_DIST_M: f32 = 0.3;
+mod tour;
 
-// The standard DPI of a monitor
-const STANDARD_DPI: f32 = 96.;
+mod trails;
+use trails::Trail;
+
+mod velocity_arrows;
+
+mod view_presets;
+
+#[cfg(test)]
+mod test;
+
+// The default radius of the rendering volume in AU
+const WORLD_RADIUS_AU: f32 = 100.;
 
 // The minimum distance in AU away from the camera for an object to be rendered
 const ZNEAR_AU: f32 = 0.001;
 
-// The maximum distance in AU away from the camera for an object to be rendered
+// The default maximum distance in AU away from the camera for an object to be rendered
 const ZFAR_AU: f32 = 100.;
 
-// The scaling to prevent the Sun's light from saturating the camera and causing distortions
-const LUMINOSITY_SCALE: f32 = 1e-26;
-
-// The offset of the label below the body in normalized device units
+// The default offset of the label from the body in normalized device units
 const LABEL_OFFSET: f32 = 0.03;
 
-// The scaling applied to the labels to get the to an appropriate size.
+// The default scaling applied to the labels to get the to an appropriate size.
 const LABEL_SCALE: f32 = 0.0003;
 
+// The height in logical pixels of the window the label scale sizes the labels for. The labels keep
+// the same size on screen in taller or shorter windows.
+const LABEL_REFERENCE_HEIGHT: f32 = 1080.;
+
+// The extent of Saturn's main rings, from the inner edge of the C ring to the outer edge of the A
+// ring, in multiples of Saturn's radius
+const SATURN_RINGS_INNER_RADII: f32 = 1.28;
+const SATURN_RINGS_OUTER_RADII: f32 = 2.35;
+
+// The extent of Uranus's main rings, from ring 6 to the epsilon ring, in multiples of Uranus's
+// radius. Uranus is tilted nearly onto its side, so its rings stand almost upright to its orbit.
+const URANUS_RINGS_INNER_RADII: f32 = 1.64;
+const URANUS_RINGS_OUTER_RADII: f32 = 2.;
+
 // Manages the visual display properties of a body
 struct BodyVisual {
     name: String,
     color: Color,
+    palette_colors: HashMap<Palette, Color>,
+    rings: Option<Rings>,
+    atmosphere: Option<Atmosphere>,
 }
 
 impl BodyVisual {
@@ -76,35 +242,72 @@ impl BodyVisual {
         Self {
             name: name.to_string(),
             color: *color,
+            palette_colors: HashMap::new(),
+            rings: None,
+            atmosphere: None,
         }
     }
 
+    // Derive the body's color in each palette from its natural color
+    pub fn with_palette_colors(mut self, body: Body) -> Self {
+        let natural = self.color;
+        self.palette_colors = Palette::ALL
+            .iter()
+            .map(|palette| (*palette, palette.color_of(body, natural)))
+            .collect();
+        self
+    }
+
+    pub fn with_rings(mut self, rings: Rings) -> Self {
+        self.rings = Some(rings);
+        self
+    }
+
+    pub fn with_atmosphere(mut self, thickness_radii: f32, color: Color) -> Self {
+        self.atmosphere = Some(Atmosphere { thickness_radii, color });
+        self
+    }
+
     pub fn name(&self) -> &String {
         &self.name
     }
 
-    pub fn color(&self) -> &Color {
-        &self.color
+    pub fn color(&self, palette: Palette) -> &Color {
+        self.palette_colors.get(&palette).unwrap_or(&self.color)
+    }
+
+    pub fn rings(&self) -> Option<&Rings> {
+        self.rings.as_ref()
+    }
+
+    pub fn atmosphere(&self) -> Option<&Atmosphere> {
+        self.atmosphere.as_ref()
     }
 }
 
 #[derive(Resource)]
-struct Simulation {
+pub struct Simulation {
     solar_system: SolarSystem,
     body_visuals: HashMap<Body, BodyVisual>,
+    palette: Palette,
+    spin_lead: Time,
+    radial_mode: RadialMode,
+    reference_frame: Option<Body>,
 }
 
 // This provides an interface to the solar system model. It ensures all the data types match those
 // expected by bevy, and it ensures that the coordinate system and units are consistent with the
 // World.
 impl Simulation {
-    // The simulation time step size in seconds
-    const DT: f64 = 30.; // half an hour
+    // The simulation time step size in minutes at a time scale of 1. At larger time scales, the
+    // fixed step propagators advance the solar system model in sub-steps no longer than this.
+    const DT: f64 = clock::STEP_MIN; // half an hour
 
-    // The Julian Date when the simulation begins (2023-01-01T00:00:00 UTC)
+    // The Julian Date when the simulation begins by default (2023-01-01T00:00:00 UTC)
     const EPOCH_JD: f64 = 2_459_945.5;
 
-    pub fn init() -> Self {
+    // Create a simulation starting at the given Julian Date
+    pub fn init_at(epoch: f64::Time) -> Self {
         let mut visuals = HashMap::new();
 
         // Color has be scaled by 10 to take advantage of HDR and bloom effects
@@ -119,45 +322,270 @@ impl Simulation {
         let saturn_color = Color::srgb_u8(0xb0, 0x8f, 0x36);
         let uranus_color = Color::srgb_u8(0x55, 0x80, 0xaa);
         let neptune_color = Color::srgb_u8(0x36, 0x68, 0x96);
+        let ceres_color = Color::srgb_u8(0x8a, 0x8a, 0x84);
+        let pluto_color = Color::srgb_u8(0xc8, 0xa8, 0x88);
+        let haumea_color = Color::srgb_u8(0xdd, 0xdd, 0xdd);
+        let makemake_color = Color::srgb_u8(0xb0, 0x80, 0x60);
+        let eris_color = Color::srgb_u8(0xf0, 0xf0, 0xf0);
+        let halley_color = Color::srgb_u8(0xa0, 0xb4, 0xc8);
+        let io_color = Color::srgb_u8(0xc8, 0xb4, 0x50);
+        let europa_color = Color::srgb_u8(0xb4, 0xa0, 0x8c);
+        let ganymede_color = Color::srgb_u8(0x8c, 0x82, 0x78);
+        let callisto_color = Color::srgb_u8(0x5a, 0x50, 0x46);
+        let triton_color = Color::srgb_u8(0xc8, 0xb4, 0xaa);
         visuals.insert(Body::Sun, BodyVisual::new("Sun", &sun_color));
         visuals.insert(Body::Mercury, BodyVisual::new("Mercury", &mercury_color));
-        visuals.insert(Body::Venus, BodyVisual::new("Venus", &venus_color));
-        visuals.insert(Body::Earth, BodyVisual::new("Earth", &earth_color));
+        visuals.insert(
+            Body::Venus,
+            BodyVisual::new("Venus", &venus_color)
+                .with_atmosphere(0.08, Color::srgba(1., 0.9, 0.6, 0.35)),
+        );
+        visuals.insert(
+            Body::Earth,
+            BodyVisual::new("Earth", &earth_color)
+                .with_atmosphere(0.06, Color::srgba(0.4, 0.6, 1., 0.35)),
+        );
         visuals.insert(Body::Moon, BodyVisual::new("Moon", &moon_color));
         visuals.insert(Body::Mars, BodyVisual::new("Mars", &mars_color));
-        visuals.insert(Body::Jupiter, BodyVisual::new("Jupiter", &jupiter_color));
-        visuals.insert(Body::Saturn, BodyVisual::new("Saturn", &saturn_color));
-        visuals.insert(Body::Uranus, BodyVisual::new("Uranus", &uranus_color));
-        visuals.insert(Body::Neptune, BodyVisual::new("Neptune", &neptune_color));
+        visuals.insert(
+            Body::Jupiter,
+            BodyVisual::new("Jupiter", &jupiter_color)
+                .with_atmosphere(0.04, Color::srgba(1., 0.85, 0.6, 0.25)),
+        );
+        visuals.insert(
+            Body::Saturn,
+            BodyVisual::new("Saturn", &saturn_color)
+                .with_rings(Rings {
+                    inner_radii: SATURN_RINGS_INNER_RADII,
+                    outer_radii: SATURN_RINGS_OUTER_RADII,
+                    color: Color::srgba_u8(0xd2, 0xbe, 0x96, 0x99),
+                })
+                .with_atmosphere(0.04, Color::srgba(1., 0.9, 0.65, 0.25)),
+        );
+        visuals.insert(
+            Body::Uranus,
+            BodyVisual::new("Uranus", &uranus_color)
+                .with_rings(Rings {
+                    inner_radii: URANUS_RINGS_INNER_RADII,
+                    outer_radii: URANUS_RINGS_OUTER_RADII,
+                    color: Color::srgba_u8(0x60, 0x60, 0x68, 0x40),
+                })
+                .with_atmosphere(0.05, Color::srgba(0.6, 0.9, 1., 0.3)),
+        );
+        visuals.insert(
+            Body::Neptune,
+            BodyVisual::new("Neptune", &neptune_color)
+                .with_atmosphere(0.05, Color::srgba(0.4, 0.6, 1., 0.3)),
+        );
+        visuals.insert(Body::Ceres, BodyVisual::new("Ceres", &ceres_color));
+        visuals.insert(Body::Pluto, BodyVisual::new("Pluto", &pluto_color));
+        visuals.insert(Body::Haumea, BodyVisual::new("Haumea", &haumea_color));
+        visuals.insert(Body::Makemake, BodyVisual::new("Makemake", &makemake_color));
+        visuals.insert(Body::Eris, BodyVisual::new("Eris", &eris_color));
+        visuals.insert(Body::Halley, BodyVisual::new("Halley's Comet", &halley_color));
+        visuals.insert(Body::Io, BodyVisual::new("Io", &io_color));
+        visuals.insert(Body::Europa, BodyVisual::new("Europa", &europa_color));
+        visuals.insert(Body::Ganymede, BodyVisual::new("Ganymede", &ganymede_color));
+        visuals.insert(Body::Callisto, BodyVisual::new("Callisto", &callisto_color));
+        visuals.insert(Body::Triton, BodyVisual::new("Triton", &triton_color));
         Self {
-            solar_system: SolarSystem::init(f64::Time::new::<day>(Self::EPOCH_JD)),
-            body_visuals: visuals,
+            solar_system: SolarSystem::init(epoch, Propagator::default()),
+            body_visuals: visuals
+                .into_iter()
+                .map(|(body, visual)| (body, visual.with_palette_colors(body)))
+                .collect(),
+            palette: Palette::default(),
+            spin_lead: Time::new::<day>(0.),
+            radial_mode: RadialMode::default(),
+            reference_frame: None,
+        }
+    }
+
+    // Advance the simulation by the given span of time. The adaptive propagator picks its own
+    // substeps, so it takes the span in one step. The others take sub-steps no longer than DT.
+    pub fn advance_by(&mut self, span: Time) {
+        match self.solar_system.propagator() {
+            Propagator::Adaptive => self.solar_system.advance_time(span),
+            _ => self.solar_system.advance_time_in_steps(span, Time::new::<minute>(Self::DT)),
         }
     }
 
-    pub fn advance(&mut self) {
-        self.solar_system.advance_time(Time::new::<minute>(Self::DT));
+    // Turn the bodies on their axes by the given span of time without moving them along their
+    // orbits. The spin clock runs ahead of the simulated time by the spans added this way, and it
+    // keeps its lead when the orbits advance again, so neither the bodies nor their spins jump.
+    pub fn advance_spin_by(&mut self, span: Time) {
+        self.spin_lead += span;
+    }
+
+    // Fast-forward the simulation to the next time a body passes closest to its primary, returning
+    // that Julian Date. None is returned, and the simulation left as is, if the body won't.
+    pub fn jump_to_next_periapsis(&mut self, body: Body) -> Option<Time> {
+        let time = self.solar_system.next_periapsis_time(body)?;
+        self.advance_by(time - self.solar_system.current_time());
+        Some(time)
+    }
+
+    // Add a body that isn't one of the built-in ones, e.g., one defined in a scene file
+    pub fn add_custom_body(
+        &mut self,
+        body: Body,
+        name: &str,
+        color: &Color,
+        props: BodyProperties,
+    ) {
+        self.solar_system.add_body(body, props);
+        self.body_visuals.insert(body, BodyVisual::new(name, color).with_palette_colors(body));
+    }
+
+    // Add massless bodies orbiting the Sun, e.g., the asteroids of the belt
+    pub fn add_asteroids(&mut self, asteroids: impl IntoIterator<Item = BodyProperties>) {
+        self.solar_system.add_asteroids(asteroids);
+    }
+
+    pub fn clear_asteroids(&mut self) {
+        self.solar_system.clear_asteroids();
+    }
+
+    // Return a simulation of only the built-in bodies starting at the given Julian Date, propagated
+    // and drawn the way this one is
+    pub fn restart_at(&self, epoch: f64::Time) -> Self {
+        let mut sim = Self::init_at(epoch);
+        sim.set_propagator(self.solar_system.propagator());
+        sim.set_palette(self.palette);
+        sim.set_radial_mode(self.radial_mode);
+        sim
+    }
+
+    // Move the origin to the barycenter, bringing it to rest. Bodies added afterward are placed
+    // relative to the origin rather than the Sun.
+    pub fn recenter_on_barycenter(&mut self) {
+        self.solar_system.recenter_on_barycenter();
+    }
+
+    // Return the directions from Earth to a body over a span of time centered on the current time.
+    pub fn apparent_path_of(&self, body: Body, span: Time, samples: usize) -> Vec<Vec3> {
+        let now = self.solar_system.current_time();
+        self.solar_system
+            .geocentric_directions(body, now - span / 2., now + span / 2., samples)
+            .iter()
+            .map(|dir| {
+                let dir = dir.cast::<f32>();
+                Vec3::new(dir.x, dir.y, dir.z)
+            })
+            .collect()
     }
 
     pub fn apsis_of(&self, body: Body) -> f32 {
         self.solar_system.properties_of(body).apsis().get::<astronomical_unit>() as f32
     }
 
+    // Return where a body appears to be from another, correcting for the light time between them
+    // and the observer's motion. The position is relative to the observer in AU at full precision.
+    pub fn apparent_position_of(&self, body: Body, observer: Body) -> DVec3 {
+        let pos = self.solar_system.apparent_position_of(body, observer);
+        let au = f64::Length::new::<meter>(1.).get::<astronomical_unit>();
+        au * DVec3::new(pos.x, pos.y, pos.z)
+    }
+
+    pub fn asteroid_positions(&self) -> Vec<Vec3> {
+        let au = f64::Length::new::<meter>(1.).get::<astronomical_unit>();
+        let positions = self.solar_system.asteroid_positions();
+        if self.draws_true_positions() {
+            positions.iter().map(to_world_position).collect()
+        } else {
+            positions
+                .iter()
+                .map(|pos| self.display_position(au * DVec3::new(pos.x, pos.y, pos.z), None))
+                .collect()
+        }
+    }
+
+    // Return the center of mass of all of the bodies
+    pub fn barycenter(&self) -> Vec3 {
+        let au = f64::Length::new::<meter>(1.).get::<astronomical_unit>();
+        (au * self.solar_system.barycenter() - self.frame_origin()).as_vec3()
+    }
+
     pub fn bodies(&self) -> HashSet<Body> {
         self.solar_system.bodies()
     }
 
+    // Return each body with its name, its color in the current palette, and its current position
+    // and radius in world units, e.g., for drawing a custom overlay. This takes one pass over the
+    // bodies instead of separate lookups with name_of, color_of, position_of, and radius_of.
+    pub fn render_data(&self) -> impl Iterator<Item = (Body, &str, Color, Vec3, f32)> {
+        self.solar_system.iter_bodies().map(|(body, props, pos)| {
+            let (name, color) = match self.body_visuals.get(&body) {
+                Some(vis) => (vis.name().as_str(), *vis.color(self.palette)),
+                None => ("unknown", Color::WHITE),
+            };
+            let radius = props.radius().get::<astronomical_unit>() as f32;
+            let pos = if self.draws_true_positions() {
+                to_world_position(pos)
+            } else {
+                self.position_of(body)
+            };
+            (body, name, color, pos, radius)
+        })
+    }
+
+    // The color of the body in the current palette
     pub fn color_of(&self, body: Body) -> &Color{
         match self.body_visuals.get(&body) {
-            Some(vis) => vis.color(),
+            Some(vis) => vis.color(self.palette),
             None => &Color::WHITE,
         }
     }
 
+    // The current time as a Julian Date
+    pub fn current_time(&self) -> Time {
+        self.solar_system.current_time()
+    }
+
+    pub fn diagnostics(&self) -> SimDiagnostics {
+        self.solar_system.diagnostics()
+    }
+
+    // Return the current distance between the centers of two bodies
+    pub fn distance_between(&self, a: Body, b: Body) -> f64::Length {
+        self.solar_system.distance_between(a, b)
+    }
+
+    pub fn eccentricity_of(&self, body: Body) -> f64 {
+        self.solar_system.properties_of(body).eccentricity()
+    }
+
+    // Return the bodies whose names start with the given text, ignoring case, in order of name
+    pub fn find_bodies(&self, prefix: &str) -> Vec<Body> {
+        let prefix = prefix.to_lowercase();
+        let mut found: Vec<(String, Body)> = self
+            .body_visuals
+            .iter()
+            .map(|(body, visual)| (visual.name().to_lowercase(), *body))
+            .filter(|(name, _)| name.starts_with(&prefix))
+            .collect();
+        found.sort_by(|(a, _), (b, _)| a.cmp(b));
+        found.into_iter().map(|(_, body)| body).collect()
+    }
+
+    // Return the fraction of the disk of a body seen from an observer that the Sun lights
+    pub fn illuminated_fraction_of(&self, body: Body, observer: Body) -> f64 {
+        self.solar_system.illuminated_fraction_of(body, observer)
+    }
+
+    pub fn inclination_of(&self, body: Body) -> Angle {
+        let inc = self.solar_system.properties_of(body).inclination();
+        Angle::new::<radian>(inc.get::<radian>() as f32)
+    }
+
     pub fn luminosity_of(&self, body: Body) -> f32 {
         self.solar_system.properties_of(body).luminosity().value as f32
     }
 
+    pub fn mass_of(&self, body: Body) -> f64::Mass {
+        self.solar_system.mass_of(body)
+    }
+
     pub fn name_of(&self, body: Body) -> String {
         match self.body_visuals.get(&body) {
             Some(vis) => vis.name().clone(),
@@ -165,30 +593,204 @@ impl Simulation {
         }
     }
 
+    // Return where the point on a body's orbit with the given true anomaly is currently drawn
+    pub fn orbit_point_of(&self, body: Body, true_anomaly: f64::Angle) -> Vec3 {
+        let point = self.solar_system.orbit_point_of(body, true_anomaly);
+        if self.draws_true_positions() {
+            to_world_position(&point)
+        } else {
+            let au = f64::Length::new::<meter>(1.).get::<astronomical_unit>();
+            self.display_position(au * DVec3::new(point.x, point.y, point.z), Some(body))
+        }
+    }
+
+    // Return the closest distance in AU a body comes to its primary
+    pub fn perihelion_of(&self, body: Body) -> f32 {
+        self.solar_system.perihelion_of(body).get::<astronomical_unit>() as f32
+    }
+
+    // Return where a body is drawn in world units. This is its true position unless the radial mode
+    // compresses the distances from the Sun or the reference frame is centered on another body. Use
+    // position_in for the true position.
     pub fn position_of(&self, body: Body) -> Vec3 {
-        let pos = self.solar_system.position_of(body);
-        Vec3::new(
-            f64::Length::new::<meter>(pos.x).get::<astronomical_unit>() as f32,
-            f64::Length::new::<meter>(pos.y).get::<astronomical_unit>() as f32,
-            f64::Length::new::<meter>(pos.z).get::<astronomical_unit>() as f32,
-        )
+        self.display_position(self.position_in::<astronomical_unit>(body), Some(body))
+    }
+
+    // Return whether the bodies are drawn at their true positions
+    fn draws_true_positions(&self) -> bool {
+        self.radial_mode == RadialMode::Linear && self.reference_frame.is_none()
+    }
+
+    // Return where a point at the given position in AU is drawn in the current radial mode and
+    // reference frame. When the point belongs to a moon, it keeps its offset from the moon's
+    // planet, scaled along with the planet's neighborhood.
+    pub(crate) fn display_position(&self, pos: DVec3, body: Option<Body>) -> Vec3 {
+        (self.radial_position(pos, body) - self.frame_origin()).as_vec3()
+    }
+
+    // Return where the reference frame's body would be drawn in the radial mode if the frame were
+    // the world's own. Everything is drawn shifted by this, so the body is at the origin.
+    fn frame_origin(&self) -> DVec3 {
+        match self.reference_frame {
+            Some(frame) => {
+                self.radial_position(self.position_in::<astronomical_unit>(frame), Some(frame))
+            }
+            None => DVec3::ZERO,
+        }
+    }
+
+    fn radial_position(&self, pos: DVec3, body: Option<Body>) -> DVec3 {
+        let mode = self.radial_mode;
+        if mode == RadialMode::Linear {
+            return pos;
+        }
+        let sun = self.position_in::<astronomical_unit>(Body::Sun);
+        let offset = match body.and_then(|body| body.primary().filter(|_| body.is_moon())) {
+            Some(primary) => {
+                let primary_offset = self.position_in::<astronomical_unit>(primary) - sun;
+                let moon_offset = pos - sun - primary_offset;
+                mode.map(primary_offset) + mode.stretch_at(primary_offset) * moon_offset
+            }
+            None => mode.map(pos - sun),
+        };
+        sun + offset
+    }
+
+    // Return a body's position in the given unit of length at full precision
+    pub fn position_in<U>(&self, body: Body) -> DVec3
+    where
+        U: length::Unit + Conversion<f64, T = f64>,
+    {
+        self.solar_system.position_in::<U>(body)
+    }
+
+    // Return how long a body takes to orbit its primary. The Sun has no orbital period.
+    pub fn orbital_period_of(&self, body: Body) -> Option<Time> {
+        let period = self.solar_system.orbital_period_of(body);
+        if period.value.is_nan() {
+            None
+        } else {
+            Some(period)
+        }
+    }
+
+    // Return the rotation of a body's avatar, its spin about its axis followed by its axial tilt
+    pub fn orientation_of(&self, body: Body) -> Quat {
+        let props = self.solar_system.properties_of(body);
+        let tilt = props.axial_tilt().get::<radian>() as f32;
+        let spin = props.rotation_angle(self.spin_time()).get::<radian>() as f32;
+        Quat::from_rotation_x(tilt) * Quat::from_rotation_z(spin)
+    }
+
+    // Return how fast a body is moving away from another in the given unit of speed. It is negative
+    // when the body is approaching.
+    pub fn radial_velocity_in<U>(&self, body: Body, other: Body) -> f64
+    where
+        U: velocity::Unit + Conversion<f64, T = f64>,
+    {
+        let speed = self.solar_system.radial_velocity(body, other);
+        f64::Velocity::new::<meter_per_second>(speed).get::<U>()
     }
 
     pub fn radius_of(&self, body: Body) -> f32 {
         self.solar_system.properties_of(body).radius().get::<astronomical_unit>() as f32
     }
 
+    // Return a body's velocity relative to another's in the given unit of speed at full precision
+    pub fn relative_velocity_in<U>(&self, body: Body, other: Body) -> DVec3
+    where
+        U: velocity::Unit + Conversion<f64, T = f64>,
+    {
+        let unit = f64::Velocity::new::<meter_per_second>(1.).get::<U>();
+        self.solar_system.relative_velocity(body, other) * unit
+    }
+
+    pub fn rings_of(&self, body: Body) -> Option<&Rings> {
+        self.body_visuals.get(&body).and_then(|vis| vis.rings())
+    }
+
+    pub fn atmosphere_of(&self, body: Body) -> Option<&Atmosphere> {
+        self.body_visuals.get(&body).and_then(|vis| vis.atmosphere())
+    }
+
+    // Return the distance from a body inside of which a satellite of the given density would be
+    // torn apart by tides
+    pub fn roche_limit_of(&self, body: Body, satellite_density: f64::MassDensity) -> Length {
+        let limit = self.solar_system.roche_limit_of(body, satellite_density);
+        Length::new::<astronomical_unit>(limit.get::<astronomical_unit>() as f32)
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    pub fn set_propagator(&mut self, propagator: Propagator) {
+        self.solar_system.set_propagator(propagator);
+    }
+
+    pub fn set_radial_mode(&mut self, mode: RadialMode) {
+        self.radial_mode = mode;
+    }
+
+    // Center what is drawn on the given body. The world is centered on the Sun, so the Sun's frame
+    // leaves the positions as they are.
+    pub fn set_reference_frame(&mut self, body: Body) {
+        self.reference_frame = (body != Body::Sun).then_some(body);
+    }
+
+    pub fn shadow_of(&self, body: Body) -> Shadow {
+        self.solar_system.shadow_of(body)
+    }
+
+    // Return the Julian Date the bodies' spins are at. It is ahead of the current time by however
+    // long the bodies have been spun with their orbits paused.
+    pub fn spin_time(&self) -> Time {
+        self.current_time() + self.spin_lead
+    }
+
+    // Return a body's velocity in AU per day
     pub fn velocity_of(&self, body: Body) -> Vec3 {
-        let vel = self.solar_system.velocity_of(body);
-        let world_vel = (vel * MPS_TO_AUPD).cast::<f32>();
-        Vec3::new(world_vel.x, world_vel.y, world_vel.z)
+        (self.velocity_in::<meter_per_second>(body) * MPS_TO_AUPD).as_vec3()
+    }
+
+    // Return a body's velocity in the given unit of speed at full precision
+    pub fn velocity_in<U>(&self, body: Body) -> DVec3
+    where
+        U: velocity::Unit + Conversion<f64, T = f64>,
+    {
+        self.solar_system.velocity_in::<U>(body)
     }
 }
 
+// This converts a position in meters in the solar system model to a position in the World.
+fn to_world_position(pos: &Vector3<f64>) -> Vec3 {
+    Vec3::new(
+        f64::Length::new::<meter>(pos.x).get::<astronomical_unit>() as f32,
+        f64::Length::new::<meter>(pos.y).get::<astronomical_unit>() as f32,
+        f64::Length::new::<meter>(pos.z).get::<astronomical_unit>() as f32,
+    )
+}
 
-// This function advance the time by one step in the solar system model.
-fn advance_sim_time(mut sim: ResMut<Simulation>) {
-    sim.advance();
+// This function advance the time by one step in the solar system model unless the simulation is
+// paused. The fixed timestep is the real time a step covers. While paused, only the requested
+// single steps are taken. While only the orbits are paused, the step spins the bodies instead.
+fn advance_sim_time(
+    fixed: Res<bevy::time::Time<Fixed>>,
+    mut clock: ResMut<SimClock>,
+    mut sim: ResMut<Simulation>,
+) {
+    if clock.paused {
+        if let Some(span) = clock.take_single_step() {
+            sim.advance_by(span);
+        }
+        return;
+    }
+    let span = clock.step_span(fixed.timestep().as_secs_f64());
+    if clock.orbits_paused {
+        sim.advance_spin_by(span);
+    } else {
+        sim.advance_by(span);
+    }
 }
 
 impl Component for Body {
@@ -197,11 +799,23 @@ impl Component for Body {
     fn register_component_hooks(_hooks: &mut ComponentHooks) {}
 }
 
-#[derive(Component)]
+// These are the ways the user can move the observer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+enum CameraMode {
+    // The keyboard flies the observer around
+    #[default]
+    FreeFly,
+    // Dragging with the mouse orbits the observer around its focus
+    Orbit,
+}
+
+#[derive(Clone, Component, Debug, Deserialize, PartialEq, Serialize)]
 struct Observer {
     position: Vec3,
     facing: Dir3,
     up: Dir3,
+    focus: Vec3,
+    orbit_radius: f32,
 }
 
 impl Observer {
@@ -210,6 +824,8 @@ impl Observer {
             position: Vec3::new(0., 0., 80.),
             facing: Dir3::NEG_Z,
             up: Dir3::Y,
+            focus: Vec3::ZERO,
+            orbit_radius: 80.,
         }
     }
 
@@ -217,8 +833,67 @@ impl Observer {
         &self.position
     }
 
+    // The point the observer is looking at
+    pub fn focus(&self) -> &Vec3 {
+        &self.focus
+    }
+
+    pub fn set_focus(&mut self, focus: Vec3) {
+        self.focus = focus;
+        self.orbit_radius = self.position.distance(focus);
+    }
+
+    // Move the observer and its focus together by the given offset
+    pub fn translate(&mut self, offset: Vec3) {
+        self.position += offset;
+        self.focus += offset;
+    }
+
+    // The distance of the observer from its focus
+    pub fn orbit_radius(&self) -> f32 {
+        self.orbit_radius
+    }
+
+    // Place the observer orbit_radius away from its focus, looking at it with the given orientation
+    pub fn orbit(&mut self, facing: Dir3, up: Dir3, orbit_radius: f32) {
+        self.facing = facing;
+        self.up = up;
+        self.orbit_radius = orbit_radius;
+        self.position = self.focus - orbit_radius * *facing;
+    }
+
+    pub fn pose(&self) -> ObserverPose {
+        ObserverPose {
+            position: self.position,
+            facing: self.facing,
+            up: self.up,
+        }
+    }
+
+    pub fn set_pose(&mut self, pose: &ObserverPose) {
+        self.position = pose.position;
+        self.facing = pose.facing;
+        self.up = pose.up;
+        self.orbit_radius = self.position.distance(self.focus);
+    }
+
+    // The camera's up direction is the observer's made perpendicular to its facing direction, so
+    // the transform stays valid even when the two are nearly parallel.
     pub fn mk_transform(&self) -> Transform {
-        Transform::from_translation(self.position).looking_to(self.facing, self.up)
+        Transform::from_translation(self.position)
+            .looking_to(self.facing, upright(self.facing, *self.up))
+    }
+}
+
+// Return the direction perpendicular to the facing direction that is nearest the given up
+// direction. When they are nearly parallel, there is no nearest one, so any perpendicular
+// direction is returned.
+fn upright(facing: Dir3, up: Vec3) -> Dir3 {
+    let perp = up - up.dot(*facing) * *facing;
+    if perp.length() > 1e-4 {
+        Dir3::new_unchecked(perp.normalize())
+    } else {
+        Dir3::new_unchecked(facing.any_orthonormal_vector())
     }
 }
 
@@ -226,11 +901,14 @@ fn create_observer(mut commands: Commands) {
     commands.spawn(Observer::new());
 }
 
-// This is the view model of a celestial body.
+// This is the view model of a celestial body. The position before the latest fixed update step is
+// kept, so the body can be drawn between its positions at the steps.
 #[derive(Component, Default)]
 struct BodyModel {
     position: Vec3,
+    previous_position: Vec3,
     avatar: Option<Entity>,
+    avatar_radius: f32,
     label: Option<Entity>,
 }
 
@@ -238,6 +916,7 @@ impl BodyModel {
     pub fn new(pos: &Vec3) -> Self {
         Self {
             position: *pos,
+            previous_position: *pos,
             ..default()
         }
     }
@@ -250,6 +929,14 @@ impl BodyModel {
         self.avatar = Some(avatar);
     }
 
+    pub fn avatar_radius(&self) -> f32 {
+        self.avatar_radius
+    }
+
+    pub fn set_avatar_radius(&mut self, radius: f32) {
+        self.avatar_radius = radius;
+    }
+
     pub fn label(&self) -> Option<Entity> {
         self.label
     }
@@ -263,8 +950,50 @@ impl BodyModel {
     }
 
     pub fn update_position(&mut self, position: &Vec3) {
+        self.previous_position = self.position;
         self.position = *position;
     }
+
+    // Return where the body is the given fraction of the way from its previous position to its
+    // current one
+    pub fn interpolated_position(&self, fraction: f32) -> Vec3 {
+        self.previous_position.lerp(self.position, fraction)
+    }
+}
+
+// This schedule spawns everything drawn for the bodies: their models, avatars, and labels, and
+// whatever a module draws for each of them. It runs at startup and again whenever another scenario
+// is loaded, so a module drawing something for each body spawns it here rather than in Startup.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, ScheduleLabel)]
+struct SpawnBodies;
+
+// This marks the entities SpawnBodies spawns, so they can all be despawned with their children.
+#[derive(Component)]
+struct BodyEntity;
+
+// Run SpawnBodies, marking what it spawns. A spawned child is only marked when its parent was there
+// before, since it is despawned along with its parent otherwise.
+fn spawn_bodies(world: &mut World) {
+    let before: HashSet<Entity> = world.iter_entities().map(|entity| entity.id()).collect();
+    world.run_schedule(SpawnBodies);
+    let spawned: Vec<Entity> = world
+        .iter_entities()
+        .filter(|entity| !before.contains(&entity.id()))
+        .filter(|entity| entity.get::<Parent>().is_none_or(|parent| before.contains(&parent.get())))
+        .map(|entity| entity.id())
+        .collect();
+    for entity in spawned {
+        world.entity_mut(entity).insert(BodyEntity);
+    }
+}
+
+// Despawn everything SpawnBodies spawned, so no avatar, light, ring, or label is left behind.
+fn despawn_bodies(world: &mut World) {
+    let marked: Vec<Entity> =
+        world.query_filtered::<Entity, With<BodyEntity>>().iter(world).collect();
+    for entity in marked {
+        world.entity_mut(entity).despawn_recursive();
+    }
 }
 
 // This adds the celestial bodies being watched to the bevy World.
@@ -275,130 +1004,435 @@ fn create_body_models(sim: Res<Simulation>, mut commands: Commands) {
 }
 
 // This aligns the position of the bodies in the World with their positions in the solar system
-// model.
-fn update_bodies(sim: Res<Simulation>, mut bodies: Query<(&Body, &mut BodyModel)>) {
-    for (body, mut model) in &mut bodies {
+// model, recording the new positions in the bodies' trails.
+fn update_bodies(
+    sim: Res<Simulation>,
+    mut bodies: Query<(&Body, &mut BodyModel, Option<&mut Trail>)>,
+) {
+    for (body, mut model, trail) in &mut bodies {
         model.update_position(&sim.position_of(*body));
+        if let Some(mut trail) = trail {
+            trail.record(*model.position());
+        }
+    }
+}
+
+// Return the radius of a body's avatar. When the body's size is visible, the avatar is made large
+// enough to be seen even from the far side of the body's orbit, when the camera is cam_dist from
+// the Sun. Otherwise, the avatar has the body's true radius.
+fn avatar_radius(
+    sim: &Simulation,
+    body: Body,
+    min_ang: f32,
+    cam_dist: f32,
+    mode: ScaleMode,
+) -> f32 {
+    match mode {
+        ScaleMode::Realistic => sim.radius_of(body),
+        ScaleMode::Visible => {
+            let max_sun_dist = match body.primary() {
+                Some(primary) if body.is_moon() => sim.apsis_of(body) + sim.apsis_of(primary),
+                _ => sim.apsis_of(body)
+            };
+            let max_dist = (max_sun_dist.powi(2) + cam_dist.powi(2)).sqrt();
+            let min_radius = max_dist * min_ang.tan() / 2.;
+            sim.radius_of(body).max(min_radius)
+        }
+    }
+}
+
+// Return the light a luminous body emits from inside of its avatar of the given radius
+fn mk_light_bundle(
+    sim: &Simulation,
+    body: Body,
+    (settings, tuning, bounds): (&RenderSettings, &RenderTuning, &WorldBounds),
+    avatar_radius: f32,
+) -> PointLightBundle {
+    let mut light = PointLight {
+        color: *sim.color_of(body),
+        intensity: tuning.light_intensity(sim, body),
+        range: bounds.radius_au,
+        radius: avatar_radius,
+        ..default()
+    };
+    settings.configure_shadows(&mut light);
+    PointLightBundle {
+        point_light: light,
+        ..default()
     }
 }
 
-fn min_ang_res(win: &Window) -> f32 {
-    let pix_size = Length::new::<inch>(1. / (STANDARD_DPI * win.scale_factor()));
-    let obs_dist = Length::new::<meter>(MIN_OBSERVER_DIST_M);
-    let disp_ang_res = 2. * (pix_size / (2. * obs_dist)).atan();
-    Angle::new::<radian>(EYE_ANG_RES_RAD)
-        .max(disp_ang_res)
-        .get::<radian>()
+// These are what create_avatars reads to size, light, and decorate each avatar.
+#[derive(SystemParam)]
+struct AvatarParams<'w, 's> {
+    sim: Res<'w, Simulation>,
+    settings: Res<'w, RenderSettings>,
+    tuning: Res<'w, RenderTuning>,
+    bounds: Res<'w, WorldBounds>,
+    scale_mode: Res<'w, ScaleMode>,
+    visibility: Res<'w, VisibilityTuning>,
+    glow: Res<'w, AtmosphereGlow>,
+    window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    observer: Query<'w, 's, &'static Observer>,
 }
 
 fn create_avatars(
-    sim: Res<Simulation>,
+    params: AvatarParams,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    window: Query<&Window, With<PrimaryWindow>>,
-    observer: Query<&Observer>,
     mut bodies: Query<(&Body, &mut BodyModel)>,
 ) {
-    let min_ang = min_ang_res(window.single());
+    let AvatarParams {
+        sim,
+        settings,
+        tuning,
+        bounds,
+        scale_mode,
+        visibility,
+        glow,
+        window,
+        observer,
+    } = params;
+    let min_ang = visibility.min_ang_res(window.single());
     let cam_dist = observer.single().position().length();
+    // The avatars start with the most detail and are coarsened as they are first updated.
+    let spheres = settings.mk_spheres(&mut meshes);
+    let lod = settings.sphere_subdivisions;
+    let mesh = spheres[lod].clone();
+    commands.insert_resource(AvatarMeshes(spheres));
 
     for (body, mut model) in &mut bodies {
-        let max_sun_dist = match body {
-            Body::Moon => sim.apsis_of(*body) + sim.apsis_of(Body::Earth),
-            _ => sim.apsis_of(*body)
-        };
-        let max_dist = (max_sun_dist.powi(2) + cam_dist.powi(2)).sqrt();
-
-        let min_radius = max_dist * min_ang.tan() / 2.;
-        let avatar_radius = sim.radius_of(*body).max(min_radius);
+        let avatar_radius = avatar_radius(&sim, *body, min_ang, cam_dist, *scale_mode);
         let avatar_color = sim.color_of(*body);
-        let avatar_lum = sim.luminosity_of(*body) * LUMINOSITY_SCALE;
-        let mut avatar = commands.spawn(PbrBundle {
-            mesh: meshes.add(Sphere::new(avatar_radius)),
-            material: materials.add(if avatar_lum > 0. {
-                StandardMaterial {
-                    emissive: (*avatar_color).into(),
-                    ..default()
-                }
-            } else {
-                StandardMaterial {
-                    base_color: *avatar_color,
-                    ..default()
-                }
-            }),
-            transform: Transform::from_translation(*model.position()),
-            ..default()
-        });
+        let avatar_lum = tuning.light_intensity(&sim, *body);
+        let mut avatar = commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: materials.add(if avatar_lum > 0. {
+                    StandardMaterial {
+                        emissive: (*avatar_color).into(),
+                        ..default()
+                    }
+                } else {
+                    StandardMaterial {
+                        base_color: *avatar_color,
+                        ..default()
+                    }
+                }),
+                transform: Transform::from_translation(*model.position())
+                    .with_rotation(sim.orientation_of(*body))
+                    .with_scale(Vec3::splat(avatar_radius)),
+                ..default()
+            },
+            AvatarLod(lod),
+        ));
         if avatar_lum > 0. {
             avatar.with_children(|parent| {
-                parent.spawn(PointLightBundle {
-                    point_light: PointLight {
-                        color: *avatar_color,
-                        intensity: avatar_lum,
-                        range: WORLD_RADIUS_AU,
-                        radius: avatar_radius,
-                        shadows_enabled: true,
+                parent.spawn(mk_light_bundle(
+                    &sim,
+                    *body,
+                    (&settings, &tuning, &bounds),
+                    avatar_radius,
+                ));
+            });
+            // The light is inside the avatar, so the avatar would otherwise shadow everything.
+            avatar.insert(NotShadowCaster);
+        }
+        if let Some(rings) = sim.rings_of(*body) {
+            avatar.with_children(|parent| {
+                parent.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(rings.mk_mesh(1.)),
+                        material: materials.add(rings.mk_material()),
                         ..default()
                     },
-                    ..default()
-                });
+                    RingsOf(*body),
+                ));
+            });
+        }
+        // The glow is a child of the avatar like the light, so it is sized with the avatar. It
+        // neither casts nor receives shadows, and picking only considers the avatars' radii.
+        if let Some(atmosphere) = sim.atmosphere_of(*body).filter(|_| glow.0) {
+            avatar.with_children(|parent| {
+                parent.spawn((
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: materials.add(atmosphere.mk_material()),
+                        transform: Transform::from_scale(Vec3::splat(atmosphere.shell_scale())),
+                        ..default()
+                    },
+                    NotShadowCaster,
+                    NotShadowReceiver,
+                ));
             });
         }
         model.set_avatar(avatar.id());
+        model.set_avatar_radius(avatar_radius);
+    }
+}
+
+// This is how far the frame being drawn is from the latest fixed update step toward the next one,
+// as a fraction of a step. The bodies and the camera are drawn that fraction of the way from their
+// previous positions to their current ones, so they move smoothly even when a frame falls between
+// steps. This lags the simulation by up to a step. While paused, the positions don't change
+// between steps, so this has no effect.
+#[derive(Default, Resource)]
+struct StepFraction(f32);
+
+// While recording, each frame is exactly one step, and it shows the simulation posed at the step,
+// so the current positions are drawn.
+fn measure_step_fraction(
+    fixed: Res<bevy::time::Time<Fixed>>,
+    recording: Option<Res<FrameRecording>>,
+    mut fraction: ResMut<StepFraction>,
+) {
+    fraction.0 = if recording.is_some() { 1. } else { fixed.overstep_fraction() };
+}
+
+// This finds where the bodies are drawn in the frame being drawn, between where they were at the
+// last two steps. The overlays are drawn every frame from it, so they stay attached to the
+// avatars between steps.
+#[derive(SystemParam)]
+struct DrawnPositions<'w, 's> {
+    sim: Res<'w, Simulation>,
+    fraction: Res<'w, StepFraction>,
+    models: Query<'w, 's, (&'static Body, &'static BodyModel)>,
+}
+
+impl DrawnPositions<'_, '_> {
+    // Return where a body is drawn. A body without a model is drawn where it is at the latest step.
+    fn of(&self, body: Body) -> Vec3 {
+        self.models
+            .iter()
+            .find(|(model_body, _)| **model_body == body)
+            .map_or(self.sim.position_of(body), |(_, model)| {
+                model.interpolated_position(self.fraction.0)
+            })
     }
+
+    // Return how far a body is drawn from where it is at the latest step. Something placed
+    // relative to the body is drawn shifted as much.
+    fn shift_of(&self, body: Body) -> Vec3 {
+        self.of(body) - self.sim.position_of(body)
+    }
+}
+
+// These are the camera's transforms at the previous and latest fixed update steps.
+#[derive(Resource)]
+struct CameraSteps {
+    previous: Transform,
+    current: Transform,
+}
+
+// The camera is drawn between its transforms at the last two steps like the bodies, so a followed
+// body doesn't shift against the camera between steps.
+fn interpolate_camera(
+    fraction: Res<StepFraction>,
+    steps: Res<CameraSteps>,
+    mut cam: Query<&mut Transform, With<Camera3d>>,
+) {
+    let (previous, current) = (steps.previous, steps.current);
+    *cam.single_mut() = Transform {
+        translation: previous.translation.lerp(current.translation, fraction.0),
+        rotation: previous.rotation.slerp(current.rotation, fraction.0),
+        scale: previous.scale.lerp(current.scale, fraction.0),
+    };
 }
 
+// This moves the avatars to their bodies' positions. Each avatar's sphere is swapped for one with
+// more or less detail as the avatar looks larger or smaller from the observer. The spheres all have
+// a unit radius, so this doesn't change the avatar's size or what can be picked.
 fn update_avatars(
+    (spheres, visibility): (Res<AvatarMeshes>, Res<VisibilityTuning>),
+    fraction: Res<StepFraction>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    observer: Query<&Observer>,
     bodies: Query<&BodyModel, With<Body>>,
-    mut transforms: Query<&mut Transform>,
+    mut avatars: Query<(&mut Transform, &mut Handle<Mesh>, &mut AvatarLod)>,
 ) {
+    let min_ang = visibility.min_ang_res(window.single());
+    let observer_pos = *observer.single().position();
+    let max_lod = spheres.0.len() - 1;
     for model in &bodies {
+        let Some((mut transform, mut mesh, mut lod)) =
+            model.avatar().and_then(|avatar| avatars.get_mut(avatar).ok())
+        else {
+            continue;
+        };
+        transform.translation = model.interpolated_position(fraction.0);
+        let dist = observer_pos.distance(transform.translation);
+        let apparent_radius = model.avatar_radius().atan2(dist) / min_ang;
+        let next = next_lod(lod.0, apparent_radius, max_lod);
+        if next != lod.0 {
+            lod.0 = next;
+            *mesh = spheres.0[next].clone();
+        }
+    }
+}
+
+// This turns the avatars about their tilted spin axes to match the current simulated time.
+fn rotate_avatars(
+    sim: Res<Simulation>,
+    bodies: Query<(&Body, &BodyModel)>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (body, model) in &bodies {
         if let Some(avatar) = model.avatar() {
             if let Ok(mut transform) = transforms.get_mut(avatar) {
-                *transform = Transform::from_translation(*model.position());
+                transform.rotation = sim.orientation_of(*body);
             }
         }
     }
 }
 
+// This is the side of its body a label is placed on.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LabelAnchor {
+    Above,
+    #[default]
+    Below,
+    Right,
+}
+
+impl LabelAnchor {
+    // Return the anchor keeping the label of a body at the given height in normalized device
+    // units on screen. A label above or below a body too near the top or bottom of the screen for
+    // it to fit is flipped to the other side.
+    fn on_screen(self, body_y: f32, offset: f32) -> Self {
+        match self {
+            Self::Above if body_y + 2. * offset > 1. => Self::Below,
+            Self::Below if body_y - 2. * offset < -1. => Self::Above,
+            _ => self,
+        }
+    }
+
+    // Return the point of the label's text placed at the label's position
+    fn text_anchor(self) -> Anchor {
+        match self {
+            Self::Right => Anchor::CenterLeft,
+            _ => Anchor::Center,
+        }
+    }
+}
+
+// This is how the labels are placed and sized. The offset is the distance between a body and its
+// label in normalized device units, measured along the height of the window. The scale sizes the
+// labels for a window LABEL_REFERENCE_HEIGHT logical pixels tall. Changes take effect
+// immediately.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct LabelStyle {
+    pub offset: f32,
+    pub scale: f32,
+    pub anchor: LabelAnchor,
+}
+
+impl Default for LabelStyle {
+    fn default() -> Self {
+        Self {
+            offset: LABEL_OFFSET,
+            scale: LABEL_SCALE,
+            anchor: LabelAnchor::default(),
+        }
+    }
+}
+
+// Return the scale of a label dist AU from the observer, so it has the same size on screen wherever
+// it is and however tall the window is
+fn label_scale(dist: f32, window: &Window, style: &LabelStyle) -> f32 {
+    style.scale * dist * LABEL_REFERENCE_HEIGHT / window.height().max(1.)
+}
+
+// Return the transform placing a label next to its body along with the side of the body it is on,
+// or None if the body is behind the camera or its label's position can't be determined.
 fn mk_lbl_transform(
-    model: &BodyModel,
+    position: Vec3,
     observer: &Observer,
-    cam: &Camera,
-    cam_trans: &GlobalTransform,
-) -> Transform {
-    let avatar_ndc = cam.world_to_ndc(cam_trans, *model.position());
-
-    let lbl_pos = match avatar_ndc {
-        None => *model.position(),
-        Some(avatar_ndc) => {
-            // The avatar position in NDC can be infinite, causing a failure to
-            // determine the label's position in world coordinates. Since this
-            // will only happen when the avatar is off camera, set the label's
-            // position to be the avatar's position.
-            let lbl_ndc = avatar_ndc + Vec3::new(0., -LABEL_OFFSET, 0.);
-            cam.ndc_to_world(cam_trans, lbl_ndc).unwrap_or_else(|| *model.position())
-        },
+    (cam, cam_trans): (&Camera, &GlobalTransform),
+    (window, style): (&Window, &LabelStyle),
+) -> Option<(Transform, LabelAnchor)> {
+    // The avatar position in NDC can be infinite, causing a failure to determine the label's
+    // position in world coordinates. This only happens when the avatar is off camera, so the label
+    // is hidden.
+    let avatar_ndc = cam.world_to_ndc(cam_trans, position)?;
+    if !(0. ..=1.).contains(&avatar_ndc.z) {
+        return None;
+    }
+    let anchor = style.anchor.on_screen(avatar_ndc.y, style.offset);
+    let aspect = window.height().max(1.) / window.width().max(1.);
+    let offset_ndc = match anchor {
+        LabelAnchor::Above => Vec3::new(0., style.offset, 0.),
+        LabelAnchor::Below => Vec3::new(0., -style.offset, 0.),
+        LabelAnchor::Right => Vec3::new(style.offset * aspect, 0., 0.),
     };
+    let lbl_pos = cam.ndc_to_world(cam_trans, avatar_ndc + offset_ndc)?;
+
+    let lbl_scale = label_scale(position.distance(*observer.position()), window, style);
+    Some((Transform::from_translation(lbl_pos).with_scale(Vec3::splat(lbl_scale)), anchor))
+}
 
-    let lbl_scale = LABEL_SCALE * model.position().distance(*observer.position());
-    Transform::from_translation(lbl_pos).with_scale(Vec3::splat(lbl_scale))
+// These are the distances in AU from the camera over which the labels fade out as their bodies
+// approach the far plane. A label is opaque nearer than fade_start and invisible beyond fade_end.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct LabelFade {
+    pub fade_start: f32,
+    pub fade_end: f32,
+}
+
+impl Default for LabelFade {
+    fn default() -> Self {
+        Self::for_far_plane(ZFAR_AU)
+    }
+}
+
+impl LabelFade {
+    // Fade the labels out over the farthest 40% of the distance to the far plane in AU
+    fn for_far_plane(zfar_au: f32) -> Self {
+        Self {
+            fade_start: 0.6 * zfar_au,
+            fade_end: zfar_au,
+        }
+    }
+
+    // Return the opacity of the label of a body dist AU from the camera
+    fn alpha(&self, dist: f32) -> f32 {
+        let t = (dist - self.fade_start) / (self.fade_end - self.fade_start);
+        1. - t.clamp(0., 1.)
+    }
+}
+
+// Determine whether any of the occluders, given as avatar positions and radii, is between the
+// camera and the target position and covers it
+fn occluded(cam_pos: Vec3, target: Vec3, mut occluders: impl Iterator<Item = (Vec3, f32)>) -> bool {
+    let Ok(ray) = Dir3::new(target - cam_pos) else {
+        return false;
+    };
+    let target_dist = target.distance(cam_pos);
+    occluders.any(|(pos, radius)| {
+        let along = (pos - cam_pos).dot(*ray);
+        let off_axis = (pos - cam_pos - along * *ray).length();
+        along > 0. && along + radius < target_dist && off_axis < radius
+    })
 }
 
 fn create_labels(
-    sim: Res<Simulation>,
+    (sim, style): (Res<Simulation>, Res<LabelStyle>),
     mut commands: Commands,
     mut bodies: Query<(&Body, &mut BodyModel)>,
     observer: Query<&Observer>,
-    cam: Query<(&Camera, &GlobalTransform)>,
+    cam: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    window: Query<&Window, With<PrimaryWindow>>,
 ) {
-    let (cam, cam_trans) = cam.single();
+    let cam = cam.single();
+    let window = window.single();
 
     for (body, mut model) in &mut bodies {
         match body {
-            Body::Moon => (),
+            _ if body.is_moon() => (),
             _ => {
+                let (transform, anchor) =
+                    mk_lbl_transform(*model.position(), observer.single(), cam, (window, &style))
+                        .unwrap_or_default();
                 let lbl = commands.spawn(BillboardTextBundle {
                     text: Text::from_section(
                         sim.name_of(*body),
@@ -407,7 +1441,8 @@ fn create_labels(
                             ..default()
                         },
                     ),
-                    transform: mk_lbl_transform(&model, observer.single(), cam, cam_trans),
+                    text_anchor: anchor.text_anchor(),
+                    transform,
                     ..default()
                 });
                 model.set_label(lbl.id());
@@ -416,73 +1451,736 @@ fn create_labels(
     }
 }
 
+// Labels fade as their bodies approach the far plane. A label is hidden when its body is behind the
+// camera or behind a nearer body, or when its layer is hidden. Hidden moons don't hide labels. The
+// labels follow the label style.
 fn update_labels(
-    bodies: Query<&BodyModel, With<Body>>,
-    observer: Query<&Observer>,
-    cam: Query<(&Camera, &GlobalTransform)>,
-    mut transforms: Query<&mut Transform>,
+    (fade, fraction, layers): (Res<LabelFade>, Res<StepFraction>, Res<Layers>),
+    (style, bodies): (Res<LabelStyle>, Query<(Entity, &Body, &BodyModel)>),
+    (observer, window): (Query<&Observer>, Query<&Window, With<PrimaryWindow>>),
+    cam: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut labels: Query<(&mut Transform, &mut Anchor, &mut Text, &mut Visibility)>,
 ) {
-    let (cam, cam_trans) = cam.single();
-    for model in &bodies {
-        if let Some(label) = model.label() {
-            if let Ok(mut transform) = transforms.get_mut(label) {
-                *transform  = mk_lbl_transform(model, observer.single(), cam, cam_trans);
-            }
+    let observer = observer.single();
+    let cam = cam.single();
+    let window = window.single();
+    for (entity, body, model) in &bodies {
+        let Some(Ok((mut transform, mut text_anchor, mut text, mut visibility))) =
+            model.label().map(|label| labels.get_mut(label))
+        else {
+            continue;
+        };
+        if !layers.shows(Layer::Label, *body) {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        let others = bodies
+            .iter()
+            .filter(|(other, other_body, _)| *other != entity && layers.shows_body(**other_body))
+            .map(|(.., other)| (other.interpolated_position(fraction.0), other.avatar_radius()));
+        let position = model.interpolated_position(fraction.0);
+        let lbl_transform = mk_lbl_transform(position, observer, cam, (window, &style))
+            .filter(|_| !occluded(*observer.position(), position, others));
+        let Some((lbl_transform, anchor)) = lbl_transform else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        *transform = lbl_transform;
+        *text_anchor = anchor.text_anchor();
+        visibility.set_if_neq(Visibility::Inherited);
+        let alpha = fade.alpha(position.distance(*observer.position()));
+        for section in &mut text.sections {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
+
+// This is the extent of the rendered world. The observer is kept within the radius in AU of the
+// Sun, the Sun's light reaches that far, and the ecliptic grid spans it. Nothing farther than the
+// far plane in AU from the camera is rendered, and the stars are drawn just inside of it. To see
+// bodies beyond Neptune's neighborhood, like Eris at aphelion, raise both. Bevy's perspective
+// projection puts the far plane at infinity in the depth buffer with reversed depth, so raising
+// the far plane costs no depth precision; the far plane only culls. Changes take effect at
+// startup.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct WorldBounds {
+    pub radius_au: f32,
+    pub zfar_au: f32,
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self {
+            radius_au: WORLD_RADIUS_AU,
+            zfar_au: ZFAR_AU,
         }
     }
 }
 
-fn create_camera(mut commands: Commands, observer: Query<&Observer>) {
-    commands.spawn((
+fn mk_projection(bounds: &WorldBounds) -> PerspectiveProjection {
+    PerspectiveProjection {
+        near: ZNEAR_AU,
+        far: bounds.zfar_au,
+        ..default()
+    }
+}
+
+fn create_camera(
+    (settings, tuning, bounds): (Res<RenderSettings>, Res<RenderTuning>, Res<WorldBounds>),
+    mut commands: Commands,
+    observer: Query<&Observer>,
+) {
+    let transform = observer.single().mk_transform();
+    commands.insert_resource(CameraSteps {
+        previous: transform,
+        current: transform,
+    });
+    let mut cam = commands.spawn((
         Camera3dBundle {
             camera: Camera {
                 hdr: true,
                 ..default()
             },
-            projection: Projection::Perspective(PerspectiveProjection {
-                near: ZNEAR_AU,
-                far: ZFAR_AU,
-                ..default()
-            }),
-            tonemapping: Tonemapping::TonyMcMapface,
-            transform: observer.single().mk_transform(),
+            projection: Projection::Perspective(mk_projection(&bounds)),
+            tonemapping: tuning.tonemapping,
+            transform,
             ..default()
         },
-        BloomSettings::NATURAL,
+        IsDefaultUiCamera,
     ));
+    if settings.bloom {
+        cam.insert(tuning.bloom());
+    }
+}
+
+// This moves the camera along any animation in progress. Otherwise, if the camera is following a
+// body, it eases the camera toward its following position. The observer's focus stays on the
+// followed body. When no body is followed, the focus stays wherever it was last put. The camera's
+// transform is recorded for the step, and it is drawn between its last two transforms.
+fn update_camera(
+    time: Res<bevy::time::Time>,
+    target: Res<CameraTarget>,
+    mut anim: ResMut<CameraAnimation>,
+    mut steps: ResMut<CameraSteps>,
+    mut observer: Query<&mut Observer>,
+    bodies: Query<(&Body, &BodyModel)>,
+) {
+    let mut observer = observer.single_mut();
+    let model_of = |body| bodies.iter().find(|(b, _)| **b == body).map(|(_, model)| model);
+    let followed = target.0.and_then(model_of);
+    if let Some(model) = followed {
+        observer.set_focus(*model.position());
+    }
+
+    if let Some(pose) = anim.advance(time.delta_seconds()) {
+        observer.set_pose(&pose);
+    } else if let Some(model) = followed {
+        let sun_pos = model_of(Body::Sun).map_or(Vec3::ZERO, |sun| *sun.position());
+        let t = 1. - (-FOLLOW_RATE * time.delta_seconds()).exp();
+        let pose = follow_pose(
+            &observer.pose(),
+            sun_pos,
+            *model.position(),
+            FOLLOW_DISTANCE_RADII * model.avatar_radius(),
+            t,
+        );
+        observer.set_pose(&pose);
+    }
+    steps.previous = steps.current;
+    steps.current = observer.mk_transform();
 }
 
-fn update_camera(mut cam: Query<&mut Transform, With<Camera>>, observer: Query<&Observer>) {
-    *cam.single_mut() = observer.single().mk_transform();
+
+// These are the groups of simulation systems run during FixedUpdate. They run in the order they are
+// declared, so a system added by a user of this crate can be ordered relative to them, e.g.
+// `my_overlay.after(SimSet::UpdateBodies)` will see the body positions for the current time step.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, SystemSet)]
+pub enum SimSet {
+    // Advances the solar system model by one time step
+    Advance,
+    // Aligns the body models and the camera with the solar system model
+    UpdateBodies,
+    // Draws the overlays and turns the avatars. The avatars and labels are moved every frame in
+    // Update, between the body models' positions at the last two steps.
+    Render,
 }
 
+// Return the Julian Date the simulation begins at when no other epoch is given
+pub fn default_epoch() -> f64::Time {
+    f64::Time::new::<day>(Simulation::EPOCH_JD)
+}
 
-pub fn setup(app: &mut App) -> &mut App {
-    app.add_plugins((
-        BillboardPlugin,
-        FramepacePlugin,
-    ))
-    .insert_resource(Simulation::init())
-    .insert_resource(ClearColor(Color::BLACK))
-    .add_systems(
-        Startup,
-        (
-            create_observer,
+// Advance a simulation starting at the given Julian Date by a number of steps, each STEP_MIN long,
+// and return where the bodies end up in AU. This is the same stepping the app does at a time scale
+// of 1, without anything drawn.
+pub fn simulate(epoch: f64::Time, steps: usize) -> HashMap<Body, DVec3> {
+    let mut sim = Simulation::init_at(epoch);
+    for _ in 0..steps {
+        sim.advance_by(Time::new::<minute>(Simulation::DT));
+    }
+    sim.bodies()
+        .into_iter()
+        .map(|body| (body, sim.position_in::<astronomical_unit>(body)))
+        .collect()
+}
+
+// Add only the simulation and the clock advancing it to the app, starting it at the given Julian
+// Date. These need no window, renderer, or input, so they run under MinimalPlugins, e.g., in
+// tests. The clock's controls and everything drawn are added by setup.
+pub fn setup_simulation(app: &mut App, epoch: f64::Time) -> &mut App {
+    app.insert_resource(Simulation::init_at(epoch))
+        .init_resource::<SimClock>()
+        .configure_sets(
+            FixedUpdate,
+            (SimSet::Advance, SimSet::UpdateBodies, SimSet::Render).chain(),
+        )
+        .add_systems(FixedUpdate, advance_sim_time.in_set(SimSet::Advance))
+}
+
+// This adds the simulation to an app along with everything drawing it and the controls. By
+// default, the simulation starts at default_epoch with a time scale of 1, and the orbit trails, the
+// ecliptic grid, and the starfield are included. See parse_epoch for reading an epoch from a Julian
+// Date or a Gregorian calendar date. For example,
+// `app.add_plugins(SolarSystemPlugin::default().with_epoch(epoch).with_starfield(false))`.
+pub struct SolarSystemPlugin {
+    epoch: f64::Time,
+    time_scale: f64,
+    trails: bool,
+    ecliptic_grid: bool,
+    starfield: bool,
+    recording: Option<FrameRecording>,
+    spacecraft: Option<Spacecraft>,
+    seed: SimSeed,
+    bounds: WorldBounds,
+    fixed_hz: f64,
+    atmospheres: bool,
+    propagator: Propagator,
+    slowdown: AutoSlowdown,
+    scenarios: Vec<Scenario>,
+    snapshot: SnapshotExport,
+}
+
+impl Default for SolarSystemPlugin {
+    fn default() -> Self {
+        Self {
+            epoch: default_epoch(),
+            time_scale: 1.,
+            trails: true,
+            ecliptic_grid: true,
+            starfield: true,
+            recording: None,
+            spacecraft: None,
+            seed: SimSeed::default(),
+            bounds: WorldBounds::default(),
+            fixed_hz: clock::REFERENCE_HZ,
+            atmospheres: false,
+            propagator: Propagator::default(),
+            slowdown: AutoSlowdown::default(),
+            scenarios: Vec::new(),
+            snapshot: SnapshotExport::default(),
+        }
+    }
+}
+
+impl SolarSystemPlugin {
+    // Start the simulation at the given Julian Date
+    pub fn with_epoch(mut self, epoch: f64::Time) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    // Start the simulation at the given time scale. See SimClock.
+    pub fn with_time_scale(mut self, time_scale: f64) -> Self {
+        self.time_scale = time_scale;
+        self
+    }
+
+    // Include or leave out the orbit trails
+    pub fn with_trails(mut self, enabled: bool) -> Self {
+        self.trails = enabled;
+        self
+    }
+
+    // Include or leave out the ecliptic grid. When included, it starts hidden.
+    pub fn with_ecliptic_grid(mut self, enabled: bool) -> Self {
+        self.ecliptic_grid = enabled;
+        self
+    }
+
+    // Include or leave out the starfield
+    pub fn with_starfield(mut self, enabled: bool) -> Self {
+        self.starfield = enabled;
+        self
+    }
+
+    // Record the simulation to a sequence of images instead of running it in real time. The
+    // simulation starts at the recording's start in place of the epoch. See FrameRecording.
+    pub fn with_recording(mut self, recording: FrameRecording) -> Self {
+        self.recording = Some(recording);
+        self
+    }
+
+    // Add a spacecraft coasting through the solar system with patched conics. See Spacecraft.
+    pub fn with_spacecraft(mut self, spacecraft: Spacecraft) -> Self {
+        self.spacecraft = Some(spacecraft);
+        self
+    }
+
+    // Draw all of the procedural content, like the asteroid belt, from the given seed. See
+    // SimSeed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = SimSeed(seed);
+        self
+    }
+
+    // Keep the observer within the given distance in AU of the Sun. See WorldBounds.
+    pub fn with_world_radius(mut self, radius_au: f32) -> Self {
+        self.bounds.radius_au = radius_au;
+        self
+    }
+
+    // Render nothing farther than the given distance in AU from the camera. See WorldBounds.
+    pub fn with_far_plane(mut self, zfar_au: f32) -> Self {
+        self.bounds.zfar_au = zfar_au;
+        self
+    }
+
+    // Take the FixedUpdate steps advancing the simulation at the given rate in Hz instead of
+    // Bevy's default 64 Hz. Simulated time passes at the same rate either way. See SimClock.
+    pub fn with_fixed_rate(mut self, hz: f64) -> Self {
+        self.fixed_hz = hz;
+        self
+    }
+
+    // Integrate the bodies' motions with the given propagator instead of the adaptive one
+    pub fn with_propagator(mut self, propagator: Propagator) -> Self {
+        self.propagator = propagator;
+        self
+    }
+
+    // Draw a glow around Venus, Earth, and the giant planets, the bodies with thick atmospheres.
+    // It is left out by default.
+    pub fn with_atmospheres(mut self, enabled: bool) -> Self {
+        self.atmospheres = enabled;
+        self
+    }
+
+    // Start with the auto-slowdown on, multiplying the time scale by the factor within the window
+    // of simulated time around each event. See AutoSlowdown. Insert switches it off and on.
+    pub fn with_auto_slowdown(mut self, window: f64::Time, factor: f64) -> Self {
+        self.slowdown = AutoSlowdown { enabled: true, window, factor };
+        self
+    }
+
+    // Add a scenario to the menu of scenarios. The simulation starts with the first one added in
+    // place of the epoch, and the built-in solar system starting at the epoch stays first in the
+    // menu. See Scenarios.
+    pub fn with_scenario(mut self, scenario: Scenario) -> Self {
+        self.scenarios.push(scenario);
+        self
+    }
+
+    // Export the glTF snapshots of the scene to the given file instead of solar-system.glb. See
+    // SnapshotExport.
+    pub fn with_snapshot_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot.path = path.into();
+        self
+    }
+}
+
+impl Plugin for SolarSystemPlugin {
+    fn build(&self, app: &mut App) {
+        let mut list = vec![Scenario::solar_system(self.epoch)];
+        list.extend(self.scenarios.iter().cloned());
+        let scenarios = Scenarios::new(list, usize::from(!self.scenarios.is_empty()));
+        match &self.recording {
+            Some(recording) => {
+                setup_simulation(app, recording.start).insert_resource(recording.clone())
+            }
+            None => setup_simulation(app, scenarios.current().epoch),
+        };
+        add_bodies(app, &scenarios.current().bodies);
+        app.world_mut().resource_mut::<Simulation>().set_propagator(self.propagator);
+        app.insert_resource(scenarios);
+        if let Some(spacecraft) = &self.spacecraft {
+            app.insert_resource(spacecraft.clone());
+        }
+        app.insert_resource(SimClock {
+            time_scale: self.time_scale,
+            ..default()
+        })
+        .insert_resource(bevy::time::Time::<Fixed>::from_hz(self.fixed_hz))
+        .insert_resource(self.seed)
+        .insert_resource(self.bounds)
+        .insert_resource(AtmosphereGlow(self.atmospheres))
+        .insert_resource(self.slowdown.clone())
+        .insert_resource(self.snapshot.clone())
+        .add_plugins((
+            BillboardPlugin,
+            FramepacePlugin,
+        ))
+        .insert_resource(ClearColor(Color::BLACK))
+        .init_resource::<CameraAnimation>()
+        .init_resource::<CameraMode>()
+        .insert_resource(LabelFade::for_far_plane(self.bounds.zfar_au))
+        .init_resource::<LabelStyle>()
+        .init_resource::<StepFraction>()
+        .add_systems(Startup, (create_observer, create_camera, spawn_bodies).chain())
+        .add_systems(SpawnBodies, (create_body_models, (create_avatars, create_labels)).chain())
+        .add_systems(
+            FixedUpdate,
             (
-                create_body_models,
-                create_camera,
+                (update_bodies, update_camera).chain().in_set(SimSet::UpdateBodies),
+                rotate_avatars.in_set(SimSet::Render),
             ),
-            (create_avatars, create_labels),
         )
-            .chain(),
-    )
-    .add_systems(
-        FixedUpdate,
-        (
-            advance_sim_time,
-            (update_bodies, update_camera),
-            (update_avatars, update_labels),
-        )
-            .chain(),
-    )
+        .add_systems(
+            Update,
+            (measure_step_fraction, (interpolate_camera, update_avatars, update_labels)).chain(),
+        );
+        asteroid_belt::setup(app);
+        auto_slowdown::setup(app);
+        barycenter::setup(app);
+        bookmarks::setup(app);
+        clock::setup(app);
+        comet_tail::setup(app);
+        cursor_coords::setup(app);
+        diagnostics::setup(app);
+        drop_lines::setup(app);
+        eclipses::setup(app);
+        if self.ecliptic_grid {
+            ecliptic_grid::setup(app);
+        }
+        events::setup(app);
+        fps_overlay::setup(app);
+        free_fly::setup(app);
+        follow_camera::setup(app);
+        gltf_export::setup(app);
+        hud::setup(app);
+        info_panel::setup(app);
+        lagrange::setup(app);
+        layers::setup(app);
+        measure::setup(app);
+        meridians::setup(app);
+        minimap::setup(app);
+        occultations::setup(app);
+        orbit_control::setup(app);
+        orbit_markers::setup(app);
+        palette::setup(app);
+        radial_mode::setup(app);
+        recording::setup(app);
+        reference_frame::setup(app);
+        render_scale::setup(app);
+        render_settings::setup(app);
+        retrograde::setup(app);
+        ring_shadows::setup(app);
+        roche::setup(app);
+        saved_state::setup(app);
+        scale_bar::setup(app);
+        scale_mode::setup(app);
+        scenarios::setup(app);
+        screenshot::setup(app);
+        search::setup(app);
+        selection::setup(app);
+        shadow_cone::setup(app);
+        spacecraft::setup(app);
+        sim_rate::setup(app);
+        if self.starfield {
+            starfield::setup(app);
+        }
+        terminator::setup(app);
+        tour::setup(app);
+        if self.trails {
+            trails::setup(app);
+        }
+        velocity_arrows::setup(app);
+        view_presets::setup(app);
+    }
+}
+
+// Add the simulation to the app, starting it at the given Julian Date, with everything else as
+// SolarSystemPlugin has it by default
+#[deprecated(note = "add SolarSystemPlugin instead")]
+pub fn setup(app: &mut App, epoch: f64::Time) -> &mut App {
+    app.add_plugins(SolarSystemPlugin::default().with_epoch(epoch))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        math::Vec3A,
+        render::{camera::CameraProjection, primitives::Frustum},
+        time::TimeUpdateStrategy,
+    };
+
+    use super::*;
+    use uom_wrapper::si::time::second;
+
+    #[test]
+    fn test_real_time_minute() {
+        let clock = SimClock { real_time: true, ..default() };
+        let mut sim = Simulation::init_at(default_epoch());
+        let start = sim.current_time();
+        let tick_s = bevy::time::Time::<Fixed>::default().timestep().as_secs_f64();
+        for _ in 0..(60. / tick_s).round() as usize {
+            sim.advance_by(clock.step_span(tick_s));
+        }
+        let elapsed = (sim.current_time() - start).get::<second>();
+        assert!((elapsed - 60.).abs() < 0.1, "{}", elapsed);
+    }
+
+    #[test]
+    fn test_body_model_interpolated_position() {
+        let mut model = BodyModel::new(&Vec3::X);
+        assert_eq!(model.interpolated_position(0.5), Vec3::X);
+        model.update_position(&Vec3::new(3., 0., 0.));
+        assert_eq!(model.interpolated_position(0.), Vec3::X);
+        assert_eq!(model.interpolated_position(0.5), Vec3::new(2., 0., 0.));
+        assert_eq!(model.interpolated_position(1.), Vec3::new(3., 0., 0.));
+        model.update_position(&Vec3::new(3., 0., 0.));
+        assert_eq!(model.interpolated_position(0.25), Vec3::new(3., 0., 0.));
+    }
+
+    #[test]
+    fn test_simulate_earth_year() {
+        // A sidereal year brings Earth back to the same place relative to the Sun.
+        let steps = (365.256_363 * 24. * 60. / Simulation::DT).round() as usize;
+        let start = simulate(default_epoch(), 0);
+        let end = simulate(default_epoch(), steps);
+        let earth = |pos: &HashMap<Body, DVec3>| pos[&Body::Earth] - pos[&Body::Sun];
+        let miss = earth(&end).distance(earth(&start));
+        assert!(miss < 0.01, "{}", miss);
+    }
+
+    #[test]
+    fn test_setup_simulation_headless() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        setup_simulation(&mut app, default_epoch());
+        for _ in 0..48 {
+            app.world_mut().run_schedule(FixedUpdate);
+        }
+        let elapsed = app.world().resource::<Simulation>().current_time() - default_epoch();
+        assert!((elapsed.get::<second>() - 86_400.).abs() < 1e-3, "{:?}", elapsed);
+    }
+
+    // Run an app taking FixedUpdate steps at the given rate for the given number of frames of the
+    // given length. Return the simulated days that pass and the seconds of virtual time.
+    fn days_simulated(hz: f64, frame: Duration, frames: usize) -> (f64, f64) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(bevy::time::Time::<Fixed>::from_hz(hz))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(frame));
+        setup_simulation(&mut app, default_epoch());
+        for _ in 0..frames {
+            app.update();
+        }
+        let world = app.world();
+        let elapsed = world.resource::<Simulation>().current_time() - default_epoch();
+        let virtual_s = world.resource::<bevy::time::Time<Virtual>>().elapsed_seconds_f64();
+        (elapsed.get::<day>(), virtual_s)
+    }
+
+    #[test]
+    fn test_fixed_rate_keeps_simulated_rate() {
+        let days_per_sec = clock::STEP_MIN * clock::REFERENCE_HZ / 1_440.;
+        for hz in [8., 64.] {
+            let (days, secs) = days_simulated(hz, Duration::from_secs_f64(1. / 60.), 120);
+            let step_days = days_per_sec / hz;
+            assert!((days - days_per_sec * secs).abs() <= step_days, "{} Hz: {}", hz, days);
+        }
+    }
+
+    #[test]
+    fn test_low_fixed_rate_catches_up_in_few_steps() {
+        // Bevy limits the virtual time a stalled frame adds to a quarter second, so at 8 Hz the
+        // simulation catches up at most two steps however long the stall. No time passes in the
+        // first frame.
+        let step_days = clock::STEP_MIN * clock::REFERENCE_HZ / 8. / 1_440.;
+        let (days, _) = days_simulated(8., Duration::from_secs(5), 2);
+        assert!(days > 0. && days <= 2. * step_days + 1e-9, "{}", days);
+    }
+
+    #[test]
+    fn test_mk_transform_up_parallel_to_facing() {
+        let mut observer = Observer::new();
+        observer.up = Dir3::new_unchecked(Vec3::new(1e-6, 0., -1.).normalize());
+        let transform = observer.mk_transform();
+        assert!(transform.rotation.is_finite() && transform.rotation.is_normalized());
+        assert!(transform.forward().dot(Vec3::NEG_Z) > 0.999_9, "{:?}", transform);
+    }
+
+    #[test]
+    fn test_upright_nearest_up() {
+        let facing = Dir3::new_unchecked(Vec3::new(1., 0., 1.).normalize());
+        let up = upright(facing, Vec3::Z);
+        assert!(up.dot(*facing).abs() < 1e-6);
+        assert!(up.distance(Vec3::new(-1., 0., 1.).normalize()) < 1e-6, "{:?}", up);
+    }
+
+    #[test]
+    fn test_render_data_matches_lookups() {
+        let sim = Simulation::init_at(default_epoch());
+        let data: Vec<_> = sim.render_data().collect();
+        assert_eq!(data.len(), sim.bodies().len());
+        for (body, name, color, position, radius) in data {
+            assert_eq!(name, sim.name_of(body));
+            assert_eq!(color, *sim.color_of(body));
+            assert_eq!(position, sim.position_of(body));
+            assert_eq!(radius, sim.radius_of(body));
+        }
+    }
+
+    #[test]
+    fn test_plugin_builder() {
+        let epoch = f64::Time::new::<day>(2_451_545.);
+        let plugin = SolarSystemPlugin::default()
+            .with_epoch(epoch)
+            .with_time_scale(4.)
+            .with_trails(false)
+            .with_starfield(false)
+            .with_fixed_rate(16.)
+            .with_atmospheres(true)
+            .with_propagator(Propagator::RungeKutta4)
+            .with_auto_slowdown(f64::Time::new::<day>(1.), 0.125)
+            .with_snapshot_path("bodies.glb");
+        assert_eq!(plugin.epoch, epoch);
+        assert_eq!(plugin.time_scale, 4.);
+        assert_eq!(plugin.fixed_hz, 16.);
+        assert!(plugin.atmospheres);
+        assert_eq!(plugin.propagator, Propagator::RungeKutta4);
+        assert!(plugin.slowdown.enabled && plugin.slowdown.factor == 0.125);
+        assert_eq!(plugin.snapshot.path, PathBuf::from("bodies.glb"));
+        assert!(!plugin.trails && plugin.ecliptic_grid && !plugin.starfield);
+    }
+
+    #[test]
+    fn test_jump_to_next_periapsis() {
+        let mut sim = Simulation::init_at(default_epoch());
+        assert_eq!(sim.jump_to_next_periapsis(Body::Sun), None);
+        assert_eq!(sim.current_time(), default_epoch());
+        let time = sim.jump_to_next_periapsis(Body::Moon).unwrap();
+        assert!((sim.current_time() - time).abs().get::<second>() < 1., "{:?}", time);
+    }
+
+    #[test]
+    fn test_orbits_paused_keeps_spinning() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        setup_simulation(&mut app, default_epoch());
+        app.insert_resource(SimClock { orbits_paused: true, ..default() });
+        let sim = app.world().resource::<Simulation>();
+        let (earth, spin) = (sim.position_of(Body::Earth), sim.orientation_of(Body::Earth));
+        for _ in 0..4 {
+            app.world_mut().run_schedule(FixedUpdate);
+        }
+
+        // Four steps of half an hour each turn Earth 30° but leave it in place.
+        let sim = app.world().resource::<Simulation>();
+        assert_eq!(sim.current_time(), default_epoch());
+        assert_eq!(sim.position_of(Body::Earth), earth);
+        let turned = sim.orientation_of(Body::Earth).angle_between(spin).to_degrees();
+        assert!((turned - 30.).abs() < 0.1, "{}", turned);
+
+        // Resuming the orbits moves Earth on from where it stopped, still spinning from where it
+        // had turned to.
+        let spin = sim.orientation_of(Body::Earth);
+        app.world_mut().resource_mut::<SimClock>().orbits_paused = false;
+        app.world_mut().run_schedule(FixedUpdate);
+        let sim = app.world().resource::<Simulation>();
+        let moved = sim.position_of(Body::Earth).distance(earth);
+        assert!(moved > 0. && moved < 1e-3, "{}", moved);
+        let turned = sim.orientation_of(Body::Earth).angle_between(spin).to_degrees();
+        assert!((turned - 7.5).abs() < 0.1, "{}", turned);
+    }
+
+    #[test]
+    fn test_uranus_rings_perpendicular_to_spin_axis() {
+        // The rings lie in the XY plane of the avatar, which is turned to the body's orientation.
+        let props = BodyProperties::properties_for(Body::Uranus);
+        let axis = props.spin_axis().cast::<f32>();
+        let axis = Vec3::new(axis.x, axis.y, axis.z);
+        // Uranus is tilted past its side, so it spins retrograde.
+        assert!(axis.z < 0., "{}", axis);
+        let period = props.orbital_period();
+        for quarter in 0..4 {
+            let sim = Simulation::init_at(default_epoch() + quarter as f64 * period / 4.);
+            assert!(sim.rings_of(Body::Uranus).is_some());
+            let normal = sim.orientation_of(Body::Uranus) * Vec3::Z;
+            assert!(normal.dot(axis) > 0.999_99, "{} {}", normal, axis);
+        }
+    }
+
+    #[test]
+    fn test_atmospheres_only_on_thick_atmosphere_bodies() {
+        let sim = Simulation::init_at(default_epoch());
+        for body in [Body::Venus, Body::Earth, Body::Jupiter, Body::Saturn, Body::Neptune] {
+            assert!(sim.atmosphere_of(body).is_some(), "{:?}", body);
+        }
+        for body in [Body::Sun, Body::Mercury, Body::Moon, Body::Mars] {
+            assert!(sim.atmosphere_of(body).is_none(), "{:?}", body);
+        }
+    }
+
+    #[test]
+    fn test_label_fade_alpha() {
+        let fade = LabelFade { fade_start: 60., fade_end: 100. };
+        assert_eq!(fade.alpha(10.), 1.);
+        assert_eq!(fade.alpha(80.), 0.5);
+        assert_eq!(fade.alpha(120.), 0.);
+    }
+
+    // The body is 120 AU in front of a camera at the Sun, so it's culled unless the far plane is
+    // raised past it, and its label fades out unless the fade follows the far plane.
+    #[test]
+    fn test_world_bounds_raised_renders_distant_body() {
+        let body = bevy::render::primitives::Sphere {
+            center: Vec3A::new(0., 0., -120.),
+            radius: 0.01,
+        };
+        let in_view = |bounds: &WorldBounds| {
+            let proj = mk_projection(bounds);
+            let frustum = Frustum::from_clip_from_world_custom_far(
+                &proj.get_clip_from_view(),
+                &Vec3::ZERO,
+                &Vec3::Z,
+                proj.far,
+            );
+            frustum.intersects_sphere(&body, true)
+        };
+        let raised = WorldBounds { radius_au: 150., zfar_au: 150. };
+        assert!(!in_view(&WorldBounds::default()));
+        assert!(in_view(&raised));
+        assert_eq!(LabelFade::default().alpha(120.), 0.);
+        assert!(LabelFade::for_far_plane(raised.zfar_au).alpha(120.) > 0.);
+    }
+
+    #[test]
+    fn test_label_flipped_near_edge_keeps_scale() {
+        // With the default camera, world coordinates are normalized device coordinates.
+        let cam = (&Camera::default(), &GlobalTransform::IDENTITY);
+        let (observer, window, style) = (Observer::new(), Window::default(), LabelStyle::default());
+        let near_bottom = Vec3::new(0., -0.98, 0.5);
+        let (transform, anchor) =
+            mk_lbl_transform(near_bottom, &observer, cam, (&window, &style)).unwrap();
+        assert_eq!(anchor, LabelAnchor::Above);
+        assert!(transform.translation.y > near_bottom.y);
+        let dist = near_bottom.distance(*observer.position());
+        assert_eq!(transform.scale, Vec3::splat(label_scale(dist, &window, &style)));
+
+        let centered = Vec3::new(0., 0., 0.5);
+        let (_, anchor) = mk_lbl_transform(centered, &observer, cam, (&window, &style)).unwrap();
+        assert_eq!(anchor, LabelAnchor::Below);
+    }
+
+    #[test]
+    fn test_occluded_by_nearer_body() {
+        let cam = Vec3::new(0., 0., 10.);
+        let sun = [(Vec3::ZERO, 0.5)];
+        assert!(occluded(cam, Vec3::new(0.1, 0., -5.), sun.into_iter()));
+        assert!(!occluded(cam, Vec3::new(2., 0., -5.), sun.into_iter()));
+        assert!(!occluded(cam, Vec3::new(0.1, 0., 5.), sun.into_iter()));
+    }
 }
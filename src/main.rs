@@ -1,9 +1,12 @@
 extern crate bevy;
 use bevy::{prelude::*, window::{Window, WindowPlugin}};
 #[cfg(not(target_family = "wasm"))]
-use bevy::window::WindowMode;
+use bevy::{math::DVec3, window::WindowMode};
 
-use solar_system;
+use solar_system::{self, SolarSystemPlugin};
+
+extern crate uom;
+use uom::si::f64;
 
 #[cfg(target_family = "wasm")]
 fn mk_window() -> Window {
@@ -22,6 +25,124 @@ fn mk_window() -> Window {
 }
 
 
+// The arguments are an optional epoch, any number of `--scene FILE` naming TOML files defining
+// more bodies, each a scenario in the scenario menu, an optional `--barycentric` centering the
+// world on the solar system's barycenter rather than on where the Sun starts, an optional
+// `--record START END FRAMES` recording the given number of frames from the START to the END
+// epoch, an optional `--craft BODY X Y Z VX VY VZ` launching a spacecraft from the position in km
+// and with the velocity in km/s relative to BODY when the simulation starts, and an optional
+// `--snapshot FILE` naming the file the glTF snapshots are exported to.
+#[cfg(not(target_family = "wasm"))]
+struct Args {
+    epoch: Option<String>,
+    scenes: Vec<String>,
+    barycentric: bool,
+    record: Option<[String; 3]>,
+    craft: Option<[String; 7]>,
+    snapshot: Option<String>,
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn exit_with(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1)
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read_args() -> Args {
+    let mut args = Args {
+        epoch: None,
+        scenes: Vec::new(),
+        barycentric: false,
+        record: None,
+        craft: None,
+        snapshot: None,
+    };
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        if arg == "--scene" {
+            args.scenes.push(argv.next().unwrap_or_else(|| exit_with("--scene needs a file")));
+        } else if arg == "--barycentric" {
+            args.barycentric = true;
+        } else if arg == "--record" {
+            let mut next =
+                || argv.next().unwrap_or_else(|| exit_with("--record needs START END FRAMES"));
+            args.record = Some([next(), next(), next()]);
+        } else if arg == "--craft" {
+            let mut next =
+                || argv.next().unwrap_or_else(|| exit_with("--craft needs BODY X Y Z VX VY VZ"));
+            args.craft = Some(std::array::from_fn(|_| next()));
+        } else if arg == "--snapshot" {
+            let path = argv.next().unwrap_or_else(|| exit_with("--snapshot needs a file"));
+            args.snapshot = Some(path);
+        } else if args.epoch.is_none() {
+            args.epoch = Some(arg);
+        } else {
+            exit_with(&format!("unexpected argument: {}", arg));
+        }
+    }
+    args
+}
+
+// The simulation starts at the given epoch, either a Julian Date or a UTC Gregorian calendar date
+// like "2024-04-08 18:00".
+#[cfg(not(target_family = "wasm"))]
+fn read_epoch(args: &Args) -> f64::Time {
+    match &args.epoch {
+        Some(arg) => solar_system::parse_epoch(arg).unwrap_or_else(|| {
+            exit_with(&format!("not a Julian Date or Gregorian date: {}", arg))
+        }),
+        None => solar_system::default_epoch(),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read_recording(args: &Args) -> Option<solar_system::FrameRecording> {
+    let [start, end, frames] = args.record.as_ref()?;
+    let read_time = |arg: &String| {
+        solar_system::parse_epoch(arg).unwrap_or_else(|| {
+            exit_with(&format!("not a Julian Date or Gregorian date: {}", arg))
+        })
+    };
+    let frames = frames
+        .parse()
+        .ok()
+        .filter(|frames| *frames > 0)
+        .unwrap_or_else(|| exit_with(&format!("not a positive frame count: {}", frames)));
+    Some(solar_system::FrameRecording::new(read_time(start), read_time(end), frames))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read_spacecraft(args: &Args, launch: f64::Time) -> Option<solar_system::Spacecraft> {
+    let [body, coords @ ..] = args.craft.as_ref()?;
+    let body = *solar_system::Body::VARIANTS
+        .iter()
+        .find(|variant| format!("{:?}", variant).eq_ignore_ascii_case(body))
+        .unwrap_or_else(|| exit_with(&format!("not a body: {}", body)));
+    let coords = coords.clone().map(|coord| {
+        coord.parse().unwrap_or_else(|_| exit_with(&format!("not a number: {}", coord)))
+    });
+    let [x, y, z, vx, vy, vz] = coords;
+    Some(solar_system::Spacecraft::new(
+        body,
+        launch,
+        DVec3::new(x, y, z),
+        DVec3::new(vx, vy, vz),
+    ))
+}
+
+// A scene without its own epoch starts at the given one.
+#[cfg(not(target_family = "wasm"))]
+fn read_scenarios(args: &Args, epoch: f64::Time) -> Vec<solar_system::Scenario> {
+    args.scenes
+        .iter()
+        .map(|path| {
+            solar_system::read_scenario(path, epoch).unwrap_or_else(|err| exit_with(&err))
+        })
+        .collect()
+}
+
+
 #[cfg(not(target_family = "wasm"))]
 fn quit(input: Res<ButtonInput<KeyCode>>, mut app_exit_events: ResMut<Events<AppExit>>) {
     if input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
@@ -42,7 +163,33 @@ pub fn main() {
     app.add_plugins(DefaultPlugins.set(win_plug));
 
     #[cfg(not(target_family = "wasm"))]
-    app.add_systems(FixedUpdate, quit);
+    {
+        app.add_systems(FixedUpdate, quit);
+        let args = read_args();
+        let epoch = read_epoch(&args);
+        let mut plugin = SolarSystemPlugin::default().with_epoch(epoch);
+        let scenarios = read_scenarios(&args, epoch);
+        let start = scenarios.first().map_or(epoch, |scenario| scenario.epoch);
+        for scenario in scenarios {
+            plugin = plugin.with_scenario(scenario);
+        }
+        let recording = read_recording(&args);
+        if let Some(craft) = read_spacecraft(&args, recording.as_ref().map_or(start, |r| r.start)) {
+            plugin = plugin.with_spacecraft(craft);
+        }
+        if let Some(recording) = recording {
+            plugin = plugin.with_recording(recording);
+        }
+        if let Some(path) = &args.snapshot {
+            plugin = plugin.with_snapshot_path(path);
+        }
+        app.add_plugins(plugin);
+        if args.barycentric {
+            solar_system::center_on_barycenter(&mut app);
+        }
+    }
+    #[cfg(target_family = "wasm")]
+    app.add_plugins(SolarSystemPlugin::default());
 
-    solar_system::setup(&mut app).run();
+    app.run();
 }
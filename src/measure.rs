@@ -0,0 +1,151 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    selection::{body_under_cursor, shift_held, Selected},
+    simulation::{Body, LIGHT_SPEED_MPS},
+    uom_wrapper::si::{
+        f64::Length,
+        length::{astronomical_unit, kilometer, meter},
+    },
+    BodyModel, DrawnPositions, Simulation,
+};
+
+// The key clearing the measured pair
+const CLEAR_KEY: KeyCode = KeyCode::Escape;
+
+// These are the two bodies whose separation is being measured, if any.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub struct MeasurePair(pub Option<(Body, Body)>);
+
+// Return the time light takes to cross a distance, e.g., "8.3 min"
+fn fmt_light_time(distance: Length) -> String {
+    let secs = distance.get::<meter>() / LIGHT_SPEED_MPS;
+    if secs < 60. {
+        format!("{:.2} s", secs)
+    } else if secs < 3600. {
+        format!("{:.1} min", secs / 60.)
+    } else {
+        format!("{:.2} h", secs / 3600.)
+    }
+}
+
+fn measurement_text(sim: &Simulation, a: Body, b: Body) -> String {
+    let dist = sim.distance_between(a, b);
+    format!(
+        "{} to {}: {:.6} AU ({:.0} km), light time {}",
+        sim.name_of(a),
+        sim.name_of(b),
+        dist.get::<astronomical_unit>(),
+        dist.get::<kilometer>(),
+        fmt_light_time(dist),
+    )
+}
+
+// Shift-clicking a body pairs it with the selected body. Escape clears the pair.
+fn choose_pair(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    selected: Res<Selected>,
+    mut pair: ResMut<MeasurePair>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    cam: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    bodies: Query<(&Body, &BodyModel)>,
+) {
+    if keys.just_pressed(CLEAR_KEY) {
+        pair.0 = None;
+        return;
+    }
+    if !buttons.just_pressed(MouseButton::Left) || !shift_held(&keys) {
+        return;
+    }
+    let (cam, cam_trans) = cam.single();
+    let second = body_under_cursor(window.single(), cam, cam_trans, &bodies);
+    if let (Some(first), Some(second)) = (selected.0, second) {
+        if first != second {
+            pair.0 = Some((first, second));
+        }
+    }
+}
+
+#[derive(Component)]
+struct MeasureText;
+
+fn create_text(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.),
+            left: Val::Px(10.),
+            ..default()
+        }),
+        MeasureText,
+    ));
+}
+
+fn update_text(
+    sim: Res<Simulation>,
+    pair: Res<MeasurePair>,
+    mut text: Query<&mut Text, With<MeasureText>>,
+) {
+    let value = match pair.0 {
+        Some((a, b)) => measurement_text(&sim, a, b),
+        None => String::new(),
+    };
+    for mut text in &mut text {
+        text.sections[0].value.clone_from(&value);
+    }
+}
+
+// A thin line connects the centers of the paired avatars. It is drawn every frame from where the
+// avatars are drawn.
+fn draw_pair_line(pair: Res<MeasurePair>, drawn: DrawnPositions, mut gizmos: Gizmos) {
+    let Some((a, b)) = pair.0 else {
+        return;
+    };
+    gizmos.line(drawn.of(a), drawn.of(b), Color::srgb(0.8, 0.8, 0.2));
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<MeasurePair>()
+        .add_systems(Startup, create_text)
+        .add_systems(
+            Update,
+            ((choose_pair, update_text).chain(), draw_pair_line.after(crate::update_avatars)),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_fmt_light_time_sun_earth() {
+        assert_eq!(fmt_light_time(Length::new::<astronomical_unit>(1.)), "8.3 min")
+    }
+
+    #[test]
+    fn test_fmt_light_time_earth_moon() {
+        assert_eq!(fmt_light_time(Length::new::<kilometer>(384_400.)), "1.28 s")
+    }
+
+    #[test]
+    fn test_fmt_light_time_neptune() {
+        assert_eq!(fmt_light_time(Length::new::<astronomical_unit>(30.)), "4.16 h")
+    }
+
+    #[test]
+    fn test_measurement_text() {
+        let sim = Simulation::init_at(default_epoch());
+        let text = measurement_text(&sim, Body::Earth, Body::Moon);
+        assert!(text.starts_with("Earth to Moon: 0.00"), "{}", text);
+    }
+}
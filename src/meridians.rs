@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::{layers::Layers, selection::Selected, simulation::Body, BodyModel, Simulation};
+
+// The key toggling the selected body's marker while shift is held
+const TOGGLE_KEY: KeyCode = KeyCode::KeyM;
+
+// The radius of a marker relative to its avatar's, just enough to keep it clear of the avatar's
+// surface
+const MARKER_RADIUS: f32 = 1.01;
+
+// The number of segments each marker is drawn with from pole to pole
+const SEGMENTS: usize = 32;
+
+const MARKER_COLOR: Color = Color::srgb(1., 1., 0.6);
+
+// These are the bodies marked with a bright stripe along their prime meridians, the line of
+// longitude 0 running from pole to pole, so their spins can be followed. No body is marked by
+// default.
+#[derive(Debug, Default, Resource)]
+pub struct MeridianMarkers {
+    pub bodies: HashSet<Body>,
+}
+
+impl MeridianMarkers {
+    // Mark the body or unmark it if it is marked, and return whether it is marked afterward
+    pub fn toggle(&mut self, body: Body) -> bool {
+        !self.bodies.remove(&body) && self.bodies.insert(body)
+    }
+}
+
+// Return where a marker is drawn on an avatar posed by the given transform, from the body's south
+// pole to its north. An avatar is a unit sphere turned to its body's orientation, so it spins about
+// its Z axis, and its prime meridian crosses its equator on its X axis.
+fn marker_points(avatar: &Transform) -> Vec<Vec3> {
+    (0..=SEGMENTS)
+        .map(|seg| {
+            let lat = std::f32::consts::PI * (seg as f32 / SEGMENTS as f32 - 0.5);
+            let (sin, cos) = lat.sin_cos();
+            avatar.transform_point(MARKER_RADIUS * Vec3::new(cos, 0., sin))
+        })
+        .collect()
+}
+
+// Shift+M marks the selected body's prime meridian, or unmarks it.
+fn toggle_marker(
+    input: Res<ButtonInput<KeyCode>>,
+    (selected, sim): (Res<Selected>, Res<Simulation>),
+    mut markers: ResMut<MeridianMarkers>,
+) {
+    let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !input.just_pressed(TOGGLE_KEY) || !shift {
+        return;
+    }
+    if let Some(body) = selected.0 {
+        let marked = markers.toggle(body);
+        info!("{}'s meridian marker: {}", sim.name_of(body), marked);
+    }
+}
+
+// The markers are drawn every frame on the avatars as they are posed, so they move along with their
+// bodies between steps and turn with them as they spin.
+fn draw_markers(
+    (markers, layers): (Res<MeridianMarkers>, Res<Layers>),
+    bodies: Query<(&Body, &BodyModel)>,
+    avatars: Query<&Transform>,
+    mut gizmos: Gizmos,
+) {
+    for (body, model) in bodies.iter().filter(|(body, _)| markers.bodies.contains(body)) {
+        if !layers.shows_body(*body) {
+            continue;
+        }
+        if let Some(avatar) = model.avatar().and_then(|avatar| avatars.get(avatar).ok()) {
+            gizmos.linestrip(marker_points(avatar), MARKER_COLOR);
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<MeridianMarkers>()
+        .add_systems(Update, (toggle_marker, draw_markers.after(crate::update_avatars)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        default_epoch,
+        uom_wrapper::si::{f64::Time, time::hour},
+    };
+
+    #[test]
+    fn test_markers_toggle() {
+        let mut markers = MeridianMarkers::default();
+        assert!(markers.toggle(Body::Mars));
+        assert!(markers.bodies.contains(&Body::Mars));
+        assert!(!markers.toggle(Body::Mars));
+        assert!(markers.bodies.is_empty());
+    }
+
+    #[test]
+    fn test_marker_runs_pole_to_pole() {
+        let avatar = Transform::from_xyz(1., 2., 3.).with_scale(Vec3::splat(0.1));
+        let points = marker_points(&avatar);
+        let radius = 0.1 * MARKER_RADIUS;
+        assert!(points[0].distance(Vec3::new(1., 2., 3. - radius)) < 1e-6, "{}", points[0]);
+        assert!(points[SEGMENTS].distance(Vec3::new(1., 2., 3. + radius)) < 1e-6);
+        let equator = points[SEGMENTS / 2];
+        assert!(equator.distance(Vec3::new(1. + radius, 2., 3.)) < 1e-6, "{}", equator);
+    }
+
+    #[test]
+    fn test_marker_turns_with_body() {
+        // Earth turns about a quarter of the way around in six hours.
+        let mut sim = Simulation::init_at(default_epoch());
+        let avatar = Transform::from_rotation(sim.orientation_of(Body::Earth));
+        let before = marker_points(&avatar)[SEGMENTS / 2];
+        sim.advance_by(Time::new::<hour>(6.));
+        let avatar = Transform::from_rotation(sim.orientation_of(Body::Earth));
+        let after = marker_points(&avatar)[SEGMENTS / 2];
+        let turned = before.angle_between(after).to_degrees();
+        assert!((85. ..95.).contains(&turned), "{}", turned);
+
+        // The poles stay on the spin axis.
+        let pole = sim.orientation_of(Body::Earth) * Vec3::Z * MARKER_RADIUS;
+        assert!(marker_points(&avatar)[SEGMENTS].distance(pole) < 1e-5);
+    }
+}
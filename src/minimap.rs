@@ -0,0 +1,255 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{ClearColorConfig, Viewport},
+        view::RenderLayers,
+    },
+    window::PrimaryWindow,
+};
+
+use crate::{follow_camera::FlyToBody, selection::Selected, simulation::Body, Simulation};
+
+// The key toggling the minimap
+const TOGGLE_KEY: KeyCode = KeyCode::Semicolon;
+
+// The width and height of the minimap in logical pixels and its offsets from the left and bottom
+// of the window. The bottom offset keeps it clear of the measurement readout.
+const SIZE_PX: f32 = 200.;
+const LEFT_PX: f32 = 10.;
+const BOTTOM_PX: f32 = 40.;
+
+// The radius in logical pixels of the circle the bodies are plotted in
+const MAP_RADIUS_PX: f32 = SIZE_PX / 2. - 6.;
+
+// The distance in AU from the Sun plotted at the edge of the minimap, and the distance where the
+// scale turns from about linear to logarithmic
+const REACH_AU: f32 = 100.;
+const KNEE_AU: f32 = 0.2;
+
+// The radius in logical pixels of a body's dot, and how close to a dot a click must be to pick it
+const DOT_PX: f32 = 2.5;
+const PICK_PX: f32 = 8.;
+
+// The number of points along each edge of the camera's footprint, which reaches from the near
+// plane to the far plane
+const FOOTPRINT_POINTS: usize = 24;
+
+// The render layer only the minimap's camera draws
+const LAYER: usize = 1;
+
+const BACKGROUND: Color = Color::srgb(0.02, 0.02, 0.05);
+const BORDER_COLOR: Color = Color::srgb(0.3, 0.3, 0.4);
+const FOOTPRINT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+
+// The minimap's lines are drawn with their own gizmos, so only its camera draws them.
+#[derive(Default, GizmoConfigGroup, Reflect)]
+struct MinimapGizmos;
+
+// This marks the minimap's camera.
+#[derive(Component)]
+struct Minimap;
+
+// Return where a point in the ecliptic plane, given in AU from the Sun, is plotted on the minimap
+// in logical pixels from its center. The distance from the Sun is scaled logarithmically, so the
+// inner planets spread out, and points beyond the reach are pinned to the edge.
+fn to_map(offset: Vec2) -> Vec2 {
+    let scaled = (1. + offset.length() / KNEE_AU).ln() / (1. + REACH_AU / KNEE_AU).ln();
+    offset.normalize_or_zero() * MAP_RADIUS_PX * scaled.min(1.)
+}
+
+// Return the body whose dot is nearest a point on the minimap, if the point is close enough to
+// it. Moons aren't plotted, since they would sit on their planets.
+fn dot_at(sim: &Simulation, point: Vec2) -> Option<Body> {
+    let sun = sim.position_of(Body::Sun);
+    sim.render_data()
+        .filter(|(body, ..)| !body.is_moon())
+        .map(|(body, _, _, pos, _)| (body, to_map((pos - sun).truncate()).distance(point)))
+        .filter(|(_, dist)| *dist <= PICK_PX)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(body, _)| body)
+}
+
+// Return the minimap's viewport in the bottom left corner of a window. None is returned when the
+// window is too small to hold it.
+fn viewport_for(window: &Window) -> Option<Viewport> {
+    let scale = window.scale_factor();
+    let size = (SIZE_PX * scale) as u32;
+    let left = (LEFT_PX * scale) as u32;
+    let top = window.physical_height().checked_sub(size + (BOTTOM_PX * scale) as u32)?;
+    if left + size > window.physical_width() {
+        return None;
+    }
+    Some(Viewport {
+        physical_position: UVec2::new(left, top),
+        physical_size: UVec2::splat(size),
+        ..default()
+    })
+}
+
+// The minimap is drawn by a 2D camera rendering after the others, so it covers its corner of the
+// window. The main camera draws the UI. The camera stays inactive until it has been placed.
+fn create_minimap(mut commands: Commands, mut gizmo_configs: ResMut<GizmoConfigStore>) {
+    let (config, _) = gizmo_configs.config_mut::<MinimapGizmos>();
+    config.render_layers = RenderLayers::layer(LAYER);
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: 2,
+                is_active: false,
+                clear_color: ClearColorConfig::Custom(BACKGROUND),
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::layer(LAYER),
+        Minimap,
+    ));
+}
+
+// This is whether the minimap is shown. It is shown at startup.
+#[derive(Resource)]
+struct MinimapShown(bool);
+
+impl Default for MinimapShown {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+fn toggle_minimap(input: Res<ButtonInput<KeyCode>>, mut shown: ResMut<MinimapShown>) {
+    if input.just_pressed(TOGGLE_KEY) {
+        shown.0 = !shown.0;
+    }
+}
+
+// The minimap stays in its corner as the window is resized, and it is hidden while the window is
+// too small for it.
+fn place_minimap(
+    shown: Res<MinimapShown>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut minimap: Query<&mut Camera, With<Minimap>>,
+) {
+    let viewport = viewport_for(window.single());
+    for mut cam in &mut minimap {
+        let active = shown.0 && viewport.is_some();
+        if cam.is_active != active {
+            cam.is_active = active;
+        }
+        let current = cam.viewport.as_ref().map(|vp| (vp.physical_position, vp.physical_size));
+        let wanted = viewport.as_ref().map(|vp| (vp.physical_position, vp.physical_size));
+        if wanted.is_some() && current != wanted {
+            cam.viewport.clone_from(&viewport);
+        }
+    }
+}
+
+// The minimap looks down on the ecliptic with the Sun at its center. Each body other than the
+// moons is a dot, and the main camera's horizontal field of view is drawn from its position.
+fn draw_minimap(
+    sim: Res<Simulation>,
+    mut gizmos: Gizmos<MinimapGizmos>,
+    minimap: Query<&Camera, With<Minimap>>,
+    cam: Query<(&GlobalTransform, &Projection), With<Camera3d>>,
+) {
+    if !minimap.iter().any(|cam| cam.is_active) {
+        return;
+    }
+    let sun = sim.position_of(Body::Sun);
+    gizmos.circle_2d(Vec2::ZERO, MAP_RADIUS_PX, BORDER_COLOR);
+    for (_, _, color, pos, _) in sim.render_data().filter(|(body, ..)| !body.is_moon()) {
+        gizmos.circle_2d(to_map((pos - sun).truncate()), DOT_PX, color);
+    }
+
+    let (cam_trans, projection) = cam.single();
+    let Projection::Perspective(projection) = projection else {
+        return;
+    };
+    let half_fov = ((projection.fov / 2.).tan() * projection.aspect_ratio).atan();
+    let cam_pos = cam_trans.translation() - sun;
+    for side in [-1., 1.] {
+        let edge = Quat::from_axis_angle(*cam_trans.up(), side * half_fov) * *cam_trans.forward();
+        let reach = projection.far / projection.near;
+        gizmos.linestrip_2d(
+            (0..FOOTPRINT_POINTS).map(|i| {
+                let dist = projection.near * reach.powf(i as f32 / (FOOTPRINT_POINTS - 1) as f32);
+                to_map((cam_pos + dist * edge).truncate())
+            }),
+            FOOTPRINT_COLOR,
+        );
+    }
+}
+
+// Clicking a body's dot on the minimap selects the body and flies the camera to it.
+fn pick_on_minimap(
+    buttons: Res<ButtonInput<MouseButton>>,
+    sim: Res<Simulation>,
+    mut selected: ResMut<Selected>,
+    mut flights: EventWriter<FlyToBody>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    minimap: Query<&Camera, With<Minimap>>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = window.single().cursor_position() else {
+        return;
+    };
+    for cam in minimap.iter().filter(|cam| cam.is_active) {
+        let Some(rect) = cam.logical_viewport_rect().filter(|rect| rect.contains(cursor)) else {
+            continue;
+        };
+        let point = Vec2::new(cursor.x - rect.center().x, rect.center().y - cursor.y);
+        if let Some(body) = dot_at(&sim, point) {
+            selected.0 = Some(body);
+            flights.send(FlyToBody(body));
+            info!("Selected {} on the minimap", sim.name_of(body));
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_gizmo_group::<MinimapGizmos>()
+        .init_resource::<MinimapShown>()
+        .add_systems(Startup, create_minimap)
+        .add_systems(
+            Update,
+            (toggle_minimap, place_minimap, draw_minimap, pick_on_minimap).chain(),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_to_map_spreads_inner_planets() {
+        let earth = to_map(Vec2::X).length();
+        let neptune = to_map(Vec2::new(0., 30.)).length();
+        assert!(earth > 0.25 * MAP_RADIUS_PX, "{}", earth);
+        assert!(neptune > earth && neptune < MAP_RADIUS_PX, "{} {}", earth, neptune);
+        assert_eq!(to_map(Vec2::new(-500., 0.)), Vec2::new(-MAP_RADIUS_PX, 0.));
+        assert_eq!(to_map(Vec2::ZERO), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_dot_at_picks_planet() {
+        let sim = Simulation::init_at(default_epoch());
+        let sun = sim.position_of(Body::Sun);
+        let jupiter = to_map((sim.position_of(Body::Jupiter) - sun).truncate());
+        assert_eq!(dot_at(&sim, jupiter + Vec2::new(1., -1.)), Some(Body::Jupiter));
+        assert_eq!(dot_at(&sim, Vec2::splat(SIZE_PX)), None);
+    }
+
+    #[test]
+    fn test_viewport_for_bottom_left() {
+        let mut window = Window::default();
+        window.resolution.set_physical_resolution(1920, 1080);
+        window.resolution.set_scale_factor_override(Some(2.));
+        let viewport = viewport_for(&window).unwrap();
+        assert_eq!(viewport.physical_position, UVec2::new(20, 1080 - 400 - 80));
+        assert_eq!(viewport.physical_size, UVec2::splat(400));
+        window.resolution.set_physical_resolution(300, 300);
+        assert!(viewport_for(&window).is_none());
+    }
+}
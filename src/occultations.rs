@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{math::DVec3, prelude::*};
+
+use crate::{
+    photometry,
+    simulation::Body,
+    uom_wrapper::si::{angle::radian, f64::Length, length::astronomical_unit},
+    SimSet, Simulation, SolarSystemEvent,
+};
+
+// These are the bodies currently hidden behind the Sun as seen from the observer, e.g., a planet
+// near superior conjunction seen from Earth. A body is hidden when its center is behind the Sun's
+// disk. The angles between the bodies and the Sun as seen from the observer over the last two
+// steps are remembered to find when each passes closest to the Sun.
+#[derive(Debug, Resource)]
+pub struct Occultations {
+    pub observer: Body,
+    occulted: HashSet<Body>,
+    elongations: HashMap<Body, [f64; 2]>,
+}
+
+impl Default for Occultations {
+    fn default() -> Self {
+        Self {
+            observer: Body::Earth,
+            occulted: HashSet::new(),
+            elongations: HashMap::new(),
+        }
+    }
+}
+
+impl Occultations {
+    pub fn is_occulted(&self, body: Body) -> bool {
+        self.occulted.contains(&body)
+    }
+
+    // Forget the bodies hidden and the angles remembered, e.g., when another scenario is loaded
+    pub fn forget(&mut self) {
+        self.occulted.clear();
+        self.elongations.clear();
+    }
+}
+
+// Determine whether the target is farther from the observer than the Sun and its direction is
+// within the Sun's angular radius. The positions and radius are in AU.
+fn behind_sun(sun_pos: DVec3, sun_radius: f64, observer_pos: DVec3, target_pos: DVec3) -> bool {
+    let to_sun = sun_pos - observer_pos;
+    let to_target = target_pos - observer_pos;
+    let sun_diameter = photometry::angular_diameter(
+        Length::new::<astronomical_unit>(sun_radius),
+        Length::new::<astronomical_unit>(to_sun.length()),
+    );
+    to_target.length() > to_sun.length()
+        && to_target.angle_between(to_sun) < sun_diameter.get::<radian>() / 2.
+}
+
+// Return the angle in radians between the target and the Sun as seen from the observer
+fn elongation_in(sim: &Simulation, observer: Body, target: Body) -> f64 {
+    let observer_pos = sim.position_in::<astronomical_unit>(observer);
+    let to_sun = sim.position_in::<astronomical_unit>(Body::Sun) - observer_pos;
+    (sim.position_in::<astronomical_unit>(target) - observer_pos).angle_between(to_sun)
+}
+
+fn occulted_in(sim: &Simulation, observer: Body, target: Body) -> bool {
+    behind_sun(
+        sim.position_in::<astronomical_unit>(Body::Sun),
+        sim.radius_of(Body::Sun) as f64,
+        sim.position_in::<astronomical_unit>(observer),
+        sim.position_in::<astronomical_unit>(target),
+    )
+}
+
+// A body passed closest to the Sun in the observer's sky at the last step if its angle from the
+// Sun shrank up to then and has grown since.
+fn detect_conjunctions(
+    sim: Res<Simulation>,
+    mut occultations: ResMut<Occultations>,
+    mut events: EventWriter<SolarSystemEvent>,
+) {
+    let observer = occultations.observer;
+    for body in sim.bodies().into_iter().filter(|body| ![Body::Sun, observer].contains(body)) {
+        let angle = elongation_in(&sim, observer, body);
+        let [before, last] = occultations.elongations.get(&body).copied().unwrap_or([angle; 2]);
+        if before > last && last < angle {
+            events.send(SolarSystemEvent::Conjunction { body, observer });
+        }
+        occultations.elongations.insert(body, [last, angle]);
+    }
+}
+
+// Each body going behind the Sun or coming back out is logged.
+fn detect_occultations(
+    sim: Res<Simulation>,
+    mut occultations: ResMut<Occultations>,
+    mut events: EventWriter<SolarSystemEvent>,
+) {
+    let observer = occultations.observer;
+    let current = sim
+        .render_data()
+        .map(|(body, ..)| body)
+        .filter(|body| ![Body::Sun, observer].contains(body))
+        .filter(|body| occulted_in(&sim, observer, *body))
+        .collect::<HashSet<_>>();
+    if current == occultations.occulted {
+        return;
+    }
+
+    let observer_name = sim.name_of(observer);
+    for body in current.difference(&occultations.occulted) {
+        info!("{} is behind the Sun as seen from {}", sim.name_of(*body), observer_name);
+        events.send(SolarSystemEvent::OccultationStarted { body: *body, observer });
+    }
+    for body in occultations.occulted.difference(&current) {
+        info!("{} came out from behind the Sun as seen from {}", sim.name_of(*body), observer_name);
+        events.send(SolarSystemEvent::OccultationEnded { body: *body, observer });
+    }
+    occultations.occulted = current;
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<Occultations>()
+        .add_systems(
+            FixedUpdate,
+            (detect_occultations, detect_conjunctions).chain().after(SimSet::UpdateBodies),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    // This is in AU.
+    const SUN_RADIUS: f64 = 4.650e-3;
+
+    #[test]
+    fn test_behind_sun_at_superior_conjunction() {
+        let earth = DVec3::new(1., 0., 0.);
+        let venus = DVec3::new(-0.723, 0., 0.007);
+        assert!(behind_sun(DVec3::ZERO, SUN_RADIUS, earth, venus));
+    }
+
+    #[test]
+    fn test_behind_sun_not_beside_disk() {
+        let earth = DVec3::new(1., 0., 0.);
+        let venus = DVec3::new(-0.723, 0., 0.01);
+        assert!(!behind_sun(DVec3::ZERO, SUN_RADIUS, earth, venus));
+    }
+
+    #[test]
+    fn test_behind_sun_not_in_transit() {
+        let earth = DVec3::new(1., 0., 0.);
+        let mercury = DVec3::new(0.387, 0., 0.);
+        assert!(!behind_sun(DVec3::ZERO, SUN_RADIUS, earth, mercury));
+    }
+
+    // Return whether a planet half again as far from Earth as the Sun is hidden when it is the
+    // given number of solar radii, as seen from Earth, off of the line through the Sun's center.
+    // Earth, the Sun, and the Sun's radius are the simulation's.
+    fn hidden_beyond_sun(offset_radii: f64) -> bool {
+        let sim = Simulation::init_at(default_epoch());
+        let earth = sim.position_in::<astronomical_unit>(Body::Earth);
+        let sun = sim.position_in::<astronomical_unit>(Body::Sun);
+        let radius = sim.radius_of(Body::Sun) as f64;
+        let side = (sun - earth).cross(DVec3::Z).normalize();
+        let planet = sun + 0.5 * (sun - earth) + 1.5 * offset_radii * radius * side;
+        behind_sun(sun, radius, earth, planet)
+    }
+
+    #[test]
+    fn test_occulted_behind_sun_disk() {
+        // Halfway from the Sun's center to its limb, the planet must be hidden.
+        assert!(hidden_beyond_sun(0.5));
+    }
+
+    #[test]
+    fn test_not_occulted_beside_sun_disk() {
+        // Half a solar radius past the limb, the planet must be visible.
+        assert!(!hidden_beyond_sun(1.5));
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    camera_animation::{CameraAnimation, ObserverPose, DEFAULT_DURATION_S},
+    CameraMode, Observer, WorldBounds,
+};
+
+// The key switching between the free-fly and orbit camera modes
+const MODE_KEY: KeyCode = KeyCode::KeyC;
+
+// The angle in radians the observer orbits for each pixel the cursor is dragged
+const DRAG_RATE: f32 = 0.005;
+
+// The factor the orbit radius shrinks by for each line the scroll wheel is turned
+const ZOOM_FACTOR: f32 = 1.1;
+
+// The number of pixels of scrolling equivalent to scrolling one line
+const PIXELS_PER_LINE: f32 = 100.;
+
+// The closest in AU the observer may come to its focus
+const MIN_ORBIT_RADIUS_AU: f32 = 0.01;
+
+// Return the orbit radius after scrolling the given number of lines toward the focus. It stays
+// within the world's radius.
+fn zoom(orbit_radius: f32, lines: f32, bounds: &WorldBounds) -> f32 {
+    (orbit_radius * ZOOM_FACTOR.powf(-lines)).clamp(MIN_ORBIT_RADIUS_AU, bounds.radius_au)
+}
+
+// C switches between the camera modes. When switching to orbit mode, the observer turns to look at
+// its focus.
+fn toggle_mode(
+    input: Res<ButtonInput<KeyCode>>,
+    mut mode: ResMut<CameraMode>,
+    mut anim: ResMut<CameraAnimation>,
+    observer: Query<&Observer>,
+) {
+    if !input.just_pressed(MODE_KEY) {
+        return;
+    }
+
+    *mode = match *mode {
+        CameraMode::FreeFly => CameraMode::Orbit,
+        CameraMode::Orbit => CameraMode::FreeFly,
+    };
+
+    let observer = observer.single();
+    if *mode == CameraMode::Orbit {
+        let start = observer.pose();
+        if let Ok(facing) = Dir3::new(*observer.focus() - start.position) {
+            let up = Dir3::new(*start.up - start.up.dot(*facing) * *facing).unwrap_or(start.up);
+            let end = ObserverPose { facing, up, ..start };
+            anim.start(start, end, DEFAULT_DURATION_S);
+        }
+    }
+    info!("Camera mode: {:?}", *mode);
+}
+
+// In orbit mode, dragging with the right mouse button held orbits the observer around its focus,
+// and the scroll wheel moves the observer toward or away from its focus. Either cancels any camera
+// animation in progress.
+fn orbit(
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut wheel: EventReader<MouseWheel>,
+    (mut anim, bounds): (ResMut<CameraAnimation>, Res<WorldBounds>),
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut last_cursor: Local<Option<Vec2>>,
+    mut observer: Query<&mut Observer>,
+) {
+    let cursor = window.single().cursor_position();
+    let drag = match (*last_cursor, cursor) {
+        (Some(last), Some(cur)) if buttons.pressed(MouseButton::Right) => cur - last,
+        _ => Vec2::ZERO,
+    };
+    *last_cursor = cursor;
+
+    let lines: f32 = wheel
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / PIXELS_PER_LINE,
+        })
+        .sum();
+
+    if drag == Vec2::ZERO && lines == 0. {
+        return;
+    }
+
+    anim.cancel();
+    let mut observer = observer.single_mut();
+    let pose = observer.pose();
+    let right = pose.facing.cross(*pose.up);
+    let rot = Quat::from_axis_angle(*pose.up, -drag.x * DRAG_RATE)
+        * Quat::from_axis_angle(right, -drag.y * DRAG_RATE);
+    let radius = zoom(observer.orbit_radius(), lines, &bounds);
+    observer.orbit(rot * pose.facing, rot * pose.up, radius);
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(
+        Update,
+        (toggle_mode, orbit.run_if(resource_equals(CameraMode::Orbit))).chain(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zoom_in() {
+        assert!(zoom(10., 1., &WorldBounds::default()) < 10.)
+    }
+
+    #[test]
+    fn test_zoom_clamped() {
+        let bounds = WorldBounds::default();
+        assert_eq!(zoom(10., -1000., &bounds), bounds.radius_au);
+        assert_eq!(zoom(10., 1000., &bounds), MIN_ORBIT_RADIUS_AU);
+    }
+}
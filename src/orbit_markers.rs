@@ -0,0 +1,170 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_mod_billboard::prelude::*;
+
+use crate::{
+    simulation::Body,
+    uom_wrapper::si::{angle::revolution, f64::Angle},
+    label_scale, DrawnPositions, LabelStyle, Observer, Simulation,
+};
+
+// The number of points the orbit ellipses are drawn with
+const ORBIT_SAMPLES: usize = 128;
+
+// The radius in AU of the apsis markers
+const MARKER_RADIUS_AU: f32 = 0.02;
+
+const PERIHELION_COLOR: Color = Color::srgb(1., 0.6, 0.2);
+const APHELION_COLOR: Color = Color::srgb(0.3, 0.6, 1.);
+
+// This controls the display of the orbits of eccentric bodies with their closest and farthest
+// points from their primaries marked.
+#[derive(Resource)]
+pub struct OrbitMarkers {
+    pub visible: bool,
+
+    // The orbits of bodies with at least this eccentricity are drawn.
+    pub min_eccentricity: f64,
+}
+
+impl Default for OrbitMarkers {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            min_eccentricity: 0.09,
+        }
+    }
+}
+
+impl OrbitMarkers {
+    fn shows(&self, sim: &Simulation, body: Body) -> bool {
+        self.visible
+            && body.primary().is_some()
+            && sim.eccentricity_of(body) >= self.min_eccentricity
+    }
+}
+
+// This labels a body's perihelion with its distance.
+#[derive(Component)]
+struct PerihelionLabel(Body);
+
+fn perihelion_text(sim: &Simulation, body: Body) -> String {
+    format!("q = {:.3} AU", sim.perihelion_of(body))
+}
+
+fn create_perihelion_labels(sim: Res<Simulation>, mut commands: Commands) {
+    for body in sim.bodies() {
+        if body.primary().is_none() {
+            continue;
+        }
+        commands.spawn((
+            BillboardTextBundle {
+                text: Text::from_section(
+                    perihelion_text(&sim, body),
+                    TextStyle {
+                        color: PERIHELION_COLOR,
+                        ..default()
+                    },
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            PerihelionLabel(body),
+        ));
+    }
+}
+
+// O toggles the orbit markers.
+fn toggle_orbit_markers(input: Res<ButtonInput<KeyCode>>, mut markers: ResMut<OrbitMarkers>) {
+    if input.just_pressed(KeyCode::KeyO) {
+        markers.visible = !markers.visible;
+    }
+}
+
+// Return the point on a body's orbit with the given true anomaly where it is drawn. The orbit is
+// centered on where the body's primary is drawn.
+fn drawn_orbit_point(sim: &Simulation, drawn: &DrawnPositions, body: Body, nu: Angle) -> Vec3 {
+    let shift = body.primary().map_or(Vec3::ZERO, |primary| drawn.shift_of(primary));
+    sim.orbit_point_of(body, nu) + shift
+}
+
+// The markers are recomputed from the orbital elements every frame, so they follow any change in
+// the orbits and stay centered on the primaries' avatars between steps.
+fn draw_orbit_markers(
+    sim: Res<Simulation>,
+    markers: Res<OrbitMarkers>,
+    drawn: DrawnPositions,
+    mut gizmos: Gizmos,
+) {
+    for body in sim.bodies() {
+        if !markers.shows(&sim, body) {
+            continue;
+        }
+
+        let color = sim.color_of(body).with_luminance(0.5);
+        gizmos.linestrip(
+            (0..=ORBIT_SAMPLES).map(|i| {
+                let nu = Angle::new::<revolution>(i as f64 / ORBIT_SAMPLES as f64);
+                drawn_orbit_point(&sim, &drawn, body, nu)
+            }),
+            color,
+        );
+
+        let peri = drawn_orbit_point(&sim, &drawn, body, Angle::new::<revolution>(0.));
+        let aph = drawn_orbit_point(&sim, &drawn, body, Angle::new::<revolution>(0.5));
+        gizmos.sphere(peri, Quat::IDENTITY, MARKER_RADIUS_AU, PERIHELION_COLOR);
+        gizmos.sphere(aph, Quat::IDENTITY, MARKER_RADIUS_AU, APHELION_COLOR);
+    }
+}
+
+// The distances on the labels shown are recomputed whenever the simulation changes, so they match
+// the orbits drawn. A label's text is only replaced when its distance reads differently.
+fn update_perihelion_labels(
+    (sim, markers, style): (Res<Simulation>, Res<OrbitMarkers>, Res<LabelStyle>),
+    (observer, window): (Query<&Observer>, Query<&Window, With<PrimaryWindow>>),
+    drawn: DrawnPositions,
+    mut labels: Query<(&PerihelionLabel, &mut Text, &mut Transform, &mut Visibility)>,
+) {
+    let obs_pos = *observer.single().position();
+    let window = window.single();
+    for (label, mut text, mut transform, mut vis) in &mut labels {
+        let body = label.0;
+        if markers.shows(&sim, body) {
+            if sim.is_changed() || *vis == Visibility::Hidden {
+                let value = perihelion_text(&sim, body);
+                if text.sections[0].value != value {
+                    text.sections[0].value = value;
+                }
+            }
+            let peri = drawn_orbit_point(&sim, &drawn, body, Angle::new::<revolution>(0.));
+            let scale = label_scale(peri.distance(obs_pos), window, &style);
+            *transform = Transform::from_translation(peri).with_scale(Vec3::splat(scale));
+            *vis = Visibility::Visible;
+        } else {
+            *vis = Visibility::Hidden;
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<OrbitMarkers>()
+        .add_systems(crate::SpawnBodies, create_perihelion_labels)
+        .add_systems(
+            Update,
+            (
+                toggle_orbit_markers,
+                (draw_orbit_markers, update_perihelion_labels).after(crate::update_avatars),
+            ),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_perihelion_text() {
+        let sim = Simulation::init_at(default_epoch());
+        assert_eq!(perihelion_text(&sim, Body::Mercury), "q = 0.307 AU");
+    }
+}
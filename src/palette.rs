@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+
+use crate::{simulation::Body, BodyModel, Simulation};
+
+// The key cycling through the palettes
+const CYCLE_KEY: KeyCode = KeyCode::KeyP;
+
+// The least lightness a body has in the high contrast palette, so none is lost against the black
+// sky
+const MIN_CONTRAST_LIGHTNESS: f32 = 0.6;
+
+// The factor the high contrast palette multiplies a body's saturation by
+const CONTRAST_SATURATION_GAIN: f32 = 1.5;
+
+// This is the set of colors the bodies are drawn with. The natural palette approximates the bodies'
+// true colors. The deuteranopia palette is built from the Okabe-Ito colors, which stay distinct
+// for people with red-green color blindness. The high contrast palette brightens and saturates the
+// natural colors so dark bodies like Mercury stand out.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Resource)]
+pub enum Palette {
+    #[default]
+    Natural,
+    Deuteranopia,
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Natural, Palette::Deuteranopia, Palette::HighContrast];
+
+    fn next(self) -> Self {
+        match self {
+            Self::Natural => Self::Deuteranopia,
+            Self::Deuteranopia => Self::HighContrast,
+            Self::HighContrast => Self::Natural,
+        }
+    }
+
+    // Return the color of a body in this palette given its natural color. The Sun keeps its natural
+    // color in every palette, since its color is scaled past white to make it bloom.
+    pub fn color_of(self, body: Body, natural: Color) -> Color {
+        match self {
+            _ if body == Body::Sun => natural,
+            Self::Natural => natural,
+            Self::Deuteranopia => deuteranopia_color(body).unwrap_or(natural),
+            Self::HighContrast => {
+                let hsla = Hsla::from(natural);
+                hsla.with_saturation((CONTRAST_SATURATION_GAIN * hsla.saturation).min(1.))
+                    .with_lightness(hsla.lightness.max(MIN_CONTRAST_LIGHTNESS))
+                    .into()
+            }
+        }
+    }
+}
+
+// Neighboring bodies are given different colors. Custom bodies have no assigned color.
+fn deuteranopia_color(body: Body) -> Option<Color> {
+    let (r, g, b) = match body {
+        Body::Sun => (0xf0, 0xe4, 0x42),
+        Body::Mercury => (0xbb, 0xbb, 0xbb),
+        Body::Venus => (0xf0, 0xe4, 0x42),
+        Body::Earth => (0x00, 0x72, 0xb2),
+        Body::Moon => (0xbb, 0xbb, 0xbb),
+        Body::Mars => (0xd5, 0x5e, 0x00),
+        Body::Jupiter => (0xe6, 0x9f, 0x00),
+        Body::Io => (0xf0, 0xe4, 0x42),
+        Body::Europa => (0x56, 0xb4, 0xe9),
+        Body::Ganymede => (0xff, 0xff, 0xff),
+        Body::Callisto => (0xcc, 0x79, 0xa7),
+        Body::Saturn => (0xcc, 0x79, 0xa7),
+        Body::Uranus => (0x56, 0xb4, 0xe9),
+        Body::Neptune => (0x00, 0x9e, 0x73),
+        Body::Triton => (0xf0, 0xe4, 0x42),
+        Body::Ceres => (0xbb, 0xbb, 0xbb),
+        Body::Pluto => (0xe6, 0x9f, 0x00),
+        Body::Haumea => (0xff, 0xff, 0xff),
+        Body::Makemake => (0xd5, 0x5e, 0x00),
+        Body::Eris => (0x56, 0xb4, 0xe9),
+        Body::Halley => (0xff, 0xff, 0xff),
+        Body::Custom(_) => return None,
+    };
+    Some(Color::srgb_u8(r, g, b))
+}
+
+fn cycle_palette(input: Res<ButtonInput<KeyCode>>, mut palette: ResMut<Palette>) {
+    if input.just_pressed(CYCLE_KEY) {
+        *palette = palette.next();
+        info!("Palette: {:?}", *palette);
+    }
+}
+
+// When the palette changes, this recolors the avatars, the light the Sun emits, and the labels.
+// Everything else drawn in a body's color picks up the new color the next time it is drawn.
+fn apply_palette(
+    palette: Res<Palette>,
+    mut sim: ResMut<Simulation>,
+    bodies: Query<(&Body, &BodyModel)>,
+    avatars: Query<(&Handle<StandardMaterial>, Option<&Children>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut lights: Query<&mut PointLight>,
+    mut labels: Query<&mut Text>,
+) {
+    if !palette.is_changed() || palette.is_added() {
+        return;
+    }
+
+    sim.set_palette(*palette);
+    for (body, model) in &bodies {
+        let color = *sim.color_of(*body);
+        let luminous = sim.luminosity_of(*body) > 0.;
+        if let Some((handle, children)) = model.avatar().and_then(|avatar| avatars.get(avatar).ok())
+        {
+            if let Some(material) = materials.get_mut(handle) {
+                if luminous {
+                    material.emissive = color.into();
+                } else {
+                    material.base_color = color;
+                }
+            }
+            for child in children.into_iter().flatten() {
+                if let Ok(mut light) = lights.get_mut(*child) {
+                    light.color = color;
+                }
+            }
+        }
+        if let Some(mut text) = model.label().and_then(|label| labels.get_mut(label).ok()) {
+            for section in &mut text.sections {
+                let alpha = section.style.color.alpha();
+                section.style.color = color.with_luminance(1.).with_alpha(alpha);
+            }
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<Palette>().add_systems(Update, (cycle_palette, apply_palette).chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_palette_cycles_through_all() {
+        let mut palette = Palette::default();
+        for exp in Palette::ALL {
+            assert_eq!(palette, exp);
+            palette = palette.next();
+        }
+        assert_eq!(palette, Palette::default());
+    }
+
+    #[test]
+    fn test_sun_stays_bright_in_every_palette() {
+        let mut sim = Simulation::init_at(default_epoch());
+        for palette in Palette::ALL {
+            sim.set_palette(palette);
+            let sun = LinearRgba::from(*sim.color_of(Body::Sun));
+            assert!(sun.red > 1. && sun.green > 1., "{:?}: {:?}", palette, sun);
+        }
+    }
+
+    #[test]
+    fn test_high_contrast_brightens_mercury() {
+        let mut sim = Simulation::init_at(default_epoch());
+        let natural = Hsla::from(*sim.color_of(Body::Mercury)).lightness;
+        sim.set_palette(Palette::HighContrast);
+        let act = Hsla::from(*sim.color_of(Body::Mercury)).lightness;
+        assert!(natural < 0.2 && act >= MIN_CONTRAST_LIGHTNESS, "{} -> {}", natural, act);
+    }
+
+    #[test]
+    fn test_deuteranopia_separates_earth_and_mars() {
+        let earth = deuteranopia_color(Body::Earth);
+        let mars = deuteranopia_color(Body::Mars);
+        assert_ne!(earth, mars);
+    }
+}
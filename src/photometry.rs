@@ -0,0 +1,65 @@
+use std::f64::consts;
+
+use crate::uom_wrapper::si::{
+    angle::radian,
+    f64::{Angle, Length},
+    length::{astronomical_unit, kilometer},
+};
+
+// The diameter in km of a body with a geometric albedo of 1 whose absolute magnitude is 0. See
+// https://en.wikipedia.org/wiki/Absolute_magnitude#Solar_System_bodies_(H)
+const H_DIAMETER_KM: f64 = 1_329.;
+
+// The illuminance in lux of a source of magnitude 0
+const ZERO_MAGNITUDE_LUX: f64 = 2.54e-6;
+
+// Return the angle a sphere of the given radius subtends at the given distance from its center
+pub fn angular_diameter(radius: Length, distance: Length) -> Angle {
+    2. * Angle::new::<radian>(f64::from(radius / distance).min(1.).asin())
+}
+
+// Return the fraction of its full brightness a sphere that scatters light evenly in all directions,
+// a Lambertian sphere, shows at the given phase angle
+fn lambert_phase(phase_angle: Angle) -> f64 {
+    let a = phase_angle.get::<radian>();
+    ((consts::PI - a) * a.cos() + a.sin()) / consts::PI
+}
+
+// Return the apparent magnitude of a body lit by the Sun. Its absolute magnitude comes from its
+// size and geometric albedo, and it is dimmed by its distances from the Sun and the observer and
+// by how much of its disk is dark at the phase angle. It is rough, since real surfaces don't
+// scatter light like a Lambertian sphere, e.g., the full Moon brightens sharply near opposition.
+// See https://en.wikipedia.org/wiki/Apparent_magnitude#Calculations
+pub fn reflected_magnitude(
+    radius: Length,
+    albedo: f64,
+    sun_distance: Length,
+    observer_distance: Length,
+    phase_angle: Angle,
+) -> f64 {
+    let diameter = 2. * radius.get::<kilometer>();
+    let absolute = 5. * (H_DIAMETER_KM / (diameter * albedo.sqrt())).log10();
+    let au = |len: Length| len.get::<astronomical_unit>();
+    absolute + 5. * (au(sun_distance) * au(observer_distance)).log10()
+        - 2.5 * lambert_phase(phase_angle).log10()
+}
+
+// Return the apparent magnitude of a source giving off the given luminous flux in lumens evenly in
+// all directions seen from the given distance
+pub fn emitted_magnitude(flux_lm: f64, distance: Length) -> f64 {
+    let lux = flux_lm / (4. * consts::PI * distance.value.powi(2));
+    -2.5 * (lux / ZERO_MAGNITUDE_LUX).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lambert_phase() {
+        let phase = |angle| lambert_phase(Angle::new::<radian>(angle));
+        assert!((phase(0.) - 1.).abs() < 1e-12);
+        assert!((phase(consts::FRAC_PI_2) - 1. / consts::PI).abs() < 1e-12);
+        assert!(phase(consts::PI).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,130 @@
+use bevy::{math::DVec3, prelude::*};
+
+use crate::{Simulation, Trail};
+
+// The key toggling between the radial modes
+const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+
+// The distance in AU from the Sun where the logarithmic mode turns from about linear to
+// logarithmic, and the gain in AU setting how far out the compressed distances reach. Neptune is
+// drawn about 28.5 AU from the Sun and Mercury about 8 AU.
+const KNEE_AU: f64 = 0.1;
+const GAIN_AU: f64 = 5.;
+
+// This is how the distances of the bodies from the Sun are drawn. The planets span from 0.39 AU to
+// 30 AU, so at their true distances the inner planets are crowded around the Sun. The logarithmic
+// mode compresses the distances so all of the planets are comfortably spaced, while keeping each
+// body's direction from the Sun. The simulation itself is unaffected.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub enum RadialMode {
+    #[default]
+    Linear,
+    Logarithmic,
+}
+
+impl RadialMode {
+    fn toggle(self) -> Self {
+        match self {
+            Self::Linear => Self::Logarithmic,
+            Self::Logarithmic => Self::Linear,
+        }
+    }
+
+    // Return where a point the given offset in AU from the Sun is drawn relative to the Sun
+    pub fn map(self, offset: DVec3) -> DVec3 {
+        match self {
+            Self::Linear => offset,
+            Self::Logarithmic => {
+                let dist = GAIN_AU * (1. + offset.length() / KNEE_AU).ln();
+                dist * offset.normalize_or_zero()
+            }
+        }
+    }
+
+    // Return the factor the neighborhood of a point the given offset in AU from the Sun is scaled
+    // by across its direction from the Sun. A moon's offset from its planet is scaled by this, so
+    // its orbit keeps its shape.
+    pub fn stretch_at(self, offset: DVec3) -> f64 {
+        let dist = offset.length();
+        if dist > 0. {
+            self.map(offset).length() / dist
+        } else {
+            1.
+        }
+    }
+}
+
+fn toggle_radial_mode(input: Res<ButtonInput<KeyCode>>, mut mode: ResMut<RadialMode>) {
+    if input.just_pressed(TOGGLE_KEY) {
+        *mode = mode.toggle();
+        info!("Radial mode: {:?}", *mode);
+    }
+}
+
+// When the mode changes, the bodies are drawn at their new distances the next time they are
+// updated, and the trails are cleared, since their points were recorded in the old mode.
+fn apply_radial_mode(
+    mode: Res<RadialMode>,
+    mut sim: ResMut<Simulation>,
+    mut trails: Query<&mut Trail>,
+) {
+    if !mode.is_changed() || mode.is_added() {
+        return;
+    }
+
+    sim.set_radial_mode(*mode);
+    for mut trail in &mut trails {
+        trail.clear();
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<RadialMode>()
+        .add_systems(Update, (toggle_radial_mode, apply_radial_mode).chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{default_epoch, simulation::Body};
+
+    #[test]
+    fn test_radial_mode_logarithmic_keeps_direction() {
+        let offset = DVec3::new(3., 4., 0.);
+        let mapped = RadialMode::Logarithmic.map(offset);
+        assert!(mapped.normalize().abs_diff_eq(offset.normalize(), 1e-12));
+        assert_eq!(RadialMode::Linear.map(offset), offset);
+        assert_eq!(RadialMode::Logarithmic.map(DVec3::ZERO), DVec3::ZERO);
+    }
+
+    #[test]
+    fn test_radial_mode_logarithmic_preserves_ordering() {
+        let mut sim = Simulation::init_at(default_epoch());
+        let planets = [
+            Body::Mercury,
+            Body::Venus,
+            Body::Earth,
+            Body::Mars,
+            Body::Jupiter,
+            Body::Saturn,
+            Body::Uranus,
+            Body::Neptune,
+        ];
+        let order = |sim: &Simulation| {
+            let sun = sim.position_of(Body::Sun);
+            let mut bodies = planets.to_vec();
+            bodies.sort_by(|a, b| {
+                sun.distance(sim.position_of(*a)).total_cmp(&sun.distance(sim.position_of(*b)))
+            });
+            bodies
+        };
+        let linear = order(&sim);
+        sim.set_radial_mode(RadialMode::Logarithmic);
+        assert_eq!(order(&sim), linear);
+
+        let sun = sim.position_of(Body::Sun);
+        let mercury = sun.distance(sim.position_of(Body::Mercury));
+        let neptune = sun.distance(sim.position_of(Body::Neptune));
+        assert!(neptune / mercury < 10., "{} {}", mercury, neptune);
+    }
+}
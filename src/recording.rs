@@ -0,0 +1,208 @@
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    render::view::screenshot::ScreenshotManager,
+    time::TimeUpdateStrategy,
+    window::PrimaryWindow,
+};
+use bevy_framepace::{FramepaceSettings, Limiter};
+
+use crate::{
+    clock::SimClock,
+    uom_wrapper::si::{f64::Time, time::day},
+    SimSet, Simulation,
+};
+
+// The number of frames drawn before the first one is saved, giving the assets time to load
+const WARMUP_FRAMES: usize = 4;
+
+// This is a recording of the simulation as a sequence of numbered PNG images, e.g., for making a
+// movie. The frames are evenly spaced in simulated time from the start to the end Julian Date
+// inclusive, and they are saved to dir. While recording, each frame advances Bevy's clock by
+// exactly one fixed timestep however long it takes to draw, and the frame rate isn't limited, so
+// camera flights and the tour move the same amount from one frame to the next, and the same
+// recording always produces the same images. The clock's controls have no effect on the recorded
+// times. Once the last frame is saved, the recording is removed, and the app goes back to running
+// in real time with the clock paused at the end.
+#[derive(Clone, Debug, PartialEq, Resource)]
+pub struct FrameRecording {
+    pub dir: PathBuf,
+    pub start: Time,
+    pub end: Time,
+    pub frames: usize,
+}
+
+impl FrameRecording {
+    // Record the given number of frames from the start to the end Julian Date to the frames
+    // directory
+    pub fn new(start: Time, end: Time, frames: usize) -> Self {
+        Self {
+            dir: PathBuf::from("frames"),
+            start,
+            end,
+            frames,
+        }
+    }
+
+    // Return the simulated Julian Date a frame shows
+    fn frame_time(&self, frame: usize) -> Time {
+        if self.frames < 2 {
+            return self.start;
+        }
+        self.start + (self.end - self.start) * (frame as f64 / (self.frames - 1) as f64)
+    }
+}
+
+fn frame_name(frame: usize) -> String {
+    format!("frame_{:05}.png", frame)
+}
+
+// This is how far along the recording is. The frame is the next one to save, and posed tells
+// whether the simulation has been moved to its time.
+#[derive(Debug, Default, Resource)]
+struct Progress {
+    warmup: usize,
+    frame: usize,
+    posed: bool,
+    done: bool,
+}
+
+fn start_recording(
+    recording: Res<FrameRecording>,
+    fixed: Res<bevy::time::Time<Fixed>>,
+    mut commands: Commands,
+    mut framepace: ResMut<FramepaceSettings>,
+    mut clock: ResMut<SimClock>,
+    mut progress: ResMut<Progress>,
+) {
+    #[cfg(not(target_family = "wasm"))]
+    if let Err(err) = std::fs::create_dir_all(&recording.dir) {
+        error!("Failed to create recording directory {}: {}", recording.dir.display(), err);
+        progress.done = true;
+        return;
+    }
+
+    commands.insert_resource(TimeUpdateStrategy::ManualDuration(fixed.timestep()));
+    framepace.limiter = Limiter::Off;
+    clock.paused = true;
+    progress.done = recording.frames == 0;
+    info!(
+        "Recording {} frames from JD {:.5} to JD {:.5} to {}",
+        recording.frames,
+        recording.start.get::<day>(),
+        recording.end.get::<day>(),
+        recording.dir.display()
+    );
+}
+
+// The simulation is moved to the time of the frame being recorded after the clock has advanced it,
+// so the frame's time only depends on its index.
+fn pose_frame(
+    recording: Res<FrameRecording>,
+    mut progress: ResMut<Progress>,
+    mut sim: ResMut<Simulation>,
+) {
+    if progress.done {
+        return;
+    }
+    let span = recording.frame_time(progress.frame) - sim.current_time();
+    sim.advance_by(span);
+    progress.posed = true;
+}
+
+// A frame is saved once the simulation has been posed for it. Progress is logged every tenth of
+// the way.
+fn capture_frame(
+    recording: Res<FrameRecording>,
+    mut progress: ResMut<Progress>,
+    mut commands: Commands,
+    mut framepace: ResMut<FramepaceSettings>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+) {
+    if progress.done || !progress.posed {
+        return;
+    }
+    if progress.warmup < WARMUP_FRAMES {
+        progress.warmup += 1;
+        return;
+    }
+
+    let path = recording.dir.join(frame_name(progress.frame));
+    if let Err(err) = screenshots.save_screenshot_to_disk(window.single(), &path) {
+        error!("Failed to save frame to {}: {}", path.display(), err);
+        return;
+    }
+    progress.frame += 1;
+    progress.posed = false;
+    if progress.frame * 10 / recording.frames != (progress.frame - 1) * 10 / recording.frames {
+        info!("Recorded {} of {} frames", progress.frame, recording.frames);
+    }
+
+    if progress.frame == recording.frames {
+        progress.done = true;
+        commands.insert_resource(TimeUpdateStrategy::Automatic);
+        commands.remove_resource::<FrameRecording>();
+        *framepace = FramepaceSettings::default();
+        info!("Recording saved to {}", recording.dir.display());
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<Progress>()
+        .add_systems(Startup, start_recording.run_if(resource_exists::<FrameRecording>))
+        .add_systems(
+            FixedUpdate,
+            pose_frame
+                .in_set(SimSet::Advance)
+                .after(crate::advance_sim_time)
+                .run_if(resource_exists::<FrameRecording>),
+        )
+        .add_systems(Update, capture_frame.run_if(resource_exists::<FrameRecording>))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{default_epoch, setup_simulation, uom_wrapper::si::time::second};
+
+    #[test]
+    fn test_frame_name() {
+        assert_eq!(frame_name(42), "frame_00042.png");
+    }
+
+    #[test]
+    fn test_frame_time_spans_start_to_end() {
+        let start = default_epoch();
+        let recording = FrameRecording::new(start, start + Time::new::<day>(10.), 11);
+        assert_eq!(recording.frame_time(0), start);
+        assert_eq!(recording.frame_time(10), recording.end);
+        assert!(((recording.frame_time(3) - start).get::<day>() - 3.).abs() < 1e-9);
+        let single = FrameRecording::new(start, recording.end, 1);
+        assert_eq!(single.frame_time(0), start);
+    }
+
+    #[test]
+    fn test_pose_frame_ignores_clock() {
+        let start = default_epoch() + Time::new::<day>(2.);
+        let recording = FrameRecording::new(start, start + Time::new::<day>(5.), 6);
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        setup_simulation(&mut app, default_epoch())
+            .insert_resource(recording.clone())
+            .init_resource::<Progress>()
+            .add_systems(
+                FixedUpdate,
+                pose_frame.in_set(SimSet::Advance).after(crate::advance_sim_time),
+            );
+        app.world_mut().resource_mut::<SimClock>().time_scale = 1000.;
+        for frame in [0, 3] {
+            app.world_mut().resource_mut::<Progress>().frame = frame;
+            app.world_mut().run_schedule(FixedUpdate);
+            let act = app.world().resource::<Simulation>().current_time();
+            let err = (act - recording.frame_time(frame)).abs().get::<second>();
+            assert!(err < 1e-3, "{} {}", frame, err);
+        }
+    }
+}
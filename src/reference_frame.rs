@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+
+use crate::{selection::Selected, simulation::Body, CameraSteps, Observer, Simulation, Trail};
+
+// The key centering the frame on the selected body, or back on the Sun when nothing is selected
+const CENTER_KEY: KeyCode = KeyCode::Digit0;
+
+// This is the body everything is drawn around. It sits at the origin, and every other body is drawn
+// where it is relative to it, e.g., in Earth's frame the Sun circles Earth over a year, and in
+// Jupiter's frame its moons circle in place. The world is centered on the Sun by default. The
+// simulation itself is unaffected.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Resource)]
+pub struct ReferenceFrame(pub Body);
+
+impl Default for ReferenceFrame {
+    fn default() -> Self {
+        Self(Body::Sun)
+    }
+}
+
+fn center_frame(
+    input: Res<ButtonInput<KeyCode>>,
+    selected: Res<Selected>,
+    sim: Res<Simulation>,
+    mut frame: ResMut<ReferenceFrame>,
+) {
+    if input.just_pressed(CENTER_KEY) {
+        frame.set_if_neq(ReferenceFrame(selected.0.unwrap_or(Body::Sun)));
+        info!("Reference frame: {}", sim.name_of(frame.0));
+    }
+}
+
+// When the frame changes, everything is shifted so the new frame's body is at the origin. The
+// camera is shifted along with it, so the view doesn't jump, and the trails are cleared, since
+// their points were recorded in the old frame.
+fn apply_reference_frame(
+    frame: Res<ReferenceFrame>,
+    mut sim: ResMut<Simulation>,
+    mut cam_steps: ResMut<CameraSteps>,
+    mut observers: Query<&mut Observer>,
+    mut trails: Query<&mut Trail>,
+) {
+    if !frame.is_changed() || frame.is_added() {
+        return;
+    }
+
+    let before = sim.position_of(Body::Sun);
+    sim.set_reference_frame(frame.0);
+    let shift = sim.position_of(Body::Sun) - before;
+    for mut observer in &mut observers {
+        observer.translate(shift);
+    }
+    cam_steps.previous.translation += shift;
+    cam_steps.current.translation += shift;
+    for mut trail in &mut trails {
+        trail.clear();
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<ReferenceFrame>()
+        .add_systems(Update, (center_frame, apply_reference_frame).chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        default_epoch,
+        uom_wrapper::si::{f64::Time, length::astronomical_unit, time::day},
+    };
+
+    #[test]
+    fn test_earth_frame_centers_earth_and_moon_orbits_it() {
+        let mut sim = Simulation::init_at(default_epoch());
+        sim.set_reference_frame(Body::Earth);
+        let moon_dist = sim.distance_between(Body::Moon, Body::Earth).get::<astronomical_unit>();
+        assert_eq!(sim.position_of(Body::Earth), Vec3::ZERO);
+        let start = sim.position_of(Body::Moon);
+        assert!((start.length() as f64 - moon_dist).abs() < 1e-6, "{} {}", start, moon_dist);
+
+        // A week later, Earth is still at the origin, and the Moon has swept about a quarter of
+        // the way around it while staying as far from it.
+        sim.advance_by(Time::new::<day>(7.));
+        assert_eq!(sim.position_of(Body::Earth), Vec3::ZERO);
+        let end = sim.position_of(Body::Moon);
+        let moon_dist = sim.distance_between(Body::Moon, Body::Earth).get::<astronomical_unit>();
+        assert!((end.length() as f64 - moon_dist).abs() < 1e-6, "{} {}", end, moon_dist);
+        let swept = start.angle_between(end).to_degrees();
+        assert!((75. ..105.).contains(&swept), "{}", swept);
+    }
+
+    #[test]
+    fn test_sun_frame_leaves_positions() {
+        let mut sim = Simulation::init_at(default_epoch());
+        let mars = sim.position_of(Body::Mars);
+        sim.set_reference_frame(Body::Jupiter);
+        assert_ne!(sim.position_of(Body::Mars), mars);
+        sim.set_reference_frame(Body::Sun);
+        assert_eq!(sim.position_of(Body::Mars), mars);
+    }
+}
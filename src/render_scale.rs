@@ -0,0 +1,154 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        texture::BevyDefault,
+        view::RenderLayers,
+    },
+    window::{PrimaryWindow, WindowRef, WindowResized, WindowScaleFactorChanged},
+};
+
+use crate::render_settings::RenderTuning;
+
+// The render layer only the camera showing the scaled scene draws
+const LAYER: usize = 2;
+
+// The scaled scene is shown after the main camera renders it and before the minimap is drawn over
+// it.
+const ORDER: isize = 1;
+
+// When the scene is rendered at a resolution scale other than 1, the main camera renders it to
+// this image instead of the window, and a 2D camera stretches it over the window. The HDR, bloom,
+// and tonemapping are applied as the main camera renders to the image, so they aren't affected.
+#[derive(Resource)]
+struct ScaledTarget(Handle<Image>);
+
+// This marks the camera and the sprite showing the scaled scene.
+#[derive(Component)]
+struct ScaledView;
+
+// Return the size in pixels of the image a window's scene is rendered to at a resolution scale.
+// It is never empty.
+fn scaled_size(physical_size: UVec2, scale: f32) -> UVec2 {
+    (physical_size.as_vec2() * scale).round().as_uvec2().max(UVec2::ONE)
+}
+
+fn mk_target_image(size: UVec2) -> Image {
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::bevy_default(),
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+// The view starts hidden until a scale other than 1 is applied.
+fn create_scaled_view(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(mk_target_image(UVec2::ONE));
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: ORDER,
+                is_active: false,
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::layer(LAYER),
+        ScaledView,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: image.clone(),
+            ..default()
+        },
+        RenderLayers::layer(LAYER),
+        ScaledView,
+    ));
+    commands.insert_resource(ScaledTarget(image));
+}
+
+// When the resolution scale changes or the window is resized, this points the main camera at the
+// window or at an image of the scaled size. The UI moves to whichever camera draws to the window,
+// so it stays sharp.
+fn apply_resolution_scale(
+    (tuning, target): (Res<RenderTuning>, Res<ScaledTarget>),
+    mut resized: EventReader<WindowResized>,
+    mut rescaled: EventReader<WindowScaleFactorChanged>,
+    (mut commands, mut images): (Commands, ResMut<Assets<Image>>),
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut cams: Query<(Entity, &mut Camera, Has<ScaledView>, Has<Camera3d>)>,
+    mut sprites: Query<&mut Sprite, With<ScaledView>>,
+) {
+    let window_changed = resized.read().count() + rescaled.read().count() > 0;
+    if !(tuning.is_changed() || window_changed) {
+        return;
+    }
+
+    let window = window.single();
+    let scaled = tuning.resolution_scale != 1.;
+    if scaled {
+        let size = scaled_size(window.physical_size(), tuning.resolution_scale);
+        if let Some(image) = images.get_mut(&target.0).filter(|image| image.size() != size) {
+            *image = mk_target_image(size);
+        }
+        for mut sprite in &mut sprites {
+            sprite.custom_size = Some(window.size());
+        }
+    }
+
+    for (entity, mut cam, is_view, is_main) in &mut cams {
+        let draws_to_window = if is_view {
+            cam.is_active = scaled;
+            scaled
+        } else if is_main {
+            cam.target = if scaled {
+                RenderTarget::Image(target.0.clone())
+            } else {
+                RenderTarget::Window(WindowRef::Primary)
+            };
+            !scaled
+        } else {
+            continue;
+        };
+        if draws_to_window {
+            commands.entity(entity).insert(IsDefaultUiCamera);
+        } else {
+            commands.entity(entity).remove::<IsDefaultUiCamera>();
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Startup, create_scaled_view).add_systems(Update, apply_resolution_scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaled_size() {
+        assert_eq!(scaled_size(UVec2::new(1920, 1080), 0.75), UVec2::new(1440, 810));
+        assert_eq!(scaled_size(UVec2::new(1, 1), 0.5), UVec2::ONE);
+    }
+}
@@ -0,0 +1,596 @@
+use bevy::{
+    core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping},
+    pbr::PointLightShadowMap,
+    prelude::*,
+};
+
+use crate::{
+    asteroid_belt::AsteroidBelt, simulation::Body, BodyModel, Simulation, VisibilityTuning,
+};
+
+// The key cycling through the quality presets. With shift held, it cycles through the resolution
+// scales instead, and with control held, through the MSAA sample counts.
+const PRESET_KEY: KeyCode = KeyCode::F2;
+
+// The key toggling shadows independently of the preset, or ring shadows with shift held
+const SHADOW_TOGGLE_KEY: KeyCode = KeyCode::F8;
+
+// The keys dimming and brightening the light the luminous bodies emit
+const DIMMER_KEY: KeyCode = KeyCode::Minus;
+const BRIGHTER_KEY: KeyCode = KeyCode::Equal;
+
+// The key weakening the bloom, or strengthening it with shift held
+const BLOOM_KEY: KeyCode = KeyCode::KeyB;
+
+// The key cycling through the tonemapping methods
+const TONEMAPPING_KEY: KeyCode = KeyCode::KeyM;
+
+// The range of the luminosity scale. The Sun's light washes out everything near it above the
+// range and barely lights the outer planets below it.
+const MIN_LUMINOSITY_SCALE: f32 = 1e-28;
+const MAX_LUMINOSITY_SCALE: f32 = 1e-24;
+
+// The amount each press of the bloom key changes the bloom intensity by
+const BLOOM_STEP: f32 = 0.05;
+
+// These are the tonemapping methods cycled through, starting with the default.
+const TONEMAPPINGS: [Tonemapping; 5] = [
+    Tonemapping::TonyMcMapface,
+    Tonemapping::AgX,
+    Tonemapping::AcesFitted,
+    Tonemapping::ReinhardLuminance,
+    Tonemapping::SomewhatBoringDisplayTransform,
+];
+
+// These are the resolution scales and MSAA sample counts cycled through.
+const RESOLUTION_SCALES: [f32; 3] = [1., 0.75, 0.5];
+const MSAA_LEVELS: [Msaa; 3] = [Msaa::Off, Msaa::Sample4, Msaa::Sample8];
+
+// The depth bias is in world units, i.e., AU. Bevy's default of 0.08 suits scenes measured in
+// meters. Here it would push each surface 12 million km toward the Sun before testing whether it is
+// in shadow, so nothing closer than that could shade it. This is a small fraction of the distance
+// between neighboring avatars while still hiding the depth errors.
+const SHADOW_DEPTH_BIAS_AU: f32 = 1e-4;
+
+// The normal bias is in shadow map texels, so it doesn't depend on the scale of the scene. It keeps
+// the lit side of the large avatars from shadowing itself in stripes, known as shadow acne.
+const SHADOW_NORMAL_BIAS_TEXELS: f32 = PointLight::DEFAULT_SHADOW_NORMAL_BIAS;
+
+// The widest an edge of an avatar's sphere may appear, in multiples of the least angle the eye can
+// resolve, before the sphere is subdivided further
+const MAX_APPARENT_EDGE: f32 = 6.;
+
+// The factor an avatar's apparent size must shrink past a level's threshold before the avatar is
+// given a coarser sphere. This keeps an avatar hovering at a threshold from flipping between
+// levels.
+const LOD_HYSTERESIS: f32 = 1.25;
+
+// The angle an edge of an icosahedron subtends at its center. Each subdivision halves it.
+const ICOSAHEDRON_EDGE_RAD: f32 = 1.107;
+
+// These are the levels of rendering quality. Each trades visual fidelity for performance.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum QualityPreset {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl QualityPreset {
+    fn next(self) -> Self {
+        match self {
+            Self::Low => Self::Medium,
+            Self::Medium => Self::High,
+            Self::High => Self::Low,
+        }
+    }
+
+    // The anti-aliasing the preset calls for. See RenderTuning.
+    pub fn msaa(self) -> Msaa {
+        match self {
+            Self::Low => Msaa::Off,
+            Self::Medium => Msaa::Sample4,
+            Self::High => Msaa::Sample8,
+        }
+    }
+}
+
+// These are the settings controlling how expensive the rendering is. Selecting a preset sets all of
+// them, but each may be overridden afterwards.
+#[derive(Resource)]
+pub struct RenderSettings {
+    pub preset: QualityPreset,
+    pub shadows: bool,
+    // The length in texels of the edges of each face of the shadow cube map around the Sun
+    pub shadow_map_size: usize,
+    pub shadow_depth_bias: f32,
+    pub shadow_normal_bias: f32,
+    pub bloom: bool,
+    // Whether ringed bodies and their rings shade each other
+    pub ring_shadows: bool,
+    // The subdivisions of the sphere of an avatar filling the view. Smaller looking avatars get
+    // spheres with fewer subdivisions.
+    pub sphere_subdivisions: usize,
+    // The least angle in radians an enlarged avatar spans. See VisibilityTuning. Without MSAA, an
+    // avatar only a few pixels wide flickers as it moves, so the low preset keeps them larger.
+    pub avatar_ang_res_rad: f32,
+    // The number of asteroids in the belt. See AsteroidBelt.
+    pub asteroid_count: usize,
+}
+
+impl RenderSettings {
+    pub fn apply_preset(&mut self, preset: QualityPreset) {
+        *self = Self::from(preset);
+    }
+
+    // Configure a light to cast shadows, or not, as the settings call for
+    pub fn configure_shadows(&self, light: &mut PointLight) {
+        light.shadows_enabled = self.shadows;
+        light.shadow_depth_bias = self.shadow_depth_bias;
+        light.shadow_normal_bias = self.shadow_normal_bias;
+    }
+
+    // Create a unit sphere mesh for each level of detail up to the configured one, adding them to
+    // the meshes. The index of a sphere's handle is the number of times it is subdivided.
+    pub fn mk_spheres(&self, meshes: &mut Assets<Mesh>) -> Vec<Handle<Mesh>> {
+        (0..=self.sphere_subdivisions).map(|n| meshes.add(mk_sphere(n))).collect()
+    }
+}
+
+fn mk_sphere(subdivisions: usize) -> Mesh {
+    Sphere::new(1.)
+        .mesh()
+        .ico(subdivisions)
+        .unwrap_or_else(|_| Sphere::new(1.).mesh().build())
+}
+
+// Return the subdivisions an avatar's sphere needs so none of its edges appear too wide, given the
+// avatar's apparent radius in multiples of the least angle the eye can resolve
+pub fn lod_for(apparent_radius: f32, max_subdivisions: usize) -> usize {
+    let edge = ICOSAHEDRON_EDGE_RAD * apparent_radius;
+    let needed = (edge / MAX_APPARENT_EDGE).log2().ceil();
+    if needed > 0. {
+        (needed as usize).min(max_subdivisions)
+    } else {
+        0
+    }
+}
+
+// Return the level of detail an avatar at the given level should have now. The level rises as soon
+// as the avatar looks large enough, but it only falls once the avatar looks a good deal smaller.
+pub fn next_lod(current: usize, apparent_radius: f32, max_subdivisions: usize) -> usize {
+    let needed = lod_for(apparent_radius, max_subdivisions);
+    if needed >= current {
+        needed
+    } else {
+        lod_for(LOD_HYSTERESIS * apparent_radius, max_subdivisions).min(current)
+    }
+}
+
+impl From<QualityPreset> for RenderSettings {
+    fn from(preset: QualityPreset) -> Self {
+        let ang_res_rad = VisibilityTuning::default().eye_ang_res_rad;
+        let asteroid_count = AsteroidBelt::default().count;
+        match preset {
+            QualityPreset::Low => Self {
+                preset,
+                shadows: false,
+                shadow_map_size: 1024,
+                shadow_depth_bias: SHADOW_DEPTH_BIAS_AU,
+                shadow_normal_bias: SHADOW_NORMAL_BIAS_TEXELS,
+                bloom: false,
+                ring_shadows: false,
+                sphere_subdivisions: 2,
+                avatar_ang_res_rad: 2. * ang_res_rad,
+                asteroid_count: asteroid_count / 3,
+            },
+            QualityPreset::Medium => Self {
+                preset,
+                shadows: true,
+                shadow_map_size: 2048,
+                shadow_depth_bias: SHADOW_DEPTH_BIAS_AU,
+                shadow_normal_bias: SHADOW_NORMAL_BIAS_TEXELS,
+                bloom: true,
+                ring_shadows: true,
+                sphere_subdivisions: 5,
+                avatar_ang_res_rad: ang_res_rad,
+                asteroid_count,
+            },
+            QualityPreset::High => Self {
+                preset,
+                shadows: true,
+                shadow_map_size: 4096,
+                shadow_depth_bias: SHADOW_DEPTH_BIAS_AU,
+                shadow_normal_bias: SHADOW_NORMAL_BIAS_TEXELS,
+                bloom: true,
+                ring_shadows: true,
+                sphere_subdivisions: 6,
+                avatar_ang_res_rad: ang_res_rad / 2.,
+                asteroid_count: 2 * asteroid_count,
+            },
+        }
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self::from(QualityPreset::default())
+    }
+}
+
+// These settings control how the light the bodies emit is turned into colors on the display and
+// how finely the scene is sampled onto it. Displays and the machines driving them differ, so what
+// keeps the Sun from washing out its neighbors on one may be too dim on another, and the
+// anti-aliasing that suits a fast machine may be too slow for another. The luminosity scale
+// converts a body's luminosity in lumens into the intensity of its point light. The Sun's true
+// luminosity would saturate the camera. The quality presets set the MSAA and leave the rest alone.
+#[derive(Resource)]
+pub struct RenderTuning {
+    pub bloom_intensity: f32,
+    pub tonemapping: Tonemapping,
+    pub luminosity_scale: f32,
+    pub msaa: Msaa,
+    // The fraction of the window's resolution the scene is rendered at. The scene is stretched to
+    // fill the window, so a lower scale renders faster but looks blurrier. The UI is always drawn
+    // at the window's resolution.
+    pub resolution_scale: f32,
+}
+
+impl Default for RenderTuning {
+    fn default() -> Self {
+        Self {
+            bloom_intensity: BloomSettings::NATURAL.intensity,
+            tonemapping: TONEMAPPINGS[0],
+            luminosity_scale: 1e-26,
+            msaa: Msaa::Sample4,
+            resolution_scale: 1.,
+        }
+    }
+}
+
+impl RenderTuning {
+    pub fn apply_preset(&mut self, preset: QualityPreset) {
+        self.msaa = preset.msaa();
+    }
+
+    // Return the intensity of the point light a body emits, zero for a body emitting no light
+    pub fn light_intensity(&self, sim: &Simulation, body: Body) -> f32 {
+        sim.luminosity_of(body) * self.luminosity_scale
+    }
+
+    pub fn bloom(&self) -> BloomSettings {
+        BloomSettings {
+            intensity: self.bloom_intensity,
+            ..BloomSettings::NATURAL
+        }
+    }
+
+    fn scale_luminosity(&mut self, factor: f32) {
+        self.luminosity_scale =
+            (self.luminosity_scale * factor).clamp(MIN_LUMINOSITY_SCALE, MAX_LUMINOSITY_SCALE);
+    }
+
+    fn step_bloom(&mut self, steps: f32) {
+        self.bloom_intensity = (self.bloom_intensity + steps * BLOOM_STEP).clamp(0., 1.);
+    }
+
+    fn next_tonemapping(&mut self) {
+        let idx = TONEMAPPINGS.iter().position(|t| *t == self.tonemapping).unwrap_or(0);
+        self.tonemapping = TONEMAPPINGS[(idx + 1) % TONEMAPPINGS.len()];
+    }
+
+    fn next_resolution_scale(&mut self) {
+        let idx = RESOLUTION_SCALES.iter().position(|s| *s == self.resolution_scale);
+        self.resolution_scale =
+            RESOLUTION_SCALES[idx.map_or(0, |idx| idx + 1) % RESOLUTION_SCALES.len()];
+    }
+
+    fn next_msaa(&mut self) {
+        let idx = MSAA_LEVELS.iter().position(|m| *m == self.msaa);
+        self.msaa = MSAA_LEVELS[idx.map_or(0, |idx| idx + 1) % MSAA_LEVELS.len()];
+    }
+}
+
+// These are the unit sphere meshes the avatars share, one for each level of detail, indexed by the
+// number of times the sphere is subdivided. Each avatar is scaled to its radius.
+#[derive(Resource)]
+pub struct AvatarMeshes(pub Vec<Handle<Mesh>>);
+
+// This is the level of detail of an avatar's sphere.
+#[derive(Component)]
+pub struct AvatarLod(pub usize);
+
+// F2 cycles through the quality presets. With shift or control held, it adjusts the tuning
+// instead, overriding what the preset set.
+fn cycle_preset(
+    input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<RenderSettings>,
+    mut tuning: ResMut<RenderTuning>,
+) {
+    let modified = input.any_pressed([
+        KeyCode::ShiftLeft,
+        KeyCode::ShiftRight,
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+    ]);
+    if input.just_pressed(PRESET_KEY) && !modified {
+        let next = settings.preset.next();
+        settings.apply_preset(next);
+        tuning.apply_preset(next);
+        info!("Quality preset: {:?}", next);
+    }
+}
+
+// F8 turns shadows off to save rendering time, or back on. With shift held, it does the same for
+// the shadows between the ringed bodies and their rings. The next preset selected overrides both.
+fn toggle_shadows(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<RenderSettings>) {
+    if !input.just_pressed(SHADOW_TOGGLE_KEY) {
+        return;
+    }
+    if input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        settings.ring_shadows = !settings.ring_shadows;
+        info!("Ring shadows: {}", settings.ring_shadows);
+    } else {
+        settings.shadows = !settings.shadows;
+        info!("Shadows: {}", settings.shadows);
+    }
+}
+
+// - and = halve and double the light the luminous bodies emit. B weakens the bloom, and shift+B
+// strengthens it. M cycles through the tonemapping methods, and shift+M is left to the meridian
+// markers. Shift+F2 cycles through the resolution scales, and control+F2 through the MSAA sample
+// counts.
+fn adjust_tuning(input: Res<ButtonInput<KeyCode>>, mut tuning: ResMut<RenderTuning>) {
+    let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let control = input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if input.just_pressed(DIMMER_KEY) {
+        tuning.scale_luminosity(0.5);
+        info!("Luminosity scale: {:e}", tuning.luminosity_scale);
+    } else if input.just_pressed(BRIGHTER_KEY) {
+        tuning.scale_luminosity(2.);
+        info!("Luminosity scale: {:e}", tuning.luminosity_scale);
+    } else if input.just_pressed(BLOOM_KEY) {
+        tuning.step_bloom(if shift { 1. } else { -1. });
+        info!("Bloom intensity: {:.2}", tuning.bloom_intensity);
+    } else if input.just_pressed(TONEMAPPING_KEY) && !shift {
+        tuning.next_tonemapping();
+        info!("Tonemapping: {:?}", tuning.tonemapping);
+    } else if input.just_pressed(PRESET_KEY) && shift {
+        tuning.next_resolution_scale();
+        info!("Resolution scale: {}%", 100. * tuning.resolution_scale);
+    } else if input.just_pressed(PRESET_KEY) && control {
+        tuning.next_msaa();
+        info!("MSAA: {:?}", tuning.msaa);
+    }
+}
+
+// When the tuning changes, this applies it to the renderer, the camera, and the lights the bodies
+// emit. The camera and lights are created with the initial tuning, but the renderer isn't.
+fn apply_tuning(
+    tuning: Res<RenderTuning>,
+    (sim, mut msaa): (Res<Simulation>, ResMut<Msaa>),
+    mut cams: Query<(&mut Tonemapping, Option<&mut BloomSettings>), With<Camera3d>>,
+    bodies: Query<(&Body, &BodyModel)>,
+    avatars: Query<&Children>,
+    mut lights: Query<&mut PointLight>,
+) {
+    if !tuning.is_changed() {
+        return;
+    }
+    *msaa = tuning.msaa;
+    if tuning.is_added() {
+        return;
+    }
+
+    for (mut tonemapping, bloom) in &mut cams {
+        *tonemapping = tuning.tonemapping;
+        if let Some(mut bloom) = bloom {
+            bloom.intensity = tuning.bloom_intensity;
+        }
+    }
+
+    for (body, model) in &bodies {
+        let Some(children) = model.avatar().and_then(|avatar| avatars.get(avatar).ok()) else {
+            continue;
+        };
+        for child in children {
+            if let Ok(mut light) = lights.get_mut(*child) {
+                light.intensity = tuning.light_intensity(&sim, *body);
+            }
+        }
+    }
+}
+
+// When the settings change, this applies them to the renderer, the camera, the lights, the avatars,
+// and the asteroid belt. The app's own visibility tuning and belt stand until a preset is selected.
+fn apply_settings(
+    (settings, tuning): (Res<RenderSettings>, Res<RenderTuning>),
+    (visibility, belt): (Option<ResMut<VisibilityTuning>>, Option<ResMut<AsteroidBelt>>),
+    mut commands: Commands,
+    mut shadow_map: ResMut<PointLightShadowMap>,
+    (mut meshes, avatar_meshes): (ResMut<Assets<Mesh>>, Option<ResMut<AvatarMeshes>>),
+    cams: Query<Entity, With<Camera3d>>,
+    mut lights: Query<&mut PointLight>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    shadow_map.size = settings.shadow_map_size;
+
+    for cam in &cams {
+        if settings.bloom {
+            commands.entity(cam).insert(tuning.bloom());
+        } else {
+            commands.entity(cam).remove::<BloomSettings>();
+        }
+    }
+
+    for mut light in &mut lights {
+        settings.configure_shadows(&mut light);
+    }
+
+    // The avatars with more detail than the settings allow are coarsened as they are next updated.
+    if let Some(mut avatar_meshes) = avatar_meshes {
+        let levels = &mut avatar_meshes.0;
+        levels.truncate(settings.sphere_subdivisions + 1);
+        for n in levels.len()..=settings.sphere_subdivisions {
+            levels.push(meshes.add(mk_sphere(n)));
+        }
+    }
+
+    if settings.is_added() {
+        return;
+    }
+    if let Some(mut visibility) = visibility {
+        if visibility.eye_ang_res_rad != settings.avatar_ang_res_rad {
+            visibility.eye_ang_res_rad = settings.avatar_ang_res_rad;
+        }
+    }
+    if let Some(mut belt) = belt {
+        if belt.count != settings.asteroid_count {
+            belt.count = settings.asteroid_count;
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<RenderSettings>().init_resource::<RenderTuning>().add_systems(
+        Update,
+        (cycle_preset, toggle_shadows, apply_settings, adjust_tuning, apply_tuning).chain(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_presets_raise_shadow_resolution() {
+        let sizes = [QualityPreset::Low, QualityPreset::Medium, QualityPreset::High]
+            .map(|preset| RenderSettings::from(preset).shadow_map_size);
+        assert!(sizes[0] < sizes[1] && sizes[1] < sizes[2], "{:?}", sizes);
+    }
+
+    #[test]
+    fn test_preset_applied_to_visibility_and_belt() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<PointLightShadowMap>()
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<RenderSettings>()
+            .init_resource::<RenderTuning>()
+            .init_resource::<VisibilityTuning>()
+            .insert_resource(AsteroidBelt { count: 10, ..default() })
+            .add_systems(Update, apply_settings);
+        app.update();
+        assert_eq!(app.world().resource::<AsteroidBelt>().count, 10);
+
+        app.world_mut().resource_mut::<RenderSettings>().apply_preset(QualityPreset::Low);
+        app.update();
+        let low = RenderSettings::from(QualityPreset::Low);
+        assert_eq!(app.world().resource::<AsteroidBelt>().count, low.asteroid_count);
+        let visibility = app.world().resource::<VisibilityTuning>();
+        assert_eq!(visibility.eye_ang_res_rad, low.avatar_ang_res_rad);
+    }
+
+    #[test]
+    fn test_presets_set_msaa() {
+        let mut tuning = RenderTuning::default();
+        assert_eq!(tuning.msaa, QualityPreset::default().msaa());
+        tuning.apply_preset(QualityPreset::High);
+        assert_eq!(tuning.msaa, Msaa::Sample8);
+        tuning.apply_preset(QualityPreset::Low);
+        assert_eq!(tuning.msaa, Msaa::Off);
+    }
+
+    #[test]
+    fn test_resolution_scale_and_msaa_cycle() {
+        let mut tuning = RenderTuning::default();
+        tuning.next_resolution_scale();
+        assert_eq!(tuning.resolution_scale, 0.75);
+        tuning.resolution_scale = 0.6;
+        tuning.next_resolution_scale();
+        assert_eq!(tuning.resolution_scale, 1.);
+        tuning.next_msaa();
+        assert_eq!(tuning.msaa, Msaa::Sample8);
+        tuning.next_msaa();
+        assert_eq!(tuning.msaa, Msaa::Off);
+    }
+
+    #[test]
+    fn test_shadow_depth_bias_inside_moon_orbit() {
+        // The Moon can only shade Earth if the bias is less than its distance from Earth.
+        let sim = Simulation::init_at(default_epoch());
+        let settings = RenderSettings::default();
+        assert!(settings.shadow_depth_bias < sim.perihelion_of(Body::Moon) / 10.);
+    }
+
+    #[test]
+    fn test_lod_rises_with_apparent_size() {
+        assert_eq!(lod_for(1., 5), 0);
+        assert!(lod_for(20., 5) < lod_for(50., 5));
+        assert_eq!(lod_for(1e6, 5), 5);
+    }
+
+    #[test]
+    fn test_lod_holds_near_threshold() {
+        // Find the apparent radius where the level rises from 2 to 3.
+        let mut radius = 1.;
+        while lod_for(radius, 6) < 3 {
+            radius *= 1.01;
+        }
+        assert_eq!(next_lod(2, radius, 6), 3);
+        assert_eq!(next_lod(3, radius / 1.1, 6), 3);
+        assert_eq!(next_lod(3, radius / 1.5, 6), 2);
+        assert_eq!(next_lod(6, radius, 2), 2);
+    }
+
+    #[test]
+    fn test_luminosity_scale_clamped() {
+        let mut tuning = RenderTuning::default();
+        for _ in 0..20 {
+            tuning.scale_luminosity(2.);
+        }
+        assert_eq!(tuning.luminosity_scale, MAX_LUMINOSITY_SCALE);
+    }
+
+    #[test]
+    fn test_bloom_steps_stay_in_range() {
+        let mut tuning = RenderTuning::default();
+        tuning.step_bloom(-10.);
+        assert_eq!(tuning.bloom_intensity, 0.);
+        tuning.step_bloom(1.);
+        assert_eq!(tuning.bloom().intensity, BLOOM_STEP);
+    }
+
+    #[test]
+    fn test_tonemapping_cycles_back() {
+        let mut tuning = RenderTuning::default();
+        for _ in 0..TONEMAPPINGS.len() {
+            tuning.next_tonemapping();
+        }
+        assert_eq!(tuning.tonemapping, RenderTuning::default().tonemapping);
+    }
+
+    #[test]
+    fn test_light_intensity_scaled() {
+        let sim = Simulation::init_at(default_epoch());
+        let mut tuning = RenderTuning::default();
+        let bright = tuning.light_intensity(&sim, Body::Sun);
+        tuning.scale_luminosity(0.5);
+        assert_eq!(tuning.light_intensity(&sim, Body::Sun), bright / 2.);
+        assert_eq!(tuning.light_intensity(&sim, Body::Earth), 0.);
+    }
+
+    #[test]
+    fn test_configure_shadows() {
+        let settings = RenderSettings::from(QualityPreset::Low);
+        let mut light = PointLight { shadows_enabled: true, ..default() };
+        settings.configure_shadows(&mut light);
+        assert!(!light.shadows_enabled);
+        assert_eq!(light.shadow_depth_bias, SHADOW_DEPTH_BIAS_AU);
+    }
+}
@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use crate::{
+    simulation::Body,
+    uom_wrapper::si::{f64::Time, time::day},
+    DrawnPositions, Simulation,
+};
+
+// The radius in AU of the sky sphere centered on Earth that apparent paths are drawn on
+const SKY_RADIUS_AU: f32 = 0.5;
+
+// The number of times an apparent path is sampled
+const PATH_SAMPLES: usize = 256;
+
+// The bodies that can be selected for viewing in the order they are cycled through
+const TARGETS: [Body; 5] = [Body::Mars, Body::Jupiter, Body::Saturn, Body::Uranus, Body::Neptune];
+
+// The shortest and longest spans of time in days an apparent path can cover
+const MIN_SPAN_D: f64 = 7.;
+const MAX_SPAN_D: f64 = 1_792.;
+
+// The radius in AU of the markers placed where the body becomes stationary on the sky
+const STATIONARY_MARKER_AU: f32 = 0.005;
+
+// The color of the retrograde portion of an apparent path
+const RETROGRADE_COLOR: Color = Color::srgb(1., 0.25, 0.25);
+
+// This controls which body has its apparent path, as seen from Earth, plotted on the sky and how
+// long of a time span, centered on the current time, the path covers.
+#[derive(Resource)]
+pub struct RetrogradeView {
+    pub target: Option<Body>,
+    pub span: Time,
+}
+
+impl Default for RetrogradeView {
+    fn default() -> Self {
+        Self {
+            target: None,
+            span: Time::new::<day>(224.),
+        }
+    }
+}
+
+// For each segment of an apparent path, this determines if the body is moving westward, i.e., its
+// ecliptic longitude is decreasing.
+fn retrograde_segments(path: &[Vec3]) -> Vec<bool> {
+    path.windows(2)
+        .map(|seg| seg[0].x * seg[1].y - seg[0].y * seg[1].x < 0.)
+        .collect()
+}
+
+// R cycles through the targets and off. [ and ] halve and double the time span.
+fn control_retrograde(input: Res<ButtonInput<KeyCode>>, mut view: ResMut<RetrogradeView>) {
+    if input.just_pressed(KeyCode::KeyR) {
+        view.target = match view.target.and_then(|t| TARGETS.iter().position(|&b| b == t)) {
+            None => Some(TARGETS[0]),
+            Some(idx) => TARGETS.get(idx + 1).copied(),
+        };
+    }
+
+    let span_d = view.span.get::<day>();
+    if input.just_pressed(KeyCode::BracketLeft) {
+        view.span = Time::new::<day>((span_d / 2.).max(MIN_SPAN_D));
+    } else if input.just_pressed(KeyCode::BracketRight) {
+        view.span = Time::new::<day>((span_d * 2.).min(MAX_SPAN_D));
+    }
+}
+
+// This draws the target's apparent path on a sphere centered on Earth. The prograde portions are
+// drawn in the target's color, and retrograde portions are highlighted with the points where the
+// target is stationary marked. The sphere is drawn every frame centered on where Earth's avatar is
+// drawn.
+fn draw_retrograde(
+    sim: Res<Simulation>,
+    view: Res<RetrogradeView>,
+    drawn: DrawnPositions,
+    mut gizmos: Gizmos,
+) {
+    let Some(target) = view.target else {
+        return;
+    };
+
+    let center = drawn.of(Body::Earth);
+    let path = sim.apparent_path_of(target, view.span, PATH_SAMPLES);
+    let retrograde = retrograde_segments(&path);
+    let prograde_color = sim.color_of(target).with_luminance(0.7);
+
+    for (i, seg) in path.windows(2).enumerate() {
+        let color = if retrograde[i] { RETROGRADE_COLOR } else { prograde_color };
+        gizmos.line(center + SKY_RADIUS_AU * seg[0], center + SKY_RADIUS_AU * seg[1], color);
+        if i > 0 && retrograde[i] != retrograde[i - 1] {
+            gizmos.sphere(
+                center + SKY_RADIUS_AU * seg[0],
+                Quat::IDENTITY,
+                STATIONARY_MARKER_AU,
+                RETROGRADE_COLOR,
+            );
+        }
+    }
+
+    // Mark where the target is now, the middle of the path
+    gizmos.sphere(
+        center + SKY_RADIUS_AU * path[path.len() / 2],
+        Quat::IDENTITY,
+        STATIONARY_MARKER_AU,
+        prograde_color,
+    );
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<RetrogradeView>()
+        .add_systems(Update, (control_retrograde, draw_retrograde.after(crate::update_avatars)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_retrograde_segments_direction() {
+        let path = [Vec3::X, Vec3::new(1., 0.1, 0.), Vec3::new(1., 0.05, 0.)];
+        assert_eq!(retrograde_segments(&path), vec![false, true]);
+    }
+
+    #[test]
+    fn test_mars_loops_near_opposition() {
+        let sim = Simulation::init_at(default_epoch());
+        let span = Time::new::<day>(730.);
+        let mars = sim.apparent_path_of(Body::Mars, span, 731);
+        let sun = sim.apparent_path_of(Body::Sun, span, 731);
+        // At opposition, Mars is opposite the Sun in Earth's sky.
+        let opposition = (0..mars.len())
+            .min_by(|a, b| mars[*a].dot(sun[*a]).total_cmp(&mars[*b].dot(sun[*b])))
+            .unwrap();
+        let retrograde = retrograde_segments(&mars);
+        let days = retrograde.iter().filter(|r| **r).count();
+        assert!((60..90).contains(&days), "{}", days);
+        assert!(retrograde[opposition - 20] && retrograde[opposition + 20], "{}", opposition);
+        assert!(!retrograde[opposition - 50] && !retrograde[opposition + 50], "{}", opposition);
+    }
+}
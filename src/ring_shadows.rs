@@ -0,0 +1,226 @@
+use bevy::{
+    ecs::schedule::common_conditions::any_with_component,
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+
+use crate::{
+    render_settings::RenderSettings,
+    rings::{Rings, RingsOf},
+    simulation::Body,
+    BodyModel, SimSet, Simulation,
+};
+
+// The radius of the shell carrying the rings' shadow relative to its avatar's, just enough to keep
+// it clear of the avatar's surface and inside a night side shell
+const SHELL_RADIUS: f32 = 1.004;
+
+// The number of segments around the shell and from pole to pole
+const SHELL_SECTORS: usize = 96;
+const SHELL_STACKS: usize = 48;
+
+// The width in body radii of the soft edge of a body's shadow on its rings
+const SHADOW_EDGE_RADII: f32 = 0.05;
+
+// How bright the rings are in their body's shadow relative to their lit brightness. The ambient
+// light still reaches them there.
+const SHADED_BRIGHTNESS: f32 = 0.1;
+
+// The shadows between a ringed body and its rings are drawn as vertex colors instead of with the
+// Sun's shadow map, whose texels are too coarse at Saturn's distance to resolve them. A body's
+// shadow darkens the vertices of its rings, and the rings' shadow darkens a shell over the body.
+// While they are drawn, the rings neither cast nor receive shadows from the shadow map. Both are
+// computed in the body's frame, where the body has a unit radius and the rings lie in the XY plane.
+
+// Return how much of the Sun's light a body blocks from a point, from 0 to 1. The Sun is far enough
+// away that the body's shadow is a cylinder.
+fn body_shadow(point: Vec3, sun_dir: Vec3) -> f32 {
+    let along = point.dot(sun_dir);
+    if along >= 0. {
+        return 0.;
+    }
+    let off_axis = (point - along * sun_dir).length();
+    ((1. + SHADOW_EDGE_RADII / 2. - off_axis) / SHADOW_EDGE_RADII).clamp(0., 1.)
+}
+
+// Return the opacity of the rings lying between a point on the surface of their body and the Sun.
+// Only the day side is shaded, since the night side is already unlit.
+fn rings_shadow(point: Vec3, sun_dir: Vec3, rings: &Rings) -> f32 {
+    if point.dot(sun_dir) <= 0. || sun_dir.z == 0. {
+        return 0.;
+    }
+    let dist = -point.z / sun_dir.z;
+    let radius = (point + dist * sun_dir).truncate().length();
+    if dist > 0. && (rings.inner_radii..=rings.outer_radii).contains(&radius) {
+        rings.color.alpha()
+    } else {
+        0.
+    }
+}
+
+// Return the direction to the Sun from a body in the body's frame
+fn sun_direction(sim: &Simulation, body: Body) -> Vec3 {
+    let to_sun = sim.position_of(Body::Sun) - sim.position_of(body);
+    sim.orientation_of(body).inverse() * to_sun.normalize_or_zero()
+}
+
+// Color each vertex of a mesh by its position
+fn recolor(mesh: &mut Mesh, color: impl Fn(Vec3) -> LinearRgba) {
+    let Some(points) = mesh.attribute(Mesh::ATTRIBUTE_POSITION).and_then(|attr| attr.as_float3())
+    else {
+        return;
+    };
+    let colors: Vec<[f32; 4]> =
+        points.iter().map(|point| color(Vec3::from(*point)).to_f32_array()).collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+// This is the shell carrying the shadow of a body's rings.
+#[derive(Component)]
+struct RingShadow(Body);
+
+fn visibility(settings: &RenderSettings) -> Visibility {
+    if settings.ring_shadows {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+// Each shell is a child of its body's avatar, so it is sized with the avatar. Only bodies with
+// rings get one.
+fn create_shells(
+    (settings, sim): (Res<RenderSettings>, Res<Simulation>),
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    bodies: Query<(&Body, &BodyModel)>,
+) {
+    let mut mesh = Sphere::new(1.).mesh().uv(SHELL_SECTORS, SHELL_STACKS);
+    recolor(&mut mesh, |_| LinearRgba::NONE);
+    let mesh = meshes.add(mesh);
+    let material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    for (body, model) in &bodies {
+        let Some(avatar) = model.avatar().filter(|_| sim.rings_of(*body).is_some()) else {
+            continue;
+        };
+        commands.entity(avatar).with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_scale(Vec3::splat(SHELL_RADIUS)),
+                    visibility: visibility(&settings),
+                    ..default()
+                },
+                NotShadowCaster,
+                NotShadowReceiver,
+                RingShadow(*body),
+            ));
+        });
+    }
+}
+
+// The shadows move as the bodies move and spin.
+fn shade(
+    (settings, sim): (Res<RenderSettings>, Res<Simulation>),
+    mut meshes: ResMut<Assets<Mesh>>,
+    rings: Query<(&RingsOf, &Handle<Mesh>)>,
+    shells: Query<(&RingShadow, &Handle<Mesh>)>,
+) {
+    if !settings.ring_shadows {
+        return;
+    }
+    for (RingsOf(body), handle) in &rings {
+        let sun_dir = sun_direction(&sim, *body);
+        if let Some(mesh) = meshes.get_mut(handle) {
+            recolor(mesh, |point| {
+                let lit = 1. - body_shadow(point, sun_dir);
+                LinearRgba::gray(SHADED_BRIGHTNESS.lerp(1., lit))
+            });
+        }
+    }
+    for (RingShadow(body), handle) in &shells {
+        let (Some(rings), Some(mesh)) = (sim.rings_of(*body), meshes.get_mut(handle)) else {
+            continue;
+        };
+        let sun_dir = sun_direction(&sim, *body);
+        recolor(mesh, |point| LinearRgba::BLACK.with_alpha(rings_shadow(point, sun_dir, rings)));
+    }
+}
+
+// When the ring shadows are turned off, the shells are hidden, the rings are relit, and the rings
+// go back to casting and receiving shadows from the shadow map.
+fn apply_settings(
+    settings: Res<RenderSettings>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    rings: Query<(Entity, &Handle<Mesh>), With<RingsOf>>,
+    mut shells: Query<&mut Visibility, With<RingShadow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut shell_visibility in &mut shells {
+        *shell_visibility = visibility(&settings);
+    }
+    for (entity, handle) in &rings {
+        if settings.ring_shadows {
+            commands.entity(entity).insert((NotShadowCaster, NotShadowReceiver));
+        } else {
+            commands.entity(entity).remove::<(NotShadowCaster, NotShadowReceiver)>();
+            if let Some(mesh) = meshes.get_mut(handle) {
+                recolor(mesh, |_| LinearRgba::WHITE);
+            }
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(crate::SpawnBodies, create_shells.after(crate::create_avatars))
+        .add_systems(Update, apply_settings.run_if(any_with_component::<RingsOf>))
+        .add_systems(
+            FixedUpdate,
+            shade.in_set(SimSet::Render).run_if(any_with_component::<RingsOf>),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RINGS: Rings = Rings {
+        inner_radii: 1.2,
+        outer_radii: 2.3,
+        color: Color::srgba(1., 1., 1., 0.6),
+    };
+
+    #[test]
+    fn test_body_shadow_falls_behind_body() {
+        let sun_dir = Vec3::X;
+        assert_eq!(body_shadow(Vec3::new(-2., 0., 0.), sun_dir), 1.);
+        assert_eq!(body_shadow(Vec3::new(2., 0., 0.), sun_dir), 0.);
+        assert_eq!(body_shadow(Vec3::new(-2., 1.5, 0.), sun_dir), 0.);
+    }
+
+    #[test]
+    fn test_rings_shadow_bands_day_side() {
+        // With the Sun above the rings, they shade the southern hemisphere's day side.
+        let sun_dir = Vec3::new(1., 0., 0.5).normalize();
+        let shaded = Vec3::new(1., 0., -0.2).normalize();
+        assert_eq!(rings_shadow(shaded, sun_dir, &RINGS), 0.6);
+        assert_eq!(rings_shadow(Vec3::new(1., 0., 0.2).normalize(), sun_dir, &RINGS), 0.);
+        assert_eq!(rings_shadow(-shaded, sun_dir, &RINGS), 0.);
+    }
+
+    #[test]
+    fn test_rings_shadow_vanishes_at_equinox() {
+        let point = Vec3::new(1., 0., -0.2).normalize();
+        assert_eq!(rings_shadow(point, Vec3::X, &RINGS), 0.);
+    }
+}
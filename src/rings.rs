@@ -0,0 +1,93 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+
+use crate::simulation::Body;
+
+// The number of segments the rings' circles are drawn with
+const RING_RESOLUTION: usize = 128;
+
+// The number of circles of vertices spanning the width of the rings. The shadow a body casts on
+// its rings is shaded at the vertices, so it needs more than the inner and outer edges.
+const RING_CIRCLES: usize = 16;
+
+// This is the ring system of a body. The rings lie in the body's equatorial plane. Their radii are
+// multiples of the radius of the body, so the rings scale with the body's avatar.
+#[derive(Clone, Debug)]
+pub struct Rings {
+    pub inner_radii: f32,
+    pub outer_radii: f32,
+
+    // The alpha channel controls how transparent the rings are.
+    pub color: Color,
+}
+
+impl Rings {
+    // Create a flat annulus for a body of the given radius lying in the body's equatorial plane.
+    // Its vertices are white, so their colors can be darkened to shade the rings.
+    pub fn mk_mesh(&self, body_radius: f32) -> Mesh {
+        let mut positions = Vec::new();
+        for circle in 0..RING_CIRCLES {
+            let frac = circle as f32 / (RING_CIRCLES - 1) as f32;
+            let radius = body_radius * self.inner_radii.lerp(self.outer_radii, frac);
+            for seg in 0..=RING_RESOLUTION {
+                let lon = std::f32::consts::TAU * seg as f32 / RING_RESOLUTION as f32;
+                positions.push(Vec3::new(radius * lon.cos(), radius * lon.sin(), 0.));
+            }
+        }
+        let mut indices = Vec::new();
+        for circle in 0..RING_CIRCLES as u32 - 1 {
+            for seg in 0..RING_RESOLUTION as u32 {
+                let a = circle * (RING_RESOLUTION as u32 + 1) + seg;
+                let b = a + RING_RESOLUTION as u32 + 1;
+                indices.extend([a, b, a + 1, a + 1, b, b + 1]);
+            }
+        }
+        let normals = vec![Vec3::Z; positions.len()];
+        let colors = vec![LinearRgba::WHITE.to_f32_array(); positions.len()];
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+            .with_inserted_indices(Indices::U32(indices))
+    }
+
+    // The rings are visible from both sides.
+    pub fn mk_material(&self) -> StandardMaterial {
+        StandardMaterial {
+            base_color: self.color,
+            alpha_mode: AlphaMode::Blend,
+            cull_mode: None,
+            double_sided: true,
+            ..default()
+        }
+    }
+}
+
+// This marks the rings of a body. They are a child of the body's avatar.
+#[derive(Component)]
+pub struct RingsOf(pub Body);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rings_mesh_spans_radii() {
+        let rings = Rings {
+            inner_radii: 1.5,
+            outer_radii: 2.5,
+            color: Color::WHITE,
+        };
+        let mesh = rings.mk_mesh(2.);
+        assert_eq!(mesh.count_vertices(), RING_CIRCLES * (RING_RESOLUTION + 1));
+        let points = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().as_float3().unwrap();
+        let radii = points.iter().map(|p| Vec3::from(*p).length());
+        let (min, max) = radii.fold((f32::MAX, 0f32), |(min, max), r| (min.min(r), max.max(r)));
+        assert!((min - 3.).abs() < 1e-5 && (max - 5.).abs() < 1e-5, "{} {}", min, max);
+    }
+}
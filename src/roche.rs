@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::{
+    simulation::Body,
+    uom_wrapper::si::{
+        f64::MassDensity, length::astronomical_unit, mass_density::kilogram_per_cubic_meter,
+    },
+    DrawnPositions, Simulation,
+};
+
+// The density of water ice, a typical density for the moons of the outer planets
+const ICE_DENSITY_KGPM3: f64 = 917.;
+
+// The opacity of the Roche limit spheres
+const ROCHE_ALPHA: f32 = 0.25;
+
+// This controls the display of the Roche limits of the planets for a satellite of a given density.
+#[derive(Resource)]
+pub struct RocheLimits {
+    pub visible: bool,
+    pub satellite_density: MassDensity,
+}
+
+impl Default for RocheLimits {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            satellite_density: MassDensity::new::<kilogram_per_cubic_meter>(ICE_DENSITY_KGPM3),
+        }
+    }
+}
+
+// L toggles the Roche limits.
+fn toggle_roche_limits(input: Res<ButtonInput<KeyCode>>, mut limits: ResMut<RocheLimits>) {
+    if input.just_pressed(KeyCode::KeyL) {
+        limits.visible = !limits.visible;
+    }
+}
+
+// The limits are drawn every frame around where the avatars are drawn.
+fn draw_roche_limits(
+    sim: Res<Simulation>,
+    limits: Res<RocheLimits>,
+    drawn: DrawnPositions,
+    mut gizmos: Gizmos,
+) {
+    if !limits.visible {
+        return;
+    }
+
+    for body in sim.bodies() {
+        if body.primary() == Some(Body::Sun) {
+            let radius = sim.roche_limit_of(body, limits.satellite_density);
+            gizmos.sphere(
+                drawn.of(body),
+                Quat::IDENTITY,
+                radius.get::<astronomical_unit>(),
+                sim.color_of(body).with_alpha(ROCHE_ALPHA),
+            );
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<RocheLimits>()
+        .add_systems(Update, (toggle_roche_limits, draw_roche_limits.after(crate::update_avatars)))
+}
@@ -0,0 +1,143 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera_animation::CameraAnimation,
+    follow_camera::CameraTarget,
+    simulation::SolarSystem,
+    uom_wrapper::jd_to_gregorian,
+    Observer, Simulation,
+};
+
+// The keys saving and restoring the state
+const SAVE_KEY: KeyCode = KeyCode::F10;
+const RESTORE_KEY: KeyCode = KeyCode::F11;
+
+// This is what is needed to pick up where a session left off, the state of the solar system
+// model, which includes the current time, and the observer.
+#[derive(Deserialize, Serialize)]
+struct SavedState {
+    solar_system: SolarSystem,
+    observer: Observer,
+}
+
+// This is the file the state is saved to. It is written in RON, Rusty Object Notation. Rust writes
+// the shortest decimal form of a float that reads back to the same float, so restoring the state
+// gives back exactly the state that was saved.
+#[derive(Resource)]
+pub struct SaveFile {
+    pub path: PathBuf,
+}
+
+impl Default for SaveFile {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("solar-system-state.ron"),
+        }
+    }
+}
+
+fn encode(solar_system: &SolarSystem, observer: &Observer) -> Result<String, String> {
+    let state = SavedState {
+        solar_system: solar_system.clone(),
+        observer: observer.clone(),
+    };
+    ron::ser::to_string_pretty(&state, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+}
+
+// The saved solar system must have the same bodies as the current one, since the bodies are only
+// drawn at startup. The asteroids aren't saved, so they are carried over from the current solar
+// system.
+fn decode(text: &str, current: &SolarSystem) -> Result<SavedState, String> {
+    let mut state: SavedState = ron::from_str(text).map_err(|err| err.to_string())?;
+    let (saved_bodies, current_bodies) = (state.solar_system.bodies(), current.bodies());
+    let mut differing: Vec<_> = saved_bodies.symmetric_difference(&current_bodies).collect();
+    differing.sort_by_key(|body| format!("{:?}", body));
+    if let Some(body) = differing.first() {
+        return Err(format!("it was saved with different bodies, e.g., {:?}", body));
+    }
+    state.solar_system.copy_asteroids_from(current);
+    Ok(state)
+}
+
+// F10 saves the state, and F11 restores it. Restoring the state stops following any body and stops
+// any camera animation.
+fn save_or_restore(
+    input: Res<ButtonInput<KeyCode>>,
+    file: Res<SaveFile>,
+    mut sim: ResMut<Simulation>,
+    mut target: ResMut<CameraTarget>,
+    mut anim: ResMut<CameraAnimation>,
+    mut observer: Query<&mut Observer>,
+) {
+    let path = &file.path;
+    if input.just_pressed(SAVE_KEY) {
+        let result = encode(&sim.solar_system, observer.single())
+            .and_then(|text| fs::write(path, text).map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => info!("Saved the state at {}", jd_to_gregorian(sim.current_time())),
+            Err(err) => error!("Failed to save the state to {}: {}", path.display(), err),
+        }
+    } else if input.just_pressed(RESTORE_KEY) {
+        let result = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| decode(&text, &sim.solar_system));
+        match result {
+            Ok(state) => {
+                sim.solar_system = state.solar_system;
+                *observer.single_mut() = state.observer;
+                target.0 = None;
+                anim.cancel();
+                info!("Restored the state at {}", jd_to_gregorian(sim.current_time()));
+            }
+            Err(err) => error!("Failed to restore the state from {}: {}", path.display(), err),
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<SaveFile>().add_systems(Update, save_or_restore)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        default_epoch,
+        simulation::{Body, BodyProperties, Propagator},
+        uom_wrapper::si::{f64::Time, time::minute},
+    };
+
+    #[test]
+    fn test_restored_state_steps_identically() {
+        let step = Time::new::<minute>(30.);
+        let mut saved = SolarSystem::init(default_epoch(), Propagator::default());
+        saved.advance_time_in_steps(Time::new::<minute>(3000.), step);
+        let mut observer = Observer::new();
+        observer.set_focus(Vec3::new(1., 2., 3.));
+        let text = encode(&saved, &observer).unwrap();
+        let restored = decode(&text, &saved).unwrap();
+        assert_eq!(restored.observer, observer);
+        let mut sys = restored.solar_system;
+        assert_eq!(sys.current_time(), saved.current_time());
+        saved.advance_time(step);
+        sys.advance_time(step);
+        for body in saved.bodies() {
+            assert_eq!(sys.position_of(body), saved.position_of(body), "{:?}", body);
+            assert_eq!(sys.velocity_of(body), saved.velocity_of(body), "{:?}", body);
+        }
+    }
+
+    #[test]
+    fn test_restore_rejects_different_bodies() {
+        let sys = SolarSystem::init(default_epoch(), Propagator::default());
+        let text = encode(&sys, &Observer::new()).unwrap();
+        let mut current = sys.clone();
+        current.add_body(Body::Custom(0), BodyProperties::properties_for(Body::Ceres));
+        let err = decode(&text, &current).map(|_| ()).unwrap_err();
+        assert!(err.contains("Custom(0)"), "{}", err);
+    }
+}
@@ -0,0 +1,169 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    uom_wrapper::si::{
+        f64::Length,
+        length::{astronomical_unit, kilometer},
+    },
+    Observer,
+};
+
+// The longest the bar may be in logical pixels
+const MAX_BAR_PX: f32 = 160.;
+
+// The bar's length is given in km instead of AU when it is shorter than this many AU.
+const KM_BELOW_AU: f64 = 0.01;
+
+const BAR_HEIGHT_PX: f32 = 2.;
+
+const FONT_SIZE: f32 = 14.;
+
+// How far up from the bottom of the window in logical pixels the bar is
+const BOTTOM_PX: f32 = 34.;
+
+// This is the bar whose width shows the scale.
+#[derive(Component)]
+struct ScaleBar;
+
+// This is the label giving the length the bar represents.
+#[derive(Component)]
+struct ScaleLabel;
+
+// Return the largest round length, 1, 2, or 5 times a power of 10, that is no longer than the given
+// length along with the number of decimal places needed to write it. The length must be positive.
+fn round_length(max_len: f64) -> (f64, usize) {
+    let exp = max_len.log10().floor();
+    let power = 10_f64.powf(exp);
+    let mantissa = [5., 2., 1.].into_iter().find(|m| m * power <= max_len).unwrap_or(1.);
+    (mantissa * power, (-exp).max(0.) as usize)
+}
+
+// Return the round length in AU the bar represents when a pixel spans the given number of AU,
+// along with its label. Near a body, where the bar spans a tiny fraction of an AU, the length is
+// rounded in km instead.
+fn bar_length(au_per_px: f64) -> (f64, String) {
+    let max_au = au_per_px * MAX_BAR_PX as f64;
+    if max_au >= KM_BELOW_AU {
+        let (len, decimals) = round_length(max_au);
+        return (len, format!("{:.*} AU", decimals, len));
+    }
+
+    let max_km = Length::new::<astronomical_unit>(max_au).get::<kilometer>();
+    let (len_km, decimals) = round_length(max_km);
+    let len_au = Length::new::<kilometer>(len_km).get::<astronomical_unit>();
+    (len_au, format!("{:.*} km", decimals, len_km))
+}
+
+// The bar is centered at the bottom of the window with its label above it. It sits above the line
+// the cursor coordinates readout takes at the very bottom, so the two don't overlap.
+fn create_scale_bar(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(BOTTOM_PX),
+                width: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: FONT_SIZE,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                ScaleLabel,
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(0.),
+                        height: Val::Px(BAR_HEIGHT_PX),
+                        ..default()
+                    },
+                    background_color: Color::WHITE.into(),
+                    ..default()
+                },
+                ScaleBar,
+            ));
+        });
+}
+
+// The scale is measured at the distance of the observer's focus. Two points that far in front of
+// the camera and that far apart are projected through the camera, and the distance between them
+// on the screen gives the pixels per AU. They are projected to normalized device coordinates, so
+// the scale holds whatever resolution the scene is rendered at.
+fn update_scale_bar(
+    window: Query<&Window, With<PrimaryWindow>>,
+    cam: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    observer: Query<&Observer>,
+    mut bar: Query<&mut Style, With<ScaleBar>>,
+    mut label: Query<&mut Text, With<ScaleLabel>>,
+) {
+    let (cam, cam_transform) = cam.single();
+    let dist = observer.single().orbit_radius();
+    let center = cam_transform.translation() + dist * *cam_transform.forward();
+    let edge = center + dist * *cam_transform.right();
+    let (Some(center_ndc), Some(edge_ndc)) =
+        (cam.world_to_ndc(cam_transform, center), cam.world_to_ndc(cam_transform, edge))
+    else {
+        return;
+    };
+    let span_px = (edge_ndc.x - center_ndc.x) / 2. * window.single().width();
+    if span_px.is_nan() || span_px <= 0. {
+        return;
+    }
+
+    let au_per_px = dist as f64 / span_px as f64;
+    let (len_au, text) = bar_length(au_per_px);
+    for mut style in &mut bar {
+        style.width = Val::Px((len_au / au_per_px) as f32);
+    }
+    for mut label in &mut label {
+        label.sections[0].value.clone_from(&text);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Startup, create_scale_bar)
+        .add_systems(Update, update_scale_bar.after(crate::interpolate_camera))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_length() {
+        assert_eq!(round_length(7.3), (5., 0));
+        assert_eq!(round_length(2.), (2., 0));
+        assert_eq!(round_length(1_999.), (1_000., 0));
+        let (len, decimals) = round_length(0.034);
+        assert!((len - 0.02).abs() < 1e-12, "{}", len);
+        assert_eq!(decimals, 2);
+    }
+
+    #[test]
+    fn test_bar_length_in_au() {
+        let (len, text) = bar_length(0.1);
+        assert_eq!(len, 10.);
+        assert_eq!(text, "10 AU");
+        assert_eq!(bar_length(3e-4).1, "0.02 AU");
+    }
+
+    #[test]
+    fn test_bar_length_in_km_near_body() {
+        let km_per_au = Length::new::<astronomical_unit>(1.).get::<kilometer>();
+        let (len, text) = bar_length(1_000. / km_per_au);
+        assert_eq!(text, "100000 km");
+        assert!((len * km_per_au - 100_000.).abs() < 1e-6, "{}", len);
+        assert!(len * km_per_au <= 1_000. * MAX_BAR_PX as f64);
+    }
+}
@@ -0,0 +1,235 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowResized, WindowScaleFactorChanged},
+};
+
+use crate::{
+    avatar_radius,
+    simulation::Body,
+    uom_wrapper::si::{
+        angle::radian,
+        f32::{Angle, Length},
+        length::{inch, meter},
+    },
+    BodyModel, Observer, Simulation,
+};
+
+// The key toggling between the scale modes
+const TOGGLE_KEY: KeyCode = KeyCode::KeyZ;
+
+// Twice the minimum angular resolution in radians of the human eye
+const EYE_ANG_RES_RAD: f32 = 3e-4 * 2.;
+
+// The minimum distance in meters someone typically sits away from a laptop display.
+const MIN_OBSERVER_DIST_M: f32 = 0.3;
+
+// The standard DPI of a monitor
+const STANDARD_DPI: f32 = 96.;
+
+// This is how the avatars are sized. Most bodies are far smaller than a pixel at their true size,
+// so by default small bodies are inflated until they can be seen. The orbit trails still lead to
+// the bodies when they are drawn at their true size.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub enum ScaleMode {
+    Realistic,
+    #[default]
+    Visible,
+}
+
+impl ScaleMode {
+    fn toggle(self) -> Self {
+        match self {
+            Self::Realistic => Self::Visible,
+            Self::Visible => Self::Realistic,
+        }
+    }
+}
+
+// These are the assumptions setting how large the enlarged avatars are. An avatar spans at least
+// the larger of the eye's angular resolution and the angle a pixel spans from where the viewer
+// sits, even from the far side of its body's orbit, and never less than a pixel of the window.
+// Raise the resolution to make the bodies larger for a presentation, or lower it toward the eye's
+// true resolution for realism. Changes resize the avatars.
+#[derive(Debug, PartialEq, Resource)]
+pub struct VisibilityTuning {
+    pub eye_ang_res_rad: f32,
+    pub min_observer_dist_m: f32,
+    pub standard_dpi: f32,
+}
+
+impl Default for VisibilityTuning {
+    fn default() -> Self {
+        Self {
+            eye_ang_res_rad: EYE_ANG_RES_RAD,
+            min_observer_dist_m: MIN_OBSERVER_DIST_M,
+            standard_dpi: STANDARD_DPI,
+        }
+    }
+}
+
+impl VisibilityTuning {
+    // Return the least angle in radians an avatar may span in the window
+    pub fn min_ang_res(&self, window: &Window) -> f32 {
+        self.min_ang_for(window.scale_factor(), window.physical_height())
+    }
+
+    // Return the least angle in radians an avatar may span in a window the given number of physical
+    // pixels tall on a display with the given scale factor. However the assumptions are tuned, an
+    // avatar spans at least one of the window's pixels.
+    pub fn min_ang_for(&self, scale_factor: f32, physical_height: u32) -> f32 {
+        let pix_size = Length::new::<inch>(1. / (self.standard_dpi * scale_factor));
+        let obs_dist = Length::new::<meter>(self.min_observer_dist_m);
+        let disp_ang_res = 2. * (pix_size / (2. * obs_dist)).atan();
+        let win_ang_res = PerspectiveProjection::default().fov / physical_height.max(1) as f32;
+        Angle::new::<radian>(self.eye_ang_res_rad)
+            .max(disp_ang_res)
+            .get::<radian>()
+            .max(win_ang_res)
+    }
+}
+
+fn toggle_scale_mode(input: Res<ButtonInput<KeyCode>>, mut mode: ResMut<ScaleMode>) {
+    if input.just_pressed(TOGGLE_KEY) {
+        *mode = mode.toggle();
+        info!("Scale mode: {:?}", *mode);
+    }
+}
+
+// When the mode or the visibility tuning changes, or the window is resized or moved to a display
+// with a different scale factor, this rescales the avatars, and with them their rings, and resizes
+// the light they emit. The avatars end up the size they would have had if the app had started this
+// way.
+fn resize_avatars(
+    (mode, tuning): (Res<ScaleMode>, Res<VisibilityTuning>),
+    mut resized: EventReader<WindowResized>,
+    mut rescaled: EventReader<WindowScaleFactorChanged>,
+    (sim, window, observer): (
+        Res<Simulation>,
+        Query<&Window, With<PrimaryWindow>>,
+        Query<&Observer>,
+    ),
+    mut bodies: Query<(&Body, &mut BodyModel)>,
+    mut avatars: Query<(&mut Transform, Option<&Children>)>,
+    mut lights: Query<&mut PointLight>,
+) {
+    let window_changed = resized.read().count() + rescaled.read().count() > 0;
+    if !(mode.is_changed() || tuning.is_changed() || window_changed) {
+        return;
+    }
+
+    let min_ang = tuning.min_ang_res(window.single());
+    let cam_dist = observer.single().position().length();
+    for (body, mut model) in &mut bodies {
+        let radius = avatar_radius(&sim, *body, min_ang, cam_dist, *mode);
+        model.set_avatar_radius(radius);
+        let Some((mut transform, children)) =
+            model.avatar().and_then(|avatar| avatars.get_mut(avatar).ok())
+        else {
+            continue;
+        };
+        transform.scale = Vec3::splat(radius);
+        for child in children.into_iter().flatten() {
+            if let Ok(mut light) = lights.get_mut(*child) {
+                light.radius = radius;
+            }
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<ScaleMode>()
+        .init_resource::<VisibilityTuning>()
+        .add_systems(Update, (toggle_scale_mode, resize_avatars).chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_avatar_radius_realistic() {
+        let sim = Simulation::init_at(default_epoch());
+        let act = avatar_radius(&sim, Body::Earth, 6e-4, 80., ScaleMode::Realistic);
+        assert_eq!(act, sim.radius_of(Body::Earth));
+    }
+
+    #[test]
+    fn test_avatar_radius_visible_inflates_small_bodies() {
+        let sim = Simulation::init_at(default_epoch());
+        let act = avatar_radius(&sim, Body::Earth, 6e-4, 80., ScaleMode::Visible);
+        assert!(act > 100. * sim.radius_of(Body::Earth), "{}", act);
+    }
+
+    #[test]
+    fn test_avatar_radius_grows_with_min_angle() {
+        let sim = Simulation::init_at(default_epoch());
+        // Below about 9e-4 radians, a pixel's angular size sets the least angle instead.
+        let radii = [1e-3, 2e-3, 5e-3, 1e-2].map(|eye_ang_res_rad| {
+            let tuning = VisibilityTuning {
+                eye_ang_res_rad,
+                ..default()
+            };
+            let min_ang = tuning.min_ang_for(1., 2160);
+            avatar_radius(&sim, Body::Earth, min_ang, 80., ScaleMode::Visible)
+        });
+        assert!(radii.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", radii);
+    }
+
+    #[test]
+    fn test_min_ang_res_limited_by_pixels() {
+        let tuning = VisibilityTuning {
+            eye_ang_res_rad: 0.,
+            ..default()
+        };
+        assert!(tuning.min_ang_for(1., 2160) > tuning.min_ang_for(2., 2160));
+    }
+
+    #[test]
+    fn test_min_ang_at_least_a_window_pixel() {
+        let tuning = VisibilityTuning::default();
+        let fov = PerspectiveProjection::default().fov;
+        assert_eq!(tuning.min_ang_for(1., 100), fov / 100.);
+        assert!(tuning.min_ang_for(1., 2160) > fov / 2160.);
+    }
+
+    #[test]
+    fn test_resize_matches_startup_size() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<WindowResized>()
+            .add_event::<WindowScaleFactorChanged>()
+            .insert_resource(Simulation::init_at(default_epoch()))
+            .init_resource::<ScaleMode>()
+            .init_resource::<VisibilityTuning>()
+            .add_systems(Update, resize_avatars);
+        let window = app.world_mut().spawn((Window::default(), PrimaryWindow)).id();
+        app.world_mut().spawn(Observer::new());
+        let avatar = app.world_mut().spawn(Transform::default()).id();
+        let mut model = BodyModel::default();
+        model.set_avatar(avatar);
+        app.world_mut().spawn((Body::Earth, model));
+        app.update();
+        let before = app.world().get::<Transform>(avatar).unwrap().scale.x;
+
+        let mut win = app.world_mut().get_mut::<Window>(window).unwrap();
+        win.resolution.set_physical_resolution(2560, 1440);
+        let (width, height) = (win.width(), win.height());
+        app.world_mut().send_event(WindowResized { window, width, height });
+        app.update();
+
+        let world = app.world();
+        let min_ang = VisibilityTuning::default().min_ang_res(world.get::<Window>(window).unwrap());
+        let sim = world.resource::<Simulation>();
+        let exp = avatar_radius(sim, Body::Earth, min_ang, 80., ScaleMode::Visible);
+        let act = world.get::<Transform>(avatar).unwrap().scale.x;
+        assert_eq!(act, exp);
+        assert!(act < before, "{} {}", act, before);
+    }
+
+    #[test]
+    fn test_scale_mode_toggle() {
+        assert_eq!(ScaleMode::Visible.toggle(), ScaleMode::Realistic);
+        assert_eq!(ScaleMode::Realistic.toggle(), ScaleMode::Visible);
+    }
+}
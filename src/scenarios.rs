@@ -0,0 +1,262 @@
+use bevy::prelude::*;
+
+use crate::{
+    eclipses::EclipseState,
+    follow_camera::CameraTarget,
+    layers::Layers,
+    measure::MeasurePair,
+    occultations::Occultations,
+    reference_frame::ReferenceFrame,
+    scene::{self, BodyDef},
+    selection::Selected,
+    uom_wrapper::si::f64::Time,
+    Simulation, Spacecraft,
+};
+
+// The key showing and hiding the scenario menu
+const MENU_KEY: KeyCode = KeyCode::Delete;
+
+const FONT_SIZE: f32 = 14.;
+
+// This is a set of bodies to simulate, the built-in ones along with the custom bodies, starting at
+// a Julian Date. It is usually read from a scene file. See read_scenario.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    pub epoch: Time,
+    pub bodies: Vec<BodyDef>,
+}
+
+impl Scenario {
+    // Return the scenario of only the built-in bodies starting at the given Julian Date
+    pub fn solar_system(epoch: Time) -> Self {
+        Self {
+            name: String::from("Solar System"),
+            epoch,
+            bodies: Vec::new(),
+        }
+    }
+}
+
+// These are the scenarios that can be switched between from the menu. Switching to one restarts
+// the simulation at its epoch with only its bodies, despawning everything drawn for the bodies of
+// the one before and resetting the selection, the camera target, the measured pair, and the
+// reference frame. A spacecraft is dropped, since it was launched at another time. When barycentric
+// is set, each scenario is recentered on its barycenter as it is loaded.
+#[derive(Debug, Resource)]
+pub struct Scenarios {
+    pub list: Vec<Scenario>,
+    pub current: usize,
+    pub barycentric: bool,
+    requested: Option<usize>,
+}
+
+impl Scenarios {
+    pub fn new(list: Vec<Scenario>, current: usize) -> Self {
+        Self {
+            list,
+            current,
+            barycentric: false,
+            requested: None,
+        }
+    }
+
+    pub fn current(&self) -> &Scenario {
+        &self.list[self.current]
+    }
+
+    // Load the scenario at the given index in the list at the start of the next frame
+    pub fn switch_to(&mut self, idx: usize) {
+        if idx < self.list.len() {
+            self.requested = Some(idx);
+        }
+    }
+}
+
+// Set a resource back to its default, if the app has it
+fn reset<R: Resource + Default + PartialEq>(world: &mut World) {
+    if let Some(mut res) = world.get_resource_mut::<R>() {
+        res.set_if_neq(R::default());
+    }
+}
+
+// The requested scenario is loaded before anything else runs in the frame, so no system sees the
+// bodies of both scenarios. The simulation is replaced rather than modified, so systems remembering
+// something about it can tell it was restarted with is_added.
+fn load_requested(world: &mut World) {
+    let Some(idx) = world.resource_mut::<Scenarios>().requested.take() else {
+        return;
+    };
+
+    crate::despawn_bodies(world);
+    let scenarios = world.resource::<Scenarios>();
+    let scenario = scenarios.list[idx].clone();
+    let mut sim = world.resource::<Simulation>().restart_at(scenario.epoch);
+    scene::add_defs(&mut sim, &scenario.bodies);
+    if scenarios.barycentric {
+        sim.recenter_on_barycenter();
+    }
+    world.remove_resource::<Simulation>();
+    world.insert_resource(sim);
+    world.resource_mut::<Scenarios>().current = idx;
+
+    reset::<Selected>(world);
+    reset::<CameraTarget>(world);
+    reset::<MeasurePair>(world);
+    reset::<ReferenceFrame>(world);
+    reset::<EclipseState>(world);
+    if let Some(mut occultations) = world.get_resource_mut::<Occultations>() {
+        occultations.forget();
+    }
+    world.remove_resource::<Spacecraft>();
+
+    crate::spawn_bodies(world);
+    // The layers are applied to the new bodies as they are at startup.
+    if let Some(mut layers) = world.get_resource_mut::<Layers>() {
+        layers.set_changed();
+    }
+    info!("Scenario: {}", scenario.name);
+}
+
+#[derive(Component)]
+struct ScenarioMenu;
+
+// This is the menu item loading the scenario at its index in the list.
+#[derive(Component)]
+struct ScenarioItem(usize);
+
+fn item_text(scenarios: &Scenarios, idx: usize) -> String {
+    let marker = if idx == scenarios.current { ">" } else { " " };
+    format!("{} {}", marker, scenarios.list[idx].name)
+}
+
+fn create_menu(scenarios: Res<Scenarios>, mut commands: Commands) {
+    let text_style = TextStyle {
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(60.),
+                    right: Val::Px(10.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ScenarioMenu,
+        ))
+        .with_children(|menu| {
+            for idx in 0..scenarios.list.len() {
+                menu.spawn((
+                    TextBundle::from_section(item_text(&scenarios, idx), text_style.clone()),
+                    Interaction::default(),
+                    ScenarioItem(idx),
+                ));
+            }
+        });
+}
+
+fn toggle_menu(
+    input: Res<ButtonInput<KeyCode>>,
+    mut menu: Query<&mut Visibility, With<ScenarioMenu>>,
+) {
+    if input.just_pressed(MENU_KEY) {
+        for mut vis in &mut menu {
+            *vis = match *vis {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+// Clicking a scenario other than the current one loads it.
+fn click_items(
+    mut scenarios: ResMut<Scenarios>,
+    items: Query<(&Interaction, &ScenarioItem), Changed<Interaction>>,
+) {
+    for (_, item) in items.iter().filter(|(int, _)| **int == Interaction::Pressed) {
+        if item.0 != scenarios.current {
+            scenarios.switch_to(item.0);
+        }
+    }
+}
+
+fn update_items(scenarios: Res<Scenarios>, mut items: Query<(&ScenarioItem, &mut Text)>) {
+    if !scenarios.is_changed() {
+        return;
+    }
+    for (item, mut text) in &mut items {
+        text.sections[0].value = item_text(&scenarios, item.0);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Startup, create_menu)
+        .add_systems(First, load_requested)
+        .add_systems(Update, (toggle_menu, click_items, update_items).chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        clock::SimClock, default_epoch, scene::parse_scenario, setup_simulation, simulation::Body,
+        BodyEntity, BodyModel, SpawnBodies,
+    };
+
+    // Return the bodies that have models in the world
+    fn modeled(app: &mut App) -> Vec<Body> {
+        let world = app.world_mut();
+        world.query_filtered::<&Body, With<BodyModel>>().iter(world).copied().collect()
+    }
+
+    #[test]
+    fn test_switching_scenarios_replaces_bodies() {
+        let vulcan = include_str!("../examples/vulcan.toml");
+        let text = format!("epoch_jd = 2460000.5\n{}", vulcan);
+        let scenario = parse_scenario(&text, "vulcan", default_epoch()).unwrap();
+        let epoch = scenario.epoch;
+        let list = vec![Scenario::solar_system(default_epoch()), scenario];
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        setup_simulation(&mut app, default_epoch())
+            .insert_resource(Scenarios::new(list, 0))
+            .insert_resource(SimClock { paused: true, ..default() })
+            .insert_resource(Selected(Some(Body::Mars)))
+            .add_systems(SpawnBodies, crate::create_body_models)
+            .add_systems(First, load_requested);
+        crate::spawn_bodies(app.world_mut());
+        let count = modeled(&mut app).len();
+
+        // Something hung off of a body is despawned along with it.
+        let world = app.world_mut();
+        let model = world.query_filtered::<Entity, With<BodyEntity>>().iter(world).next().unwrap();
+        let child = world.spawn_empty().set_parent(model).id();
+
+        app.world_mut().resource_mut::<Scenarios>().switch_to(1);
+        app.update();
+        assert!(app.world().get_entity(child).is_none());
+        assert_eq!(app.world().resource::<Simulation>().current_time(), epoch);
+        assert_eq!(app.world().resource::<Selected>().0, None);
+        let bodies = modeled(&mut app);
+        assert_eq!(bodies.len(), count + 1);
+        assert!(bodies.contains(&Body::Custom(0)));
+
+        app.world_mut().resource_mut::<Scenarios>().switch_to(0);
+        app.update();
+        assert_eq!(app.world().resource::<Simulation>().current_time(), default_epoch());
+        let bodies = modeled(&mut app);
+        assert_eq!(bodies.len(), count);
+        assert!(!bodies.contains(&Body::Custom(0)));
+        assert_eq!(app.world().resource::<Scenarios>().current, 0);
+    }
+}
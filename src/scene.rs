@@ -0,0 +1,470 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use nalgebra::Vector3;
+use serde::{
+    de::{self, value::MapDeserializer, value::SeqDeserializer, IntoDeserializer, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+use toml_edit::{DocumentMut, Value};
+
+use crate::{
+    kepler_orbit as kepler,
+    scenarios::Scenario,
+    simulation::{Body, BodyProperties, OrbitalElements},
+    uom_wrapper::si::{
+        angle::degree,
+        f64::{Angle, Length, LuminousIntensity, Mass, SolidAngle, Time},
+        length::{astronomical_unit, kilometer},
+        luminous_intensity::candela,
+        mass::kilogram,
+        solid_angle::steradian,
+        time::day,
+    },
+    Simulation,
+};
+
+// The most custom bodies a scene may define
+const MAX_BODIES: usize = u8::MAX as usize + 1;
+
+// This is a body's heliocentric position and velocity at a Julian Date, relative to the ecliptic
+// and equinox of J2000. This is how JPL Horizons reports a body's state when asked for vectors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StateVector {
+    pub epoch_jd: f64,
+    pub position_km: Vector3<f64>,
+    pub velocity_km_s: Vector3<f64>,
+}
+
+impl StateVector {
+    fn is_finite(&self) -> bool {
+        self.epoch_jd.is_finite()
+            && self.position_km.iter().chain(&self.velocity_km_s).all(|x| x.is_finite())
+    }
+
+    // Convert the state into the elements of the body's orbit about the Sun and the time of its
+    // nearest periapsis passage.
+    pub fn to_elements(&self, sun_mass: Mass, body_mass: Mass) -> (OrbitalElements, Time) {
+        let elements = kepler::elements_from_state(
+            sun_mass,
+            body_mass,
+            &(self.position_km * 1e3),
+            &(self.velocity_km_s * 1e3),
+        );
+        let periapsis_time = kepler::periapsis_time(
+            sun_mass,
+            body_mass,
+            elements.semimajor_axis,
+            elements.eccentricity,
+            elements.true_anomaly,
+            Time::new::<day>(self.epoch_jd),
+        );
+        (elements, periapsis_time)
+    }
+}
+
+// This defines a body added to the simulation from a scene file. The body orbits the Sun.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BodyDef {
+    pub name: String,
+    pub color: Color,
+    pub mass_kg: f64,
+    pub radius_km: f64,
+    // The light the body emits in lumens. Most bodies emit none.
+    pub luminosity_lm: f64,
+    pub semimajor_axis_au: f64,
+    pub eccentricity: f64,
+    pub inclination_deg: f64,
+    pub ascending_node_deg: f64,
+    pub periapsis_argument_deg: f64,
+    // The Julian Date of one of the body's periapsis passages
+    pub periapsis_jd: f64,
+}
+
+// This is a [[body]] table as it is written in a scene file. A body's orbit is given either by its
+// orbital elements or by its state vector, so the keys of both are optional here.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BodyTable {
+    name: String,
+    color: Option<String>,
+    mass_kg: f64,
+    radius_km: f64,
+    #[serde(default)]
+    luminosity_lm: f64,
+    semimajor_axis_au: Option<f64>,
+    eccentricity: Option<f64>,
+    inclination_deg: Option<f64>,
+    ascending_node_deg: Option<f64>,
+    periapsis_argument_deg: Option<f64>,
+    periapsis_jd: Option<f64>,
+    epoch_jd: Option<f64>,
+    position_km: Option<[f64; 3]>,
+    velocity_km_s: Option<[f64; 3]>,
+}
+
+impl BodyTable {
+    fn has_elements(&self) -> bool {
+        [
+            self.semimajor_axis_au,
+            self.eccentricity,
+            self.inclination_deg,
+            self.ascending_node_deg,
+            self.periapsis_argument_deg,
+            self.periapsis_jd,
+        ]
+        .iter()
+        .any(Option::is_some)
+    }
+}
+
+impl TryFrom<BodyTable> for BodyDef {
+    type Error = String;
+
+    fn try_from(table: BodyTable) -> Result<Self, String> {
+        let name = table.name.clone();
+        let required =
+            |val: Option<f64>, key: &str| val.ok_or_else(|| format!("{}: missing {}", name, key));
+        let color = match &table.color {
+            None => Color::WHITE,
+            Some(hex) => Srgba::hex(hex)
+                .map(Color::from)
+                .map_err(|_| format!("{}: color is not a hex color like \"#ffa040\"", name))?,
+        };
+        // The orbit is given either by its elements or by the body's state at an epoch.
+        let orbit = if let Some(position_km) = table.position_km {
+            if table.has_elements() {
+                return Err(format!(
+                    "{}: give either orbital elements or a state vector, not both",
+                    name
+                ));
+            }
+            let velocity_km_s = table
+                .velocity_km_s
+                .ok_or_else(|| format!("{}: missing velocity_km_s", name))?;
+            let state = StateVector {
+                epoch_jd: required(table.epoch_jd, "epoch_jd")?,
+                position_km: Vector3::from(position_km),
+                velocity_km_s: Vector3::from(velocity_km_s),
+            };
+            if !state.is_finite() {
+                return Err(format!("{}: the state vector and epoch_jd must be finite", name));
+            }
+            let sun_mass = BodyProperties::properties_for(Body::Sun).mass();
+            let (elements, periapsis_time) =
+                state.to_elements(sun_mass, Mass::new::<kilogram>(table.mass_kg));
+            [
+                elements.semimajor_axis.get::<astronomical_unit>(),
+                elements.eccentricity,
+                elements.inclination.get::<degree>(),
+                elements.ascending_node.get::<degree>(),
+                elements.periapsis_argument.get::<degree>(),
+                periapsis_time.get::<day>(),
+            ]
+        } else {
+            [
+                required(table.semimajor_axis_au, "semimajor_axis_au")?,
+                required(table.eccentricity, "eccentricity")?,
+                table.inclination_deg.unwrap_or(0.),
+                table.ascending_node_deg.unwrap_or(0.),
+                table.periapsis_argument_deg.unwrap_or(0.),
+                required(table.periapsis_jd, "periapsis_jd")?,
+            ]
+        };
+        let def = Self {
+            name: table.name,
+            color,
+            mass_kg: table.mass_kg,
+            radius_km: table.radius_km,
+            luminosity_lm: table.luminosity_lm,
+            semimajor_axis_au: orbit[0],
+            eccentricity: orbit[1],
+            inclination_deg: orbit[2],
+            ascending_node_deg: orbit[3],
+            periapsis_argument_deg: orbit[4],
+            periapsis_jd: orbit[5],
+        };
+        def.validate()?;
+        Ok(def)
+    }
+}
+
+// This reads a TOML value into anything deriving Deserialize. toml_edit is built without its serde
+// support, so the scene's tables are read through this instead.
+struct ValueDeserializer<'a>(&'a Value);
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::String(val) => visitor.visit_str(val.value()),
+            Value::Integer(val) => visitor.visit_i64(*val.value()),
+            Value::Float(val) => visitor.visit_f64(*val.value()),
+            Value::Boolean(val) => visitor.visit_bool(*val.value()),
+            Value::Datetime(val) => visitor.visit_string(val.value().to_string()),
+            Value::Array(vals) => {
+                let mut seq = SeqDeserializer::new(vals.iter().map(ValueDeserializer));
+                let val = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(val)
+            }
+            Value::InlineTable(table) => {
+                let entries = table.iter().map(|(key, val)| (key, ValueDeserializer(val)));
+                let mut map = MapDeserializer::new(entries);
+                let val = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(val)
+            }
+        }
+    }
+
+    // A key that is present always has a value.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, de::value::Error> for ValueDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl BodyDef {
+    // The orbit must be closed, so the body stays in the scene.
+    fn validate(&self) -> Result<(), String> {
+        let check = |ok: bool, what: &str| {
+            if ok {
+                Ok(())
+            } else {
+                Err(format!("{}: {}", self.name, what))
+            }
+        };
+        check(self.mass_kg > 0., "mass_kg must be positive")?;
+        check(self.radius_km > 0., "radius_km must be positive")?;
+        check(self.luminosity_lm >= 0., "luminosity_lm must not be negative")?;
+        check(
+            (0. ..1.).contains(&self.eccentricity),
+            "eccentricity must be at least 0 and less than 1 for a closed orbit",
+        )?;
+        check(self.semimajor_axis_au > 0., "semimajor_axis_au must be positive")?;
+        let orbit = [
+            self.inclination_deg,
+            self.ascending_node_deg,
+            self.periapsis_argument_deg,
+            self.periapsis_jd,
+        ];
+        check(
+            orbit.iter().all(|x| x.is_finite()),
+            "the orbit's angles and periapsis_jd must be finite",
+        )
+    }
+
+    pub fn properties(&self) -> BodyProperties {
+        let deg = Angle::new::<degree>;
+        let luminosity = LuminousIntensity::new::<candela>(self.luminosity_lm)
+            * SolidAngle::new::<steradian>(1.);
+        BodyProperties::new(
+            Mass::new::<kilogram>(self.mass_kg),
+            Length::new::<kilometer>(self.radius_km),
+        )
+        .with_luminosity(luminosity)
+        .with_sun_orbit(
+            Length::new::<astronomical_unit>(self.semimajor_axis_au),
+            self.eccentricity,
+            deg(self.inclination_deg),
+            deg(self.ascending_node_deg),
+            deg(self.periapsis_argument_deg),
+            Time::new::<day>(self.periapsis_jd),
+        )
+    }
+}
+
+// Parse the bodies defined in a scene. Each body is a [[body]] table.
+pub fn parse_scene(text: &str) -> Result<Vec<BodyDef>, String> {
+    let doc: DocumentMut = text.parse().map_err(|err| format!("not a TOML document: {}", err))?;
+    let Some(item) = doc.get("body") else {
+        return Ok(Vec::new());
+    };
+    let tables = item.as_array_of_tables().ok_or("body must be an array of [[body]] tables")?;
+    if tables.len() > MAX_BODIES {
+        return Err(format!("a scene can define at most {} bodies", MAX_BODIES));
+    }
+    tables
+        .iter()
+        .enumerate()
+        .map(|(idx, table)| {
+            // A body whose table can't be read is named by its name if it has one, or else by its
+            // place in the scene.
+            let label = match table.get("name").and_then(|name| name.as_str()) {
+                Some(name) => name.to_string(),
+                None => format!("body {}", idx + 1),
+            };
+            let value = Value::InlineTable(table.clone().into_inline_table());
+            let table = BodyTable::deserialize(ValueDeserializer(&value))
+                .map_err(|err| format!("{}: {}", label, err))?;
+            BodyDef::try_from(table)
+        })
+        .collect()
+}
+
+// Read the bodies defined in a scene file
+pub fn read_scene(path: &str) -> Result<Vec<BodyDef>, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    parse_scene(&text).map_err(|err| format!("{}: {}", path, err))
+}
+
+// Parse a scenario, a scene that may also be given a name and the Julian Date it starts at with
+// the top-level keys name and epoch_jd. A scenario without them is named default_name and starts
+// at default_epoch.
+pub fn parse_scenario(
+    text: &str,
+    default_name: &str,
+    default_epoch: Time,
+) -> Result<Scenario, String> {
+    let doc: DocumentMut = text.parse().map_err(|err| format!("not a TOML document: {}", err))?;
+    let name = match doc.get("name") {
+        None => default_name.to_string(),
+        Some(item) => item.as_str().ok_or("name must be a string")?.to_string(),
+    };
+    let epoch = match doc.get("epoch_jd") {
+        None => default_epoch,
+        Some(item) => {
+            let jd = item.as_float().or_else(|| item.as_integer().map(|jd| jd as f64));
+            match jd.filter(|jd| jd.is_finite()) {
+                Some(jd) => Time::new::<day>(jd),
+                None => return Err(String::from("epoch_jd must be a finite number")),
+            }
+        }
+    };
+    Ok(Scenario {
+        name,
+        epoch,
+        bodies: parse_scene(text)?,
+    })
+}
+
+// Read a scenario file. A scenario without a name is named after its file.
+pub fn read_scenario(path: &str, default_epoch: Time) -> Result<Scenario, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let stem = Path::new(path).file_stem().map(|stem| stem.to_string_lossy());
+    parse_scenario(&text, stem.as_deref().unwrap_or(path), default_epoch)
+        .map_err(|err| format!("{}: {}", path, err))
+}
+
+// Add the defined bodies to the simulation
+pub(crate) fn add_defs(sim: &mut Simulation, defs: &[BodyDef]) {
+    for (idx, def) in defs.iter().enumerate() {
+        sim.add_custom_body(Body::Custom(idx as u8), &def.name, &def.color, def.properties());
+    }
+}
+
+// Add the defined bodies to the simulation. This must be called after setup and before the app
+// runs, so the bodies are drawn along with the built-in ones.
+pub fn add_bodies<'a>(app: &'a mut App, defs: &[BodyDef]) -> &'a mut App {
+    add_defs(&mut app.world_mut().resource_mut::<Simulation>(), defs);
+    app
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    const VULCAN: &str = include_str!("../examples/vulcan.toml");
+    const COUNTER_EARTH: &str = include_str!("../examples/counter_earth.toml");
+
+    #[test]
+    fn test_parse_scene_example() {
+        let defs = parse_scene(VULCAN).unwrap();
+        assert_eq!(defs.len(), 1);
+        assert_eq!(defs[0].name, "Vulcan");
+        assert_eq!(defs[0].semimajor_axis_au, 0.21);
+    }
+
+    #[test]
+    fn test_parse_scene_rejects_open_orbit() {
+        let text = VULCAN.replace("eccentricity = 0.05", "eccentricity = 1.2");
+        let err = parse_scene(&text).unwrap_err();
+        assert!(err.contains("Vulcan: eccentricity"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_scene_rejects_missing_field() {
+        let text = VULCAN.replace("semimajor_axis_au = 0.21", "");
+        assert_eq!(parse_scene(&text), Err(String::from("Vulcan: missing semimajor_axis_au")));
+    }
+
+    #[test]
+    fn test_loaded_body_in_bodies() {
+        let mut sim = Simulation::init_at(default_epoch());
+        let def = &parse_scene(VULCAN).unwrap()[0];
+        sim.add_custom_body(Body::Custom(0), &def.name, &def.color, def.properties());
+        assert!(sim.bodies().contains(&Body::Custom(0)));
+        assert_eq!(sim.name_of(Body::Custom(0)), "Vulcan");
+        let sun_dist = sim.position_of(Body::Custom(0)).distance(sim.position_of(Body::Sun));
+        assert!(sun_dist > 0.19 && sun_dist < 0.23, "{}", sun_dist);
+    }
+
+    #[test]
+    fn test_parse_scene_state_vector() {
+        let def = &parse_scene(COUNTER_EARTH).unwrap()[0];
+        assert!((def.semimajor_axis_au - 1.).abs() < 0.001, "{}", def.semimajor_axis_au);
+        assert!((def.eccentricity - 0.016_7).abs() < 0.000_5, "{}", def.eccentricity);
+    }
+
+    #[test]
+    fn test_parse_scene_rejects_unknown_field() {
+        let text = VULCAN.replace("eccentricity =", "eccentricty = 0.1\neccentricity =");
+        let err = parse_scene(&text).unwrap_err();
+        assert!(err.starts_with("Vulcan: unknown field `eccentricty`"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_scene_rejects_wrong_type() {
+        let text = VULCAN.replace("radius_km = 2000", "radius_km = \"2000\"");
+        let err = parse_scene(&text).unwrap_err();
+        assert!(err.starts_with("Vulcan: invalid type: string"), "{}", err);
+        let text = COUNTER_EARTH.replace("6.164166e2]", "6.164166e2, 1]");
+        let err = parse_scene(&text).unwrap_err();
+        assert!(err.starts_with("Antichthon: invalid length 4"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_scenario() {
+        let text = format!("name = \"Inner\"\nepoch_jd = 2460000.5\n{}", VULCAN);
+        let scenario = parse_scenario(&text, "vulcan", default_epoch()).unwrap();
+        assert_eq!(scenario.name, "Inner");
+        assert_eq!(scenario.epoch, Time::new::<day>(2_460_000.5));
+        assert_eq!(scenario.bodies, parse_scene(VULCAN).unwrap());
+
+        let scenario = parse_scenario(VULCAN, "vulcan", default_epoch()).unwrap();
+        assert_eq!(scenario.name, "vulcan");
+        assert_eq!(scenario.epoch, default_epoch());
+    }
+
+    #[test]
+    fn test_parse_scenario_rejects_wrong_type() {
+        let text = format!("epoch_jd = \"2023-01-01\"\n{}", VULCAN);
+        let err = parse_scenario(&text, "vulcan", default_epoch()).unwrap_err();
+        assert_eq!(err, "epoch_jd must be a finite number");
+        let text = format!("name = 3\n{}", VULCAN);
+        let err = parse_scenario(&text, "vulcan", default_epoch()).unwrap_err();
+        assert_eq!(err, "name must be a string");
+    }
+
+    #[test]
+    fn test_parse_scene_rejects_elements_with_state_vector() {
+        let text = COUNTER_EARTH.replace("epoch_jd =", "eccentricity = 0.1\nepoch_jd =");
+        let err = parse_scene(&text).unwrap_err();
+        assert!(err.contains("not both"), "{}", err);
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    render::view::screenshot::ScreenshotManager,
+    utils::SystemTime,
+    window::PrimaryWindow,
+};
+
+use crate::{uom_wrapper::si::time::day, Simulation};
+
+// This is where and when screenshots are saved
+#[derive(Resource)]
+pub struct ScreenshotCapture {
+    pub dir: PathBuf,
+    pub key: KeyCode,
+}
+
+impl Default for ScreenshotCapture {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("screenshots"),
+            key: KeyCode::F12,
+        }
+    }
+}
+
+// The name of a screenshot embeds the simulated Julian Date it shows. The wall clock time in
+// milliseconds since the Unix epoch keeps screenshots taken while the simulation is paused from
+// overwriting each other.
+fn screenshot_name(jd: f64, unix_ms: u128) -> String {
+    format!("solar-system_JD{:.5}_{}.png", jd, unix_ms)
+}
+
+// The screenshot is written once the frame has been rendered, so the simulation keeps running.
+fn capture_screenshot(
+    input: Res<ButtonInput<KeyCode>>,
+    capture: Res<ScreenshotCapture>,
+    sim: Res<Simulation>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+) {
+    if !input.just_pressed(capture.key) {
+        return;
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    if let Err(err) = std::fs::create_dir_all(&capture.dir) {
+        error!("Failed to create screenshot directory {}: {}", capture.dir.display(), err);
+        return;
+    }
+
+    let unix_ms = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |t| t.as_millis());
+    let path = capture.dir.join(screenshot_name(sim.current_time().get::<day>(), unix_ms));
+    match screenshots.save_screenshot_to_disk(window.single(), &path) {
+        Ok(()) => info!("Saving screenshot to {}", path.display()),
+        Err(err) => error!("Failed to save screenshot to {}: {}", path.display(), err),
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<ScreenshotCapture>().add_systems(Update, capture_screenshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshot_name() {
+        assert_eq!(
+            screenshot_name(2_459_945.5, 1_672_531_200_000),
+            "solar-system_JD2459945.50000_1672531200000.png"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState, InputSystem,
+    },
+    prelude::*,
+};
+
+use crate::{follow_camera::FlyToBody, selection::Selected, simulation::Body, Simulation};
+
+// The key opening the search box
+const OPEN_KEY: KeyCode = KeyCode::Slash;
+
+// The most suggestions listed when the text matches more than one body
+const MAX_SUGGESTIONS: usize = 5;
+
+const FONT_SIZE: f32 = 20.;
+
+// This is the text typed into the search box while it is open.
+#[derive(Default, Resource)]
+struct BodySearch {
+    open: bool,
+    text: String,
+}
+
+// This is the outcome of searching for a body by the start of its name.
+#[derive(Debug, PartialEq)]
+enum SearchResult {
+    Found(Body),
+    NotFound,
+    // The text starts the names of several bodies, none of which it names exactly.
+    Ambiguous(Vec<Body>),
+}
+
+// A body is found if the text is its whole name or only its name starts with the text, ignoring
+// case. This way, "Eris" finds Eris even though it could start another body's name.
+fn search(sim: &Simulation, text: &str) -> SearchResult {
+    let text = text.trim();
+    let found = if text.is_empty() { Vec::new() } else { sim.find_bodies(text) };
+    match found[..] {
+        [] => SearchResult::NotFound,
+        [body] => SearchResult::Found(body),
+        _ => match found.iter().find(|body| sim.name_of(**body).eq_ignore_ascii_case(text)) {
+            Some(body) => SearchResult::Found(*body),
+            None => SearchResult::Ambiguous(found),
+        },
+    }
+}
+
+// Return what the search box shows, the text typed followed by a cursor and any suggestions
+fn box_text(sim: &Simulation, text: &str) -> String {
+    let hint = match search(sim, text) {
+        _ if text.trim().is_empty() => String::from("type the start of a body's name"),
+        SearchResult::Found(body) => sim.name_of(body),
+        SearchResult::NotFound => String::from("no body has that name"),
+        SearchResult::Ambiguous(bodies) => {
+            let mut names: Vec<String> =
+                bodies.iter().take(MAX_SUGGESTIONS).map(|body| sim.name_of(*body)).collect();
+            if bodies.len() > MAX_SUGGESTIONS {
+                names.push(String::from("…"));
+            }
+            names.join(", ")
+        }
+    };
+    format!("Go to: {}_\n{}", text, hint)
+}
+
+#[derive(Component)]
+struct SearchBox;
+
+// The box is centered at the top of the window.
+fn create_box(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                width: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    visibility: Visibility::Hidden,
+                    background_color: Color::srgba(0., 0., 0., 0.7).into(),
+                    ..TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: FONT_SIZE,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )
+                    .with_style(Style {
+                        padding: UiRect::all(Val::Px(8.)),
+                        ..default()
+                    })
+                },
+                SearchBox,
+            ));
+        });
+}
+
+// / opens the search box. While it is open, the typed text goes into the box instead of
+// controlling the simulation. Enter flies the camera to the body found and selects it, and Escape
+// closes the box. This runs before the other systems read the keyboard, so it can hide the keys
+// from them.
+fn type_search(
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut typed: EventReader<KeyboardInput>,
+    sim: Res<Simulation>,
+    mut search_box: ResMut<BodySearch>,
+    mut selected: ResMut<Selected>,
+    mut fly_to: EventWriter<FlyToBody>,
+) {
+    if !search_box.open {
+        typed.clear();
+        if keys.just_pressed(OPEN_KEY) {
+            search_box.open = true;
+            search_box.text.clear();
+            keys.reset_all();
+        }
+        return;
+    }
+
+    for ev in typed.read() {
+        if ev.state != ButtonState::Pressed {
+            continue;
+        }
+        match &ev.logical_key {
+            Key::Character(chars) => search_box.text.push_str(chars),
+            Key::Space => search_box.text.push(' '),
+            Key::Backspace => {
+                search_box.text.pop();
+            }
+            Key::Escape => search_box.open = false,
+            Key::Enter => match search(&sim, &search_box.text) {
+                SearchResult::Found(body) => {
+                    selected.0 = Some(body);
+                    fly_to.send(FlyToBody(body));
+                    search_box.open = false;
+                    info!("Going to {}", sim.name_of(body));
+                }
+                _ => info!("No single body found for \"{}\"", search_box.text.trim()),
+            },
+            _ => {}
+        }
+    }
+    keys.reset_all();
+}
+
+fn update_box(
+    sim: Res<Simulation>,
+    search_box: Res<BodySearch>,
+    mut text: Query<(&mut Text, &mut Visibility), With<SearchBox>>,
+) {
+    if !search_box.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in &mut text {
+        *visibility = if search_box.open { Visibility::Visible } else { Visibility::Hidden };
+        text.sections[0].value = box_text(&sim, &search_box.text);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<BodySearch>()
+        .add_systems(Startup, create_box)
+        .add_systems(PreUpdate, type_search.after(InputSystem))
+        .add_systems(Update, update_box)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_search_prefix_ignores_case() {
+        let sim = Simulation::init_at(default_epoch());
+        assert_eq!(search(&sim, "jup"), SearchResult::Found(Body::Jupiter));
+        assert_eq!(search(&sim, "GANY"), SearchResult::Found(Body::Ganymede));
+        assert_eq!(search(&sim, "make"), SearchResult::Found(Body::Makemake));
+    }
+
+    #[test]
+    fn test_search_ambiguous() {
+        let sim = Simulation::init_at(default_epoch());
+        assert_eq!(
+            search(&sim, "e"),
+            SearchResult::Ambiguous(vec![Body::Earth, Body::Eris, Body::Europa])
+        );
+        assert_eq!(search(&sim, "xyz"), SearchResult::NotFound);
+        assert_eq!(search(&sim, " "), SearchResult::NotFound);
+    }
+
+    #[test]
+    fn test_search_exact_name_wins() {
+        let mut sim = Simulation::init_at(default_epoch());
+        let props = crate::BodyProperties::properties_for(Body::Ceres);
+        sim.add_custom_body(Body::Custom(0), "Marsupial", &Color::WHITE, props);
+        assert_eq!(search(&sim, "mars"), SearchResult::Found(Body::Mars));
+        assert_eq!(search(&sim, "mar"), SearchResult::Ambiguous(vec![Body::Mars, Body::Custom(0)]));
+    }
+
+    #[test]
+    fn test_box_text_suggests() {
+        let sim = Simulation::init_at(default_epoch());
+        assert_eq!(box_text(&sim, "e"), "Go to: e_\nEarth, Eris, Europa");
+    }
+}
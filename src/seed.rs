@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+// This seeds all of the procedural content, like the asteroid belt, so the same seed always
+// produces the same scene. Each generator draws from its own stream, so adding a generator
+// doesn't change what the others produce.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Resource)]
+pub struct SimSeed(pub u64);
+
+impl Default for SimSeed {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl SimSeed {
+    // Return a random number generator for the given stream. Stream 0 is seeded with the seed
+    // itself.
+    pub fn rng(&self, stream: u64) -> fastrand::Rng {
+        fastrand::Rng::with_seed(self.0 ^ stream.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sim_seed_streams_differ() {
+        let seed = SimSeed(7);
+        assert_eq!(seed.rng(1).u64(..), seed.rng(1).u64(..));
+        assert_ne!(seed.rng(0).u64(..), seed.rng(1).u64(..));
+    }
+}
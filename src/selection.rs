@@ -0,0 +1,128 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    simulation::Body, uom_wrapper::si::length::astronomical_unit, BodyModel, Simulation,
+};
+
+// This is the body the user last clicked on, if any.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub struct Selected(pub Option<Body>);
+
+// Return the distance along a ray to where it first enters a sphere. None is returned if the ray
+// misses the sphere or the sphere is behind the ray's origin.
+fn ray_sphere_distance(origin: Vec3, dir: Dir3, center: Vec3, radius: f32) -> Option<f32> {
+    let to_center = center - origin;
+    let closest = to_center.dot(*dir);
+    let miss_sq = to_center.length_squared() - closest * closest;
+    let half_chord_sq = radius * radius - miss_sq;
+    if half_chord_sq < 0. {
+        return None;
+    }
+    let half_chord = half_chord_sq.sqrt();
+    if closest + half_chord < 0. {
+        None
+    } else {
+        Some((closest - half_chord).max(0.))
+    }
+}
+
+// Return whether either shift key is held. Shift-clicking measures rather than selects.
+pub fn shift_held(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+// Return the ray from the camera through the cursor. The scene may be rendered at a different
+// resolution than the window's, so the cursor is scaled to the camera's viewport.
+pub fn cursor_ray(window: &Window, cam: &Camera, cam_trans: &GlobalTransform) -> Option<Ray3d> {
+    let scale = cam.logical_viewport_size()? / window.size();
+    cam.viewport_to_world(cam_trans, window.cursor_position()? * scale)
+}
+
+// Return the body whose avatar is under the cursor. When avatars overlap, the one closest to the
+// camera is returned.
+pub fn body_under_cursor(
+    window: &Window,
+    cam: &Camera,
+    cam_trans: &GlobalTransform,
+    bodies: &Query<(&Body, &BodyModel)>,
+) -> Option<Body> {
+    let ray = cursor_ray(window, cam, cam_trans)?;
+    bodies
+        .iter()
+        .filter_map(|(body, model)| {
+            ray_sphere_distance(ray.origin, ray.direction, *model.position(), model.avatar_radius())
+                .map(|dist| (*body, dist))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(body, _)| body)
+}
+
+// Clicking the left mouse button selects the body whose avatar is under the cursor. Clicking empty
+// space clears the selection. Clicks on an inset view, like the minimap, are left to it, as are
+// clicks on a UI control, like a check box of the layers panel.
+fn pick(
+    (buttons, keys): (Res<ButtonInput<MouseButton>>, Res<ButtonInput<KeyCode>>),
+    sim: Res<Simulation>,
+    mut selected: ResMut<Selected>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    cam: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    (insets, controls): (Query<&Camera, Without<Camera3d>>, Query<&Interaction>),
+    bodies: Query<(&Body, &BodyModel)>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) || shift_held(&keys) {
+        return;
+    }
+    if controls.iter().any(|interaction| *interaction != Interaction::None) {
+        return;
+    }
+    let window = window.single();
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let over_inset = |inset: &Camera| {
+        inset.is_active
+            && inset.viewport.is_some()
+            && inset.logical_viewport_rect().is_some_and(|rect| rect.contains(cursor))
+    };
+    if insets.iter().any(over_inset) {
+        return;
+    }
+    let (cam, cam_trans) = cam.single();
+    let hit = body_under_cursor(window, cam, cam_trans, &bodies);
+
+    selected.0 = hit;
+    if let Some(body) = hit {
+        let sun_dist = sim.distance_between(body, Body::Sun).get::<astronomical_unit>();
+        info!(
+            "Selected {}: {:.4} AU from the Sun, moving at {:.4} AU/day",
+            sim.name_of(body),
+            sun_dist,
+            sim.velocity_of(body).length(),
+        );
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<Selected>().add_systems(Update, pick)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ray_sphere_distance_hit() {
+        let dist = ray_sphere_distance(Vec3::ZERO, Dir3::X, Vec3::new(10., 0.5, 0.), 1.);
+        assert!(dist.is_some_and(|d| d > 9. && d < 10.), "{:?}", dist);
+    }
+
+    #[test]
+    fn test_ray_sphere_distance_miss() {
+        assert_eq!(ray_sphere_distance(Vec3::ZERO, Dir3::X, Vec3::new(10., 2., 0.), 1.), None)
+    }
+
+    #[test]
+    fn test_ray_sphere_distance_behind() {
+        assert_eq!(ray_sphere_distance(Vec3::ZERO, Dir3::X, Vec3::new(-10., 0., 0.), 1.), None)
+    }
+}
@@ -0,0 +1,105 @@
+use crate::uom_wrapper::si::f64::{Angle, Length};
+
+// This describes the conical shadow a spherical body casts when lit by a larger spherical light
+// source. The umbra is the region where the source is completely hidden, and the penumbra is where
+// it is partially hidden. See https://en.wikipedia.org/wiki/Umbra,_penumbra_and_antumbra
+#[derive(Clone, Copy, Debug)]
+pub struct Shadow {
+    umbra_length: Length,
+    penumbra_apex_distance: Length,
+    umbra_half_angle: Angle,
+    penumbra_half_angle: Angle,
+}
+
+impl Shadow {
+    // source_radius is the radius of the light source, and distance is the separation of its center
+    // and the body's center.
+    pub fn new(source_radius: Length, body_radius: Length, distance: Length) -> Self {
+        Self {
+            umbra_length: distance * body_radius / (source_radius - body_radius),
+            penumbra_apex_distance: distance * body_radius / (source_radius + body_radius),
+            umbra_half_angle: ((source_radius - body_radius) / distance).asin(),
+            penumbra_half_angle: ((source_radius + body_radius) / distance).asin(),
+        }
+    }
+
+    // The distance from the body's center to the tip of the umbra
+    pub fn umbra_length(&self) -> Length {
+        self.umbra_length
+    }
+
+    pub fn umbra_half_angle(&self) -> Angle {
+        self.umbra_half_angle
+    }
+
+    pub fn penumbra_half_angle(&self) -> Angle {
+        self.penumbra_half_angle
+    }
+
+    // Return the radius of the umbra at the given distance behind the body's center. It is zero
+    // past the tip of the umbra.
+    pub fn umbra_radius_at(&self, distance: Length) -> Length {
+        let radius = (self.umbra_length - distance) * f64::from(self.umbra_half_angle.tan());
+        radius.max(Length::default())
+    }
+
+    // Return the radius of the penumbra at the given distance behind the body's center. The apex of
+    // the penumbra's cone is between the body and the light source.
+    pub fn penumbra_radius_at(&self, distance: Length) -> Length {
+        (self.penumbra_apex_distance + distance) * f64::from(self.penumbra_half_angle.tan())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::uom_wrapper::si::{angle::radian, length::meter};
+
+    use crate::test::{assert_abs_eq, assert_rel_eq};
+
+    use super::*;
+
+    fn mk_shadow() -> Shadow {
+        Shadow::new(Length::new::<meter>(3.), Length::new::<meter>(1.), Length::new::<meter>(10.))
+    }
+
+    #[test]
+    fn test_umbra_length() {
+        assert_rel_eq!(mk_shadow().umbra_length().get::<meter>(), 5.)
+    }
+
+    #[test]
+    fn test_umbra_half_angle() {
+        assert_rel_eq!(mk_shadow().umbra_half_angle().get::<radian>(), f64::asin(0.2))
+    }
+
+    #[test]
+    fn test_penumbra_half_angle() {
+        assert_rel_eq!(mk_shadow().penumbra_half_angle().get::<radian>(), f64::asin(0.4))
+    }
+
+    #[test]
+    fn test_umbra_radius_at_tip() {
+        let shadow = mk_shadow();
+        assert_eq!(shadow.umbra_radius_at(shadow.umbra_length()).get::<meter>(), 0.);
+        assert_eq!(shadow.umbra_radius_at(Length::new::<meter>(10.)).get::<meter>(), 0.);
+    }
+
+    #[test]
+    fn test_umbra_radius_at_body() {
+        let r = mk_shadow().umbra_radius_at(Length::new::<meter>(0.)).get::<meter>();
+        assert_rel_eq!(r, 5. * 0.2 / f64::sqrt(0.96))
+    }
+
+    #[test]
+    fn test_penumbra_radius_at_apex() {
+        let r = mk_shadow().penumbra_radius_at(Length::new::<meter>(-2.5)).get::<meter>();
+        assert_abs_eq!(r, 0.)
+    }
+
+    #[test]
+    fn test_penumbra_wider_than_umbra() {
+        let shadow = mk_shadow();
+        let d = Length::new::<meter>(1.);
+        assert!(shadow.penumbra_radius_at(d) > shadow.umbra_radius_at(d))
+    }
+}
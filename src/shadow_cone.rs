@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+
+use crate::{
+    simulation::Body,
+    uom_wrapper::si::{angle::radian, f64::Length, length::astronomical_unit},
+    DrawnPositions, Simulation,
+};
+
+// The number of lines drawn along the surface of each cone
+const CONE_LINES: usize = 16;
+
+// How far past the umbra's tip the penumbra is drawn as a multiple of the umbra's length
+const PENUMBRA_EXTENT: f32 = 1.5;
+
+const UMBRA_COLOR: Color = Color::srgba(0.8, 0.8, 1., 0.6);
+const PENUMBRA_COLOR: Color = Color::srgba(0.5, 0.5, 0.8, 0.3);
+
+// The bodies whose shadows can be shown in the order they are cycled through
+const TARGETS: [Body; 9] = [
+    Body::Mercury,
+    Body::Venus,
+    Body::Earth,
+    Body::Moon,
+    Body::Mars,
+    Body::Jupiter,
+    Body::Saturn,
+    Body::Uranus,
+    Body::Neptune,
+];
+
+// This controls which body has its shadow cone drawn.
+#[derive(Default, Resource)]
+pub struct ShadowConeView {
+    pub target: Option<Body>,
+}
+
+// U cycles through the bodies and off.
+fn control_shadow_cone(input: Res<ButtonInput<KeyCode>>, mut view: ResMut<ShadowConeView>) {
+    if input.just_pressed(KeyCode::KeyU) {
+        view.target = match view.target.and_then(|t| TARGETS.iter().position(|&b| b == t)) {
+            None => Some(TARGETS[0]),
+            Some(idx) => TARGETS.get(idx + 1).copied(),
+        };
+    }
+}
+
+// This draws the umbra and penumbra of the target extending away from the Sun. The cones are drawn
+// to true scale, so they are hidden inside of avatars that have been enlarged to be visible. They
+// are drawn every frame from where the avatars are drawn.
+fn draw_shadow_cone(
+    sim: Res<Simulation>,
+    view: Res<ShadowConeView>,
+    drawn: DrawnPositions,
+    mut gizmos: Gizmos,
+) {
+    let Some(target) = view.target else {
+        return;
+    };
+
+    let pos = drawn.of(target);
+    let Ok(axis) = Dir3::new(pos - drawn.of(Body::Sun)) else {
+        return;
+    };
+
+    let shadow = sim.shadow_of(target);
+    let umbra_len = shadow.umbra_length().get::<astronomical_unit>() as f32;
+    let penumbra_len = PENUMBRA_EXTENT * umbra_len;
+    let umbra_base = shadow.umbra_radius_at(Length::default()).get::<astronomical_unit>() as f32;
+    let penumbra_base =
+        shadow.penumbra_radius_at(Length::default()).get::<astronomical_unit>() as f32;
+    let penumbra_end = penumbra_base
+        + penumbra_len * (shadow.penumbra_half_angle().get::<radian>() as f32).tan();
+
+    let apex = pos + umbra_len * *axis;
+    let penumbra_center = pos + penumbra_len * *axis;
+    let (u, w) = axis.any_orthonormal_pair();
+    for i in 0..CONE_LINES {
+        let ang = std::f32::consts::TAU * i as f32 / CONE_LINES as f32;
+        let radial = ang.cos() * u + ang.sin() * w;
+        gizmos.line(pos + umbra_base * radial, apex, UMBRA_COLOR);
+        gizmos.line(
+            pos + penumbra_base * radial,
+            penumbra_center + penumbra_end * radial,
+            PENUMBRA_COLOR,
+        );
+    }
+    gizmos.circle(pos, axis, umbra_base, UMBRA_COLOR);
+    gizmos.circle(penumbra_center, axis, penumbra_end, PENUMBRA_COLOR);
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<ShadowConeView>()
+        .add_systems(Update, (control_shadow_cone, draw_shadow_cone.after(crate::update_avatars)))
+}
@@ -0,0 +1,140 @@
+use bevy::{prelude::*, time::Real};
+
+use crate::{clock::SimClock, uom_wrapper::si::time::second, Simulation};
+
+// The length in real seconds of the window the rate is averaged over
+const WINDOW_S: f64 = 1.;
+
+// These are the units the rate is described in, largest first, with their lengths in seconds.
+const UNITS: [(&str, f64); 5] = [
+    ("years", 365.25 * 86_400.),
+    ("days", 86_400.),
+    ("hours", 3_600.),
+    ("minutes", 60.),
+    ("seconds", 1.),
+];
+
+// This is the measured rate simulated time passes relative to real time, along with the rate the
+// clock asks for. The simulated time at the start of the window is in seconds since the Julian
+// Date epoch.
+#[derive(Default, Resource)]
+pub struct SimRate {
+    sim_secs_per_sec: Option<f64>,
+    requested_days_per_sec: f64,
+    window_start_sim_s: Option<f64>,
+    window_real_s: f64,
+}
+
+impl SimRate {
+    // Start measuring the rate when the clock asks for the given simulated days per real second.
+    pub fn requesting(requested_days_per_sec: f64) -> Self {
+        Self { requested_days_per_sec, ..default() }
+    }
+
+    // A human readable description of the rate, e.g., "≈ 3.2 days/sec"
+    pub fn description(&self) -> String {
+        match self.sim_secs_per_sec {
+            None => String::from("measuring..."),
+            Some(rate) => describe_rate(rate),
+        }
+    }
+
+    // The simulated days per real second the clock asked for when the measurement started
+    pub fn requested_days_per_sec(&self) -> f64 {
+        self.requested_days_per_sec
+    }
+}
+
+// Describe a rate of simulated seconds per real second using the largest sensible time unit.
+pub fn describe_rate(sim_secs_per_sec: f64) -> String {
+    let mag = sim_secs_per_sec.abs();
+    let (unit, len) = UNITS
+        .iter()
+        .find(|(_, len)| mag >= *len)
+        .unwrap_or(&UNITS[UNITS.len() - 1]);
+    format!("≈ {:.1} {}/sec", sim_secs_per_sec / len, unit)
+}
+
+// This measures how much simulated time passes over a window of real time, so it accounts for
+// everything affecting the rate, e.g., the time step size and the FixedUpdate frequency. When the
+// clock or the FixedUpdate rate changes the rate asked for, or another scenario restarts the
+// simulation, the measurement starts over, so a window doesn't mix the old rate with the new one.
+fn measure_rate(
+    (sim, clock): (Res<Simulation>, Res<SimClock>),
+    (real, fixed): (Res<Time<Real>>, Res<Time<Fixed>>),
+    mut rate: ResMut<SimRate>,
+) {
+    let requested = if clock.orbits_paused {
+        0.
+    } else {
+        clock.sim_days_per_sec(fixed.timestep().as_secs_f64())
+    };
+    if requested != rate.requested_days_per_sec || sim.is_added() {
+        *rate = SimRate::requesting(requested);
+    }
+
+    let now_s = sim.current_time().get::<second>();
+    let start_s = *rate.window_start_sim_s.get_or_insert(now_s);
+    rate.window_real_s += real.delta_seconds_f64();
+    if rate.window_real_s >= WINDOW_S {
+        rate.sim_secs_per_sec = Some((now_s - start_s) / rate.window_real_s);
+        rate.window_start_sim_s = Some(now_s);
+        rate.window_real_s = 0.;
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<SimRate>().add_systems(Update, measure_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::default_epoch;
+
+    #[test]
+    fn test_measurement_restarts_when_clock_changes() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(600)))
+            .insert_resource(Simulation::init_at(default_epoch()))
+            .init_resource::<SimClock>()
+            .init_resource::<SimRate>()
+            .add_systems(Update, measure_rate);
+        for _ in 0..3 {
+            app.update();
+        }
+        let rate = app.world().resource::<SimRate>();
+        assert!(rate.sim_secs_per_sec.is_some());
+        assert!(rate.requested_days_per_sec > 0.);
+
+        app.world_mut().resource_mut::<SimClock>().time_scale = 2.;
+        app.update();
+        let rate = app.world().resource::<SimRate>();
+        assert_eq!(rate.description(), "measuring...");
+        let scale = rate.requested_days_per_sec;
+        app.world_mut().resource_mut::<SimClock>().time_scale = 1.;
+        app.update();
+        let rate = app.world().resource::<SimRate>();
+        assert_eq!(rate.requested_days_per_sec, scale / 2.);
+    }
+
+    #[test]
+    fn test_describe_rate_days() {
+        assert_eq!(describe_rate(3.2 * 86_400.), "≈ 3.2 days/sec")
+    }
+
+    #[test]
+    fn test_describe_rate_slow() {
+        assert_eq!(describe_rate(0.5), "≈ 0.5 seconds/sec")
+    }
+
+    #[test]
+    fn test_describe_rate_years() {
+        assert_eq!(describe_rate(2. * 365.25 * 86_400.), "≈ 2.0 years/sec")
+    }
+}
@@ -5,29 +5,39 @@ use std::{
     fmt::{Debug, Formatter, Result},
 };
 
+use bevy::math::DVec3;
 use nalgebra::{Rotation3, Vector3};
-
-use strum::VariantArray;
+use serde::{Deserialize, Serialize};
 
 use crate::uom_wrapper::{
+    Conversion,
     fmt::DisplayStyle::Description,
     G,
     LuminousFlux,
     rem_euclid,
     si::{
-        angle::{degree, radian},
-        f64::{Angle, Length, LuminousIntensity, Mass, SolidAngle, Time},
-        length::{gigameter, kilometer, meter},
+        angle::{degree, radian, revolution},
+        energy::joule,
+        f64::{
+            Angle, Energy, Length, LuminousIntensity, Mass, MassDensity, SolidAngle, Time, Velocity,
+            Volume,
+        },
+        length::{self, gigameter, kilometer, meter},
         luminous_intensity::candela,
         mass::kilogram,
         solid_angle::steradian,
-        time::{day, second},
+        time::{day, hour, second},
+        velocity::{self, meter_per_second},
     },
 };
 
 use crate::kepler_orbit as kepler;
+use crate::photometry;
+use crate::shadow::Shadow;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, VariantArray)]
+// A custom body is one added at run time, e.g., from a scene file. It is identified by the order it
+// was added in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Body {
     Sun,
     Mercury,
@@ -38,13 +48,74 @@ pub enum Body {
     Saturn,
     Uranus,
     Neptune,
+    Ceres,
+    Pluto,
+    Haumea,
+    Makemake,
+    Eris,
+    Halley,
     Moon,
+    Io,
+    Europa,
+    Ganymede,
+    Callisto,
+    Triton,
+    Custom(u8),
+}
+
+impl Body {
+    // These are the built-in bodies.
+    pub const VARIANTS: &'static [Body] = &[
+        Body::Sun,
+        Body::Mercury,
+        Body::Venus,
+        Body::Earth,
+        Body::Mars,
+        Body::Jupiter,
+        Body::Saturn,
+        Body::Uranus,
+        Body::Neptune,
+        Body::Ceres,
+        Body::Pluto,
+        Body::Haumea,
+        Body::Makemake,
+        Body::Eris,
+        Body::Halley,
+        Body::Moon,
+        Body::Io,
+        Body::Europa,
+        Body::Ganymede,
+        Body::Callisto,
+        Body::Triton,
+    ];
+
+    // Return the body this one orbits. The Sun doesn't orbit anything.
+    pub fn primary(&self) -> Option<Body> {
+        match self {
+            Body::Sun => None,
+            Body::Moon => Some(Body::Earth),
+            Body::Io | Body::Europa | Body::Ganymede | Body::Callisto => Some(Body::Jupiter),
+            Body::Triton => Some(Body::Neptune),
+            _ => Some(Body::Sun),
+        }
+    }
+
+    // A moon is a body orbiting something other than the Sun.
+    pub fn is_moon(&self) -> bool {
+        !matches!(self.primary(), None | Some(Body::Sun))
+    }
+
+    pub fn is_comet(&self) -> bool {
+        matches!(self, Body::Halley)
+    }
 }
 
+#[derive(Clone, Deserialize, Serialize)]
 pub struct BodyProperties {
     luminosity: LuminousFlux,
     mass: Mass,
     radius: Length,
+    albedo: f64,
     primary: Option<Box<Self>>,
     eccentricity: f64,
     semimajor_axis: Length,
@@ -52,6 +123,37 @@ pub struct BodyProperties {
     ascending_node: Angle,
     periapsis_argument: Angle,
     periapsis_time: Time, // JD
+    axial_tilt: Angle,
+    rotation_period: Time,
+}
+
+// The speed of light in m/s
+pub const LIGHT_SPEED_MPS: f64 = 299_792_458.;
+
+// The most times the light time to a body is refined. Each refinement shrinks the error by a
+// factor of about v/c, so a few are plenty.
+const LIGHT_TIME_ITERATIONS: usize = 4;
+
+// The Galilean moons orbit close to Jupiter's equatorial plane. These are the approximate
+// inclination and ascending node of that plane relative to the ecliptic.
+const GALILEAN_INCLINATION_DEG: f64 = 2.2;
+const GALILEAN_ASCENDING_NODE_DEG: f64 = 337.;
+
+// The largest angle in radians the adaptive propagator lets the force between two bodies turn
+// between updates
+const MAX_STEP_ANGLE: f64 = 0.005;
+
+// These are the classical elements of a body's Kepler orbit about its primary along with where the
+// body is on that orbit. The angles are relative to the ecliptic. See
+// https://en.wikipedia.org/wiki/Orbital_elements
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitalElements {
+    pub semimajor_axis: Length,
+    pub eccentricity: f64,
+    pub inclination: Angle,
+    pub ascending_node: Angle,
+    pub periapsis_argument: Angle,
+    pub true_anomaly: Angle,
 }
 
 // All property values are correct as of 2023/01/01.
@@ -64,6 +166,8 @@ impl BodyProperties {
                 * SolidAngle::new::<steradian>(1.),
             mass: Mass::new::<kilogram>(1.988_5e30),
             radius: Length::new::<kilometer>(695_700.),
+            axial_tilt: Angle::new::<degree>(7.25),
+            rotation_period: Time::new::<day>(25.38),
             ..Default::default()
         }
     }
@@ -72,6 +176,7 @@ impl BodyProperties {
         Self {
             mass: Mass::new::<kilogram>(5.972_17e24),
             radius: Length::new::<kilometer>(6_371.0),
+            albedo: 0.434,
             primary: Some(Box::new(Self::sun())),
             eccentricity: 0.016_708_6,
             semimajor_axis: Length::new::<kilometer>(149_598_023.),
@@ -79,21 +184,170 @@ impl BodyProperties {
             ascending_node: rem_euclid(Angle::new::<degree>(-11.260_64), Angle::FULL_TURN),
             periapsis_argument: Angle::new::<degree>(114.207_83),
             periapsis_time: Time::new::<day>(2_459_947.368_234_879_337),
+            axial_tilt: Angle::new::<degree>(23.439_281),
+            rotation_period: Time::new::<day>(0.997_269_68),
             ..Default::default()
         }
     }
 
+    // The Moon's node and perigee are its mean ones on 2023-01-01, referred to the J2000 ecliptic.
+    // They move quickly, so the orbit is only placed well near then.
     fn moon() -> Self {
         Self {
             mass: Mass::new::<kilogram>(7.342e22),
             radius: Length::new::<kilometer>(1_737.4),
+            albedo: 0.12,
             primary: Some(Box::new(Self::earth())),
             eccentricity: 0.054_9,
             semimajor_axis: Length::new::<kilometer>(384_399.),
             inclination: Angle::new::<degree>(5.145),
-            ascending_node: Angle::new::<degree>(101.502_922_218_058_2),
-            periapsis_argument: Angle::new::<degree>(323.885_283_505_282_2),
-            periapsis_time: Time::new::<day>(2_459_912.416_812_194_511),
+            ascending_node: Angle::new::<degree>(39.885),
+            periapsis_argument: Angle::new::<degree>(258.992),
+            periapsis_time: Time::new::<day>(2_459_938.873),
+            axial_tilt: Angle::new::<degree>(6.68),
+            rotation_period: Time::new::<day>(27.321_661),
+            ..Default::default()
+        }
+    }
+
+    fn ceres() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(9.383_9e20),
+            radius: Length::new::<kilometer>(469.7),
+            albedo: 0.090,
+            primary: Some(Box::new(Self::sun())),
+            eccentricity: 0.078_5,
+            semimajor_axis: Length::new::<gigameter>(414.01),
+            inclination: Angle::new::<degree>(10.59),
+            ascending_node: Angle::new::<degree>(80.27),
+            periapsis_argument: Angle::new::<degree>(73.6),
+            periapsis_time: Time::new::<day>(2_459_920.5),
+            axial_tilt: Angle::new::<degree>(4.),
+            rotation_period: Time::new::<hour>(9.074),
+            ..Default::default()
+        }
+    }
+
+    fn eris() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(1.646_6e22),
+            radius: Length::new::<kilometer>(1_163.),
+            albedo: 0.96,
+            primary: Some(Box::new(Self::sun())),
+            eccentricity: 0.436_07,
+            semimajor_axis: Length::new::<gigameter>(10_152.4),
+            inclination: Angle::new::<degree>(44.04),
+            ascending_node: Angle::new::<degree>(35.951),
+            periapsis_argument: Angle::new::<degree>(151.639),
+            periapsis_time: Time::new::<day>(2_546_356.),
+            rotation_period: Time::new::<day>(15.786),
+            ..Default::default()
+        }
+    }
+
+    fn europa() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(4.799_844e22),
+            radius: Length::new::<kilometer>(1_560.8),
+            albedo: 0.67,
+            primary: Some(Box::new(Self::jupiter())),
+            eccentricity: 0.009,
+            semimajor_axis: Length::new::<kilometer>(670_900.),
+            inclination: Angle::new::<degree>(GALILEAN_INCLINATION_DEG),
+            ascending_node: Angle::new::<degree>(GALILEAN_ASCENDING_NODE_DEG),
+            periapsis_argument: Angle::new::<degree>(88.970),
+            periapsis_time: Time::new::<day>(2_459_944.1),
+            axial_tilt: Angle::new::<degree>(0.1),
+            rotation_period: Time::new::<day>(3.551_181),
+            ..Default::default()
+        }
+    }
+
+    fn ganymede() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(1.481_9e23),
+            radius: Length::new::<kilometer>(2_634.1),
+            albedo: 0.43,
+            primary: Some(Box::new(Self::jupiter())),
+            eccentricity: 0.001_3,
+            semimajor_axis: Length::new::<kilometer>(1_070_400.),
+            inclination: Angle::new::<degree>(GALILEAN_INCLINATION_DEG),
+            ascending_node: Angle::new::<degree>(GALILEAN_ASCENDING_NODE_DEG),
+            periapsis_argument: Angle::new::<degree>(192.417),
+            periapsis_time: Time::new::<day>(2_459_940.3),
+            axial_tilt: Angle::new::<degree>(0.33),
+            rotation_period: Time::new::<day>(7.154_553),
+            ..Default::default()
+        }
+    }
+
+    // Halley's orbit is retrograde. See https://ssd.jpl.nasa.gov/tools/sbdb_lookup.html#/?sstr=1P
+    fn halley() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(2.2e14),
+            radius: Length::new::<kilometer>(5.5),
+            albedo: 0.04,
+            primary: Some(Box::new(Self::sun())),
+            eccentricity: 0.967_143,
+            semimajor_axis: Length::new::<gigameter>(2_667.95),
+            inclination: Angle::new::<degree>(162.263),
+            ascending_node: Angle::new::<degree>(58.42),
+            periapsis_argument: Angle::new::<degree>(111.332),
+            periapsis_time: Time::new::<day>(2_446_467.395),
+            rotation_period: Time::new::<day>(2.2),
+            ..Default::default()
+        }
+    }
+
+    fn haumea() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(4.006e21),
+            radius: Length::new::<kilometer>(780.),
+            albedo: 0.51,
+            primary: Some(Box::new(Self::sun())),
+            eccentricity: 0.196_42,
+            semimajor_axis: Length::new::<gigameter>(6_450.06),
+            inclination: Angle::new::<degree>(28.213_7),
+            ascending_node: Angle::new::<degree>(122.167),
+            periapsis_argument: Angle::new::<degree>(239.041),
+            periapsis_time: Time::new::<day>(2_499_730.),
+            rotation_period: Time::new::<hour>(3.915_5),
+            ..Default::default()
+        }
+    }
+
+    fn callisto() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(1.075_938e23),
+            radius: Length::new::<kilometer>(2_410.3),
+            albedo: 0.22,
+            primary: Some(Box::new(Self::jupiter())),
+            eccentricity: 0.007_4,
+            semimajor_axis: Length::new::<kilometer>(1_882_700.),
+            inclination: Angle::new::<degree>(GALILEAN_INCLINATION_DEG),
+            ascending_node: Angle::new::<degree>(GALILEAN_ASCENDING_NODE_DEG),
+            periapsis_argument: Angle::new::<degree>(52.643),
+            periapsis_time: Time::new::<day>(2_459_941.2),
+            axial_tilt: Angle::new::<degree>(0.),
+            rotation_period: Time::new::<day>(16.689_018),
+            ..Default::default()
+        }
+    }
+
+    fn io() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(8.931_938e22),
+            radius: Length::new::<kilometer>(1_821.6),
+            albedo: 0.63,
+            primary: Some(Box::new(Self::jupiter())),
+            eccentricity: 0.004_1,
+            semimajor_axis: Length::new::<kilometer>(421_700.),
+            inclination: Angle::new::<degree>(GALILEAN_INCLINATION_DEG),
+            ascending_node: Angle::new::<degree>(GALILEAN_ASCENDING_NODE_DEG),
+            periapsis_argument: Angle::new::<degree>(84.129),
+            periapsis_time: Time::new::<day>(2_459_944.8),
+            axial_tilt: Angle::new::<degree>(0.),
+            rotation_period: Time::new::<day>(1.769_138),
             ..Default::default()
         }
     }
@@ -102,6 +356,7 @@ impl BodyProperties {
         Self {
             mass: Mass::new::<kilogram>(1.898_2e27),
             radius: Length::new::<kilometer>(69_911.),
+            albedo: 0.538,
             primary: Some(Box::new(Self::sun())),
             eccentricity: 0.048_9,
             semimajor_axis: Length::new::<gigameter>(778.479),
@@ -109,6 +364,25 @@ impl BodyProperties {
             ascending_node: Angle::new::<degree>(100.464),
             periapsis_argument: Angle::new::<degree>(273.867),
             periapsis_time: Time::new::<day>(2_459_751.897_397_325_840),
+            axial_tilt: Angle::new::<degree>(3.13),
+            rotation_period: Time::new::<hour>(9.925),
+            ..Default::default()
+        }
+    }
+
+    fn makemake() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(3.1e21),
+            radius: Length::new::<kilometer>(715.),
+            albedo: 0.82,
+            primary: Some(Box::new(Self::sun())),
+            eccentricity: 0.161_26,
+            semimajor_axis: Length::new::<gigameter>(6_796.24),
+            inclination: Angle::new::<degree>(28.983_5),
+            ascending_node: Angle::new::<degree>(79.620),
+            periapsis_argument: Angle::new::<degree>(294.834),
+            periapsis_time: Time::new::<day>(2_519_422.),
+            rotation_period: Time::new::<hour>(22.83),
             ..Default::default()
         }
     }
@@ -117,6 +391,7 @@ impl BodyProperties {
         Self {
             mass: Mass::new::<kilogram>(6.417_1e23),
             radius: Length::new::<kilometer>(3_389.5),
+            albedo: 0.170,
             primary: Some(Box::new(Self::sun())),
             eccentricity: 0.093_4,
             semimajor_axis: Length::new::<kilometer>(227_939_366.),
@@ -124,6 +399,8 @@ impl BodyProperties {
             ascending_node: Angle::new::<degree>(49.578_54),
             periapsis_argument: Angle::new::<degree>(286.5),
             periapsis_time: Time::new::<day>(2_459_751.897_397_325_840),
+            axial_tilt: Angle::new::<degree>(25.19),
+            rotation_period: Time::new::<day>(1.025_956_76),
             ..Default::default()
         }
     }
@@ -132,6 +409,7 @@ impl BodyProperties {
         Self {
             mass: Mass::new::<kilogram>(3.301_1e23),
             radius: Length::new::<kilometer>(2_439.7),
+            albedo: 0.142,
             primary: Some(Box::new(Self::sun())),
             eccentricity: 0.205_630,
             semimajor_axis: Length::new::<kilometer>(57_909_050.),
@@ -139,6 +417,8 @@ impl BodyProperties {
             ascending_node: Angle::new::<degree>(48.331),
             periapsis_argument: Angle::new::<degree>(29.124),
             periapsis_time: Time::new::<day>(2_459_947.345_508_896_280),
+            axial_tilt: Angle::new::<degree>(0.034),
+            rotation_period: Time::new::<day>(58.646),
             ..Default::default()
         }
     }
@@ -147,6 +427,7 @@ impl BodyProperties {
         Self {
             mass: Mass::new::<kilogram>(1.024_13e26),
             radius: Length::new::<kilometer>(24_622.),
+            albedo: 0.442,
             primary: Some(Box::new(Self::sun())),
             eccentricity: 0.008_678,
             semimajor_axis: Length::new::<kilometer>(4.50e9),
@@ -154,6 +435,26 @@ impl BodyProperties {
             ascending_node: Angle::new::<degree>(131.783),
             periapsis_argument: Angle::new::<degree>(273.187),
             periapsis_time: Time::new::<day>(2_464_955.570_929_014_124),
+            axial_tilt: Angle::new::<degree>(28.32),
+            rotation_period: Time::new::<hour>(16.11),
+            ..Default::default()
+        }
+    }
+
+    fn pluto() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(1.303e22),
+            radius: Length::new::<kilometer>(1_188.3),
+            albedo: 0.52,
+            primary: Some(Box::new(Self::sun())),
+            eccentricity: 0.248_8,
+            semimajor_axis: Length::new::<gigameter>(5_906.38),
+            inclination: Angle::new::<degree>(17.16),
+            ascending_node: Angle::new::<degree>(110.299),
+            periapsis_argument: Angle::new::<degree>(113.834),
+            periapsis_time: Time::new::<day>(2_447_774.5),
+            axial_tilt: Angle::new::<degree>(122.53),
+            rotation_period: Time::new::<day>(6.387_2),
             ..Default::default()
         }
     }
@@ -162,6 +463,7 @@ impl BodyProperties {
         Self {
             mass: Mass::new::<kilogram>(5.683_4e26),
             radius: Length::new::<kilometer>(58_232.),
+            albedo: 0.499,
             primary: Some(Box::new(Self::sun())),
             eccentricity: 0.056_5,
             semimajor_axis: Length::new::<kilometer>(1_433.53e6),
@@ -169,6 +471,28 @@ impl BodyProperties {
             ascending_node: Angle::new::<degree>(113.665),
             periapsis_argument: Angle::new::<degree>(339.392),
             periapsis_time: Time::new::<day>(2_459_751.897_397_325_840),
+            axial_tilt: Angle::new::<degree>(26.73),
+            rotation_period: Time::new::<hour>(10.560_6),
+            ..Default::default()
+        }
+    }
+
+    // Triton orbits Neptune retrograde, so its inclination to the ecliptic is over 90°. It keeps
+    // the same face toward Neptune, so it spins retrograde too, about the normal to its orbit.
+    fn triton() -> Self {
+        Self {
+            mass: Mass::new::<kilogram>(2.138_9e22),
+            radius: Length::new::<kilometer>(1_353.4),
+            albedo: 0.76,
+            primary: Some(Box::new(Self::neptune())),
+            eccentricity: 0.000_016,
+            semimajor_axis: Length::new::<kilometer>(354_759.),
+            inclination: Angle::new::<degree>(129.812),
+            ascending_node: Angle::new::<degree>(177.608),
+            periapsis_argument: Angle::new::<degree>(66.142),
+            periapsis_time: Time::new::<day>(2_459_943.3),
+            axial_tilt: Angle::new::<degree>(129.812),
+            rotation_period: Time::new::<day>(5.876_854),
             ..Default::default()
         }
     }
@@ -177,6 +501,7 @@ impl BodyProperties {
         Self {
             mass: Mass::new::<kilogram>(8.681_0e25),
             radius: Length::new::<kilometer>(25_362.),
+            albedo: 0.488,
             primary: Some(Box::new(Self::sun())),
             eccentricity: 0.047_17,
             semimajor_axis: Length::new::<gigameter>(2_870.972),
@@ -184,6 +509,8 @@ impl BodyProperties {
             ascending_node: Angle::new::<degree>(74.006),
             periapsis_argument: Angle::new::<degree>(96.998_857),
             periapsis_time: Time::new::<day>(2_469_819.223_219_580_948),
+            axial_tilt: Angle::new::<degree>(97.77),
+            rotation_period: Time::new::<day>(0.718_33),
             ..Default::default()
         }
     }
@@ -192,6 +519,7 @@ impl BodyProperties {
         Self {
             mass: Mass::new::<kilogram>(4.867_5e24),
             radius: Length::new::<kilometer>(6_051.8),
+            albedo: 0.689,
             primary: Some(Box::new(Self::sun())),
             eccentricity: 0.006_772,
             semimajor_axis: Length::new::<kilometer>(108_208_000.),
@@ -199,6 +527,8 @@ impl BodyProperties {
             ascending_node: Angle::new::<degree>(76.680),
             periapsis_argument: Angle::new::<degree>(54.884),
             periapsis_time: Time::new::<day>(2_460_051.982_227_367_815),
+            axial_tilt: Angle::new::<degree>(177.36),
+            rotation_period: Time::new::<day>(243.022_6),
             ..Default::default()
         }
     }
@@ -215,17 +545,111 @@ impl BodyProperties {
             Body::Saturn => Self::saturn(),
             Body::Uranus => Self::uranus(),
             Body::Neptune => Self::neptune(),
+            Body::Ceres => Self::ceres(),
+            Body::Pluto => Self::pluto(),
+            Body::Haumea => Self::haumea(),
+            Body::Makemake => Self::makemake(),
+            Body::Eris => Self::eris(),
+            Body::Halley => Self::halley(),
+            Body::Io => Self::io(),
+            Body::Europa => Self::europa(),
+            Body::Ganymede => Self::ganymede(),
+            Body::Callisto => Self::callisto(),
+            Body::Triton => Self::triton(),
+            // A custom body's properties come from its definition. See SolarSystem::add_body.
+            Body::Custom(_) => Self::default(),
         }
     }
 
+    // Create the properties of a body with the given mass and radius. Without an orbit, the body
+    // sits at the origin.
+    pub fn new(mass: Mass, radius: Length) -> Self {
+        Self {
+            mass,
+            radius,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_luminosity(mut self, luminosity: LuminousFlux) -> Self {
+        self.luminosity = luminosity;
+        self
+    }
+
+    // Put the body on a Kepler orbit about the Sun. The periapsis time is the Julian Date of one of
+    // its periapsis passages.
+    pub fn with_sun_orbit(
+        mut self,
+        semimajor_axis: Length,
+        eccentricity: f64,
+        inclination: Angle,
+        ascending_node: Angle,
+        periapsis_argument: Angle,
+        periapsis_time: Time,
+    ) -> Self {
+        self.primary = Some(Box::new(Self::sun()));
+        self.semimajor_axis = semimajor_axis;
+        self.eccentricity = eccentricity;
+        self.inclination = inclination;
+        self.ascending_node = ascending_node;
+        self.periapsis_argument = periapsis_argument;
+        self.periapsis_time = periapsis_time;
+        self
+    }
+
     pub fn luminosity(&self) -> LuminousFlux {
         self.luminosity
     }
 
+    pub fn mass(&self) -> Mass {
+        self.mass
+    }
+
+    // The body's standard gravitational parameter GM in m³/s²
+    // TODO: Replace f64 once uom PRs accepted.
+    pub fn gravitational_parameter(&self) -> f64 {
+        (G * self.mass).value
+    }
+
     pub fn radius(&self) -> Length {
         self.radius
     }
 
+    // The geometric albedo, how brightly the body reflects sunlight straight back compared to a
+    // flat, white disk of the same size. A body without one, like the Sun, is 0.
+    pub fn albedo(&self) -> f64 {
+        self.albedo
+    }
+
+    // The mean density of the body treating it as a sphere
+    pub fn density(&self) -> MassDensity {
+        let volume: Volume = 4. / 3. * f64::consts::PI * self.radius * self.radius * self.radius;
+        self.mass / volume
+    }
+
+    // The angle between the body's spin axis and the normal to its orbit. The body spins
+    // counterclockwise about its axis, so a tilt over 90° means the body rotates retrograde.
+    pub fn axial_tilt(&self) -> Angle {
+        self.axial_tilt
+    }
+
+    // The sidereal rotation period
+    pub fn rotation_period(&self) -> Time {
+        self.rotation_period
+    }
+
+    // The direction of the body's spin axis in the ecliptic frame. The axis is tilted from the
+    // ecliptic's north pole about the x axis.
+    pub fn spin_axis(&self) -> Vector3<f64> {
+        Rotation3::from_axis_angle(&Vector3::x_axis(), self.axial_tilt.get::<radian>())
+            .transform_vector(&Vector3::z())
+    }
+
+    // The angle the body has turned about its spin axis since the Julian Date 0
+    pub fn rotation_angle(&self, jd: Time) -> Angle {
+        rem_euclid(Angle::new::<revolution>((jd / self.rotation_period).into()), Angle::FULL_TURN)
+    }
+
     pub fn apsis(&self) -> Length {
         match &self.primary {
             None => Length::new::<meter>(0.),
@@ -233,6 +657,76 @@ impl BodyProperties {
         }
     }
 
+    pub fn periapsis(&self) -> Length {
+        match &self.primary {
+            None => Length::new::<meter>(0.),
+            Some(_) => kepler::periapsis(self.eccentricity, self.semimajor_axis),
+        }
+    }
+
+    // The sidereal period of the body's orbit about its primary
+    pub fn orbital_period(&self) -> Time {
+        match &self.primary {
+            None => Time::new::<second>(f64::NAN),
+            Some(primary) => kepler::period(primary.mass, self.mass, self.semimajor_axis),
+        }
+    }
+
+    // A body on a parabolic or hyperbolic orbit passes its primary once and never returns. Such an
+    // orbit has a negative or infinite semimajor axis.
+    pub fn is_bound(&self) -> bool {
+        self.eccentricity < 1.
+    }
+
+    pub fn eccentricity(&self) -> f64 {
+        match &self.primary {
+            None => 0.,
+            Some(_) => self.eccentricity,
+        }
+    }
+
+    // The inclination of the body's orbit to the ecliptic
+    pub fn inclination(&self) -> Angle {
+        match &self.primary {
+            None => Angle::new::<radian>(0.),
+            Some(_) => self.inclination,
+        }
+    }
+
+    // Return the elements of the body's orbit at the given Julian Date. The Sun has no orbit.
+    pub fn elements_at(&self, jd: Time) -> Option<OrbitalElements> {
+        self.primary.as_ref().map(|_| OrbitalElements {
+            semimajor_axis: self.semimajor_axis,
+            eccentricity: self.eccentricity,
+            inclination: self.inclination,
+            ascending_node: self.ascending_node,
+            periapsis_argument: self.periapsis_argument,
+            true_anomaly: self.true_anomaly(jd),
+        })
+    }
+
+    // Return the position relative to the primary in the ecliptic frame of the point on the orbit
+    // with the given true anomaly
+    pub fn orbit_point(&self, true_anomaly: Angle) -> Vector3<f64> {
+        match &self.primary {
+            None => Vector3::zeros(),
+            Some(_) => {
+                let r =
+                    kepler::orbital_radius(self.semimajor_axis, self.eccentricity, true_anomaly);
+                let pos_2 = kepler::position_m(r, true_anomaly);
+                self.orbit_to_ecliptic(&Vector3::new(pos_2[0], pos_2[1], 0.))
+            }
+        }
+    }
+
+    // The energy of the body's Kepler orbit about its primary. It is constant over the orbit.
+    pub fn orbital_energy(&self) -> Energy {
+        match &self.primary {
+            None => Energy::new::<joule>(0.),
+            Some(primary) => -G * primary.mass * self.mass / (2. * self.semimajor_axis),
+        }
+    }
+
     pub fn eccentric_anomaly(&self, jd: Time) -> Angle {
         match &self.primary {
             None => Angle::new::<radian>(f64::NAN),
@@ -245,19 +739,20 @@ impl BodyProperties {
         }
     }
 
-    // See https://en.wikipedia.org/wiki/Orbital_elements
+    // This rotates a vector in the orbital plane, with x pointing to periapsis, into the ecliptic
+    // frame, i.e., R_z(Ω)·R_x(i)·R_z(ω). See https://en.wikipedia.org/wiki/Orbital_elements
     pub fn orbit_to_ecliptic(&self, vector: &Vector3<f64>) -> Vector3<f64> {
         match &self.primary {
             None => *vector,
             Some(_) => {
                 let lon_rot = Rotation3::from_axis_angle(
-                    &Vector3::z_axis(), -self.ascending_node.get::<radian>()
+                    &Vector3::z_axis(), self.ascending_node.get::<radian>()
                 );
                 let inc_rot = Rotation3::from_axis_angle(
-                    &Vector3::x_axis(), -self.inclination.get::<radian>()
+                    &Vector3::x_axis(), self.inclination.get::<radian>()
                 );
                 let orb_rot = Rotation3::from_axis_angle(
-                    &Vector3::z_axis(), -self.periapsis_argument.get::<radian>());
+                    &Vector3::z_axis(), self.periapsis_argument.get::<radian>());
                 (lon_rot * inc_rot * orb_rot).transform_vector(vector)
             },
         }
@@ -266,16 +761,29 @@ impl BodyProperties {
     pub fn true_anomaly(&self, jd: Time) -> Angle {
         match &self.primary {
             None => Angle::new::<radian>(f64::NAN),
-            Some(_) => kepler::true_anomaly(self.eccentricity, self.eccentric_anomaly(jd)),
+            Some(_) if self.is_bound() => {
+                kepler::true_anomaly(self.eccentricity, self.eccentric_anomaly(jd))
+            }
+            Some(primary) => kepler::conic_true_anomaly(
+                primary.mass,
+                self.mass,
+                self.periapsis(),
+                self.eccentricity,
+                self.periapsis_time,
+                jd,
+            ),
         }
     }
 
     pub fn orbital_distance(&self, jd: Time) -> Length {
         match &self.primary {
             None => Length::new::<meter>(0.),
-            Some(_) => kepler::radial_distance(
+            Some(_) if self.is_bound() => kepler::radial_distance(
                 self.semimajor_axis, self.eccentricity, self.eccentric_anomaly(jd),
             ),
+            Some(_) => {
+                kepler::conic_radius(self.periapsis(), self.eccentricity, self.true_anomaly(jd))
+            }
         }
     }
 
@@ -306,6 +814,16 @@ impl BodyProperties {
     pub fn orbital_velocity(&self, jd: Time) -> Vector3<f64> {
         match &self.primary {
             None => Vector3::zeros(),
+            Some(primary) if !self.is_bound() => {
+                let vel_2 = kepler::conic_velocity_mps(
+                    primary.mass,
+                    self.mass,
+                    self.periapsis(),
+                    self.eccentricity,
+                    self.true_anomaly(jd),
+                );
+                Vector3::new(vel_2[0], vel_2[1], 0.)
+            }
             Some(primary) => {
                 let orbital_distance = self.orbital_distance(jd);
                 let speed = kepler::speed(
@@ -342,6 +860,7 @@ impl Default for BodyProperties {
             luminosity: LuminousFlux::default(),
             mass: Mass::default(),
             radius: Length::default(),
+            albedo: 0.,
             primary: Option::default(),
             eccentricity: f64::NAN,
             semimajor_axis: Length::default(),
@@ -349,11 +868,13 @@ impl Default for BodyProperties {
             ascending_node: Angle::new::<radian>(f64::NAN),
             periapsis_argument: Angle::new::<radian>(f64::NAN),
             periapsis_time: Time::new::<second>(f64::NAN),
+            axial_tilt: Angle::new::<radian>(0.),
+            rotation_period: Time::new::<second>(f64::INFINITY),
         }
     }
 }
 
-
+#[derive(Clone, Deserialize, Serialize)]
 struct OrbitalState {
     mass: Mass,
 
@@ -382,7 +903,15 @@ impl OrbitalState {
     }
 
     fn apply_force(&mut self, force: &Vector3<f64>, dt: Time) {
+        self.kick(force, dt);
+        self.drift(dt);
+    }
+
+    fn kick(&mut self, force: &Vector3<f64>, dt: Time) {
         self.velocity += force * dt.get::<second>() / self.mass.get::<kilogram>();
+    }
+
+    fn drift(&mut self, dt: Time) {
         self.position += self.velocity * dt.get::<second>();
     }
 }
@@ -393,46 +922,136 @@ impl Debug for OrbitalState {
     }
 }
 
+// These measure how well the integration conserves the quantities that physically should be
+// conserved. The drifts are relative to the values at the start of the simulation.
+#[derive(Clone, Debug)]
+pub struct SimDiagnostics {
+    pub total_energy: Energy,
+    pub energy_drift: f64,
+
+    // TODO: Replace Vector3<AngularMomentum> once uom PRs accepted.
+    pub angular_momentum: Vector3<f64>, // kg m²/s
+    pub angular_momentum_drift: f64,
+
+    // These are the energies of each body's orbit about its primary, found from its current
+    // position and velocity relative to the primary. The other bodies' pulls make them drift.
+    pub orbital_energies: HashMap<Body, Energy>,
+}
+
+// These are the methods of integrating the bodies' motions under their mutual gravity.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Propagator {
+    // Each step updates the velocities from the current forces and then the positions from the new
+    // velocities. It is cheap and keeps the energy from drifting over long spans.
+    SemiImplicitEuler,
+    // Each step is a fourth order Runge-Kutta step. It costs four force evaluations per step, but
+    // is far more accurate for a given step size.
+    RungeKutta4,
+    // Each step is divided into substeps by how fast the bodies move. The force between two bodies
+    // is updated on substeps short enough that it turns only a little, so the moons pulling on
+    // their planets are updated often while the Sun's pull on Neptune is updated once a step. It
+    // is second order accurate and suits steps of a day or more. Since it picks its own substeps,
+    // a step may be as long as needed.
+    #[default]
+    Adaptive,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
 pub struct SolarSystem {
     body_properties: HashMap<Body, BodyProperties>,
     body_states: HashMap<Body, OrbitalState>,
+    // The bodies in the order the integrators visit them
+    order: Vec<Body>,
+    // Massless bodies on Kepler orbits about the Sun. They aren't integrated with the other bodies.
+    // Their positions follow from their orbits and the current time, so they aren't saved.
+    #[serde(skip)]
+    asteroids: Vec<BodyProperties>,
+    propagator: Propagator,
+    // Each pair of bodies, as indexes into the integration order, with how fast in rad/s the
+    // gravity between them turns. The adaptive propagator fills this on its first step, and it's
+    // cleared whenever the bodies change.
+    #[serde(skip)]
+    pair_rates: Vec<(usize, usize, f64)>,
     epoch_jd: Time,
     elapsed_time: Time,
+    initial_energy: Energy,
+    initial_angular_momentum: Vector3<f64>,
 }
 
 impl SolarSystem {
-    pub fn init(start_time: Time) -> Self {
-        let mut body_properties = HashMap::new();
-        let mut states = HashMap::new();
-
-        for body in Body::VARIANTS {
-            let props = BodyProperties::properties_for(*body);
-            let state =  OrbitalState::new(
-                props.mass,
-                &props.sun_ecliptic_position(start_time),
-                &props.sun_ecliptic_velocity(start_time),
-            );
-            states.insert(*body, state);
-            body_properties.insert(*body, props);
-        }
+    pub fn init(start_time: Time, propagator: Propagator) -> Self {
+        let body_properties: HashMap<Body, BodyProperties> = Body::VARIANTS
+            .iter()
+            .map(|body| (*body, BodyProperties::properties_for(*body)))
+            .collect();
 
-        Self {
+        let mut sys = Self {
             body_properties,
-            body_states: states,
+            body_states: HashMap::new(),
+            order: Body::VARIANTS.to_vec(),
+            asteroids: Vec::new(),
+            propagator,
+            pair_rates: Vec::new(),
             epoch_jd: start_time,
             elapsed_time: Time::new::<second>(0.),
+            initial_energy: Energy::new::<joule>(0.),
+            initial_angular_momentum: Vector3::zeros(),
+        };
+        for body in Body::VARIANTS {
+            let (pos, vel) = sys.state_at(*body, start_time);
+            let state = OrbitalState::new(sys.body_properties[body].mass, &pos, &vel);
+            sys.body_states.insert(*body, state);
         }
+        sys.initial_energy = sys.total_energy();
+        sys.initial_angular_momentum = sys.total_angular_momentum();
+        sys
+    }
+
+    pub fn propagator(&self) -> Propagator {
+        self.propagator
+    }
+
+    pub fn set_propagator(&mut self, propagator: Propagator) {
+        self.propagator = propagator;
     }
 
     pub fn advance_time(&mut self, dt: Time) {
         self.elapsed_time += dt;
+        match self.propagator {
+            Propagator::SemiImplicitEuler => self.euler_step(dt),
+            Propagator::RungeKutta4 => self.rk4_step(dt),
+            Propagator::Adaptive => self.adaptive_step(dt),
+        }
+    }
+
+    // A step backward in time drifts the positions before kicking the velocities, the reverse of
+    // the order of a step forward, so it undoes a step forward of the same size.
+    fn euler_step(&mut self, dt: Time) {
+        if dt.value < 0. {
+            for state in self.body_states.values_mut() {
+                state.drift(dt);
+            }
+            let net_forces = self.net_forces();
+            for (i, force) in net_forces.iter().enumerate() {
+                self.body_states.get_mut(&self.order[i]).unwrap().kick(force, dt);
+            }
+            return;
+        }
+
+        let net_forces = self.net_forces();
+        for i in 0..self.body_states.len() {
+            self.body_states.get_mut(&self.order[i]).unwrap().apply_force(&net_forces[i], dt);
+        }
+    }
 
-        let mut net_forces = vec![Vector3::zeros(); Body::VARIANTS.len()];
+    // Return the net gravitational force in N on each body, in the integration order
+    fn net_forces(&self) -> Vec<Vector3<f64>> {
+        let mut net_forces = vec![Vector3::zeros(); self.order.len()];
 
-        for i in 0..Body::VARIANTS.len() {
-            let body_i = &self.body_states[&Body::VARIANTS[i]];
-            for j in (i + 1)..Body::VARIANTS.len() {
-                let body_j = &self.body_states[&Body::VARIANTS[j]];
+        for i in 0..self.order.len() {
+            let body_i = &self.body_states[&self.order[i]];
+            for j in (i + 1)..self.order.len() {
+                let body_j = &self.body_states[&self.order[j]];
                 let gmm = G * body_i.mass * body_j.mass;
                 let r = body_i.position - body_j.position;
                 let force = -gmm.value / f64::powi(r.magnitude(), 3) * r;
@@ -441,16 +1060,312 @@ impl SolarSystem {
                 net_forces[j] -= force;
             }
         }
+        net_forces
+    }
 
-        for i in 0..self.body_states.len() {
-            self.body_states.get_mut(&Body::VARIANTS[i]).unwrap().apply_force(&net_forces[i], dt);
+    // Return the gravitational acceleration in m/s² of each body, in the integration order, when
+    // the bodies are at the given positions
+    fn accelerations(&self, positions: &[Vector3<f64>]) -> Vec<Vector3<f64>> {
+        let gms: Vec<f64> = self
+            .order
+            .iter()
+            .map(|body| (G * self.body_states[body].mass).value)
+            .collect();
+        let mut accels = vec![Vector3::zeros(); positions.len()];
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let r = positions[i] - positions[j];
+                let r_hat_per_r2 = r / f64::powi(r.magnitude(), 3);
+                accels[i] -= gms[j] * r_hat_per_r2;
+                accels[j] += gms[i] * r_hat_per_r2;
+            }
+        }
+        accels
+    }
+
+    // Return how fast in rad/s a body turns about its primary, estimated from its orbital period.
+    // The Sun turns about nothing.
+    fn angular_rate_of(&self, body: Body) -> f64 {
+        let period = self.body_properties[&body].orbital_period().get::<second>();
+        if period > 0. {
+            f64::consts::TAU / period
+        } else {
+            0.
+        }
+    }
+
+    // Return how fast in rad/s the gravity between two bodies turns. For each body, this is the
+    // rate of the outermost body it rides along with that doesn't also carry the other body. For
+    // example, Io and Europa pull on each other at the rate Io orbits Jupiter, but Io and Mars pull
+    // on each other at the rate Mars orbits the Sun, since Io rides along with Jupiter.
+    fn pair_angular_rate(&self, body: Body, other: Body) -> f64 {
+        let carriers = |body: Body| std::iter::successors(Some(body), Body::primary);
+        let rate = |body: Body, other: Body| {
+            carriers(body)
+                .take_while(|carrier| !carriers(other).any(|c| c == *carrier))
+                .last()
+                .map_or(0., |carrier| self.angular_rate_of(carrier))
+        };
+        rate(body, other).max(rate(other, body))
+    }
+
+    // Return each pair of bodies, as indexes into the integration order, with the number of
+    // substeps the force between them is updated on in a step of dt. The pairs' rates must have
+    // been found.
+    fn pair_substeps(&self, dt: Time) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        let span = dt.get::<second>().abs();
+        self.pair_rates.iter().map(move |(i, j, rate)| (*i, *j, Self::substeps_for(*rate, span)))
+    }
+
+    // Return the number of substeps a pair whose gravity turns at the given rate in rad/s takes
+    // over a span in seconds. Each substep is short enough that the gravity turns no more than
+    // MAX_STEP_ANGLE during it. The counts are powers of two, so every pair's substeps start on one
+    // of the finest substeps.
+    fn substeps_for(rate: f64, span: f64) -> usize {
+        let substeps = (rate * span / MAX_STEP_ANGLE).ceil().max(1.) as usize;
+        substeps.next_power_of_two()
+    }
+
+    fn find_pair_rates(&mut self) {
+        self.pair_rates.clear();
+        for i in 0..self.order.len() {
+            for j in (i + 1)..self.order.len() {
+                let rate = self.pair_angular_rate(self.order[i], self.order[j]);
+                self.pair_rates.push((i, j, rate));
+            }
         }
     }
 
+    // This is the impulse method, a multiple time step form of the leapfrog method. See Tuckerman,
+    // Berne, and Martyna, "Reversible multiple time scale molecular dynamics", J. Chem. Phys. 97,
+    // 1990 (1992). Every body drifts on each of the finest substeps, but the force between a pair
+    // of bodies is only evaluated at the ends of its own substeps, kicking both bodies for half a
+    // substep at the ends of the step and for a whole one at the other ends. The kicks are equal
+    // and opposite, and the step is symmetric in time, so a step backward undoes a step forward of
+    // the same size.
+    fn adaptive_step(&mut self, dt: Time) {
+        if self.pair_rates.is_empty() {
+            self.find_pair_rates();
+        }
+        let span = dt.get::<second>().abs();
+        let finest = self.pair_substeps(dt).map(|(_, _, substeps)| substeps).max().unwrap_or(1);
+        for substep in 0..=finest {
+            let weight = if substep == 0 || substep == finest { 0.5 } else { 1. };
+            for (i, j, rate) in &self.pair_rates {
+                let substeps = Self::substeps_for(*rate, span);
+                if substep % (finest / substeps) != 0 {
+                    continue;
+                }
+                let (body_i, body_j) = (self.order[*i], self.order[*j]);
+                let (state_i, state_j) = (&self.body_states[&body_i], &self.body_states[&body_j]);
+                let gmm = G * state_i.mass * state_j.mass;
+                let r = state_i.position - state_j.position;
+                let force = -gmm.value / f64::powi(r.magnitude(), 3) * r;
+                let kick = weight * dt / substeps as f64;
+                self.body_states.get_mut(&body_i).unwrap().kick(&force, kick);
+                self.body_states.get_mut(&body_j).unwrap().kick(&-force, kick);
+            }
+            if substep < finest {
+                for state in self.body_states.values_mut() {
+                    state.drift(dt / finest as f64);
+                }
+            }
+        }
+    }
+
+    // See https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods
+    fn rk4_step(&mut self, dt: Time) {
+        let h = dt.get::<second>();
+        let offset = |base: &[Vector3<f64>], slope: &[Vector3<f64>], step: f64| -> Vec<_> {
+            base.iter().zip(slope).map(|(b, s)| b + step * s).collect()
+        };
+
+        let x1: Vec<_> = self.order.iter().map(|b| self.body_states[b].position).collect();
+        let v1: Vec<_> = self.order.iter().map(|b| self.body_states[b].velocity).collect();
+        let a1 = self.accelerations(&x1);
+        let (x2, v2) = (offset(&x1, &v1, h / 2.), offset(&v1, &a1, h / 2.));
+        let a2 = self.accelerations(&x2);
+        let (x3, v3) = (offset(&x1, &v2, h / 2.), offset(&v1, &a2, h / 2.));
+        let a3 = self.accelerations(&x3);
+        let (x4, v4) = (offset(&x1, &v3, h), offset(&v1, &a3, h));
+        let a4 = self.accelerations(&x4);
+
+        for (i, body) in self.order.iter().enumerate() {
+            let state = self.body_states.get_mut(body).unwrap();
+            state.position += h / 6. * (v1[i] + 2. * v2[i] + 2. * v3[i] + v4[i]);
+            state.velocity += h / 6. * (a1[i] + 2. * a2[i] + 2. * a3[i] + a4[i]);
+        }
+    }
+
+    // Advance the time by dt in equal steps no longer than max_step, so that large advances don't
+    // lose accuracy.
+    pub fn advance_time_in_steps(&mut self, dt: Time, max_step: Time) {
+        let steps = f64::from(dt / max_step).abs().ceil().max(1.);
+        let step = dt / steps;
+        for _ in 0..steps as usize {
+            self.advance_time(step);
+        }
+    }
+
+    // Return the heliocentric positions in meters of every body at evenly spaced Julian Dates from
+    // start to end inclusive. The positions come from propagating a copy of the simulation in steps
+    // no longer than max_step, so they match what the simulation itself would reach at those times.
+    // This doesn't disturb the state of the simulation.
+    pub fn ephemeris(
+        &self,
+        start: Time,
+        end: Time,
+        step: Time,
+        max_step: Time,
+    ) -> impl Iterator<Item = (Time, HashMap<Body, Vector3<f64>>)> {
+        let mut sys = self.clone();
+        if start != sys.current_time() {
+            sys.advance_time_in_steps(start - sys.current_time(), max_step);
+        }
+        let count = if end < start {
+            0
+        } else {
+            f64::from((end - start) / step).floor() as usize + 1
+        };
+        (0..count).map(move |i| {
+            if i > 0 {
+                sys.advance_time_in_steps(step, max_step);
+            }
+            let positions = sys.body_states.iter().map(|(b, s)| (*b, *s.position())).collect();
+            (sys.current_time(), positions)
+        })
+    }
+
     pub fn bodies(&self) -> HashSet<Body> {
         self.body_properties.keys().cloned().collect()
     }
 
+    // Return each body with its properties and current heliocentric position in meters, in the
+    // order the integrators visit them
+    pub fn iter_bodies(&self) -> impl Iterator<Item = (Body, &BodyProperties, &Vector3<f64>)> {
+        self.order
+            .iter()
+            .map(|body| (*body, &self.body_properties[body], self.body_states[body].position()))
+    }
+
+    // Add a body, or replace one, placing it on its Kepler orbit at the current time. The energy
+    // and angular momentum the diagnostics compare against are recomputed to include it.
+    pub fn add_body(&mut self, body: Body, props: BodyProperties) {
+        let jd = self.current_time();
+        let state = OrbitalState::new(
+            props.mass,
+            &props.sun_ecliptic_position(jd),
+            &props.sun_ecliptic_velocity(jd),
+        );
+        if self.body_states.insert(body, state).is_none() {
+            self.order.push(body);
+        }
+        self.body_properties.insert(body, props);
+        self.pair_rates.clear();
+        self.initial_energy = self.total_energy();
+        self.initial_angular_momentum = self.total_angular_momentum();
+    }
+
+    // Add massless bodies on Kepler orbits about the Sun. They pull on nothing, not even each
+    // other, so thousands of them cost little to propagate.
+    pub fn add_asteroids(&mut self, asteroids: impl IntoIterator<Item = BodyProperties>) {
+        self.asteroids.extend(asteroids);
+    }
+
+    pub fn clear_asteroids(&mut self) {
+        self.asteroids.clear();
+    }
+
+    // Replace the asteroids with those of another solar system
+    pub fn copy_asteroids_from(&mut self, other: &Self) {
+        self.asteroids.clone_from(&other.asteroids);
+    }
+
+    // Return the positions of the asteroids in meters. Each is placed on its Kepler orbit at the
+    // current time relative to where the Sun is now, so the asteroids keep pace with the other
+    // bodies however advance_time moves them.
+    pub fn asteroid_positions(&self) -> Vec<Vector3<f64>> {
+        let jd = self.current_time();
+        let sun_pos = self.position_of(Body::Sun);
+        self.asteroids.iter().map(|props| sun_pos + props.sun_ecliptic_position(jd)).collect()
+    }
+
+    // Return the current time as a Julian Date
+    pub fn current_time(&self) -> Time {
+        self.epoch_jd + self.elapsed_time
+    }
+
+    // Return the heliocentric position and velocity of a body at the given Julian Date as predicted
+    // by its Kepler orbit. This doesn't disturb the state of the simulation.
+    // A planet's orbital elements are those of the barycenter of the planet and its moons, and each
+    // moon's are relative to its planet, so the planet is offset from its orbit opposite its moons,
+    // e.g., Earth circles about 4,670 km from the Earth–Moon barycenter each month.
+    pub fn state_at(&self, body: Body, jd: Time) -> (Vector3<f64>, Vector3<f64>) {
+        let props = self.properties_of(body);
+        match body.primary() {
+            Some(primary) if body.is_moon() => {
+                let (pos, vel) = self.state_at(primary, jd);
+                (
+                    pos + props.primary_ecliptic_position(jd),
+                    vel + props.primary_ecliptic_velocity(jd),
+                )
+            }
+            _ => {
+                let (pos_offset, vel_offset) = self.barycenter_offset(body, jd);
+                (
+                    props.sun_ecliptic_position(jd) + pos_offset,
+                    props.sun_ecliptic_velocity(jd) + vel_offset,
+                )
+            }
+        }
+    }
+
+    // Return the position and velocity of a body relative to the barycenter of it and its moons as
+    // predicted by the moons' Kepler orbits
+    fn barycenter_offset(&self, body: Body, jd: Time) -> (Vector3<f64>, Vector3<f64>) {
+        let mut total_mass = self.properties_of(body).mass.get::<kilogram>();
+        let (mut pos, mut vel) = (Vector3::zeros(), Vector3::zeros());
+        // The moons are summed in the integration order, so the offset doesn't depend on the
+        // order the map happens to hold them in.
+        for moon in &self.order {
+            if moon.is_moon() && moon.primary() == Some(body) {
+                let props = &self.body_properties[moon];
+                let mass = props.mass.get::<kilogram>();
+                total_mass += mass;
+                pos += mass * props.primary_ecliptic_position(jd);
+                vel += mass * props.primary_ecliptic_velocity(jd);
+            }
+        }
+        (-pos / total_mass, -vel / total_mass)
+    }
+
+    // Return the heliocentric positions in meters and velocities in m/s of every body at the given
+    // Julian Date as predicted by their Kepler orbits. The vectors are in the ecliptic frame. This
+    // doesn't disturb the state of the simulation.
+    pub fn snapshot(&self, jd: Time) -> HashMap<Body, (Vector3<f64>, Vector3<f64>)> {
+        self.body_properties.keys().map(|body| (*body, self.state_at(*body, jd))).collect()
+    }
+
+    // Return the unit directions from Earth to a body at evenly spaced times from start to end
+    // inclusive. This is the body's apparent path across the sky as seen from Earth.
+    pub fn geocentric_directions(
+        &self,
+        body: Body,
+        start: Time,
+        end: Time,
+        samples: usize,
+    ) -> Vec<Vector3<f64>> {
+        let steps = samples.max(2) - 1;
+        (0..=steps)
+            .map(|i| {
+                let jd = start + (end - start) * (i as f64 / steps as f64);
+                let (earth_pos, _) = self.state_at(Body::Earth, jd);
+                let (body_pos, _) = self.state_at(body, jd);
+                (body_pos - earth_pos).normalize()
+            })
+            .collect()
+    }
+
     pub fn position_of(&self, body: Body) -> &Vector3<f64> {
         self.body_states.get(&body).unwrap().position()
     }
@@ -459,10 +1374,320 @@ impl SolarSystem {
         self.body_states.get(&body).unwrap().velocity()
     }
 
+    // Return a body's position in the given unit of length, e.g., position_in::<kilometer>(body)
+    pub fn position_in<U>(&self, body: Body) -> DVec3
+    where
+        U: length::Unit + Conversion<f64, T = f64>,
+    {
+        let pos = self.position_of(body);
+        let get = |x: f64| Length::new::<meter>(x).get::<U>();
+        DVec3::new(get(pos.x), get(pos.y), get(pos.z))
+    }
+
+    // Return a body's velocity in the given unit of speed, e.g.,
+    // velocity_in::<kilometer_per_second>(body)
+    pub fn velocity_in<U>(&self, body: Body) -> DVec3
+    where
+        U: velocity::Unit + Conversion<f64, T = f64>,
+    {
+        let vel = self.velocity_of(body);
+        let get = |x: f64| Velocity::new::<meter_per_second>(x).get::<U>();
+        DVec3::new(get(vel.x), get(vel.y), get(vel.z))
+    }
+
+    // Return a body's velocity relative to another's in m/s
+    pub fn relative_velocity(&self, body: Body, other: Body) -> DVec3 {
+        let vel = self.velocity_of(body) - self.velocity_of(other);
+        DVec3::new(vel.x, vel.y, vel.z)
+    }
+
+    // Return how fast a body is moving away from another in m/s, the part of its relative velocity
+    // along the line between them. It is negative when the body is approaching.
+    pub fn radial_velocity(&self, body: Body, other: Body) -> f64 {
+        let rel_pos = self.position_of(body) - self.position_of(other);
+        let dir = rel_pos.try_normalize(0.).unwrap_or_else(Vector3::zeros);
+        self.relative_velocity(body, other).dot(DVec3::new(dir.x, dir.y, dir.z))
+    }
+
+    // Return the phase angle of a body seen from an observer, the angle at the body between the
+    // directions to the Sun and to the observer. It is 0 when the body is full and 180° when new.
+    pub fn phase_angle_of(&self, body: Body, observer: Body) -> Angle {
+        let to_sun = self.position_of(Body::Sun) - self.position_of(body);
+        let to_observer = self.position_of(observer) - self.position_of(body);
+        Angle::new::<radian>(to_sun.angle(&to_observer))
+    }
+
+    // Return the fraction of the disk of a body seen from an observer that the Sun lights
+    pub fn illuminated_fraction_of(&self, body: Body, observer: Body) -> f64 {
+        (1. + self.phase_angle_of(body, observer).get::<radian>().cos()) / 2.
+    }
+
+    pub fn albedo_of(&self, body: Body) -> f64 {
+        self.properties_of(body).albedo()
+    }
+
+    // Return the angle the disk of a body spans as seen from an observer
+    pub fn angular_diameter_of(&self, body: Body, observer: Body) -> Angle {
+        let radius = self.properties_of(body).radius();
+        photometry::angular_diameter(radius, self.distance_between(body, observer))
+    }
+
+    // Return roughly how bright a body looks from an observer as an apparent magnitude. A body
+    // that gives off light is as bright as its luminosity makes it, and any other body is as
+    // bright as the sunlight it reflects makes it. A body that neither gives off nor reflects
+    // light has no magnitude.
+    pub fn apparent_magnitude_of(&self, body: Body, observer: Body) -> Option<f64> {
+        let props = self.properties_of(body);
+        let observer_dist = self.distance_between(body, observer);
+        if props.luminosity().value > 0. {
+            Some(photometry::emitted_magnitude(props.luminosity().value, observer_dist))
+        } else if props.albedo() > 0. {
+            Some(photometry::reflected_magnitude(
+                props.radius(),
+                props.albedo(),
+                self.distance_between(body, Body::Sun),
+                observer_dist,
+                self.phase_angle_of(body, observer),
+            ))
+        } else {
+            None
+        }
+    }
+
+    // Return the mass-weighted mean of a vector of each body, e.g., its position
+    fn mass_weighted_mean(&self, vector: impl Fn(&OrbitalState) -> Vector3<f64>) -> Vector3<f64> {
+        let (moment, mass) = self.body_states.values().fold(
+            (Vector3::zeros(), 0.),
+            |(moment, mass), state| {
+                let m = state.mass.get::<kilogram>();
+                (moment + m * vector(state), mass + m)
+            },
+        );
+        moment / mass
+    }
+
+    // Return the center of mass of all of the bodies in meters. The Sun orbits it, mostly because
+    // of Jupiter.
+    pub fn barycenter(&self) -> DVec3 {
+        let center = self.mass_weighted_mean(|state| state.position);
+        DVec3::new(center.x, center.y, center.z)
+    }
+
+    // Shift the bodies' positions and velocities so the barycenter is at the origin and at rest.
+    // The bodies start with the Sun at rest at the origin, so the whole system otherwise drifts
+    // with the momentum of the planets. The energy and angular momentum the diagnostics compare
+    // against are recomputed for the new frame.
+    pub fn recenter_on_barycenter(&mut self) {
+        let position = self.mass_weighted_mean(|state| state.position);
+        let velocity = self.mass_weighted_mean(|state| state.velocity);
+        for state in self.body_states.values_mut() {
+            state.position -= position;
+            state.velocity -= velocity;
+        }
+        self.initial_energy = self.total_energy();
+        self.initial_angular_momentum = self.total_angular_momentum();
+    }
+
+    // Return the sum of the kinetic and gravitational potential energies of all of the bodies
+    pub fn total_energy(&self) -> Energy {
+        let mut energy = 0.;
+        for i in 0..self.order.len() {
+            let body_i = &self.body_states[&self.order[i]];
+            energy += 0.5 * body_i.mass.get::<kilogram>() * body_i.velocity.magnitude_squared();
+            for j in (i + 1)..self.order.len() {
+                let body_j = &self.body_states[&self.order[j]];
+                let gmm = G * body_i.mass * body_j.mass;
+                energy -= gmm.value / (body_i.position - body_j.position).magnitude();
+            }
+        }
+        Energy::new::<joule>(energy)
+    }
+
+    // Return the total angular momentum of all of the bodies about the origin in kg m²/s
+    pub fn total_angular_momentum(&self) -> Vector3<f64> {
+        self.body_states
+            .values()
+            .map(|state| state.mass.get::<kilogram>() * state.position.cross(&state.velocity))
+            .sum()
+    }
+
+    pub fn diagnostics(&self) -> SimDiagnostics {
+        let total_energy = self.total_energy();
+        let angular_momentum = self.total_angular_momentum();
+        SimDiagnostics {
+            total_energy,
+            energy_drift: ((total_energy - self.initial_energy) / self.initial_energy.abs()).into(),
+            angular_momentum,
+            angular_momentum_drift: (angular_momentum - self.initial_angular_momentum).magnitude()
+                / self.initial_angular_momentum.magnitude(),
+            orbital_energies: self
+                .order
+                .iter()
+                .map(|body| (*body, self.orbital_energy_of(*body)))
+                .collect(),
+        }
+    }
+
+    // Return the energy of the orbit a body's current position and velocity relative to its
+    // primary put it on, from the vis-viva equation. The Sun has no orbit, so its energy is 0.
+    fn orbital_energy_of(&self, body: Body) -> Energy {
+        let Some(primary) = body.primary() else {
+            return Energy::new::<joule>(0.);
+        };
+        let state = &self.body_states[&body];
+        let speed = self.relative_velocity(body, primary).length();
+        let dist = (state.position - self.body_states[&primary].position).magnitude();
+        let mu = self.gravitational_parameter_of(primary);
+        Energy::new::<joule>(state.mass.get::<kilogram>() * (speed * speed / 2. - mu / dist))
+    }
+
+    // Return the closest distance a body's orbit comes to its primary
+    pub fn perihelion_of(&self, body: Body) -> Length {
+        self.properties_of(body).periapsis()
+    }
+
+    // Return the Julian Date of a body's next periapsis passage. It is predicted from the Kepler
+    // orbit the body's current position and velocity relative to its primary put it on, so it
+    // accounts for how the other bodies have perturbed the orbit so far. None is returned for the
+    // Sun and for a body on an open orbit that has already passed periapsis.
+    pub fn next_periapsis_time(&self, body: Body) -> Option<Time> {
+        let primary = body.primary()?;
+        let (mass, primary_mass) = (self.body_states[&body].mass, self.body_states[&primary].mass);
+        let elements = kepler::elements_from_state(
+            primary_mass,
+            mass,
+            &(self.position_of(body) - self.position_of(primary)),
+            &(self.velocity_of(body) - self.velocity_of(primary)),
+        );
+        let now = self.current_time();
+        let time = kepler::periapsis_time(
+            primary_mass,
+            mass,
+            elements.semimajor_axis,
+            elements.eccentricity,
+            elements.true_anomaly,
+            now,
+        );
+        if time > now {
+            Some(time)
+        } else if elements.eccentricity < 1. {
+            Some(time + kepler::period(primary_mass, mass, elements.semimajor_axis))
+        } else {
+            None
+        }
+    }
+
+    // Return where a body will be after the given span, which may be negative, as predicted by the
+    // Kepler orbit its current position and velocity relative to its primary put it on. Its primary
+    // is predicted the same way, and the Sun is left where it is. Unlike state_at, this starts from
+    // the simulated state, so it stays close over a few weeks however long the simulation has run.
+    pub fn predicted_position_of(&self, body: Body, span: Time) -> Vector3<f64> {
+        let Some(primary) = body.primary() else {
+            return *self.position_of(body);
+        };
+        let mu = self.gravitational_parameter_of(primary) + self.gravitational_parameter_of(body);
+        let (offset, _) = kepler::propagate_state(
+            mu,
+            &(self.position_of(body) - self.position_of(primary)),
+            &(self.velocity_of(body) - self.velocity_of(primary)),
+            span,
+        );
+        self.predicted_position_of(primary, span) + offset
+    }
+
+    // Return the farthest distance a body's orbit goes from its primary
+    pub fn aphelion_of(&self, body: Body) -> Length {
+        self.properties_of(body).apsis()
+    }
+
+    // Return the current distance between the centers of two bodies
+    pub fn distance_between(&self, a: Body, b: Body) -> Length {
+        Length::new::<meter>((self.position_of(a) - self.position_of(b)).magnitude())
+    }
+
+    // Return where a body appears to be from an observing body, relative to the observer in meters.
+    // The light reaching the observer now left the body a light time ago, so the body is seen where
+    // it was then. The light time is found iteratively, and the body's position then is
+    // extrapolated along its current velocity, since light crosses the solar system in hours. The
+    // observer's motion tilts the arriving light as well. To first order, this aberration is
+    // accounted for by retarding the observer's position by the light time too.
+    pub fn apparent_position_of(&self, body: Body, observer: Body) -> Vector3<f64> {
+        let rel_pos = self.position_of(body) - self.position_of(observer);
+        let rel_vel = self.velocity_of(body) - self.velocity_of(observer);
+        let mut light_time = 0.;
+        for _ in 0..LIGHT_TIME_ITERATIONS {
+            let retarded = self.position_of(body) - light_time * self.velocity_of(body);
+            light_time = (retarded - self.position_of(observer)).magnitude() / LIGHT_SPEED_MPS;
+        }
+        rel_pos - light_time * rel_vel
+    }
+
+    // Return the elements of a body's Kepler orbit about its primary at the current time. The Sun
+    // has no orbit.
+    pub fn orbital_elements_of(&self, body: Body) -> Option<OrbitalElements> {
+        self.properties_of(body).elements_at(self.current_time())
+    }
+
+    // Return how long a body takes to orbit its primary by Kepler's third law, so a moon's period
+    // depends on its planet's mass rather than the Sun's. The Sun's orbital period and that of a
+    // body on an open orbit are NaN.
+    pub fn orbital_period_of(&self, body: Body) -> Time {
+        self.properties_of(body).orbital_period()
+    }
+
+    // Return the heliocentric position of the point on a body's orbit with the given true anomaly.
+    // The orbit is centered on the current position of the body's primary.
+    pub fn orbit_point_of(&self, body: Body, true_anomaly: Angle) -> Vector3<f64> {
+        let center = match body.primary() {
+            None => Vector3::zeros(),
+            Some(primary) => *self.position_of(primary),
+        };
+        center + self.properties_of(body).orbit_point(true_anomaly)
+    }
+
+    // Return the distance from a body inside of which a satellite of the given density would be
+    // torn apart by tides
+    pub fn roche_limit_of(&self, body: Body, satellite_density: MassDensity) -> Length {
+        let props = self.properties_of(body);
+        kepler::roche_limit(props.radius(), props.density(), satellite_density)
+    }
+
+    // Return the radius of the sphere about a body inside of which a small body's motion is better
+    // treated as an orbit about it than about its primary, a (m/M)^(2/5). The Sun's is unbounded.
+    // See https://en.wikipedia.org/wiki/Sphere_of_influence_(astrodynamics)
+    pub fn sphere_of_influence_of(&self, body: Body) -> Length {
+        let props = self.properties_of(body);
+        match &props.primary {
+            None => Length::new::<meter>(f64::INFINITY),
+            Some(primary) => {
+                props.semimajor_axis.abs() * (props.mass / primary.mass).value.powf(0.4)
+            }
+        }
+    }
+
+    // Return the shadow a body currently casts in the Sun's light
+    pub fn shadow_of(&self, body: Body) -> Shadow {
+        let dist = (self.position_of(body) - self.position_of(Body::Sun)).magnitude();
+        Shadow::new(
+            self.properties_of(Body::Sun).radius(),
+            self.properties_of(body).radius(),
+            Length::new::<meter>(dist),
+        )
+    }
+
     // Return the properties for a requested body
     pub fn properties_of(&self, body: Body) -> &BodyProperties {
         self.body_properties.get(&body).unwrap()
     }
+
+    pub fn mass_of(&self, body: Body) -> Mass {
+        self.properties_of(body).mass()
+    }
+
+    // Return a body's standard gravitational parameter GM in m³/s²
+    pub fn gravitational_parameter_of(&self, body: Body) -> f64 {
+        self.properties_of(body).gravitational_parameter()
+    }
 }
 
 impl Debug for SolarSystem {
@@ -478,7 +1703,10 @@ impl Debug for SolarSystem {
 
 #[cfg(test)]
 mod tests {
-    use crate::uom_wrapper::si::angle::revolution;
+    use crate::uom_wrapper::si::{
+        length::astronomical_unit, mass_density::kilogram_per_cubic_meter, time::minute,
+        velocity::kilometer_per_second,
+    };
     use crate::test::assert_rel_eq;
     use super::*;
 
@@ -512,11 +1740,86 @@ mod tests {
         assert!(!BodyProperties::earth().eccentric_anomaly(epoch()).is_nan())
     }
 
+    #[test]
+    fn test_body_properties_elements_at_sun() {
+        assert_eq!(BodyProperties::sun().elements_at(epoch()), None)
+    }
+
+    #[test]
+    fn test_body_properties_elements_at_round_trip() {
+        let props = BodyProperties {
+            mass: Mass::new::<kilogram>(1e24),
+            primary: Some(Box::new(BodyProperties::sun())),
+            eccentricity: 0.2,
+            semimajor_axis: Length::new::<gigameter>(300.),
+            inclination: Angle::new::<degree>(10.),
+            ascending_node: Angle::new::<degree>(40.),
+            periapsis_argument: Angle::new::<degree>(70.),
+            periapsis_time: epoch(),
+            ..Default::default()
+        };
+        let jd = epoch() + Time::new::<day>(100.);
+        let act = props.elements_at(jd).unwrap();
+        assert_eq!(act.semimajor_axis, props.semimajor_axis);
+        assert_eq!(act.eccentricity, props.eccentricity);
+        assert_eq!(act.inclination, props.inclination);
+        assert_eq!(act.ascending_node, props.ascending_node);
+        assert_eq!(act.periapsis_argument, props.periapsis_argument);
+        assert_rel_eq!(props.orbit_point(act.true_anomaly), props.primary_ecliptic_position(jd));
+    }
+
+    #[test]
+    fn test_body_properties_hyperbolic_orbit() {
+        // ʻOumuamua's orbit was about this eccentric.
+        let props = BodyProperties {
+            mass: Mass::new::<kilogram>(1e10),
+            primary: Some(Box::new(BodyProperties::sun())),
+            eccentricity: 1.2,
+            semimajor_axis: Length::new::<gigameter>(-190.),
+            periapsis_time: epoch(),
+            ..Default::default()
+        };
+        assert!(!props.is_bound());
+        assert_rel_eq!(
+            props.orbital_distance(epoch()).get::<meter>(),
+            props.periapsis().get::<meter>()
+        );
+        let mut last_dist = props.periapsis();
+        for days in [10., 100., 1000.] {
+            let jd = epoch() + Time::new::<day>(days);
+            let dist = props.orbital_distance(jd);
+            assert!(dist > last_dist, "{:?}", dist);
+            assert_rel_eq!(props.orbital_position(jd).magnitude(), dist.get::<meter>());
+            last_dist = dist;
+        }
+        let leaving = props.orbital_velocity(epoch() + Time::new::<day>(100.));
+        let arriving = props.orbital_velocity(epoch() - Time::new::<day>(100.));
+        assert_rel_eq!(leaving.magnitude(), arriving.magnitude());
+    }
+
+    #[test]
+    fn test_body_properties_halley_near_aphelion() {
+        // Halley's Comet reached aphelion, about 35 AU from the Sun, in December 2023.
+        let dist = BodyProperties::halley().orbital_distance(epoch()).get::<gigameter>();
+        assert!((dist / 149.6 - 35.).abs() < 0.5, "{}", dist / 149.6);
+    }
+
     #[test]
     fn test_body_properties_orbital_distance_sun() {
         assert_eq!(BodyProperties::sun().orbital_distance(epoch()).get::<meter>(), 0.)
     }
 
+    #[test]
+    fn test_body_properties_orbital_period_earth() {
+        let period = BodyProperties::earth().orbital_period().get::<day>();
+        assert!((period - 365.256).abs() < 0.01, "{}", period);
+    }
+
+    #[test]
+    fn test_body_properties_orbital_period_sun() {
+        assert!(BodyProperties::sun().orbital_period().value.is_nan())
+    }
+
     #[test]
     fn test_body_properties_orbital_distance_not_sun() {
         assert_ne!(BodyProperties::earth().orbital_distance(epoch()).get::<meter>(), 0.)
@@ -530,7 +1833,7 @@ mod tests {
             Angle::new::<revolution>(0.25),
         );
         let act = props.orbit_to_ecliptic(&Vector3::new(1f64, 0., 0.));
-        assert_rel_eq!(act, Vector3::new(0f64, -1., 0.))
+        assert_rel_eq!(act, Vector3::new(0f64, 1., 0.))
     }
 
     #[test]
@@ -552,7 +1855,7 @@ mod tests {
             Angle::new::<revolution>(0.),
         );
         let act = props.orbit_to_ecliptic(&Vector3::new(0f64, 1., 0.));
-        assert_rel_eq!(act, Vector3::new(1f64, 0., 0.))
+        assert_rel_eq!(act, Vector3::new(-1f64, 0., 0.))
     }
 
     #[test]
@@ -598,4 +1901,628 @@ mod tests {
     fn test_body_properties_primary_ecliptic_position_not_sun() {
         assert_ne!(BodyProperties::earth().primary_ecliptic_position(epoch()), Vector3::zeros())
     }
+
+    #[test]
+    fn test_solar_system_diagnostics_orbital_energy_nearly_constant() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        let exp = sys.diagnostics().orbital_energies;
+        for _ in 0..480 {
+            sys.advance_time(Time::new::<minute>(30.));
+        }
+        // After ten days of half hour steps, the other bodies' pulls have shifted the energies, the
+        // Galilean moons' most of all, but only slightly.
+        let act = sys.diagnostics().orbital_energies;
+        for body in Body::VARIANTS.iter().filter(|body| body.primary().is_some()) {
+            let (act, exp) = (act[body].get::<joule>(), exp[body].get::<joule>());
+            assert!(act < 0., "{:?}", body);
+            assert!(((act - exp) / exp).abs() < 0.01, "{:?} {} {}", body, act, exp);
+        }
+    }
+
+    #[test]
+    fn test_solar_system_diagnostics_no_drift_at_start() {
+        let diag = SolarSystem::init(epoch(), Propagator::default()).diagnostics();
+        assert_eq!(diag.energy_drift, 0.);
+        assert_eq!(diag.angular_momentum_drift, 0.);
+    }
+
+    #[test]
+    fn test_solar_system_diagnostics_small_drift() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        for _ in 0..48 {
+            sys.advance_time(Time::new::<minute>(30.));
+        }
+        let diag = sys.diagnostics();
+        assert!(diag.energy_drift.abs() < 1e-4);
+        assert!(diag.angular_momentum_drift < 1e-4);
+    }
+
+    #[test]
+    fn test_solar_system_roche_limit_saturn_rings() {
+        let ice = MassDensity::new::<kilogram_per_cubic_meter>(917.);
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let limit = sys.roche_limit_of(Body::Saturn, ice);
+
+        // Saturn's main rings extend from the D ring's inner edge to the F ring.
+        let rings_inner = Length::new::<kilometer>(66_900.);
+        let rings_outer = Length::new::<kilometer>(140_220.);
+        assert!(limit > rings_inner && limit < rings_outer, "{:?}", limit);
+    }
+
+    #[test]
+    fn test_solar_system_sphere_of_influence_of() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let earth = sys.sphere_of_influence_of(Body::Earth).get::<kilometer>();
+        let moon = sys.sphere_of_influence_of(Body::Moon).get::<kilometer>();
+        assert!((earth - 924_000.).abs() < 5_000., "{}", earth);
+        assert!((moon - 66_100.).abs() < 1_000., "{}", moon);
+        assert!(sys.sphere_of_influence_of(Body::Sun).value.is_infinite());
+    }
+
+    #[test]
+    fn test_solar_system_shadow_of_earth_reaches_moon() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let umbra = sys.shadow_of(Body::Earth).umbra_length();
+        assert!(umbra > BodyProperties::moon().apsis(), "{:?}", umbra);
+    }
+
+    #[test]
+    fn test_body_properties_rotation_angle_earth_sidereal_day() {
+        let earth = BodyProperties::earth();
+        let start = earth.rotation_angle(epoch());
+        let end = earth.rotation_angle(epoch() + earth.rotation_period());
+        assert!((end - start).abs() < Angle::new::<radian>(1e-6), "{:?} {:?}", start, end);
+    }
+
+    #[test]
+    fn test_body_properties_spin_axis_venus_retrograde() {
+        assert!(BodyProperties::venus().spin_axis().z < 0.);
+        assert!(BodyProperties::earth().spin_axis().z > 0.);
+    }
+
+    #[test]
+    fn test_body_properties_rotation_period_jupiter_fastest_planet() {
+        let jupiter = BodyProperties::jupiter().rotation_period();
+        for planet in [Body::Mercury, Body::Venus, Body::Earth, Body::Mars, Body::Saturn] {
+            assert!(jupiter < BodyProperties::properties_for(planet).rotation_period());
+        }
+    }
+
+    #[test]
+    fn test_body_properties_orbit_point_periapsis() {
+        let props = BodyProperties::earth();
+        let act = props.orbit_point(Angle::new::<radian>(0.)).magnitude();
+        assert_rel_eq!(act, props.periapsis().get::<meter>())
+    }
+
+    #[test]
+    fn test_solar_system_orbit_point_of_moon_centered_on_earth() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let point = sys.orbit_point_of(Body::Moon, Angle::new::<revolution>(0.5));
+        let dist = (point - sys.position_of(Body::Earth)).magnitude();
+        assert_rel_eq!(dist, sys.aphelion_of(Body::Moon).get::<meter>())
+    }
+
+    #[test]
+    fn test_solar_system_step_back_undoes_step() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        sys.advance_time(Time::new::<minute>(30.));
+        let before = sys.clone();
+        sys.advance_time(Time::new::<minute>(30.));
+        sys.advance_time(Time::new::<minute>(-30.));
+        assert_eq!(sys.current_time(), before.current_time());
+        for body in Body::VARIANTS {
+            assert_rel_eq!(*sys.position_of(*body), *before.position_of(*body));
+        }
+    }
+
+    #[test]
+    fn test_solar_system_adaptive_step_back_undoes_step() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::Adaptive);
+        sys.advance_time(Time::new::<hour>(6.));
+        let before = sys.clone();
+        sys.advance_time(Time::new::<hour>(6.));
+        sys.advance_time(Time::new::<hour>(-6.));
+        assert_eq!(sys.current_time(), before.current_time());
+        for body in Body::VARIANTS {
+            assert_rel_eq!(*sys.position_of(*body), *before.position_of(*body));
+        }
+    }
+
+    #[test]
+    fn test_solar_system_adaptive_pair_rates() {
+        let sys = SolarSystem::init(epoch(), Propagator::Adaptive);
+        let rate = |body, other| sys.pair_angular_rate(body, other);
+        assert_eq!(rate(Body::Jupiter, Body::Io), sys.angular_rate_of(Body::Io));
+        assert_eq!(rate(Body::Europa, Body::Io), sys.angular_rate_of(Body::Io));
+        assert_eq!(rate(Body::Io, Body::Sun), sys.angular_rate_of(Body::Jupiter));
+        assert_eq!(rate(Body::Io, Body::Mars), sys.angular_rate_of(Body::Mars));
+        assert_eq!(rate(Body::Sun, Body::Neptune), sys.angular_rate_of(Body::Neptune));
+    }
+
+    #[test]
+    fn test_solar_system_adaptive_mercury_beats_fixed_step() {
+        let year = Time::new::<day>(365.);
+        let step = Time::new::<day>(1.);
+        let mercury_error = |sys: &SolarSystem, exp: &SolarSystem| {
+            let helio =
+                |sys: &SolarSystem| sys.position_of(Body::Mercury) - sys.position_of(Body::Sun);
+            Length::new::<meter>((helio(sys) - helio(exp)).magnitude())
+        };
+        let mut exp = SolarSystem::init(epoch(), Propagator::RungeKutta4);
+        exp.advance_time_in_steps(year, Time::new::<hour>(2.));
+
+        let mut adaptive = SolarSystem::init(epoch(), Propagator::Adaptive);
+        adaptive.advance_time_in_steps(year, step);
+        let adaptive_err = mercury_error(&adaptive, &exp);
+        assert!(adaptive_err < Length::new::<kilometer>(50_000.), "{:?}", adaptive_err);
+
+        // Cost is counted in evaluations of the force between two bodies. A fixed step evaluates
+        // every pair once, and the adaptive propagator evaluates each pair at both ends of each of
+        // its substeps.
+        let pairs: Vec<_> = adaptive.pair_substeps(step).collect();
+        let adaptive_cost = pairs.iter().map(|(_, _, substeps)| substeps + 1).sum::<usize>() as f64
+            * f64::from(year / step);
+        let fixed_steps = adaptive_cost / pairs.len() as f64;
+        let mut fixed = SolarSystem::init(epoch(), Propagator::SemiImplicitEuler);
+        fixed.advance_time_in_steps(year, year / fixed_steps);
+        let fixed_err = mercury_error(&fixed, &exp);
+        assert!(adaptive_err < fixed_err / 4., "{:?} {:?}", adaptive_err, fixed_err);
+    }
+
+    #[test]
+    fn test_solar_system_io_stays_in_orbit() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        let io = BodyProperties::io();
+        let min_dist = 0.99 * io.periapsis().get::<meter>();
+        let max_dist = 1.01 * io.apsis().get::<meter>();
+        // Io takes 1.77 days to orbit Jupiter. It's stepped the same as the app steps it.
+        let step = Time::new::<minute>(crate::Simulation::DT);
+        for _ in 0..43 {
+            sys.advance_time_in_steps(Time::new::<hour>(1.), step);
+            let dist = (sys.position_of(Body::Io) - sys.position_of(Body::Jupiter)).magnitude();
+            assert!(dist > min_dist && dist < max_dist, "{:?}", dist);
+        }
+    }
+
+    #[test]
+    fn test_body_properties_triton_orbits_retrograde() {
+        let triton = BodyProperties::triton();
+        let pos = triton.primary_ecliptic_position(epoch());
+        let vel = triton.primary_ecliptic_velocity(epoch());
+        let normal = pos.cross(&vel).normalize();
+        let inclination = normal.z.acos().to_degrees();
+        assert!((inclination - 129.812).abs() < 1e-6, "{}", inclination);
+        // The orbit is nearly circular, so Triton moves at a constant speed around it.
+        let a = triton.semimajor_axis.get::<meter>();
+        assert!((pos.magnitude() / a - 1.).abs() < 1e-4, "{}", pos.magnitude());
+        let speed = std::f64::consts::TAU * a / triton.orbital_period().get::<second>();
+        assert!((vel.magnitude() / speed - 1.).abs() < 1e-4, "{}", vel.magnitude());
+    }
+
+    #[test]
+    fn test_solar_system_triton_stays_in_retrograde_orbit() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        let triton = BodyProperties::triton();
+        let min_dist = 0.99 * triton.periapsis().get::<meter>();
+        let max_dist = 1.01 * triton.apsis().get::<meter>();
+        // Triton takes 5.88 days to orbit Neptune.
+        for _ in 0..24 {
+            sys.advance_time_in_steps(Time::new::<hour>(6.), Time::new::<minute>(5.));
+            let pos = sys.position_of(Body::Triton) - sys.position_of(Body::Neptune);
+            let vel = sys.velocity_of(Body::Triton) - sys.velocity_of(Body::Neptune);
+            assert!(pos.magnitude() > min_dist && pos.magnitude() < max_dist, "{:?}", pos);
+            assert!(pos.cross(&vel).z < 0., "{:?} {:?}", pos, vel);
+        }
+    }
+
+    #[test]
+    fn test_solar_system_rk4_earth_follows_kepler_orbit() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::RungeKutta4);
+        let year = Time::new::<day>(365.25);
+        sys.advance_time_in_steps(year, Time::new::<hour>(6.));
+        let (exp, _) = sys.state_at(Body::Earth, epoch() + year);
+        let drift = Length::new::<meter>((sys.position_of(Body::Earth) - exp).magnitude());
+        assert!(drift < Length::new::<gigameter>(1.), "{:?}", drift);
+    }
+
+    #[test]
+    fn test_solar_system_rk4_less_drift_than_euler() {
+        let drift = |propagator| {
+            let mut sys = SolarSystem::init(epoch(), propagator);
+            sys.advance_time_in_steps(Time::new::<day>(30.), Time::new::<hour>(1.));
+            sys.diagnostics().energy_drift.abs()
+        };
+        let rk4 = drift(Propagator::RungeKutta4);
+        let euler = drift(Propagator::SemiImplicitEuler);
+        assert!(rk4 < euler / 10., "{:?} {:?}", rk4, euler);
+    }
+
+    #[test]
+    fn test_solar_system_advance_time_in_steps() {
+        let mut act = SolarSystem::init(epoch(), Propagator::default());
+        let mut exp = SolarSystem::init(epoch(), Propagator::default());
+        for _ in 0..5 {
+            act.advance_time_in_steps(Time::new::<minute>(60.), Time::new::<minute>(30.));
+        }
+        for _ in 0..10 {
+            exp.advance_time(Time::new::<minute>(30.));
+        }
+        assert_eq!(act.current_time(), exp.current_time());
+        for body in Body::VARIANTS {
+            assert_rel_eq!(*act.position_of(*body), *exp.position_of(*body));
+        }
+    }
+
+    #[test]
+    fn test_solar_system_ephemeris_matches_advance() {
+        let mut live = SolarSystem::init(epoch(), Propagator::default());
+        let max_step = Time::new::<minute>(30.);
+        let rows: Vec<_> = live
+            .ephemeris(epoch(), epoch() + Time::new::<day>(2.), Time::new::<day>(1.), max_step)
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(live.current_time(), epoch());
+        for _ in 0..24 {
+            live.advance_time_in_steps(Time::new::<minute>(120.), max_step);
+        }
+        let (jd, positions) = &rows[2];
+        assert_eq!(*jd, live.current_time());
+        for body in Body::VARIANTS {
+            assert_eq!(positions[body], *live.position_of(*body));
+        }
+    }
+
+    #[test]
+    fn test_solar_system_ephemeris_empty() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let step = Time::new::<day>(1.);
+        assert_eq!(sys.ephemeris(epoch(), epoch() - step, step, step).count(), 0);
+    }
+
+    #[test]
+    fn test_solar_system_init_at_j2000() {
+        // Earth was 0.9833 AU from the Sun at J2000, a couple of days before perihelion.
+        let sys = SolarSystem::init(Time::new::<day>(2_451_545.), Propagator::default());
+        let au = Length::new::<astronomical_unit>(1.).get::<meter>();
+        let act = sys.position_of(Body::Earth) / au;
+        assert!((act.magnitude() - 0.9833).abs() < 1e-3, "{:?}", act);
+        assert!(act.z.abs() < 1e-3, "{:?}", act);
+        // Earth was at about (-0.177, 0.967) AU in the ecliptic frame. Its elements are for 2023,
+        // so 23 years back it's placed about a degree off along its orbit.
+        assert!((act.x + 0.177).abs() < 0.02, "{:?}", act);
+        assert!((act.y - 0.967).abs() < 0.02, "{:?}", act);
+    }
+
+    #[test]
+    fn test_solar_system_orbital_elements_of() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        assert_eq!(sys.orbital_elements_of(Body::Sun), None);
+        let earth = sys.orbital_elements_of(Body::Earth).unwrap();
+        assert_eq!(earth.eccentricity, BodyProperties::earth().eccentricity());
+        assert_eq!(earth.true_anomaly, BodyProperties::earth().true_anomaly(epoch()));
+    }
+
+    #[test]
+    fn test_solar_system_orbital_period_of() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let days = |body| sys.orbital_period_of(body).get::<day>();
+        assert!((days(Body::Earth) - 365.25).abs() < 0.1, "{}", days(Body::Earth));
+        assert!((days(Body::Mercury) - 88.).abs() < 0.1, "{}", days(Body::Mercury));
+        assert!((days(Body::Moon) - 27.3).abs() < 0.1, "{}", days(Body::Moon));
+        assert!(days(Body::Sun).is_nan());
+    }
+
+    #[test]
+    fn test_solar_system_next_periapsis_time() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        assert_eq!(sys.next_periapsis_time(Body::Sun), None);
+        let time = sys.next_periapsis_time(Body::Mercury).unwrap();
+        let wait = time - sys.current_time();
+        assert!(wait.value > 0. && wait < sys.orbital_period_of(Body::Mercury), "{:?}", wait);
+
+        // This is the perihelion of the orbit Mercury's current state puts it on, which differs a
+        // little from the perihelion of its nominal orbit.
+        let elements = kepler::elements_from_state(
+            sys.body_states[&Body::Sun].mass,
+            sys.body_states[&Body::Mercury].mass,
+            &(sys.position_of(Body::Mercury) - sys.position_of(Body::Sun)),
+            &(sys.velocity_of(Body::Mercury) - sys.velocity_of(Body::Sun)),
+        );
+        let perihelion = elements.semimajor_axis * (1. - elements.eccentricity);
+
+        let step = Time::new::<minute>(30.);
+        sys.advance_time_in_steps(wait, step);
+        let dist = sys.distance_between(Body::Mercury, Body::Sun);
+        assert!(f64::from((dist - perihelion) / perihelion).abs() < 1e-3, "{:?}", dist);
+        for dt in [-1., 1.] {
+            let mut near = sys.clone();
+            near.advance_time_in_steps(Time::new::<day>(dt), step);
+            assert!(near.distance_between(Body::Mercury, Body::Sun) > dist);
+        }
+    }
+
+    #[test]
+    fn test_solar_system_position_and_velocity_in_units() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let m = sys.position_in::<meter>(Body::Earth);
+        let pos = sys.position_of(Body::Earth);
+        assert_eq!(m, DVec3::new(pos.x, pos.y, pos.z));
+        assert_rel_eq!(sys.position_in::<kilometer>(Body::Earth).x, m.x / 1e3);
+        let au = sys.position_in::<astronomical_unit>(Body::Earth);
+        assert_rel_eq!(au.length(), m.length() / 1.495_979e11);
+        let km_s = sys.velocity_in::<kilometer_per_second>(Body::Earth);
+        assert_rel_eq!(km_s.y, sys.velocity_of(Body::Earth).y / 1e3);
+    }
+
+    #[test]
+    fn test_solar_system_barycenter_near_sun_toward_jupiter() {
+        // The Sun and Jupiter alone would put the barycenter just outside the Sun's surface, and
+        // the other giant planets shift it by up to about another solar radius.
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let sun = sys.position_in::<meter>(Body::Sun);
+        let offset = sys.barycenter() - sun;
+        let sun_radii = offset.length() / sys.properties_of(Body::Sun).radius().get::<meter>();
+        assert!(sun_radii < 2.2, "{}", sun_radii);
+        let to_jupiter = (sys.position_in::<meter>(Body::Jupiter) - sun).normalize();
+        assert!(offset.normalize().dot(to_jupiter) > 0.5, "{:?}", offset);
+    }
+
+    #[test]
+    fn test_solar_system_recenter_on_barycenter() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        sys.recenter_on_barycenter();
+        assert!(sys.barycenter().length() < 1., "{}", sys.barycenter());
+        sys.advance_time_in_steps(Time::new::<day>(30.), Time::new::<minute>(30.));
+        assert!(sys.barycenter().length() < 1e3, "{}", sys.barycenter());
+        assert!(sys.diagnostics().energy_drift.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_solar_system_asteroids_follow_their_orbits() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        let props = BodyProperties::new(Mass::new::<kilogram>(0.), Length::new::<meter>(0.))
+            .with_sun_orbit(
+                Length::new::<astronomical_unit>(2.5),
+                0.1,
+                Angle::new::<degree>(5.),
+                Angle::new::<degree>(80.),
+                Angle::new::<degree>(30.),
+                epoch(),
+            );
+        sys.add_asteroids([props.clone()]);
+        sys.advance_time_in_steps(Time::new::<day>(100.), Time::new::<minute>(30.));
+        let jd = sys.current_time();
+        let exp = sys.position_of(Body::Sun) + props.sun_ecliptic_position(jd);
+        assert_eq!(sys.asteroid_positions(), vec![exp]);
+        let dist = Length::new::<meter>((exp - sys.position_of(Body::Sun)).magnitude());
+        assert!(dist > Length::new::<astronomical_unit>(2.25), "{:?}", dist);
+    }
+
+    #[test]
+    fn test_solar_system_apparent_position_of_sun_from_earth() {
+        // The Sun appears displaced from where it is by about 20.5 arcseconds, Earth's orbital
+        // speed times the light time from the Sun.
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let geometric = sys.position_of(Body::Sun) - sys.position_of(Body::Earth);
+        let apparent = sys.apparent_position_of(Body::Sun, Body::Earth);
+        let shift = Angle::new::<radian>(geometric.angle(&apparent)).get::<degree>() * 3600.;
+        assert!((shift - 20.5).abs() < 0.5, "{}", shift);
+        assert!((apparent.magnitude() / geometric.magnitude() - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_solar_system_apparent_position_of_observer_unmoved() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        assert_eq!(sys.apparent_position_of(Body::Earth, Body::Earth), Vector3::zeros());
+    }
+
+    #[test]
+    fn test_solar_system_relative_velocity_same_orbit() {
+        // Two bodies a quarter turn apart on the same circular orbit keep their distance, so their
+        // relative speed is √2 times their orbital speed, none of it radial.
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        let props = |periapsis_argument| {
+            BodyProperties::new(Mass::new::<kilogram>(1.), Length::new::<kilometer>(1.))
+                .with_sun_orbit(
+                    Length::new::<astronomical_unit>(1.),
+                    0.,
+                    Angle::new::<degree>(0.),
+                    Angle::new::<degree>(0.),
+                    Angle::new::<degree>(periapsis_argument),
+                    epoch(),
+                )
+        };
+        sys.add_body(Body::Custom(0), props(0.));
+        sys.add_body(Body::Custom(1), props(90.));
+        let speed = sys.velocity_in::<meter_per_second>(Body::Custom(0)).length();
+        let rel_speed = sys.relative_velocity(Body::Custom(1), Body::Custom(0)).length();
+        assert!((rel_speed / speed - 2f64.sqrt()).abs() < 1e-6, "{} {}", rel_speed, speed);
+        assert!(sys.radial_velocity(Body::Custom(1), Body::Custom(0)).abs() < 1e-6 * speed);
+    }
+
+    #[test]
+    fn test_solar_system_radial_velocity_symmetric() {
+        // Two bodies separate at the same rate seen from either one.
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        assert_eq!(
+            sys.radial_velocity(Body::Moon, Body::Earth),
+            sys.radial_velocity(Body::Earth, Body::Moon)
+        );
+        assert_eq!(sys.radial_velocity(Body::Earth, Body::Earth), 0.);
+    }
+
+    #[test]
+    fn test_solar_system_phase_of_moon_between_earth_and_sun() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        let earth = *sys.position_of(Body::Earth);
+        let sun = *sys.position_of(Body::Sun);
+        sys.body_states.get_mut(&Body::Moon).unwrap().position = (earth + sun) / 2.;
+        assert_rel_eq!(sys.phase_angle_of(Body::Moon, Body::Earth).get::<degree>(), 180.);
+        assert!(sys.illuminated_fraction_of(Body::Moon, Body::Earth) < 1e-12);
+        sys.body_states.get_mut(&Body::Moon).unwrap().position = 2. * earth - sun;
+        assert_eq!(sys.illuminated_fraction_of(Body::Moon, Body::Earth), 1.);
+    }
+
+    #[test]
+    fn test_solar_system_apparent_magnitude_of_sun() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let mag = sys.apparent_magnitude_of(Body::Sun, Body::Earth).unwrap();
+        assert!((mag + 26.74).abs() < 0.5, "{}", mag);
+        let diameter = sys.angular_diameter_of(Body::Sun, Body::Earth).get::<degree>() * 60.;
+        assert!((diameter - 32.5).abs() < 0.2, "{}", diameter);
+    }
+
+    #[test]
+    fn test_solar_system_apparent_magnitude_of_full_moon() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        let earth = *sys.position_of(Body::Earth);
+        let anti_sun = (earth - sys.position_of(Body::Sun)).normalize();
+        let moon = earth + Length::new::<kilometer>(384_400.).get::<meter>() * anti_sun;
+        sys.body_states.get_mut(&Body::Moon).unwrap().position = moon;
+        let mag = sys.apparent_magnitude_of(Body::Moon, Body::Earth).unwrap();
+        assert!((mag + 12.74).abs() < 0.5, "{}", mag);
+    }
+
+    #[test]
+    fn test_solar_system_moon_phases_on_their_dates() {
+        // The Moon was full on 2023-01-06 at 23:08 UTC and new on 2023-01-21 at 20:53 UTC.
+        let full = Time::new::<day>(2_459_951.464);
+        let new = Time::new::<day>(2_459_966.370);
+        let lit = |sys: &SolarSystem| sys.illuminated_fraction_of(Body::Moon, Body::Earth);
+        assert!(lit(&SolarSystem::init(full, Propagator::default())) > 0.99);
+        assert!(lit(&SolarSystem::init(new, Propagator::default())) < 0.01);
+
+        // The phases also fall on their dates when the Moon is propagated from the epoch.
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        sys.advance_time_in_steps(full - epoch(), Time::new::<minute>(30.));
+        assert!(lit(&sys) > 0.99, "{}", lit(&sys));
+        sys.advance_time_in_steps(new - full, Time::new::<minute>(30.));
+        assert!(lit(&sys) < 0.01, "{}", lit(&sys));
+    }
+
+    #[test]
+    fn test_solar_system_gravitational_parameter_of_sun() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let gm = sys.gravitational_parameter_of(Body::Sun);
+        assert!((gm / 1.327_124_4e20 - 1.).abs() < 1e-4, "{:e}", gm);
+        assert_eq!(sys.mass_of(Body::Sun), BodyProperties::sun().mass());
+    }
+
+    #[test]
+    fn test_solar_system_gravitational_parameters_of_planets_and_moons() {
+        // These are JPL's values in km³/s².
+        let exp = [
+            (Body::Mercury, 22_031.9),
+            (Body::Venus, 324_858.6),
+            (Body::Earth, 398_600.4),
+            (Body::Moon, 4_902.8),
+            (Body::Mars, 42_828.4),
+            (Body::Jupiter, 126_686_534.),
+            (Body::Io, 5_959.9),
+            (Body::Europa, 3_202.7),
+            (Body::Ganymede, 9_887.8),
+            (Body::Callisto, 7_179.3),
+            (Body::Saturn, 37_931_187.),
+            (Body::Uranus, 5_793_939.),
+            (Body::Neptune, 6_836_529.),
+            (Body::Triton, 1_427.6),
+        ];
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        for (body, gm_km) in exp {
+            let act = sys.gravitational_parameter_of(body) / 1e9;
+            assert!((act / gm_km - 1.).abs() < 1e-3, "{:?}: {}", body, act);
+        }
+    }
+
+    #[test]
+    fn test_solar_system_gravitational_parameters_of_earth_and_moon() {
+        // Earth is 81.30 times as massive as the Moon, and together their GM is 4.035e14 m³/s².
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let earth = sys.gravitational_parameter_of(Body::Earth);
+        let moon = sys.gravitational_parameter_of(Body::Moon);
+        assert!((earth / moon / 81.30 - 1.).abs() < 1e-3, "{}", earth / moon);
+        assert!(((earth + moon) / 4.035_03e14 - 1.).abs() < 1e-4, "{:e}", earth + moon);
+        let ratio = f64::from(sys.mass_of(Body::Earth) / sys.mass_of(Body::Moon));
+        assert_rel_eq!(ratio, earth / moon);
+    }
+
+    #[test]
+    fn test_solar_system_distance_between_earth_moon() {
+        // The Moon stays between its perigee and apogee over a month.
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        for _ in 0..30 {
+            sys.advance_time_in_steps(Time::new::<day>(1.), Time::new::<minute>(30.));
+            let dist = sys.distance_between(Body::Earth, Body::Moon).get::<kilometer>();
+            assert!((dist - 384_400.).abs() < 25_000., "{}", dist);
+        }
+        assert_eq!(
+            sys.distance_between(Body::Moon, Body::Earth),
+            sys.distance_between(Body::Earth, Body::Moon)
+        );
+    }
+
+    #[test]
+    fn test_solar_system_state_at_epoch() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let (pos, vel) = sys.state_at(Body::Mars, epoch());
+        assert_eq!(pos, *sys.position_of(Body::Mars));
+        assert_eq!(vel, *sys.velocity_of(Body::Mars));
+    }
+
+    #[test]
+    fn test_solar_system_earth_orbits_earth_moon_barycenter() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let earth_props = sys.properties_of(Body::Earth);
+        let mass = |body| sys.mass_of(body).get::<kilogram>();
+        for days in [0., 7., 14., 21.] {
+            let jd = epoch() + Time::new::<day>(days);
+            let (earth, _) = sys.state_at(Body::Earth, jd);
+            let (moon, _) = sys.state_at(Body::Moon, jd);
+            let offset = earth - earth_props.sun_ecliptic_position(jd);
+            let offset_km = Length::new::<meter>(offset.magnitude()).get::<kilometer>();
+            assert!((4_400. ..4_950.).contains(&offset_km), "{}", offset_km);
+            let barycenter = (mass(Body::Earth) * earth + mass(Body::Moon) * moon)
+                / (mass(Body::Earth) + mass(Body::Moon));
+            assert_rel_eq!(barycenter, earth_props.sun_ecliptic_position(jd));
+            assert!(offset.dot(&(moon - earth)) < 0.);
+        }
+        let (earth, _) = sys.state_at(Body::Earth, epoch());
+        assert_eq!(earth, *sys.position_of(Body::Earth));
+    }
+
+    #[test]
+    fn test_solar_system_predicted_position_follows_simulation() {
+        let mut sys = SolarSystem::init(epoch(), Propagator::default());
+        let now = sys.predicted_position_of(Body::Moon, Time::new::<day>(0.));
+        assert_eq!(now, *sys.position_of(Body::Moon));
+        let span = Time::new::<day>(5.);
+        let exp = sys.predicted_position_of(Body::Moon, span);
+        let earth = sys.predicted_position_of(Body::Earth, span);
+        sys.advance_time_in_steps(span, Time::new::<minute>(30.));
+        // The Sun pulls the Moon off of its Kepler orbit around Earth by a little.
+        let err = (sys.position_of(Body::Moon) - exp).norm() / (exp - earth).norm();
+        assert!(err < 0.02, "{}", err);
+    }
+
+    #[test]
+    fn test_solar_system_snapshot_matches_state_at() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let jd = epoch() + Time::new::<day>(42.);
+        let snap = sys.snapshot(jd);
+        assert_eq!(snap.len(), Body::VARIANTS.len());
+        assert_eq!(snap[&Body::Jupiter], sys.state_at(Body::Jupiter, jd));
+    }
+
+    #[test]
+    fn test_solar_system_geocentric_directions() {
+        let sys = SolarSystem::init(epoch(), Propagator::default());
+        let dirs = sys.geocentric_directions(
+            Body::Mars, epoch(), epoch() + Time::new::<day>(10.), 11
+        );
+        assert_eq!(dirs.len(), 11);
+        let (earth_pos, _) = sys.state_at(Body::Earth, epoch());
+        let (mars_pos, _) = sys.state_at(Body::Mars, epoch());
+        assert_rel_eq!(dirs[0], (mars_pos - earth_pos).normalize());
+    }
 }
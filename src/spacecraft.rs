@@ -0,0 +1,317 @@
+use bevy::{math::DVec3, prelude::*};
+use nalgebra::Vector3;
+
+use crate::{
+    kepler_orbit as kepler,
+    simulation::{Body, SolarSystem},
+    to_world_position, DrawnPositions,
+    uom_wrapper::si::{
+        f64::{Length, Time},
+        length::{kilometer, meter},
+        time::{day, hour},
+    },
+    SimSet, Simulation,
+};
+
+// The longest span in hours the spacecraft coasts along one conic before checking whether it has
+// crossed into another body's sphere of influence
+const PATCH_STEP_H: f64 = 1.;
+
+// How far ahead in days the spacecraft's predicted trajectory reaches
+const PREDICTION_SPAN_D: f64 = 14.;
+
+// The radius in AU of the marker drawn at the spacecraft
+const MARKER_AU: f32 = 2e-4;
+
+const CRAFT_COLOR: Color = Color::srgb(1., 0.4, 0.8);
+const PREDICTION_COLOR: Color = Color::srgb(0.6, 0.25, 0.5);
+
+// This is a massless test particle, like a spacecraft, propagated with patched conics. It coasts on
+// a Kepler orbit about its primary, the body whose sphere of influence it is in, and when it
+// crosses into another sphere of influence, its orbit is re-anchored to that body. The position in
+// meters and velocity in m/s are relative to the primary at the time, a Julian Date. The bodies
+// are placed on their own Kepler orbits, so the spacecraft's motion doesn't depend on how the
+// simulation integrates them, and the predicted trajectory is the one the spacecraft follows.
+#[derive(Clone, Debug, Resource)]
+pub struct Spacecraft {
+    primary: Body,
+    position: Vector3<f64>,
+    velocity: Vector3<f64>,
+    time: Time,
+}
+
+impl Spacecraft {
+    // Launch a spacecraft at a Julian Date from the given position in km and velocity in km/s
+    // relative to a body. If the simulation is at another time, the spacecraft is moved along its
+    // trajectory to the simulation's time.
+    pub fn new(body: Body, time: Time, position_km: DVec3, velocity_kmps: DVec3) -> Self {
+        let to_m = |v: DVec3| 1e3 * Vector3::new(v.x, v.y, v.z);
+        Self {
+            primary: body,
+            position: to_m(position_km),
+            velocity: to_m(velocity_kmps),
+            time,
+        }
+    }
+
+    // The body whose sphere of influence the spacecraft is in
+    pub fn primary(&self) -> Body {
+        self.primary
+    }
+
+    // Return the distance from the spacecraft to its primary
+    pub fn distance(&self) -> Length {
+        Length::new::<meter>(self.position.norm())
+    }
+
+    // Return the heliocentric position and velocity of a body's Kepler orbit at the spacecraft's
+    // time
+    fn state_of(&self, sys: &SolarSystem, body: Body) -> (Vector3<f64>, Vector3<f64>) {
+        sys.state_at(body, self.time)
+    }
+
+    // Re-anchor the spacecraft's orbit to the body whose sphere of influence it is in, moving up
+    // to the primary's primary while outside the primary's sphere and down to a satellite of the
+    // primary while inside the satellite's sphere
+    fn repatch(&mut self, sys: &SolarSystem) {
+        loop {
+            let (primary_pos, primary_vel) = self.state_of(sys, self.primary);
+            let soi = sys.sphere_of_influence_of(self.primary).get::<meter>();
+            if let Some(outer) = self.primary.primary().filter(|_| self.position.norm() > soi) {
+                let (outer_pos, outer_vel) = self.state_of(sys, outer);
+                self.position += primary_pos - outer_pos;
+                self.velocity += primary_vel - outer_vel;
+                self.primary = outer;
+                continue;
+            }
+
+            let inner = sys.bodies().into_iter().find_map(|body| {
+                if body.primary() != Some(self.primary) {
+                    return None;
+                }
+                let (pos, vel) = self.state_of(sys, body);
+                let rel_pos = self.position + primary_pos - pos;
+                let soi = sys.sphere_of_influence_of(body).get::<meter>();
+                (rel_pos.norm() < soi).then(|| (body, rel_pos, self.velocity + primary_vel - vel))
+            });
+            match inner {
+                Some((body, pos, vel)) => {
+                    (self.primary, self.position, self.velocity) = (body, pos, vel);
+                }
+                None => return,
+            }
+        }
+    }
+
+    // Move the spacecraft along its trajectory for a span of time, which may be negative
+    pub fn advance(&mut self, sys: &SolarSystem, span: Time) {
+        let max_step = Time::new::<hour>(PATCH_STEP_H);
+        let steps = f64::from(span / max_step).abs().ceil();
+        let step = if steps > 0. { span / steps } else { span };
+        self.repatch(sys);
+        for _ in 0..steps as usize {
+            let mu = sys.gravitational_parameter_of(self.primary);
+            (self.position, self.velocity) =
+                kepler::propagate_state(mu, &self.position, &self.velocity, step);
+            self.time += step;
+            self.repatch(sys);
+        }
+    }
+
+    // Return the spacecraft's positions in meters, relative to its current primary, at evenly
+    // spaced times over a span starting now. Each position is relative to where the primary is
+    // at that time.
+    pub fn predict(&self, sys: &SolarSystem, span: Time, samples: usize) -> Vec<Vector3<f64>> {
+        let mut craft = self.clone();
+        let step = span / samples.max(2).saturating_sub(1) as f64;
+        (0..samples.max(2))
+            .map(|i| {
+                if i > 0 {
+                    craft.advance(sys, step);
+                }
+                let (pos, _) = craft.state_of(sys, craft.primary);
+                let (center, _) = craft.state_of(sys, self.primary);
+                pos + craft.position - center
+            })
+            .collect()
+    }
+}
+
+// This is the spacecraft's predicted trajectory, positions in meters relative to its primary at
+// the times from the start Julian Date a step apart. The spacecraft coasts along it, so it only
+// needs to be predicted again when the spacecraft is re-anchored to another body, is launched
+// anew, or leaves the span it covers.
+#[derive(Default, Resource)]
+struct PredictedPath {
+    primary: Option<Body>,
+    start: Time,
+    step: Time,
+    positions: Vec<Vector3<f64>>,
+}
+
+impl PredictedPath {
+    fn predict(craft: &Spacecraft, sys: &SolarSystem) -> Self {
+        let span = Time::new::<day>(PREDICTION_SPAN_D);
+        let samples = (PREDICTION_SPAN_D * 24. / PATCH_STEP_H) as usize + 1;
+        Self {
+            primary: Some(craft.primary),
+            start: craft.time,
+            step: span / (samples - 1) as f64,
+            positions: craft.predict(sys, span, samples),
+        }
+    }
+
+    // Determine whether the path still holds the spacecraft's trajectory from where it is now
+    fn covers(&self, craft: &Spacecraft) -> bool {
+        let end = self.start + self.step * self.positions.len().saturating_sub(1) as f64;
+        self.primary == Some(craft.primary) && craft.time >= self.start && craft.time <= end
+    }
+
+    // Return the positions the spacecraft hasn't reached yet
+    fn ahead_of(&self, craft: &Spacecraft) -> &[Vector3<f64>] {
+        let passed = f64::from((craft.time - self.start) / self.step).floor().max(0.) as usize;
+        &self.positions[passed.min(self.positions.len())..]
+    }
+}
+
+// The spacecraft keeps pace with the simulated time, forward or backward. Each time it crosses
+// into another body's sphere of influence, it is logged.
+fn advance_spacecraft(sim: Res<Simulation>, mut craft: ResMut<Spacecraft>) {
+    let span = sim.current_time() - craft.time;
+    if span.value == 0. {
+        return;
+    }
+
+    let before = craft.primary;
+    craft.advance(&sim.solar_system, span);
+    if craft.primary != before {
+        info!(
+            "The spacecraft entered the sphere of influence of {} {:.0} km from it",
+            sim.name_of(craft.primary),
+            craft.distance().get::<kilometer>()
+        );
+    }
+}
+
+// The spacecraft is drawn at its position relative to where its primary is drawn, along with the
+// rest of its predicted trajectory relative to its primary. It is drawn every frame, so it stays
+// with its primary's avatar between steps.
+fn draw_spacecraft(
+    sim: Res<Simulation>,
+    craft: Res<Spacecraft>,
+    mut path: ResMut<PredictedPath>,
+    drawn: DrawnPositions,
+    mut gizmos: Gizmos,
+) {
+    if craft.is_added() || !path.covers(&craft) {
+        *path = PredictedPath::predict(&craft, &sim.solar_system);
+    }
+    let center = drawn.of(craft.primary);
+    let ahead = path.ahead_of(&craft).iter().map(|pos| center + to_world_position(pos));
+    gizmos.linestrip(ahead, PREDICTION_COLOR);
+    gizmos.sphere(
+        center + to_world_position(&craft.position),
+        Quat::IDENTITY,
+        MARKER_AU,
+        CRAFT_COLOR,
+    );
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<PredictedPath>()
+        .add_systems(
+            FixedUpdate,
+            advance_spacecraft
+                .in_set(SimSet::UpdateBodies)
+                .run_if(resource_exists::<Spacecraft>),
+        )
+        .add_systems(
+            Update,
+            draw_spacecraft
+                .after(crate::update_avatars)
+                .run_if(resource_exists::<Spacecraft>),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Rotation3, Unit};
+
+    use super::*;
+    use crate::{default_epoch, simulation::Propagator};
+
+    fn km(vector: &Vector3<f64>) -> DVec3 {
+        DVec3::new(vector.x, vector.y, vector.z) / 1e3
+    }
+
+    #[test]
+    fn test_predicted_path_kept_until_repatched() {
+        let sys = SolarSystem::init(default_epoch(), Propagator::default());
+        let (earth, _) = sys.state_at(Body::Earth, default_epoch());
+        let (moon, _) = sys.state_at(Body::Moon, default_epoch());
+        let pos = km(&(0.5 * (moon - earth)));
+        let mut craft = Spacecraft::new(Body::Earth, default_epoch(), pos, DVec3::ZERO);
+        let path = PredictedPath::predict(&craft, &sys);
+        craft.advance(&sys, Time::new::<day>(1.));
+        assert!(path.covers(&craft));
+        assert_eq!(path.ahead_of(&craft).len(), path.positions.len() - 24);
+
+        // Re-anchored to the Moon, the spacecraft follows another trajectory.
+        craft.primary = Body::Moon;
+        assert!(!path.covers(&craft));
+
+        // Nor does the path hold the trajectory past its end.
+        craft.primary = Body::Earth;
+        craft.time = default_epoch() + Time::new::<day>(PREDICTION_SPAN_D + 1.);
+        assert!(!path.covers(&craft));
+    }
+
+    #[test]
+    fn test_repatch_enters_moon_sphere() {
+        let sys = SolarSystem::init(default_epoch(), Propagator::default());
+        let (earth, _) = sys.state_at(Body::Earth, default_epoch());
+        let (moon, _) = sys.state_at(Body::Moon, default_epoch());
+        let pos = km(&(earth + 0.95 * (moon - earth)));
+        let mut craft = Spacecraft::new(Body::Sun, default_epoch(), pos, DVec3::ZERO);
+        craft.advance(&sys, Time::new::<day>(0.));
+        assert_eq!(craft.primary(), Body::Moon);
+        let dist = craft.distance().get::<kilometer>();
+        let exp = 0.05 * (moon - earth).norm() / 1e3;
+        assert!((dist - exp).abs() < 1., "{} {}", dist, exp);
+    }
+
+    // The spacecraft leaves a 200 km high parking orbit in the plane of the Moon's orbit on a
+    // trajectory swinging around the Moon and back to Earth without firing its engines again.
+    #[test]
+    fn test_free_return_around_moon() {
+        let start = default_epoch();
+        let sys = SolarSystem::init(start, Propagator::default());
+        let (earth_pos, earth_vel) = sys.state_at(Body::Earth, start);
+        let (moon_pos, moon_vel) = sys.state_at(Body::Moon, start);
+        let moon_dir = (moon_pos - earth_pos).normalize();
+        let pole = Unit::new_normalize(moon_dir.cross(&(moon_vel - earth_vel)));
+        let launch_dir = Rotation3::from_axis_angle(&pole, 60_f64.to_radians()) * -moon_dir;
+        let mut craft = Spacecraft::new(
+            Body::Earth,
+            start,
+            km(&(6_578e3 * launch_dir)),
+            km(&(10_910. * pole.cross(&launch_dir))),
+        );
+
+        let mut lunar_closest = f64::INFINITY;
+        let mut return_closest = f64::INFINITY;
+        for _ in 0..14 * 24 {
+            craft.advance(&sys, Time::new::<hour>(1.));
+            let dist = craft.distance().get::<kilometer>();
+            match craft.primary() {
+                Body::Moon => lunar_closest = lunar_closest.min(dist),
+                Body::Earth if lunar_closest.is_finite() => {
+                    return_closest = return_closest.min(dist)
+                }
+                _ => (),
+            }
+        }
+        assert!(lunar_closest > 1_737. && lunar_closest < 66_000., "{}", lunar_closest);
+        assert!(return_closest < 20_000., "{}", return_closest);
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+
+use crate::{Observer, SimSet, WorldBounds};
+
+// The catalog of stars as lines of Hipparcos number, J2000 right ascension and declination in
+// degrees, and visual magnitude
+const CATALOG: &str = include_str!("../data/hipparcos_bright.csv");
+
+// The angle between the celestial equator and the ecliptic at J2000
+const OBLIQUITY_DEG: f32 = 23.439_28;
+
+// The stars are drawn on a sphere around the camera just inside of the far clipping plane. This is
+// the sphere's radius as a fraction of the distance to the far plane.
+const SPHERE_FRACTION: f32 = 0.9;
+
+// The angular radius in radians of a magnitude 0 star
+const MAG0_ANG_RADIUS: f32 = 1.2e-3;
+
+// The brightness of a star is compressed relative to its true flux, 10^(-0.4 m), so that the faint
+// stars remain visible next to the bright ones.
+fn brightness(vmag: f32) -> f32 {
+    10_f32.powf(-0.2 * vmag)
+}
+
+fn ang_radius(vmag: f32) -> f32 {
+    MAG0_ANG_RADIUS * 10_f32.powf(-0.1 * vmag)
+}
+
+// A star from the catalog
+#[derive(Clone, Copy, Debug)]
+struct Star {
+    ra_deg: f32,
+    dec_deg: f32,
+    vmag: f32,
+}
+
+impl Star {
+    // The unit direction toward the star in the ecliptic frame
+    fn direction(&self) -> Vec3 {
+        let (ra, dec) = (self.ra_deg.to_radians(), self.dec_deg.to_radians());
+        let equatorial = Vec3::new(dec.cos() * ra.cos(), dec.cos() * ra.sin(), dec.sin());
+        Quat::from_rotation_x(-OBLIQUITY_DEG.to_radians()) * equatorial
+    }
+}
+
+// Return a direction in the ecliptic frame in the equatorial frame instead, undoing the tilt
+// Star::direction applies
+pub fn ecliptic_to_equatorial(direction: Vec3) -> Vec3 {
+    Quat::from_rotation_x(OBLIQUITY_DEG.to_radians()) * direction
+}
+
+fn parse_catalog(catalog: &str) -> Vec<Star> {
+    catalog
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter_map(|line| {
+            let fields: Vec<f32> = line.split(',').filter_map(|f| f.trim().parse().ok()).collect();
+            match fields[..] {
+                [_, ra_deg, dec_deg, vmag] => Some(Star { ra_deg, dec_deg, vmag }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// This is the celestial sphere. It moves with the camera, so the stars only respond to the camera
+// turning, as real stars are too far away to show any parallax.
+#[derive(Component)]
+struct Starfield;
+
+fn create_starfield(
+    bounds: Res<WorldBounds>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(Sphere::new(1.).mesh().uv(8, 6));
+    let sphere_radius = SPHERE_FRACTION * bounds.zfar_au;
+    commands
+        .spawn((SpatialBundle::default(), Starfield))
+        .with_children(|parent| {
+            for star in parse_catalog(CATALOG) {
+                let radius = sphere_radius * ang_radius(star.vmag);
+                parent.spawn((
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: materials.add(StandardMaterial {
+                            emissive: LinearRgba::WHITE * brightness(star.vmag),
+                            unlit: true,
+                            ..default()
+                        }),
+                        transform: Transform::from_translation(sphere_radius * star.direction())
+                            .with_scale(Vec3::splat(radius)),
+                        ..default()
+                    },
+                    NotShadowCaster,
+                    NotShadowReceiver,
+                ));
+            }
+        });
+}
+
+fn update_starfield(
+    observer: Query<&Observer>,
+    mut starfield: Query<&mut Transform, With<Starfield>>,
+) {
+    let position = *observer.single().position();
+    for mut transform in &mut starfield {
+        transform.translation = position;
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.add_systems(Startup, create_starfield)
+        .add_systems(FixedUpdate, update_starfield.in_set(SimSet::Render))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_catalog() {
+        let stars = parse_catalog(CATALOG);
+        assert!(stars.len() > 90, "{}", stars.len());
+        assert!(stars.iter().all(|s| s.vmag < 4.));
+    }
+
+    #[test]
+    fn test_star_direction_celestial_pole() {
+        let pole = Star { ra_deg: 0., dec_deg: 90., vmag: 0. }.direction();
+        let ecliptic_lat = pole.z.asin().to_degrees();
+        assert!((ecliptic_lat - (90. - OBLIQUITY_DEG)).abs() < 1e-4, "{}", ecliptic_lat);
+    }
+
+    #[test]
+    fn test_star_direction_vernal_equinox() {
+        let equinox = Star { ra_deg: 0., dec_deg: 0., vmag: 0. }.direction();
+        assert!(equinox.distance(Vec3::X) < 1e-6, "{:?}", equinox);
+    }
+
+    #[test]
+    fn test_ecliptic_to_equatorial_undoes_direction() {
+        let star = Star { ra_deg: 83.8, dec_deg: -5.4, vmag: 0. };
+        let dir = ecliptic_to_equatorial(star.direction());
+        let (ra, dec) = (dir.y.atan2(dir.x).to_degrees(), dir.z.asin().to_degrees());
+        assert!((ra - 83.8).abs() < 1e-3 && (dec + 5.4).abs() < 1e-3, "{} {}", ra, dec);
+    }
+
+    #[test]
+    fn test_brighter_stars_larger() {
+        assert!(ang_radius(-1.) > ang_radius(2.));
+        assert!(brightness(-1.) > brightness(2.));
+    }
+}
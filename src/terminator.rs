@@ -0,0 +1,182 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+
+use crate::{simulation::Body, BodyModel, SimSet, Simulation};
+
+// The radius of the night side shell relative to its avatar's, just enough to keep it clear of the
+// avatar's surface
+const SHELL_RADIUS: f32 = 1.005;
+
+// The number of rings of vertices from the night side's pole to its edge and the number of
+// vertices around each ring
+const RINGS: usize = 24;
+const SEGMENTS: usize = 64;
+
+// The color of the dusk band along the terminator
+const DUSK_COLOR: Color = Color::srgb(1., 0.45, 0.2);
+
+// This controls the shading of the night sides of the bodies with their terminators emphasized.
+// The point light from the Sun already leaves the far side of each body unlit, but the ambient
+// light and the bloom around the Sun soften the line between day and night. Each listed body is
+// covered on the side away from the Sun by a shell that darkens it to the night opacity, fading
+// through a dusk band twilight_deg wide on each side of the terminator. Changes take effect at
+// startup.
+#[derive(Resource)]
+pub struct NightSides {
+    pub bodies: Vec<Body>,
+    pub opacity: f32,
+    pub twilight_deg: f32,
+}
+
+impl Default for NightSides {
+    fn default() -> Self {
+        Self {
+            bodies: vec![Body::Earth],
+            opacity: 0.85,
+            twilight_deg: 8.,
+        }
+    }
+}
+
+// Return the color of the shell at an angle from the night side's pole, given the width of the
+// dusk band in radians. It is the night opacity over most of the night side, warms to the dusk
+// color approaching the terminator, and is clear on the day side past the band.
+fn shell_color(angle: f32, twilight: f32, opacity: f32) -> Color {
+    let from_terminator = angle - FRAC_PI_2;
+    let darkness = 1. - ((from_terminator + twilight) / (2. * twilight)).clamp(0., 1.);
+    let dusk = 1. - (from_terminator.abs() / twilight).min(1.);
+    Color::BLACK.mix(&DUSK_COLOR, dusk).with_alpha(opacity * darkness.powi(2).max(dusk / 2.))
+}
+
+// The shell is a cap of a unit sphere centered on +Z, reaching past the equator by the width of the
+// dusk band.
+fn mk_shell_mesh(twilight: f32, opacity: f32) -> Mesh {
+    let max_angle = FRAC_PI_2 + twilight;
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    for ring in 0..=RINGS {
+        let angle = max_angle * ring as f32 / RINGS as f32;
+        let color = shell_color(angle, twilight, opacity).to_linear().to_f32_array();
+        let (sin, cos) = angle.sin_cos();
+        for seg in 0..=SEGMENTS {
+            let lon = std::f32::consts::TAU * seg as f32 / SEGMENTS as f32;
+            positions.push(Vec3::new(sin * lon.cos(), sin * lon.sin(), cos));
+            colors.push(color);
+        }
+    }
+    let mut indices = Vec::new();
+    for ring in 0..RINGS as u32 {
+        for seg in 0..SEGMENTS as u32 {
+            let a = ring * (SEGMENTS as u32 + 1) + seg;
+            let b = a + SEGMENTS as u32 + 1;
+            indices.extend([a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+    let normals = positions.clone();
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+// Return the rotation of a shell relative to its avatar that points the shell's pole away from the
+// Sun. The avatar is turned to the body's orientation.
+fn shell_rotation(orientation: Quat, body_pos: Vec3, sun_pos: Vec3) -> Quat {
+    let away = (body_pos - sun_pos).normalize_or(Vec3::Z);
+    orientation.inverse() * Quat::from_rotation_arc(Vec3::Z, away)
+}
+
+// This is the night side shell of a body.
+#[derive(Component)]
+struct NightSide(Body);
+
+// Each shell is a child of its body's avatar, so it is sized with the avatar.
+fn create_night_sides(
+    night_sides: Res<NightSides>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    bodies: Query<(&Body, &BodyModel)>,
+) {
+    let twilight = night_sides.twilight_deg.to_radians();
+    let mesh = meshes.add(mk_shell_mesh(twilight, night_sides.opacity));
+    let material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    for (body, model) in &bodies {
+        let Some(avatar) = model.avatar().filter(|_| night_sides.bodies.contains(body)) else {
+            continue;
+        };
+        commands.entity(avatar).with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_scale(Vec3::splat(SHELL_RADIUS)),
+                    ..default()
+                },
+                NotShadowCaster,
+                NotShadowReceiver,
+                NightSide(*body),
+            ));
+        });
+    }
+}
+
+// The shells turn to face away from the Sun as the bodies move and spin.
+fn orient_night_sides(sim: Res<Simulation>, mut shells: Query<(&NightSide, &mut Transform)>) {
+    let sun_pos = sim.position_of(Body::Sun);
+    for (shell, mut transform) in &mut shells {
+        transform.rotation =
+            shell_rotation(sim.orientation_of(shell.0), sim.position_of(shell.0), sun_pos);
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<NightSides>()
+        .add_systems(crate::SpawnBodies, create_night_sides.after(crate::create_avatars))
+        .add_systems(FixedUpdate, orient_night_sides.in_set(SimSet::Render))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_color_across_terminator() {
+        let twilight = 0.1;
+        assert_eq!(shell_color(0., twilight, 0.8).alpha(), 0.8);
+        assert_eq!(shell_color(FRAC_PI_2 + twilight, twilight, 0.8).alpha(), 0.);
+        let dusk = LinearRgba::from(shell_color(FRAC_PI_2, twilight, 0.8));
+        assert!(dusk.red > dusk.blue && dusk.alpha > 0., "{:?}", dusk);
+    }
+
+    #[test]
+    fn test_shell_rotation_points_away_from_sun() {
+        let orientation = Quat::from_rotation_x(0.4);
+        let rot = shell_rotation(orientation, Vec3::new(1., 1., 0.), Vec3::ZERO);
+        let pole = orientation * rot * Vec3::Z;
+        assert!(pole.distance(Vec3::new(1., 1., 0.).normalize()) < 1e-5, "{}", pole);
+    }
+
+    #[test]
+    fn test_shell_mesh_covers_night_side() {
+        let mesh = mk_shell_mesh(0.1, 0.8);
+        assert_eq!(mesh.count_vertices(), (RINGS + 1) * (SEGMENTS + 1));
+        let points = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().as_float3().unwrap();
+        let min_z = points.iter().map(|p| p[2]).fold(1., f32::min);
+        assert!((min_z + 0.1f32.sin()).abs() < 1e-5, "{}", min_z);
+    }
+}
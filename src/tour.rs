@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera_animation::{CameraAnimation, ObserverPose},
+    follow_camera::{self, FlyTo, FlyToBody, FOLLOW_RATE},
+    selection::Selected,
+    simulation::Body,
+    BodyModel, Observer,
+};
+
+// The key ending the tour, like it stops following a body
+const STOP_KEY: KeyCode = KeyCode::Escape;
+
+// This controls the tour of the planets. The tour flies the camera to each body in turn, from the
+// Sun outward, and lingers there for dwell_s seconds, circling the body at spin_rate radians per
+// second as it moves. The key starts and stops the tour, and the skip key moves on to the next
+// body early. The flights are those the fly to key makes, and the camera lingers as far from each
+// body. Simulated time passes as usual, so speeding it up shows the bodies moving along their
+// orbits during the tour.
+#[derive(Resource)]
+pub struct Tour {
+    pub key: KeyCode,
+    pub skip_key: KeyCode,
+    pub bodies: Vec<Body>,
+    pub dwell_s: f32,
+    pub spin_rate: f32,
+}
+
+impl Default for Tour {
+    fn default() -> Self {
+        Self {
+            key: KeyCode::KeyI,
+            skip_key: KeyCode::Tab,
+            bodies: vec![
+                Body::Mercury,
+                Body::Venus,
+                Body::Earth,
+                Body::Mars,
+                Body::Jupiter,
+                Body::Saturn,
+                Body::Uranus,
+                Body::Neptune,
+            ],
+            dwell_s: 6.,
+            spin_rate: 0.1,
+        }
+    }
+}
+
+// This is where the tour is. Stops are indexes into the tour's bodies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Resource)]
+enum Leg {
+    #[default]
+    Off,
+    Flying(usize),
+    Dwelling { stop: usize, elapsed: f32 },
+}
+
+impl Leg {
+    // Return the leg heading for the stop after the given one, or Off after the last of the stops
+    fn after_stop(stop: usize, stops: usize) -> Self {
+        if stop + 1 < stops {
+            Self::Flying(stop + 1)
+        } else {
+            Self::Off
+        }
+    }
+
+    fn stop(self) -> Option<usize> {
+        match self {
+            Self::Off => None,
+            Self::Flying(stop) | Self::Dwelling { stop, .. } => Some(stop),
+        }
+    }
+
+    // Return the leg dt seconds later. A flight lasts as long as its camera animation, and the
+    // camera dwells at each stop for dwell_s seconds before flying on.
+    fn advance(self, dt: f32, in_flight: bool, dwell_s: f32, stops: usize) -> Self {
+        match self {
+            Self::Off => self,
+            Self::Flying(_) if in_flight => self,
+            Self::Flying(stop) => Self::Dwelling { stop, elapsed: 0. },
+            Self::Dwelling { stop, elapsed } if elapsed + dt < dwell_s => Self::Dwelling {
+                stop,
+                elapsed: elapsed + dt,
+            },
+            Self::Dwelling { stop, .. } => Self::after_stop(stop, stops),
+        }
+    }
+}
+
+// Return the pose of an observer dwelling at a body, where spin is the angle it circles the body
+// by about the ecliptic's axis, and t is the fraction of the way it moves from its current position
+// to the given distance from the body. The observer looks at the body.
+fn dwell_pose(
+    current: &ObserverPose,
+    body_pos: Vec3,
+    distance: f32,
+    spin: f32,
+    t: f32,
+) -> ObserverPose {
+    let back = Quat::from_rotation_z(spin) * (current.position - body_pos);
+    let back = back.normalize_or(-*current.facing);
+    let position = current.position.lerp(body_pos + distance * back, t);
+    let facing = Dir3::new(body_pos - position).unwrap_or(current.facing);
+    let up = Dir3::new(*current.up - current.up.dot(*facing) * *facing).unwrap_or(current.up);
+    ObserverPose { position, facing, up }
+}
+
+// The tour's key starts the tour from the first body or stops it, the skip key heads for the next
+// body, and Escape stops the tour. Each flight selects the body it heads for.
+fn run_tour(
+    (time, input): (Res<Time>, Res<ButtonInput<KeyCode>>),
+    (tour, fly_to): (Res<Tour>, Res<FlyTo>),
+    mut leg: ResMut<Leg>,
+    (mut anim, mut selected): (ResMut<CameraAnimation>, ResMut<Selected>),
+    mut flights: EventWriter<FlyToBody>,
+    mut observer: Query<&mut Observer>,
+    bodies: Query<(&Body, &BodyModel)>,
+) {
+    let stops = tour.bodies.len();
+    let prev = *leg;
+    let next = if input.just_pressed(tour.key) {
+        if prev == Leg::Off && stops > 0 {
+            Leg::Flying(0)
+        } else {
+            Leg::Off
+        }
+    } else if input.just_pressed(STOP_KEY) {
+        Leg::Off
+    } else if let Some(stop) = prev.stop().filter(|_| input.just_pressed(tour.skip_key)) {
+        Leg::after_stop(stop, stops)
+    } else {
+        prev.advance(time.delta_seconds(), anim.is_active(), tour.dwell_s, stops)
+    };
+    leg.set_if_neq(next);
+
+    match next {
+        Leg::Off if prev != Leg::Off => {
+            anim.cancel();
+            info!("Tour ended");
+        }
+        Leg::Flying(stop) if prev != next => {
+            let body = tour.bodies[stop];
+            selected.0 = Some(body);
+            flights.send(FlyToBody(body));
+        }
+        Leg::Dwelling { stop, elapsed } => {
+            let Some((_, model)) = bodies.iter().find(|(body, _)| **body == tour.bodies[stop])
+            else {
+                return;
+            };
+            let mut observer = observer.single_mut();
+            let dt = time.delta_seconds();
+            let pose = dwell_pose(
+                &observer.pose(),
+                *model.position(),
+                fly_to.distance_radii * model.avatar_radius(),
+                tour.spin_rate * dt,
+                if elapsed > 0. { 1. - (-FOLLOW_RATE * dt).exp() } else { 0. },
+            );
+            observer.set_focus(*model.position());
+            observer.set_pose(&pose);
+        }
+        _ => (),
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<Tour>()
+        .init_resource::<Leg>()
+        .add_systems(Update, run_tour.before(follow_camera::fly_to_body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leg_dwells_after_flight() {
+        let leg = Leg::Flying(2);
+        assert_eq!(leg.advance(0.1, true, 6., 8), leg);
+        assert_eq!(leg.advance(0.1, false, 6., 8), Leg::Dwelling { stop: 2, elapsed: 0. });
+    }
+
+    #[test]
+    fn test_leg_moves_on_after_dwelling() {
+        let leg = Leg::Dwelling { stop: 2, elapsed: 5.5 };
+        assert_eq!(leg.advance(0.25, false, 6., 8), Leg::Dwelling { stop: 2, elapsed: 5.75 });
+        assert_eq!(leg.advance(0.5, false, 6., 8), Leg::Flying(3));
+        let last = Leg::Dwelling { stop: 7, elapsed: 5.5 };
+        assert_eq!(last.advance(0.5, false, 6., 8), Leg::Off);
+    }
+
+    #[test]
+    fn test_dwell_pose_circles_body() {
+        let current = ObserverPose {
+            position: Vec3::new(10., 0., 0.),
+            facing: Dir3::NEG_X,
+            up: Dir3::Z,
+        };
+        let pose = dwell_pose(&current, Vec3::ZERO, 10., std::f32::consts::FRAC_PI_2, 1.);
+        assert!(pose.position.distance(Vec3::new(0., 10., 0.)) < 1e-4, "{:?}", pose);
+        assert!(pose.facing.dot(Vec3::NEG_Y) > 0.999_9, "{:?}", pose);
+        assert_eq!(pose.up, Dir3::Z);
+    }
+}
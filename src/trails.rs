@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages},
+    utils::HashMap,
+};
+
+use crate::{layers::Layer, simulation::Body, BodyModel, Layers, Simulation, StepFraction};
+
+// By default, a planet's trail holds this many points per orbit and covers this fraction of its
+// orbit.
+const PLANET_POINTS_PER_ORBIT: f32 = 360.;
+const PLANET_TRAIL_FRACTION: f32 = 0.5;
+
+// By default, a moon's trail is sampled as finely as its primary's would be and holds enough points
+// to show a couple of the moon's loops about its primary.
+const MOON_SPACING_SCALE: f32 = 0.5;
+const MOON_TRAIL_POINTS: usize = 120;
+
+// This determines how much of a body's path its trail holds. A new point is recorded once the body
+// has moved min_spacing AU from the last recorded point. Once the trail holds max_points, the
+// oldest point is dropped when a new one is recorded.
+#[derive(Clone, Copy, Debug)]
+pub struct TrailConfig {
+    pub max_points: usize,
+    pub min_spacing: f32,
+}
+
+// The per-body trail configurations overriding the defaults. The defaults are derived from the size
+// of each body's orbit.
+#[derive(Default, Resource)]
+pub struct TrailSettings {
+    pub configs: HashMap<Body, TrailConfig>,
+}
+
+impl TrailSettings {
+    fn config_for(&self, sim: &Simulation, body: Body) -> TrailConfig {
+        if let Some(config) = self.configs.get(&body) {
+            return *config;
+        }
+        let planet_config = |planet| TrailConfig {
+            max_points: (PLANET_POINTS_PER_ORBIT * PLANET_TRAIL_FRACTION) as usize,
+            min_spacing: std::f32::consts::TAU * sim.apsis_of(planet) / PLANET_POINTS_PER_ORBIT,
+        };
+        match body.primary() {
+            Some(Body::Sun) | None => planet_config(body),
+            Some(primary) => TrailConfig {
+                max_points: MOON_TRAIL_POINTS,
+                min_spacing: MOON_SPACING_SCALE * planet_config(primary).min_spacing,
+            },
+        }
+    }
+}
+
+// This is the recent path of a body in World coordinates, oldest point first. The view is the
+// entity drawing it, if it is shown. The path is still recorded while the trail is hidden.
+#[derive(Component)]
+pub struct Trail {
+    config: TrailConfig,
+    points: VecDeque<Vec3>,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    view: Option<Entity>,
+}
+
+impl Trail {
+    fn new(config: TrailConfig, mesh: Handle<Mesh>, material: Handle<StandardMaterial>) -> Self {
+        Self {
+            config,
+            points: VecDeque::with_capacity(config.max_points + 1),
+            mesh,
+            material,
+            view: None,
+        }
+    }
+
+    fn spawn_view(&mut self, commands: &mut Commands) {
+        let view = commands.spawn(MaterialMeshBundle {
+            mesh: self.mesh.clone(),
+            material: self.material.clone(),
+            ..default()
+        });
+        self.view = Some(view.id());
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn record(&mut self, position: Vec3) {
+        let far_enough = match self.points.back() {
+            None => true,
+            Some(last) => last.distance(position) >= self.config.min_spacing,
+        };
+        if far_enough {
+            self.points.push_back(position);
+            while self.points.len() > self.config.max_points {
+                self.points.pop_front();
+            }
+        }
+    }
+}
+
+// This builds a line strip through the trail's points ending at the body's current position. The
+// trail fades out toward its oldest point.
+fn mk_trail_mesh(points: &VecDeque<Vec3>, head: Vec3, color: LinearRgba) -> Mesh {
+    let mut positions: Vec<[f32; 3]> = points.iter().map(|p| p.to_array()).collect();
+    positions.push(head.to_array());
+    if positions.len() < 2 {
+        positions.push(head.to_array());
+    }
+
+    let n = positions.len() as f32;
+    let colors: Vec<[f32; 4]> = (0..positions.len())
+        .map(|i| [color.red, color.green, color.blue, color.alpha * (i + 1) as f32 / n])
+        .collect();
+
+    Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+}
+
+// This gives every body orbiting something a trail.
+fn create_trails(
+    sim: Res<Simulation>,
+    settings: Res<TrailSettings>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    bodies: Query<(Entity, &Body, &BodyModel)>,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+
+    for (entity, body, model) in &bodies {
+        if body.primary().is_none() {
+            continue;
+        }
+        let color = sim.color_of(*body).with_luminance(0.5).to_linear();
+        let mesh = meshes.add(mk_trail_mesh(&VecDeque::new(), *model.position(), color));
+        let mut trail = Trail::new(settings.config_for(&sim, *body), mesh, material.clone());
+        trail.spawn_view(&mut commands);
+        trail.record(*model.position());
+        commands.entity(entity).insert(trail);
+    }
+}
+
+// The trails are redrawn every frame ending where the avatars are drawn, so they stay attached to
+// their bodies between steps.
+fn update_trails(
+    (sim, fraction): (Res<Simulation>, Res<StepFraction>),
+    mut meshes: ResMut<Assets<Mesh>>,
+    trails: Query<(&Body, &BodyModel, &Trail)>,
+) {
+    for (body, model, trail) in trails.iter().filter(|(.., trail)| trail.view.is_some()) {
+        let color = sim.color_of(*body).with_luminance(0.5).to_linear();
+        let head = model.interpolated_position(fraction.0);
+        meshes.insert(&trail.mesh, mk_trail_mesh(&trail.points, head, color));
+    }
+}
+
+// When the layers change, the views of the trails that were hidden are despawned, and views are
+// spawned for the trails that are shown again.
+fn apply_trail_layers(
+    layers: Res<Layers>,
+    mut commands: Commands,
+    mut trails: Query<(&Body, &mut Trail)>,
+) {
+    if !layers.is_changed() {
+        return;
+    }
+
+    for (body, mut trail) in &mut trails {
+        match (layers.shows(Layer::Trail, *body), trail.view) {
+            (true, None) => trail.spawn_view(&mut commands),
+            (false, Some(view)) => {
+                commands.entity(view).despawn();
+                trail.view = None;
+            }
+            _ => (),
+        }
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<TrailSettings>()
+        .add_systems(
+            crate::SpawnBodies,
+            create_trails.after(crate::create_body_models).before(crate::create_labels),
+        )
+        .add_systems(Update, (apply_trail_layers, update_trails.after(crate::update_avatars)))
+}
@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 extern crate uom;
 pub use uom::*;
-use si::{angle::radian, f64::Angle, ISQ, Quantity, SI};
+use si::{angle::radian, f64::{Angle, Time}, time::day, ISQ, Quantity, SI};
 use typenum::{N1, N2, P1, P3, Z0};
 
 // TODO: Switch to uom's LuminousFlux once https://github.com/iliekturtles/uom/pull/313 has been
@@ -23,3 +23,145 @@ pub const G: Quantity<ISQ<P3, N1, N2, Z0, Z0, Z0, Z0>, SI<f64>, f64> = Quantity
 pub fn rem_euclid(angle: Angle, modulus: Angle) -> Angle {
     Angle::new::<radian>(angle.value.rem_euclid(modulus.value))
 }
+
+// Return the UTC Gregorian calendar date and time, rounded to the second, of a Julian Date, e.g.,
+// "2000-01-01 12:00:00 UTC". See Meeus, Astronomical Algorithms, chapter 7.
+pub fn jd_to_gregorian(jd: Time) -> String {
+    let secs = ((jd.get::<day>() + 0.5) * 86_400.).round() as i64;
+    let z = secs.div_euclid(86_400);
+    let sec_of_day = secs.rem_euclid(86_400);
+
+    let alpha = ((z as f64 - 1_867_216.25) / 36_524.25).floor() as i64;
+    let a = z + 1 + alpha - alpha.div_euclid(4);
+    let b = a + 1524;
+    let c = ((b as f64 - 122.1) / 365.25).floor() as i64;
+    let d = (365.25 * c as f64).floor() as i64;
+    let e = ((b - d) as f64 / 30.6001).floor() as i64;
+
+    let day_of_month = b - d - (30.6001 * e as f64).floor() as i64;
+    let month = if e < 14 { e - 1 } else { e - 13 };
+    let year = if month > 2 { c - 4716 } else { c - 4715 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day_of_month,
+        sec_of_day / 3600,
+        sec_of_day % 3600 / 60,
+        sec_of_day % 60,
+    )
+}
+
+// Return the Julian Date of a UTC Gregorian calendar date and optional time, e.g., "2000-01-01",
+// "2000-01-01 12:00", or "2000-01-01T12:00:00 UTC". None is returned if the date can't be parsed.
+// See Meeus, Astronomical Algorithms, chapter 7.
+pub fn gregorian_to_jd(date: &str) -> Option<Time> {
+    let date = date.trim();
+    let date = date.strip_suffix("UTC").or(date.strip_suffix('Z')).unwrap_or(date).trim_end();
+    let (ymd, hms) = date.split_once([' ', 'T']).unwrap_or((date, "00:00"));
+
+    let ymd: Vec<i64> = ymd.split('-').map(str::parse).collect::<Result<_, _>>().ok()?;
+    let [year, month, day_of_month] = ymd[..] else {
+        return None;
+    };
+    let hms: Vec<f64> = hms.trim().split(':').map(str::parse).collect::<Result<_, _>>().ok()?;
+    let (hours, minutes, seconds) = match hms[..] {
+        [h, m] => (h, m, 0.),
+        [h, m, s] => (h, m, s),
+        _ => return None,
+    };
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_len = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=month_len).contains(&day_of_month)
+        || !(0. ..24.).contains(&hours)
+        || !(0. ..60.).contains(&minutes)
+        || !(0. ..60.).contains(&seconds)
+    {
+        return None;
+    }
+
+    let (y, m) = if month > 2 { (year, month) } else { (year - 1, month + 12) };
+    let a = y.div_euclid(100);
+    let b = 2 - a + a.div_euclid(4);
+    let jd_midnight = (365.25 * (y + 4716) as f64).floor() + (30.6001 * (m + 1) as f64).floor()
+        + (day_of_month + b) as f64
+        - 1524.5;
+    Some(Time::new::<day>(jd_midnight + (hours + (minutes + seconds / 60.) / 60.) / 24.))
+}
+
+// Return the Julian Date of an epoch given either as a Julian Date, e.g., "2451545.0", or as a UTC
+// Gregorian calendar date understood by gregorian_to_jd
+pub fn parse_epoch(epoch: &str) -> Option<Time> {
+    match epoch.trim().parse::<f64>() {
+        Ok(jd) if jd.is_finite() => Some(Time::new::<day>(jd)),
+        Ok(_) => None,
+        Err(_) => gregorian_to_jd(epoch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jd_to_gregorian_j2000() {
+        assert_eq!(jd_to_gregorian(Time::new::<day>(2_451_545.)), "2000-01-01 12:00:00 UTC")
+    }
+
+    #[test]
+    fn test_jd_to_gregorian_2023() {
+        assert_eq!(jd_to_gregorian(Time::new::<day>(2_459_945.5)), "2023-01-01 00:00:00 UTC")
+    }
+
+    #[test]
+    fn test_jd_to_gregorian_leap_day() {
+        assert_eq!(jd_to_gregorian(Time::new::<day>(2_451_604.25)), "2000-02-29 18:00:00 UTC")
+    }
+
+    #[test]
+    fn test_jd_to_gregorian_sputnik() {
+        // Meeus, example 7.c
+        assert_eq!(jd_to_gregorian(Time::new::<day>(2_436_116.31)), "1957-10-04 19:26:24 UTC")
+    }
+
+    #[test]
+    fn test_gregorian_to_jd_j2000() {
+        assert_eq!(gregorian_to_jd("2000-01-01 12:00:00 UTC"), Some(Time::new::<day>(2_451_545.)))
+    }
+
+    #[test]
+    fn test_gregorian_to_jd_formats() {
+        let exp = Some(Time::new::<day>(2_459_945.5));
+        assert_eq!(gregorian_to_jd("2023-01-01"), exp);
+        assert_eq!(gregorian_to_jd("2023-01-01T00:00Z"), exp);
+        assert_eq!(gregorian_to_jd("2023-01-01 00:00:00 UTC"), exp);
+    }
+
+    #[test]
+    fn test_gregorian_to_jd_round_trip() {
+        let jd = gregorian_to_jd("1957-10-04 19:26:24").unwrap();
+        assert_eq!(jd_to_gregorian(jd), "1957-10-04 19:26:24 UTC")
+    }
+
+    #[test]
+    fn test_gregorian_to_jd_invalid() {
+        assert_eq!(gregorian_to_jd("2023-02-29"), None);
+        assert_eq!(gregorian_to_jd("2023-13-01"), None);
+        assert_eq!(gregorian_to_jd("2023-01-01 24:00"), None);
+        assert_eq!(gregorian_to_jd("yesterday"), None);
+    }
+
+    #[test]
+    fn test_parse_epoch() {
+        assert_eq!(parse_epoch("2451545"), Some(Time::new::<day>(2_451_545.)));
+        assert_eq!(parse_epoch("2000-01-01 12:00"), Some(Time::new::<day>(2_451_545.)));
+        assert_eq!(parse_epoch("NaN"), None);
+    }
+}
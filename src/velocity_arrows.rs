@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+use crate::{layers::Layers, simulation::Body, BodyModel, Simulation, StepFraction};
+
+// The key toggling the arrows
+const TOGGLE_KEY: KeyCode = KeyCode::End;
+
+// The keys lengthening and shortening the arrows
+const LONGER_KEY: KeyCode = KeyCode::PageUp;
+const SHORTER_KEY: KeyCode = KeyCode::PageDown;
+
+// The span in days an arrow's length covers is kept within these bounds.
+const MIN_DAYS: f32 = 0.25;
+const MAX_DAYS: f32 = 1_024.;
+
+// This controls the display of arrows showing the bodies' velocities. Each arrow starts at its
+// body and is as long as the distance the body would travel in the given number of days at its
+// current velocity, so Mercury's arrow is about ten times as long as Neptune's. A body's velocity
+// is taken relative to its primary, so the arrow runs along the orbit drawn around the primary.
+#[derive(Resource)]
+pub struct VelocityArrows {
+    pub visible: bool,
+    pub days: f32,
+}
+
+impl Default for VelocityArrows {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            days: 32.,
+        }
+    }
+}
+
+// Return the offset in AU from a body to the tip of its arrow. The Sun has no primary, so it has
+// no arrow.
+fn arrow_of(sim: &Simulation, body: Body, days: f32) -> Option<Vec3> {
+    let primary = body.primary()?;
+    Some(days * (sim.velocity_of(body) - sim.velocity_of(primary)))
+}
+
+fn control_arrows(input: Res<ButtonInput<KeyCode>>, mut arrows: ResMut<VelocityArrows>) {
+    if input.just_pressed(TOGGLE_KEY) {
+        arrows.visible = !arrows.visible;
+    }
+    let days = if input.just_pressed(LONGER_KEY) {
+        arrows.days * 2.
+    } else if input.just_pressed(SHORTER_KEY) {
+        arrows.days / 2.
+    } else {
+        return;
+    };
+    arrows.days = days.clamp(MIN_DAYS, MAX_DAYS);
+    info!("The velocity arrows span {} days", arrows.days);
+}
+
+// The arrows are drawn every frame from where the avatars are drawn, so they stay attached to
+// their bodies between steps.
+fn draw_arrows(
+    (sim, arrows, layers): (Res<Simulation>, Res<VelocityArrows>, Res<Layers>),
+    fraction: Res<StepFraction>,
+    bodies: Query<(&Body, &BodyModel)>,
+    mut gizmos: Gizmos,
+) {
+    if !arrows.visible {
+        return;
+    }
+
+    for (body, model) in bodies.iter().filter(|(body, _)| layers.shows_body(**body)) {
+        let Some(arrow) = arrow_of(&sim, *body, arrows.days) else {
+            continue;
+        };
+        let start = model.interpolated_position(fraction.0);
+        gizmos.arrow(start, start + arrow, *sim.color_of(*body));
+    }
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<VelocityArrows>()
+        .add_systems(Update, (control_arrows, draw_arrows.after(crate::update_avatars)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{default_epoch, uom_wrapper::si::length::astronomical_unit};
+
+    #[test]
+    fn test_arrows_tangent_to_orbits() {
+        let sim = Simulation::init_at(default_epoch());
+        for body in [Body::Mercury, Body::Earth, Body::Moon, Body::Jupiter, Body::Neptune] {
+            let primary = body.primary().unwrap();
+            let radial = sim.position_in::<astronomical_unit>(body)
+                - sim.position_in::<astronomical_unit>(primary);
+            let arrow = arrow_of(&sim, body, 1.).unwrap();
+            // Mercury's eccentric orbit takes it up to about 12° off of perpendicular.
+            let angle = arrow.angle_between(radial.as_vec3()).to_degrees();
+            assert!((75. ..105.).contains(&angle), "{:?} {}", body, angle);
+        }
+        assert!(arrow_of(&sim, Body::Sun, 1.).is_none());
+    }
+
+    #[test]
+    fn test_arrow_length_proportional_to_speed() {
+        let sim = Simulation::init_at(default_epoch());
+        let mercury = arrow_of(&sim, Body::Mercury, 1.).unwrap();
+        let neptune = arrow_of(&sim, Body::Neptune, 1.).unwrap();
+        assert!(mercury.length() / neptune.length() > 5., "{} {}", mercury, neptune);
+        let doubled = arrow_of(&sim, Body::Neptune, 2.).unwrap();
+        assert!((doubled.length() - 2. * neptune.length()).abs() < 1e-7);
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera_animation::{CameraAnimation, ObserverPose, DEFAULT_DURATION_S},
+    follow_camera::CameraTarget,
+    simulation::Body,
+    Observer, Simulation,
+};
+
+// The fraction of the view a framed orbit fills is the reciprocal of this.
+const FRAME_MARGIN: f32 = 1.1;
+
+// This is an orientation of the observer relative to its focus.
+#[derive(Clone, Debug)]
+pub struct OrientationPreset {
+    pub name: String,
+    pub facing: Dir3,
+    pub up: Dir3,
+}
+
+impl OrientationPreset {
+    pub fn new(name: &str, facing: Dir3, up: Dir3) -> Self {
+        Self {
+            name: name.to_string(),
+            facing,
+            up,
+        }
+    }
+}
+
+// These are the orientations cycled through when the key is pressed.
+#[derive(Resource)]
+pub struct OrientationPresets {
+    pub presets: Vec<OrientationPreset>,
+    pub key: KeyCode,
+    next: usize,
+}
+
+impl Default for OrientationPresets {
+    fn default() -> Self {
+        let iso_facing = Dir3::new(Vec3::new(-1., 1., -1.)).unwrap_or(Dir3::NEG_Z);
+        let iso_up = Dir3::new(Vec3::Z - Vec3::Z.dot(*iso_facing) * *iso_facing).unwrap_or(Dir3::Y);
+        Self {
+            presets: vec![
+                OrientationPreset::new("front", Dir3::Y, Dir3::Z),
+                OrientationPreset::new("top", Dir3::NEG_Z, Dir3::Y),
+                OrientationPreset::new("side", Dir3::NEG_X, Dir3::Z),
+                OrientationPreset::new("isometric", iso_facing, iso_up),
+            ],
+            key: KeyCode::KeyV,
+            next: 0,
+        }
+    }
+}
+
+// This moves the observer to the next orientation preset keeping its distance from its focus.
+fn cycle_orientation(
+    input: Res<ButtonInput<KeyCode>>,
+    mut presets: ResMut<OrientationPresets>,
+    mut anim: ResMut<CameraAnimation>,
+    observer: Query<&Observer>,
+) {
+    if !input.just_pressed(presets.key) || presets.presets.is_empty() {
+        return;
+    }
+
+    let idx = presets.next % presets.presets.len();
+    presets.next = idx + 1;
+    let preset = &presets.presets[idx];
+
+    let observer = observer.single();
+    let focus = *observer.focus();
+    let dist = observer.position().distance(focus);
+    let end = ObserverPose {
+        position: focus - dist * *preset.facing,
+        facing: preset.facing,
+        up: preset.up,
+    };
+    anim.start(observer.pose(), end, DEFAULT_DURATION_S);
+    info!("Camera orientation: {}", preset.name);
+}
+
+// This is a named place the observer can be sent to. The observer is placed looking at the Sun
+// from the given direction, far enough away that the framed body's orbit fits in the view.
+#[derive(Clone, Debug)]
+pub struct Viewpoint {
+    pub name: String,
+    pub key: KeyCode,
+    pub facing: Dir3,
+    pub up: Dir3,
+    pub framed: Body,
+}
+
+impl Viewpoint {
+    // Return the pose of the observer at this viewpoint given the radius in AU of the framed orbit
+    pub fn pose(&self, frame_radius: f32) -> ObserverPose {
+        let half_fov = PerspectiveProjection::default().fov / 2.;
+        let dist = FRAME_MARGIN * frame_radius / half_fov.tan();
+        ObserverPose {
+            position: -dist * *self.facing,
+            facing: self.facing,
+            up: self.up,
+        }
+    }
+}
+
+// These are the viewpoints. In all of them, the vernal equinox, the +x direction, points to the
+// right. The orrery view looks straight down from the ecliptic north pole at all of the planets.
+// The dwarf planets beyond Neptune aren't framed, since they would put the observer past the far
+// plane. For the same reason, the edge-on view only frames the orbits out to Saturn's, as the far
+// side of Neptune's would be clipped.
+#[derive(Resource)]
+pub struct Viewpoints(pub Vec<Viewpoint>);
+
+impl Default for Viewpoints {
+    fn default() -> Self {
+        let view = |name: &str, key, facing, up, framed| Viewpoint {
+            name: name.to_string(),
+            key,
+            facing,
+            up,
+            framed,
+        };
+        Self(vec![
+            view("orrery", KeyCode::F5, Dir3::NEG_Z, Dir3::Y, Body::Neptune),
+            view("inner system", KeyCode::F6, Dir3::NEG_Z, Dir3::Y, Body::Mars),
+            view("ecliptic edge", KeyCode::F7, Dir3::Y, Dir3::Z, Body::Saturn),
+        ])
+    }
+}
+
+// This flies the observer to the viewpoint whose key was pressed. The camera stops following any
+// body and refocuses on the Sun.
+fn go_to_viewpoint(
+    input: Res<ButtonInput<KeyCode>>,
+    sim: Res<Simulation>,
+    viewpoints: Res<Viewpoints>,
+    mut target: ResMut<CameraTarget>,
+    mut anim: ResMut<CameraAnimation>,
+    mut observer: Query<&mut Observer>,
+) {
+    let Some(viewpoint) = viewpoints.0.iter().find(|view| input.just_pressed(view.key)) else {
+        return;
+    };
+    target.0 = None;
+    let mut observer = observer.single_mut();
+    observer.set_focus(Vec3::ZERO);
+    let end = viewpoint.pose(sim.apsis_of(viewpoint.framed));
+    anim.start(observer.pose(), end, DEFAULT_DURATION_S);
+    info!("Camera viewpoint: {}", viewpoint.name);
+}
+
+pub fn setup(app: &mut App) -> &mut App {
+    app.init_resource::<OrientationPresets>()
+        .init_resource::<Viewpoints>()
+        .add_systems(Update, (cycle_orientation, go_to_viewpoint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orrery() -> Viewpoint {
+        Viewpoints::default().0.into_iter().find(|view| view.name == "orrery").unwrap()
+    }
+
+    #[test]
+    fn test_orrery_looks_down_from_ecliptic_north() {
+        let pose = orrery().pose(30.);
+        assert_eq!(pose.facing, Dir3::NEG_Z);
+        assert!(pose.position.z > 0.);
+        assert_eq!(pose.position.x, 0.);
+        assert_eq!(pose.position.y, 0.);
+    }
+
+    #[test]
+    fn test_viewpoints_put_vernal_equinox_right() {
+        for view in Viewpoints::default().0 {
+            let right = view.facing.cross(*view.up);
+            assert!(right.distance(Vec3::X) < 1e-6, "{}", view.name);
+        }
+    }
+
+    #[test]
+    fn test_viewpoint_frames_orbit() {
+        let pose = orrery().pose(30.);
+        let half_fov = PerspectiveProjection::default().fov / 2.;
+        let half_view = pose.position.length() * half_fov.tan();
+        assert!(half_view > 30., "{}", half_view);
+        assert!(half_view < 40., "{}", half_view);
+    }
+}